@@ -0,0 +1,75 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use recursive_regex::{from_regex_tree_and_str, RegexTree};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Line<'a> {
+    name: &'a str,
+    nums: Vec<i32>,
+    yn: bool,
+}
+
+/// Lines like `name17 1 -2 3 true`, adding up to about `len` bytes
+fn lines(len: usize) -> String {
+    let mut text = String::with_capacity(len + 64);
+    let mut i = 0;
+    while text.len() < len {
+        text.push_str(&format!(
+            "name{i} {} -{} {} {}\n",
+            i % 7,
+            i % 13,
+            i,
+            i % 2 == 0
+        ));
+        i += 1;
+    }
+    text
+}
+
+fn records(c: &mut Criterion) {
+    let regex_tree = RegexTree::root(r"(?P<name>\w+) (?P<nums>[-\d ]*) (?P<yn>true|false)")
+        .with_child("nums", RegexTree::leaf(r"-?\d+"))
+        .build();
+
+    let mut group = c.benchmark_group("records");
+    for len in [1 << 16, 1 << 20, 4 << 20] {
+        let text = lines(len);
+        group.throughput(Throughput::Bytes(text.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(len), &text, |b, text| {
+            b.iter(|| from_regex_tree_and_str::<Vec<Line>>(&regex_tree, text).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn leaves(c: &mut Criterion) {
+    let regex_tree = RegexTree::leaf(r"-?\d+");
+
+    let mut group = c.benchmark_group("leaves");
+    for len in [1 << 16, 1 << 20, 4 << 20] {
+        let text = lines(len);
+        group.throughput(Throughput::Bytes(text.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(len), &text, |b, text| {
+            b.iter(|| from_regex_tree_and_str::<Vec<i64>>(&regex_tree, text).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn lines_mode(c: &mut Criterion) {
+    let regex_tree = RegexTree::lines(r"(?P<name>\w+) (?P<nums>[-\d ]*) (?P<yn>true|false)")
+        .with_child("nums", RegexTree::leaf(r"-?\d+"))
+        .build();
+
+    let text = lines(1 << 20);
+    let mut group = c.benchmark_group("lines");
+    group.throughput(Throughput::Bytes(text.len() as u64));
+    group.bench_function("1048576", |b| {
+        b.iter(|| from_regex_tree_and_str::<Vec<Line>>(&regex_tree, &text).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, records, leaves, lines_mode);
+criterion_main!(benches);
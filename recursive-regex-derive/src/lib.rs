@@ -0,0 +1,302 @@
+//! Derive macros for `recursive_regex`. Use them through the `derive` feature
+//! of `recursive_regex` rather than depending on this crate directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use regex_syntax::hir::{Hir, HirKind};
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::{braced, parse_macro_input, Data, DeriveInput, Fields, Ident, LitStr, Token, Type};
+
+/// Generate `fn regex_tree() -> RegexTree` for a struct, keeping the patterns
+/// next to the fields they fill. The struct also gets an implementation of
+/// `FromRegexTree` using the same tree.
+///
+/// The struct takes its root pattern from `#[rx(pattern = "...")]`, which may
+/// be followed by any number of `fallback = "..."` patterns. Fields may
+/// recurse into a child with either `#[rx(pattern = "...")]`, for a leaf
+/// child, or `#[rx(tree = Type)]`, to use `Type::regex_tree()` as the child.
+/// The child is named after the field unless `group = "..."` is given.
+/// Every pattern is checked when the derive expands, and one that is not a
+/// valid regex is a compile error.
+///
+/// ```ignore
+/// #[derive(Deserialize, RegexTreeFrom)]
+/// #[rx(pattern = r"(?P<name>.*)'s favorite numbers? (?:is|are) (?P<favorite_numbers>.*)")]
+/// struct Person<'a> {
+///     name: &'a str,
+///     #[rx(pattern = r"\d+")]
+///     favorite_numbers: Vec<i32>,
+/// }
+///
+/// let people: Vec<Person> = from_regex_tree_and_str(&Person::regex_tree(), text)?;
+/// ```
+#[proc_macro_derive(RegexTreeFrom, attributes(rx))]
+pub fn derive_regex_tree_from(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let root = RxAttr::from_attrs(&input.attrs)?;
+    let pattern = root.pattern.ok_or_else(|| {
+        syn::Error::new_spanned(
+            &input.ident,
+            "missing root pattern; add `#[rx(pattern = \"...\")]` to the struct",
+        )
+    })?;
+    if let Some(tree) = root.tree {
+        return Err(syn::Error::new_spanned(
+            tree,
+            "`tree` is only allowed on fields",
+        ));
+    }
+    let fallbacks = root.fallbacks;
+    check_pattern(&pattern)?;
+    for fallback in &fallbacks {
+        check_pattern(fallback)?;
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "`RegexTreeFrom` requires a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "`RegexTreeFrom` can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut children = Vec::new();
+    for field in fields {
+        let attr = RxAttr::from_attrs(&field.attrs)?;
+        if !attr.fallbacks.is_empty() {
+            return Err(syn::Error::new_spanned(
+                field,
+                "`fallback` is only allowed on the struct",
+            ));
+        }
+        let name = match attr.group {
+            Some(group) => group.value(),
+            None => field
+                .ident
+                .as_ref()
+                .expect("named fields have identifiers")
+                .to_string(),
+        };
+        let child = match (attr.pattern, attr.tree) {
+            (Some(pattern), None) => {
+                check_pattern(&pattern)?;
+                quote! { ::recursive_regex::RegexTree::leaf(#pattern) }
+            }
+            (None, Some(tree)) => quote! { <#tree>::regex_tree() },
+            (None, None) => continue,
+            (Some(_), Some(tree)) => {
+                return Err(syn::Error::new_spanned(
+                    tree,
+                    "a field may have a `pattern` or a `tree`, not both",
+                ))
+            }
+        };
+        children.push(quote! { .with_child(#name, #child) });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut trait_generics = input.generics.clone();
+    trait_generics.params.insert(0, syn::parse_quote!('__rx_de));
+    trait_generics
+        .make_where_clause()
+        .predicates
+        .push(syn::parse_quote!(Self: ::recursive_regex::__private::serde::Deserialize<'__rx_de>));
+    let (trait_impl_generics, _, trait_where_clause) = trait_generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Regex tree generated from the `rx` attributes of this type
+            pub fn regex_tree() -> ::recursive_regex::RegexTree {
+                ::recursive_regex::RegexTree::root(#pattern)
+                    #(.with_fallback(#fallbacks))*
+                    #(#children)*
+                    .build()
+            }
+        }
+
+        impl #trait_impl_generics ::recursive_regex::FromRegexTree<'__rx_de>
+            for #ident #ty_generics #trait_where_clause
+        {
+            fn regex_tree() -> ::recursive_regex::RegexTree {
+                <#ident #ty_generics>::regex_tree()
+            }
+        }
+    })
+}
+
+#[derive(Default)]
+struct RxAttr {
+    pattern: Option<LitStr>,
+    fallbacks: Vec<LitStr>,
+    tree: Option<Type>,
+    group: Option<LitStr>,
+}
+
+impl RxAttr {
+    fn from_attrs(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut rx = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("rx")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("pattern") {
+                    rx.pattern = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("fallback") {
+                    rx.fallbacks.push(meta.value()?.parse()?);
+                } else if meta.path.is_ident("tree") {
+                    rx.tree = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("group") {
+                    rx.group = Some(meta.value()?.parse()?);
+                } else {
+                    return Err(meta.error("unknown `rx` attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(rx)
+    }
+}
+
+/// Build a regex tree inline, with each child nested under the pattern it
+/// recurses from. A pattern alone is a leaf, and a pattern followed by
+/// `=> { group: tree, ... }` is a node with children for the named groups,
+/// where each child is written the same way. A group name that is not an
+/// identifier may be given as a string.
+///
+/// Patterns are checked when the macro expands: one that does not parse, or a
+/// child named after a group its parent's pattern lacks, is a compile error
+/// pointing at the pattern.
+///
+/// ```ignore
+/// let regex_tree = regex_tree! {
+///     r"(?P<name>\w+): (?P<nums>.*)" => {
+///         nums: r"\d+",
+///     }
+/// };
+/// ```
+#[proc_macro]
+pub fn regex_tree(input: TokenStream) -> TokenStream {
+    let tree = parse_macro_input!(input as TreeDef);
+    tree.expand()
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Like [`regex_tree!`], but produces a `LazyLock<RegexTree>` that builds
+/// the tree the first time it is used, for use as a `static`. The patterns
+/// are checked when the macro expands, so building the tree cannot panic.
+///
+/// ```ignore
+/// static NUMBERS: LazyLock<RegexTree> = regex_tree_static! {
+///     r"(?P<name>\w+): (?P<nums>.*)" => { nums: r"\d+" }
+/// };
+/// ```
+#[proc_macro]
+pub fn regex_tree_static(input: TokenStream) -> TokenStream {
+    let tree = parse_macro_input!(input as TreeDef);
+    tree.expand()
+        .map(|tree| quote! { ::std::sync::LazyLock::new(|| #tree) })
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// A node of a tree given to `regex_tree!`
+struct TreeDef {
+    pattern: LitStr,
+    children: Vec<(LitStr, TreeDef)>,
+}
+
+impl Parse for TreeDef {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let pattern = input.parse()?;
+        let mut children = Vec::new();
+        if input.parse::<Option<Token![=>]>>()?.is_some() {
+            let content;
+            braced!(content in input);
+            while !content.is_empty() {
+                let name = if content.peek(LitStr) {
+                    content.parse()?
+                } else {
+                    let ident: Ident = content.call(Ident::parse_any)?;
+                    LitStr::new(&ident.to_string(), ident.span())
+                };
+                content.parse::<Token![:]>()?;
+                children.push((name, content.parse()?));
+                if content.is_empty() {
+                    break;
+                }
+                content.parse::<Token![,]>()?;
+            }
+        }
+        Ok(Self { pattern, children })
+    }
+}
+
+impl TreeDef {
+    fn expand(&self) -> syn::Result<TokenStream2> {
+        let hir = check_pattern(&self.pattern)?;
+        let pattern = &self.pattern;
+        if self.children.is_empty() {
+            return Ok(quote! { ::recursive_regex::RegexTree::leaf(#pattern) });
+        }
+
+        let mut group_names = Vec::new();
+        collect_group_names(&hir, &mut group_names);
+        let mut children = Vec::new();
+        for (name, child) in &self.children {
+            if !group_names.contains(&name.value()) {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    format!("no capture group named `{}` in the pattern", name.value()),
+                ));
+            }
+            let child = child.expand()?;
+            children.push(quote! { .with_child(#name, #child) });
+        }
+        Ok(quote! {
+            ::recursive_regex::RegexTree::root(#pattern)
+                #(#children)*
+                .build()
+        })
+    }
+}
+
+/// Parse a pattern, failing at the pattern's span if it is not a valid regex
+fn check_pattern(pattern: &LitStr) -> syn::Result<Hir> {
+    regex_syntax::parse(&pattern.value())
+        .map_err(|error| syn::Error::new_spanned(pattern, format!("invalid pattern: {error}")))
+}
+
+fn collect_group_names(hir: &Hir, names: &mut Vec<String>) {
+    match hir.kind() {
+        HirKind::Capture(capture) => {
+            names.extend(capture.name.as_deref().map(str::to_owned));
+            collect_group_names(&capture.sub, names);
+        }
+        HirKind::Repetition(repetition) => collect_group_names(&repetition.sub, names),
+        HirKind::Concat(hirs) | HirKind::Alternation(hirs) => {
+            for hir in hirs {
+                collect_group_names(hir, names);
+            }
+        }
+        HirKind::Empty | HirKind::Literal(_) | HirKind::Class(_) | HirKind::Look(_) => {}
+    }
+}
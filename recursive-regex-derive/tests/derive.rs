@@ -0,0 +1,69 @@
+use recursive_regex::{from_regex_tree_and_str, FromRegexTree, RegexTreeFrom};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq, Eq, RegexTreeFrom)]
+#[rx(
+    pattern = r"(?P<year>\d{4})-(?P<month>\d\d)",
+    fallback = r"(?P<month>\d\d)/(?P<year>\d{4})"
+)]
+struct Date {
+    year: u32,
+    month: u32,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq, RegexTreeFrom)]
+#[rx(pattern = r"(?P<name>\w+) since (?P<since>\S+): (?P<nums>.*)")]
+struct Person<'a> {
+    name: &'a str,
+    #[rx(tree = Date)]
+    since: Date,
+    #[rx(pattern = r"\d+", group = "nums")]
+    #[serde(rename = "nums")]
+    favorite_numbers: Vec<u32>,
+}
+
+#[test]
+fn main() {
+    let file = "Lina since 2019-04: 2
+Selah since 11/2020: 3, 6, 8";
+
+    let regex_tree = Person::regex_tree();
+    assert_eq!(regex_tree.depth(), 2);
+
+    let people: Vec<Person> = from_regex_tree_and_str(&regex_tree, file).unwrap();
+    let expected = vec![
+        Person {
+            name: "Lina",
+            since: Date {
+                year: 2019,
+                month: 4,
+            },
+            favorite_numbers: vec![2],
+        },
+        Person {
+            name: "Selah",
+            since: Date {
+                year: 2020,
+                month: 11,
+            },
+            favorite_numbers: vec![3, 6, 8],
+        },
+    ];
+    assert_eq!(expected, people);
+}
+
+#[test]
+fn from_regex_tree() {
+    let date = Date::from_regex_str("08/2015").unwrap();
+    assert_eq!(
+        date,
+        Date {
+            year: 2015,
+            month: 8
+        }
+    );
+
+    let people = Person::all_from_regex_str("Sven since 2001-02: 1 and 5").unwrap();
+    assert_eq!(people.len(), 1);
+    assert_eq!(people[0].favorite_numbers, vec![1, 5]);
+}
@@ -0,0 +1,86 @@
+use std::sync::LazyLock;
+
+use recursive_regex::{from_regex_tree_and_str, regex_tree, RegexTree};
+use serde::Deserialize;
+
+#[test]
+fn leaf() {
+    let regex_tree = regex_tree!(r"\d+");
+    let nums: Vec<u32> = from_regex_tree_and_str(&regex_tree, "1 2 3").unwrap();
+    assert_eq!(nums, vec![1, 2, 3]);
+}
+
+#[test]
+fn children() {
+    #[derive(Debug, Deserialize, PartialEq, Eq)]
+    struct Person<'a> {
+        name: &'a str,
+        nums: Vec<u32>,
+    }
+
+    let regex_tree = regex_tree! {
+        r"(?P<name>\w+): (?P<nums>.*)" => {
+            nums: r"\d+",
+        }
+    };
+    assert_eq!(regex_tree.depth(), 2);
+    let people: Vec<Person> = from_regex_tree_and_str(&regex_tree, "Lina: 2, 7").unwrap();
+    assert_eq!(
+        people,
+        vec![Person {
+            name: "Lina",
+            nums: vec![2, 7]
+        }]
+    );
+}
+
+#[test]
+fn nested_children_and_string_names() {
+    let regex_tree = regex_tree! {
+        r"(?m)^(?P<type>\w+) (?P<list>.*)$" => {
+            "list": r"\[(?P<pair>[^\]]*)\]" => {
+                pair: r"\w+"
+            },
+            type: r"\w"
+        }
+    };
+    assert_eq!(regex_tree.depth(), 3);
+
+    type Line = (String, Vec<(Vec<String>,)>);
+    let lines: Vec<Line> = from_regex_tree_and_str(&regex_tree, "ab [x y] [z]").unwrap();
+    assert_eq!(
+        lines,
+        vec![(
+            "ab".to_owned(),
+            vec![
+                (vec!["x".to_owned(), "y".to_owned()],),
+                (vec!["z".to_owned()],)
+            ]
+        )]
+    );
+}
+
+static NUMBERS: LazyLock<RegexTree> = LazyLock::new(|| {
+    regex_tree! {
+        r"(?P<nums>.*)" => { nums: r"\d+" }
+    }
+});
+
+#[test]
+fn lazy_static_tree() {
+    let parsed: Vec<(Vec<u8>,)> = from_regex_tree_and_str(&NUMBERS, "4 5").unwrap();
+    assert_eq!(parsed, vec![(vec![4, 5],)]);
+}
+
+static PEOPLE: LazyLock<RegexTree> = recursive_regex::regex_tree_static! {
+    r"(?P<name>\w+) (?:is|are) (?P<ages>.*)" => {
+        ages: r"\d+"
+    }
+};
+
+#[test]
+fn static_tree() {
+    let people: Vec<(String, Vec<u32>)> = from_regex_tree_and_str(&PEOPLE, "Ada is 36").unwrap();
+    assert_eq!(people, vec![("Ada".to_owned(), vec![36])]);
+    assert_eq!(PEOPLE.depth(), 2);
+}
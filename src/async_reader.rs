@@ -0,0 +1,144 @@
+use std::collections::VecDeque;
+
+use futures_core::Stream;
+use serde::de::{self, DeserializeOwned};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::options::DEFAULT_OPTIONS;
+use crate::{ChunkedDeserializer, DeserializeOptions, Error, RegexTree};
+
+/// Bytes asked of the reader at a time
+const READ_SIZE: usize = 8 * 1024;
+
+/// Deserialize each record of text read from an asynchronous source, such as
+/// a socket or a log being followed, as it arrives. A record is a match of the
+/// root of the regex tree, found as with [`ChunkedDeserializer`], so a record
+/// is only yielded once the next one starts or the reader ends.
+///
+/// A record that fails to deserialize is an error in its place, and the
+/// stream carries on. Failing to read, or reading text that is not UTF-8, is
+/// an error that ends the stream.
+///
+/// ## Example
+/// ```
+/// # use futures_util::StreamExt;
+/// # use recursive_regex::{from_regex_tree_and_async_reader, RegexTree};
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let regex_tree = RegexTree::lines(r"(?P<level>\w+): (?P<message>.*)").build();
+/// let reader: &[u8] = b"INFO: started\nWARN: disk low\n";
+///
+/// let records: Vec<Result<(String, String), _>> =
+///     from_regex_tree_and_async_reader(&regex_tree, reader).collect().await;
+/// assert_eq!(
+///     records.into_iter().collect::<Result<Vec<_>, _>>().unwrap(),
+///     vec![
+///         ("INFO".to_owned(), "started".to_owned()),
+///         ("WARN".to_owned(), "disk low".to_owned()),
+///     ]
+/// );
+/// # });
+/// ```
+pub fn from_regex_tree_and_async_reader<'r, T, R>(
+    regex_tree: &'r RegexTree,
+    reader: R,
+) -> impl Stream<Item = Result<T, Error>> + 'r
+where
+    T: DeserializeOwned + 'r,
+    R: AsyncRead + Unpin + 'r,
+{
+    from_regex_tree_and_async_reader_with_options(regex_tree, reader, &DEFAULT_OPTIONS)
+}
+
+/// Like [`from_regex_tree_and_async_reader`], but with
+/// [`DeserializeOptions`] that change how the text is deserialized.
+pub fn from_regex_tree_and_async_reader_with_options<'r, T, R>(
+    regex_tree: &'r RegexTree,
+    reader: R,
+    options: &'r DeserializeOptions,
+) -> impl Stream<Item = Result<T, Error>> + 'r
+where
+    T: DeserializeOwned + 'r,
+    R: AsyncRead + Unpin + 'r,
+{
+    let state = AsyncRecords {
+        reader,
+        options,
+        deserializer: Some(ChunkedDeserializer::with_options(regex_tree, options)),
+        bytes: Vec::new(),
+        offset: options.base_offset(),
+        records: VecDeque::new(),
+    };
+    futures_util::stream::unfold(state, |mut state| async move {
+        let record = state.next().await?;
+        Some((record, state))
+    })
+}
+
+struct AsyncRecords<'r, T, R> {
+    reader: R,
+    options: &'r DeserializeOptions,
+    /// `None` once the stream has ended
+    deserializer: Option<ChunkedDeserializer<'r, T>>,
+    /// Bytes read but not yet fed, which may end partway through a character
+    bytes: Vec<u8>,
+    /// Byte offset of the start of `bytes` within the whole stream
+    offset: usize,
+    /// Records found but not yet yielded
+    records: VecDeque<Result<T, Error>>,
+}
+
+impl<T: DeserializeOwned, R: AsyncRead + Unpin> AsyncRecords<'_, T, R> {
+    async fn next(&mut self) -> Option<Result<T, Error>> {
+        loop {
+            if let Some(record) = self.records.pop_front() {
+                return Some(record);
+            }
+            let deserializer = self.deserializer.as_mut()?;
+
+            let read = self.bytes.len();
+            self.bytes.resize(read + READ_SIZE, 0);
+            let result = self.reader.read(&mut self.bytes[read..]).await;
+            self.bytes.truncate(read + *result.as_ref().unwrap_or(&0));
+            match result {
+                Err(err) => {
+                    self.deserializer = None;
+                    let error: Error = de::Error::custom(format!("cannot read input: {err}"));
+                    return Some(Err(error.in_source(self.options)));
+                }
+                Ok(0) => {
+                    let deserializer = self.deserializer.take()?;
+                    self.records.extend(deserializer.finish());
+                    if !self.bytes.is_empty() {
+                        self.records.push_back(Err(self.utf8_error()));
+                    }
+                }
+                Ok(_) => match std::str::from_utf8(&self.bytes) {
+                    Ok(text) => {
+                        self.records.extend(deserializer.feed(text));
+                        self.offset += self.bytes.len();
+                        self.bytes.clear();
+                    }
+                    Err(err) => {
+                        let valid = err.valid_up_to();
+                        let text = std::str::from_utf8(&self.bytes[..valid]).unwrap_or_default();
+                        self.records.extend(deserializer.feed(text));
+                        self.offset += valid;
+                        self.bytes.drain(..valid);
+                        // Only an incomplete character at the end may be finished by the next read
+                        if err.error_len().is_some() {
+                            let deserializer = self.deserializer.take()?;
+                            self.records.extend(deserializer.finish());
+                            self.records.push_back(Err(self.utf8_error()));
+                        }
+                    }
+                },
+            }
+        }
+    }
+
+    /// Error for the bytes that do not start a UTF-8 character
+    fn utf8_error(&self) -> Error {
+        Error::with_span("input is not valid UTF-8", self.offset, self.offset + 1)
+            .in_source(self.options)
+    }
+}
@@ -0,0 +1,146 @@
+//! `rrx`: extract structured data from text with a regex tree read from a
+//! config file.
+//!
+//! ```text
+//! rrx --tree TREE [--format json|ndjson|csv] [INPUT]
+//! ```
+//!
+//! The tree is JSON, or TOML when its file name ends in `.toml`, in the form
+//! accepted by the `deserialize-regex-tree` feature. The input is read from
+//! standard input when no file is given. The output is what
+//! [`to_value`](recursive_regex::to_value) produces: as one JSON document, as
+//! one JSON line per match, or as CSV with a row per match and a column per
+//! capture group key of the root node.
+
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process::ExitCode;
+
+use recursive_regex::{to_value, write_csv, write_ndjson, ExportError, RegexTree};
+
+const USAGE: &str = "usage: rrx --tree TREE [--format json|ndjson|csv] [INPUT]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Json,
+    Ndjson,
+    Csv,
+}
+
+struct Args {
+    tree: String,
+    format: Format,
+    input: Option<String>,
+}
+
+/// Why `rrx` stopped. Usage errors print the usage as well.
+enum Failure {
+    Usage(String),
+    Other(String),
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Usage(message) => write!(f, "{message}\n{USAGE}"),
+            Self::Other(message) => f.write_str(message),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(failure) => {
+            eprintln!("rrx: {failure}");
+            match failure {
+                Failure::Usage(_) => ExitCode::from(2),
+                Failure::Other(_) => ExitCode::FAILURE,
+            }
+        }
+    }
+}
+
+fn run() -> Result<(), Failure> {
+    let args = parse_args(std::env::args().skip(1))?;
+    let regex_tree = read_tree(&args.tree)?;
+    let text = match &args.input {
+        Some(path) => fs::read_to_string(path)
+            .map_err(|err| Failure::Other(format!("cannot read {path}: {err}")))?,
+        None => {
+            let mut text = String::new();
+            io::stdin()
+                .read_to_string(&mut text)
+                .map_err(|err| Failure::Other(format!("cannot read standard input: {err}")))?;
+            text
+        }
+    };
+
+    let mut out = io::stdout().lock();
+    let written = match args.format {
+        Format::Json => to_value(&regex_tree, &text)
+            .map_err(ExportError::from)
+            .and_then(|value| {
+                serde_json::to_writer_pretty(&mut out, &value).map_err(io::Error::from)?;
+                writeln!(out)?;
+                Ok(())
+            }),
+        Format::Ndjson => write_ndjson(&regex_tree, &text, &mut out),
+        Format::Csv => write_csv(&regex_tree, &text, &mut out),
+    };
+    written
+        .and_then(|()| Ok(out.flush()?))
+        .map_err(|err| Failure::Other(err.to_string()))
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, Failure> {
+    let mut tree = None;
+    let mut format = Format::Json;
+    let mut input = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-t" | "--tree" => tree = Some(value_of(&arg, args.next())?),
+            "-f" | "--format" => {
+                format = match value_of(&arg, args.next())?.as_str() {
+                    "json" => Format::Json,
+                    "ndjson" => Format::Ndjson,
+                    "csv" => Format::Csv,
+                    other => return Err(Failure::Usage(format!("unknown format `{other}`"))),
+                }
+            }
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                std::process::exit(0);
+            }
+            _ if arg.starts_with('-') && arg != "-" => {
+                return Err(Failure::Usage(format!("unknown option `{arg}`")))
+            }
+            _ if input.is_some() => return Err(Failure::Usage("more than one input".to_owned())),
+            // `-` is standard input, which is also the default
+            "-" => input = None,
+            _ => input = Some(arg),
+        }
+    }
+    let tree = tree.ok_or_else(|| Failure::Usage("missing --tree".to_owned()))?;
+    Ok(Args {
+        tree,
+        format,
+        input,
+    })
+}
+
+fn value_of(option: &str, value: Option<String>) -> Result<String, Failure> {
+    value.ok_or_else(|| Failure::Usage(format!("missing value for `{option}`")))
+}
+
+fn read_tree(path: &str) -> Result<RegexTree, Failure> {
+    let config = fs::read_to_string(path)
+        .map_err(|err| Failure::Other(format!("cannot read {path}: {err}")))?;
+    let parsed = if path.ends_with(".toml") {
+        toml::from_str(&config).map_err(|err| err.to_string())
+    } else {
+        serde_json::from_str(&config).map_err(|err| err.to_string())
+    };
+    parsed.map_err(|err| Failure::Other(format!("invalid regex tree in {path}: {err}")))
+}
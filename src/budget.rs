@@ -0,0 +1,53 @@
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Shared count of the bytes searched against a limit, for
+/// [`DeserializeOptions::with_scan_limit`](crate::DeserializeOptions::with_scan_limit). Clones of
+/// the options that hold it count into the same place.
+#[derive(Clone)]
+pub(crate) struct ScanBudget {
+    limit: usize,
+    scanned: Arc<AtomicUsize>,
+}
+
+impl ScanBudget {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            scanned: Arc::default(),
+        }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Bytes searched so far
+    pub fn scanned(&self) -> usize {
+        self.scanned.load(Ordering::Relaxed)
+    }
+
+    /// Count `bytes` more as searched, returning whether the total is still within the limit
+    pub fn charge(&self, bytes: usize) -> bool {
+        let scanned = self.scanned.fetch_add(bytes, Ordering::Relaxed);
+        scanned.saturating_add(bytes) <= self.limit
+    }
+}
+
+impl fmt::Debug for ScanBudget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ScanBudget")
+            .field("limit", &self.limit)
+            .field("scanned", &self.scanned())
+            .finish()
+    }
+}
+
+impl PartialEq for ScanBudget {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.scanned, &other.scanned)
+    }
+}
+
+impl Eq for ScanBudget {}
@@ -0,0 +1,143 @@
+use std::marker::PhantomData;
+
+use serde::de::DeserializeOwned;
+
+use crate::multi_capture::deserialize_capture;
+use crate::options::{DeserializeOptions, DEFAULT_OPTIONS};
+use crate::{Error, PathSegment, RegexTree};
+
+/// Push based deserializer for text that arrives in pieces, such as from a
+/// network stream. Each call to [`feed`](ChunkedDeserializer::feed) adds text
+/// and returns the records completed so far, where a record is a match of the
+/// root of the regex tree, deserialized as an element of a sequence would be.
+/// The text of records already returned is dropped, so only the text since
+/// the last complete record is kept rather than the whole stream.
+///
+/// A match is only complete once another match of the root follows it, as
+/// more text could still extend the last match. For split and line nodes,
+/// this is exact. For other nodes, it assumes a match cannot grow into the
+/// text where a later match was found, which holds for record-like patterns.
+/// [`finish`](ChunkedDeserializer::finish) returns the records left over at
+/// the end of the stream.
+///
+/// Spans of errors and [`Spanned`](crate::Spanned) values are offsets into
+/// the whole stream, after any
+/// [base offset](DeserializeOptions::with_base_offset). The root's [`Count`](crate::regex_tree::Count) is not
+/// checked.
+///
+/// ## Example
+/// ```
+/// # use recursive_regex::{ChunkedDeserializer, RegexTree};
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// struct Reading {
+///     sensor: String,
+///     value: u32,
+/// }
+///
+/// let regex_tree = RegexTree::lines(r"(?P<sensor>\w+)=(?P<value>\d+)").build();
+/// let mut deserializer = ChunkedDeserializer::<Reading>::new(&regex_tree);
+///
+/// let records = deserializer.feed("a=1\nb=");
+/// assert_eq!(records.len(), 1);
+/// assert_eq!(records[0].as_ref().unwrap().sensor, "a");
+///
+/// // The second line is only complete once the next one starts
+/// assert!(deserializer.feed("23").is_empty());
+/// let records = deserializer.feed("\nc=4");
+/// assert_eq!(records[0].as_ref().unwrap().value, 23);
+///
+/// let records = deserializer.finish();
+/// assert_eq!(records[0].as_ref().unwrap().sensor, "c");
+/// ```
+pub struct ChunkedDeserializer<'r, T> {
+    regex_tree: &'r RegexTree,
+    options: &'r DeserializeOptions,
+    /// Text fed but not yet part of a returned record
+    buffer: String,
+    /// Byte offset of the start of `buffer` within the whole stream
+    offset: usize,
+    /// Number of records returned so far
+    count: usize,
+    records: PhantomData<fn() -> T>,
+}
+
+impl<'r, T: DeserializeOwned> ChunkedDeserializer<'r, T> {
+    pub fn new(regex_tree: &'r RegexTree) -> Self {
+        Self::with_options(regex_tree, &DEFAULT_OPTIONS)
+    }
+
+    /// Like [`new`](ChunkedDeserializer::new), but with
+    /// [`DeserializeOptions`] that change how the text is deserialized
+    pub fn with_options(regex_tree: &'r RegexTree, options: &'r DeserializeOptions) -> Self {
+        Self {
+            regex_tree,
+            options,
+            buffer: String::new(),
+            offset: options.base_offset(),
+            count: 0,
+            records: PhantomData,
+        }
+    }
+
+    /// Add the next piece of the stream, and return the records it completes,
+    /// in order. A record that fails to deserialize is an error in its place,
+    /// and the records after it are still returned.
+    pub fn feed(&mut self, chunk: &str) -> Vec<Result<T, Error>> {
+        self.buffer.push_str(chunk);
+        self.records(false)
+    }
+
+    /// End the stream, and return the records that were waiting for more text
+    pub fn finish(mut self) -> Vec<Result<T, Error>> {
+        self.records(true)
+    }
+
+    /// Deserialize the complete records in the buffer, which is all of them at the end of the
+    /// stream, then drop their text
+    fn records(&mut self, end_of_stream: bool) -> Vec<Result<T, Error>> {
+        let mut matches: Vec<_> = std::iter::from_fn({
+            let mut captures_iter = self.regex_tree.captures_iter(&self.buffer);
+            move || captures_iter.try_next()
+        })
+        .collect();
+
+        // Keep the last match for more text to extend, unless the stream has ended. Without any
+        // match, the text could still become the start of one.
+        let consumed = match matches.last() {
+            _ if end_of_stream => self.buffer.len(),
+            Some(Ok(capture)) => capture.start(),
+            Some(Err(unmatched)) => unmatched.start(),
+            None => 0,
+        };
+        if !end_of_stream {
+            matches.pop();
+        }
+
+        let end = self.offset + self.buffer.len();
+        let mut count = self.count;
+        let records = matches
+            .into_iter()
+            .map(|capture| match capture {
+                Ok(capture) => {
+                    count += 1;
+                    deserialize_capture(
+                        self.regex_tree,
+                        self.options,
+                        &capture,
+                        count - 1,
+                        self.offset,
+                        PhantomData,
+                    )
+                    .map_err(|error| error.within(PathSegment::Index(count - 1)))
+                }
+                Err(unmatched) => Err(unmatched.error(self.offset, end)),
+            })
+            .map(|record| record.map_err(|error| error.in_source(self.options)))
+            .collect();
+
+        self.count = count;
+        self.buffer.drain(..consumed);
+        self.offset += consumed;
+        records
+    }
+}
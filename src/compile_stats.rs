@@ -0,0 +1,170 @@
+use regex_automata::nfa::thompson::{self, NFA};
+use regex_automata::util::syntax;
+use regex_automata::{meta, MatchKind};
+
+use crate::pattern::Pattern;
+use crate::regex_tree::CompileOptions;
+use crate::RegexTree;
+
+/// Limits the regex crate compiles with when none are set
+const DEFAULT_SIZE_LIMIT: usize = 10 * (1 << 20);
+const DEFAULT_DFA_SIZE_LIMIT: usize = 2 * (1 << 20);
+
+/// Statistics gathered by compiling every regex in a regex tree, as returned
+/// by [`RegexTree::compile_stats`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompileStats {
+    nodes: Vec<NodeStats>,
+}
+
+impl CompileStats {
+    pub(crate) fn from_regex_tree(regex_tree: &RegexTree) -> Self {
+        let mut nodes = Vec::new();
+        collect(regex_tree, Vec::new(), &mut nodes);
+        Self { nodes }
+    }
+
+    /// Statistics for each pattern of each node of the tree, parents before
+    /// their children and siblings ordered by child name. A node with fallback
+    /// patterns has one entry per pattern. Fixed-width columns are not a
+    /// regex, so they have no entry.
+    pub fn nodes(&self) -> &[NodeStats] {
+        &self.nodes
+    }
+
+    /// Whether every pattern was measured as the tree compiles it. See
+    /// [`NodeStats::is_exact`]. When checking a quota, a tree that is not
+    /// exact cannot be trusted to be within it.
+    pub fn is_exact(&self) -> bool {
+        self.nodes.iter().all(NodeStats::is_exact)
+    }
+
+    /// Total heap memory, in bytes, used by the compiled regexes of the tree.
+    pub fn memory_usage(&self) -> usize {
+        self.nodes.iter().map(NodeStats::memory_usage).sum()
+    }
+
+    /// The node whose compiled regex uses the most heap memory. Useful for
+    /// spotting a single pathological pattern in a large tree.
+    pub fn largest(&self) -> Option<&NodeStats> {
+        self.nodes.iter().max_by_key(|node| node.memory_usage)
+    }
+}
+
+/// Compile statistics for a single pattern of a regex tree node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeStats {
+    path: Vec<String>,
+    pattern_index: usize,
+    pattern: String,
+    captures_len: usize,
+    program_size: usize,
+    memory_usage: usize,
+    exact: bool,
+}
+
+impl NodeStats {
+    /// Child names leading from the root to this node, with `*` for a
+    /// [default child](crate::regex_tree::Builder::with_default_child) and
+    /// `name?n` for the `n`th
+    /// [conditional child](crate::regex_tree::Builder::with_child_if). Empty
+    /// for the root.
+    pub fn path(&self) -> &[String] {
+        &self.path
+    }
+
+    /// Index of the pattern among the node's primary pattern and fallbacks
+    pub fn pattern_index(&self) -> usize {
+        self.pattern_index
+    }
+
+    /// The source pattern of the regex
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Number of capture groups, including the implicit group 0
+    pub fn captures_len(&self) -> usize {
+        self.captures_len
+    }
+
+    /// Number of states in the compiled Thompson NFA, a rough measure of how
+    /// large the regex program is
+    pub fn program_size(&self) -> usize {
+        self.program_size
+    }
+
+    /// Heap memory, in bytes, used by the compiled regex and its NFA
+    pub fn memory_usage(&self) -> usize {
+        self.memory_usage
+    }
+
+    /// Whether the pattern was measured as the node compiles it, with its
+    /// flags and size limits. Fancy patterns that only fancy-regex can compile
+    /// cannot be measured, and their numbers are zero. A regex given to the
+    /// builder already compiled is measured from its source, as the settings
+    /// it was built with are unknown.
+    pub fn is_exact(&self) -> bool {
+        self.exact
+    }
+}
+
+fn collect(regex_tree: &RegexTree, path: Vec<String>, nodes: &mut Vec<NodeStats>) {
+    let options = regex_tree.compile_options();
+    for (pattern_index, pattern) in regex_tree.compiled_patterns().iter().enumerate() {
+        if let Pattern::Columns(_) = pattern {
+            continue;
+        }
+        let source = pattern.as_str();
+        let mut stats = NodeStats {
+            path: path.clone(),
+            pattern_index,
+            pattern: source.to_owned(),
+            captures_len: 0,
+            program_size: 0,
+            memory_usage: 0,
+            exact: false,
+        };
+        // Fancy patterns may not compile with the regex crate, and are left unmeasured
+        if let Some((regex, nfa)) = compile(source, options) {
+            stats.captures_len = regex.captures_len();
+            stats.program_size = nfa.states().len();
+            stats.memory_usage = regex.memory_usage() + nfa.memory_usage();
+            stats.exact = pattern.as_regex().is_some() && !regex_tree.is_given_compiled();
+        }
+        nodes.push(stats);
+    }
+
+    for (name, child) in regex_tree.child_nodes() {
+        let mut child_path = path.clone();
+        child_path.push(name);
+        collect(child, child_path, nodes);
+    }
+}
+
+/// Compile `pattern` with regex-automata as the regex crate does with `options`, to measure it,
+/// if it is valid
+fn compile(pattern: &str, options: CompileOptions) -> Option<(meta::Regex, NFA)> {
+    let syntax = syntax::Config::new()
+        .case_insensitive(options.case_insensitive)
+        .multi_line(options.multi_line)
+        .dot_matches_new_line(options.dot_matches_new_line)
+        .utf8(true);
+    let size_limit = options.size_limit.unwrap_or(DEFAULT_SIZE_LIMIT);
+    let config = meta::Config::new()
+        .match_kind(MatchKind::LeftmostFirst)
+        .utf8_empty(true)
+        .nfa_size_limit(Some(size_limit))
+        .hybrid_cache_capacity(options.dfa_size_limit.unwrap_or(DEFAULT_DFA_SIZE_LIMIT));
+    let regex = meta::Builder::new()
+        .configure(config)
+        .syntax(syntax)
+        .build(pattern)
+        .ok()?;
+    let nfa = thompson::Compiler::new()
+        .syntax(syntax)
+        .configure(thompson::Config::new().nfa_size_limit(Some(size_limit)))
+        .build(pattern)
+        .ok()?;
+    Some((regex, nfa))
+}
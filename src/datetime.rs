@@ -0,0 +1,101 @@
+use std::time::Duration;
+
+/// Name and fields that `std::time::Duration` deserializes itself with, which are recognized to
+/// parse durations from text such as `1h30m` instead
+pub const DURATION_NAME: &str = "Duration";
+pub const DURATION_FIELDS: &[&str] = &["secs", "nanos"];
+
+/// Parse text with a `chrono` format string, then write it in the standard form that `chrono`
+/// types deserialize from: RFC 3339 when there is an offset, otherwise the form of
+/// `NaiveDateTime`, `NaiveDate`, or `NaiveTime`, depending on what was parsed.
+#[cfg(feature = "chrono")]
+pub fn normalize(text: &str, format: &str) -> Result<String, chrono::ParseError> {
+    use chrono::format::{parse, Parsed, StrftimeItems};
+    use chrono::SecondsFormat;
+
+    let mut parsed = Parsed::new();
+    parse(&mut parsed, text, StrftimeItems::new(format))?;
+
+    if let Ok(datetime) = parsed.to_datetime() {
+        return Ok(datetime.to_rfc3339_opts(SecondsFormat::AutoSi, false));
+    }
+    match (parsed.to_naive_date(), parsed.to_naive_time()) {
+        (Ok(date), Ok(time)) => Ok(date
+            .and_time(time)
+            .format("%Y-%m-%dT%H:%M:%S%.f")
+            .to_string()),
+        (Ok(date), Err(_)) => Ok(date.format("%Y-%m-%d").to_string()),
+        (Err(_), Ok(time)) => Ok(time.format("%H:%M:%S%.f").to_string()),
+        (Err(err), Err(_)) => Err(err),
+    }
+}
+
+/// Parse a duration written as a sequence of numbers with units, like `1h 30m` or `2.5s`. The
+/// units are `d`, `h`, `m` or `min`, `s`, `ms`, `us` or `µs`, and `ns`. A number alone is a number
+/// of seconds.
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    if let Some(nanos) = parse_amount(text, 1_000_000_000) {
+        return from_nanos(nanos);
+    }
+
+    let mut total: u128 = 0;
+    let mut rest = text;
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let (number, after) = rest.split_at(number_len);
+        let unit_len = after
+            .find(|c: char| c.is_ascii_digit() || c.is_whitespace())
+            .unwrap_or(after.len());
+        let (unit, after) = after.split_at(unit_len);
+        let nanos_per_unit = match unit {
+            "d" => 86_400_000_000_000,
+            "h" => 3_600_000_000_000,
+            "m" | "min" => 60_000_000_000,
+            "s" => 1_000_000_000,
+            "ms" => 1_000_000,
+            "us" | "µs" => 1_000,
+            "ns" => 1,
+            _ => return None,
+        };
+        total = total.checked_add(parse_amount(number, nanos_per_unit)?)?;
+        rest = after.trim_start();
+    }
+    from_nanos(total)
+}
+
+/// Nanoseconds in a decimal number of units, each of the given number of nanoseconds
+fn parse_amount(number: &str, nanos_per_unit: u128) -> Option<u128> {
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    if whole.is_empty() && fraction.is_empty() {
+        return None;
+    }
+    let digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+    if !digits(whole) || !digits(fraction) {
+        return None;
+    }
+
+    let whole: u128 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().ok()?
+    };
+    let mut nanos = whole.checked_mul(nanos_per_unit)?;
+    // Digits past nanosecond precision are dropped
+    let mut scale = nanos_per_unit;
+    for digit in fraction.bytes() {
+        scale /= 10;
+        nanos += u128::from(digit - b'0') * scale;
+    }
+    Some(nanos)
+}
+
+fn from_nanos(nanos: u128) -> Option<Duration> {
+    let secs = u64::try_from(nanos / 1_000_000_000).ok()?;
+    Some(Duration::new(secs, (nanos % 1_000_000_000) as u32))
+}
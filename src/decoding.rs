@@ -0,0 +1,78 @@
+use crate::regex_tree::Decoding;
+
+impl Decoding {
+    /// Decode text into the bytes it encodes, or `None` if it is not valid
+    pub(crate) fn decode(self, text: &str) -> Option<Vec<u8>> {
+        match self {
+            Self::Hex => decode_hex(text),
+            Self::Base64 => decode_base64(text),
+            Self::Percent => decode_percent(text),
+        }
+    }
+}
+
+fn hex_digit(byte: u8) -> Option<u8> {
+    (byte as char).to_digit(16).map(|digit| digit as u8)
+}
+
+fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    let bytes = text.as_bytes();
+    if !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+    bytes
+        .chunks_exact(2)
+        .map(|pair| Some(hex_digit(pair[0])? << 4 | hex_digit(pair[1])?))
+        .collect()
+}
+
+fn base64_digit(byte: u8) -> Option<u32> {
+    let digit = match byte {
+        b'A'..=b'Z' => byte - b'A',
+        b'a'..=b'z' => byte - b'a' + 26,
+        b'0'..=b'9' => byte - b'0' + 52,
+        b'+' | b'-' => 62,
+        b'/' | b'_' => 63,
+        _ => return None,
+    };
+    Some(digit.into())
+}
+
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let unpadded = text.trim_end_matches('=');
+    let padding = text.len() - unpadded.len();
+    if padding > 2 || (padding > 0 && !text.len().is_multiple_of(4)) || unpadded.len() % 4 == 1 {
+        return None;
+    }
+
+    let mut decoded = Vec::with_capacity(unpadded.len() * 3 / 4);
+    for chunk in unpadded.as_bytes().chunks(4) {
+        let mut bits = 0;
+        for &byte in chunk {
+            bits = bits << 6 | base64_digit(byte)?;
+        }
+        // A chunk of n digits holds n - 1 bytes, and any bits left over are ignored
+        let bytes = chunk.len() - 1;
+        bits <<= 6 * (4 - chunk.len());
+        decoded.extend_from_slice(&bits.to_be_bytes()[1..=bytes]);
+    }
+    Some(decoded)
+}
+
+fn decode_percent(text: &str) -> Option<Vec<u8>> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let high = hex_digit(*bytes.get(i + 1)?)?;
+            let low = hex_digit(*bytes.get(i + 2)?)?;
+            decoded.push(high << 4 | low);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    Some(decoded)
+}
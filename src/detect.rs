@@ -0,0 +1,142 @@
+use serde::Deserialize;
+
+use crate::options::DEFAULT_OPTIONS;
+use crate::{from_regex_tree_and_str_with_options, DeserializeOptions, Error, RegexTree};
+
+/// How well the root of a regex tree fits a text, from [`rank_regex_trees`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TreeFit {
+    coverage: usize,
+    matches: usize,
+}
+
+impl TreeFit {
+    fn of(regex_tree: &RegexTree, text: &str) -> Self {
+        let mut captures_iter = regex_tree.captures_iter(text);
+        let mut fit = Self::default();
+        while let Some(capture) = captures_iter.try_next() {
+            if let Ok(capture) = capture {
+                fit.coverage += capture.end() - capture.start();
+                fit.matches += 1;
+            }
+        }
+        fit
+    }
+
+    /// Bytes of the text within matches of the root
+    pub fn coverage(&self) -> usize {
+        self.coverage
+    }
+
+    /// Number of matches of the root
+    pub fn matches(&self) -> usize {
+        self.matches
+    }
+}
+
+/// Match the root of each of several named regex trees against the same
+/// text, such as to tell which of several known log formats a file is in.
+/// Trees are returned best fit first: those whose matches cover the most of
+/// the text, then those with the most matches, then in the order given.
+///
+/// Only the roots are matched, so this is cheap even for large trees. Use
+/// [`from_regex_trees_and_str`] to deserialize with the best tree as well.
+///
+/// ## Example
+/// ```
+/// # use recursive_regex::{rank_regex_trees, RegexTree};
+/// let csv = RegexTree::lines(r"(?P<host>[^,]+),(?P<status>\d+)").build();
+/// let spaced = RegexTree::lines(r"(?P<host>\S+) (?P<status>\d+)").build();
+///
+/// let ranked = rank_regex_trees(&[("csv", &csv), ("spaced", &spaced)], "a 200\nb 404");
+/// assert_eq!(ranked[0].0, "spaced");
+/// assert_eq!(ranked[0].1.matches(), 2);
+/// assert_eq!(ranked[1].1.coverage(), 0);
+/// ```
+pub fn rank_regex_trees<'a>(
+    regex_trees: &[(&'a str, &RegexTree)],
+    text: &str,
+) -> Vec<(&'a str, TreeFit)> {
+    rank(regex_trees, text)
+        .into_iter()
+        .map(|(index, fit)| (regex_trees[index].0, fit))
+        .collect()
+}
+
+/// Indices of the trees with their fits, best first
+fn rank(regex_trees: &[(&str, &RegexTree)], text: &str) -> Vec<(usize, TreeFit)> {
+    let mut ranked: Vec<_> = regex_trees
+        .iter()
+        .map(|(_, regex_tree)| TreeFit::of(regex_tree, text))
+        .enumerate()
+        .collect();
+    // Stable, so ties keep the order they were given in
+    ranked.sort_by(|(_, a), (_, b)| b.cmp(a));
+    ranked
+}
+
+/// Deserialize text with whichever of several named regex trees fits it best,
+/// as ranked by [`rank_regex_trees`], returning the name of the tree used.
+///
+/// If the best fit fails to deserialize, the next is tried, and so on through
+/// every tree that matched at all. When none succeeds, the error is that of
+/// the best fit.
+///
+/// ## Example
+/// ```
+/// # use recursive_regex::{from_regex_trees_and_str, RegexTree};
+/// # use serde::Deserialize;
+/// #[derive(Deserialize)]
+/// struct Request {
+///     host: String,
+///     status: u16,
+/// }
+///
+/// let csv = RegexTree::lines(r"(?P<host>[^,]+),(?P<status>\d+)").build();
+/// let spaced = RegexTree::lines(r"(?P<host>\S+) (?P<status>\d+)").build();
+/// let formats = [("csv", &csv), ("spaced", &spaced)];
+///
+/// let (format, requests): (_, Vec<Request>) =
+///     from_regex_trees_and_str(&formats, "a,200\nb,404").unwrap();
+/// assert_eq!(format, "csv");
+/// assert_eq!(requests[1].status, 404);
+/// ```
+pub fn from_regex_trees_and_str<'a, 't, T>(
+    regex_trees: &[(&'a str, &RegexTree)],
+    text: &'t str,
+) -> Result<(&'a str, T), Error>
+where
+    T: Deserialize<'t>,
+{
+    from_regex_trees_and_str_with_options(regex_trees, text, &DEFAULT_OPTIONS)
+}
+
+/// Like [`from_regex_trees_and_str`], but with [`DeserializeOptions`] that
+/// change how the text is deserialized.
+pub fn from_regex_trees_and_str_with_options<'a, 't, T>(
+    regex_trees: &[(&'a str, &RegexTree)],
+    text: &'t str,
+    options: &DeserializeOptions,
+) -> Result<(&'a str, T), Error>
+where
+    T: Deserialize<'t>,
+{
+    let mut best_error = None;
+    for (index, fit) in rank(regex_trees, text) {
+        if fit.matches == 0 {
+            break;
+        }
+        let (name, regex_tree) = regex_trees[index];
+        match from_regex_tree_and_str_with_options(regex_tree, text, options) {
+            Ok(value) => return Ok((name, value)),
+            Err(error) => {
+                best_error.get_or_insert(error);
+            }
+        }
+    }
+    Err(best_error.unwrap_or_else(|| {
+        let start = options.base_offset();
+        Error::with_span("no regex tree matched the text", start, start + text.len())
+            .in_source(options)
+    }))
+}
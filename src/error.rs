@@ -0,0 +1,135 @@
+use std::fmt;
+use std::ops::Range;
+
+use serde::de;
+
+use crate::{DeserializeOptions, NodePath, PathSegment};
+
+/// Error produced while deserializing text with a regex tree.
+///
+/// Errors that come from a known part of the text, such as a leaf that fails
+/// to parse as a number or a node with too few matches, carry the byte range
+/// of that text within the originally parsed string. Errors raised by the
+/// types being deserialized into, such as a missing field or an untagged enum
+/// that no variant fits, span the match they were deserialized from when it is
+/// one of a sequence of matches, and otherwise have no span.
+///
+/// Every error records the path of sequence indices and capture group keys
+/// leading to the value that failed, and shows it before the message, like
+/// `[1].tags[0]: parsing error: invalid digit found in string`. When the
+/// text was given a [source name](crate::DeserializeOptions::with_source_name),
+/// that comes first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    message: String,
+    span: Option<Range<usize>>,
+    path: NodePath,
+    source_name: Option<String>,
+}
+
+impl Error {
+    pub(crate) fn with_span(message: impl fmt::Display, start: usize, end: usize) -> Self {
+        Self {
+            message: message.to_string(),
+            span: Some(start..end),
+            path: NodePath::new(),
+            source_name: None,
+        }
+    }
+
+    /// Give the error a span if it has none yet
+    pub(crate) fn or_span(mut self, start: usize, end: usize) -> Self {
+        self.span.get_or_insert(start..end);
+        self
+    }
+
+    /// Record that the error happened within the value at `segment`. Errors
+    /// pass up through each level of nesting, so segments are added from the
+    /// innermost outwards.
+    pub(crate) fn within(mut self, segment: PathSegment) -> Self {
+        self.path.push_front(segment);
+        self
+    }
+
+    /// Description of what went wrong, without the span
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Byte range of the text the error came from, if known
+    pub fn span(&self) -> Option<Range<usize>> {
+        self.span.clone()
+    }
+
+    /// Record the name of the document the text came from, as given by the options
+    pub(crate) fn in_source(mut self, options: &DeserializeOptions) -> Self {
+        if let Some(name) = options.source_name() {
+            self.source_name = Some(name.to_owned());
+        }
+        self
+    }
+
+    /// Name of the document the text came from, if one was given with
+    /// [`DeserializeOptions::with_source_name`]
+    pub fn source_name(&self) -> Option<&str> {
+        self.source_name.as_deref()
+    }
+
+    /// Path to the value that failed to deserialize. Empty if the error is
+    /// about the outermost value.
+    pub fn path(&self) -> &NodePath {
+        &self.path
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(source_name) = &self.source_name {
+            write!(f, "{source_name}: ")?;
+        }
+        if self.path.is_empty() {
+            f.write_str(&self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self {
+            message: msg.to_string(),
+            span: None,
+            path: NodePath::new(),
+            source_name: None,
+        }
+    }
+}
+
+/// Labels the span of the error, if it has one. The error does not hold the
+/// text it came from, so attach it to the report to see the snippet, as a
+/// [`NamedSource`](miette::NamedSource) to show the source name too.
+///
+/// ## Example
+/// ```
+/// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+/// use miette::{Diagnostic, NamedSource, Report};
+///
+/// let text = "1 2 x";
+/// let error = from_regex_tree_and_str::<Vec<u32>>(&RegexTree::leaf(r"\S+"), text).unwrap_err();
+///
+/// let label = error.labels().unwrap().next().unwrap();
+/// assert_eq!((label.offset(), label.len()), (4, 1));
+///
+/// let report = Report::new(error).with_source_code(NamedSource::new("input.txt", text));
+/// # let _ = report;
+/// ```
+#[cfg(feature = "miette")]
+impl miette::Diagnostic for Error {
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        let label = miette::LabeledSpan::underline(self.span.clone()?);
+        Some(Box::new(std::iter::once(label)))
+    }
+}
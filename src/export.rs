@@ -0,0 +1,149 @@
+use std::fmt;
+use std::io::{self, Write};
+
+use crate::{to_value, Error, RegexTree, Value};
+
+/// Error from exporting matches, either while deserializing the text or
+/// while writing the output
+#[derive(Debug)]
+pub enum ExportError {
+    Deserialize(Error),
+    Io(io::Error),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Deserialize(err) => write!(f, "{err}"),
+            Self::Io(err) => write!(f, "cannot write output: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Deserialize(err) => Some(err),
+            Self::Io(err) => Some(err),
+        }
+    }
+}
+
+impl From<Error> for ExportError {
+    fn from(err: Error) -> Self {
+        Self::Deserialize(err)
+    }
+}
+
+impl From<io::Error> for ExportError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// The top level matches of what a regex tree produces. Anything other than
+/// a sequence is a single match.
+fn records(value: &Value) -> &[Value] {
+    value.as_seq().unwrap_or(std::slice::from_ref(value))
+}
+
+/// Write each top level match of a regex tree over some text as a line of
+/// JSON, with no Rust type to deserialize into. Matches with named capture
+/// groups become objects, and the rest become strings, as with
+/// [`to_value`].
+///
+/// ## Example
+/// ```
+/// # use recursive_regex::{write_ndjson, RegexTree};
+/// let regex_tree = RegexTree::root(r"(?P<name>\w+): (?P<nums>.*)")
+///     .with_child("nums", RegexTree::leaf(r"\d+"))
+///     .build();
+///
+/// let mut out = Vec::new();
+/// write_ndjson(&regex_tree, "a: 1 2\nb: 3", &mut out).unwrap();
+/// assert_eq!(
+///     String::from_utf8(out).unwrap(),
+///     "{\"name\":\"a\",\"nums\":[\"1\",\"2\"]}\n{\"name\":\"b\",\"nums\":[\"3\"]}\n"
+/// );
+/// ```
+pub fn write_ndjson(
+    regex_tree: &RegexTree,
+    text: &str,
+    mut writer: impl Write,
+) -> Result<(), ExportError> {
+    let value = to_value(regex_tree, text)?;
+    for record in records(&value) {
+        serde_json::to_writer(&mut writer, record).map_err(io::Error::from)?;
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+/// Write each top level match of a regex tree over some text as a row of
+/// CSV, with no Rust type to deserialize into.
+///
+/// The header holds the keys of the capture groups of the root node's
+/// patterns, after any aliases, in order. A cell is empty when its group does not take part in a match, and
+/// holds JSON when its group has a child that makes more than a string. When
+/// the patterns have no named groups, there is a single `value` column
+/// holding each whole match.
+///
+/// ## Example
+/// ```
+/// # use recursive_regex::{write_csv, RegexTree};
+/// let regex_tree = RegexTree::leaf(r"(?P<key>\w+)=(?P<value>[^;]*)");
+///
+/// let mut out = Vec::new();
+/// write_csv(&regex_tree, "a=1;b=x,y", &mut out).unwrap();
+/// assert_eq!(String::from_utf8(out).unwrap(), "key,value\na,1\nb,\"x,y\"\n");
+/// ```
+pub fn write_csv(
+    regex_tree: &RegexTree,
+    text: &str,
+    mut writer: impl Write,
+) -> Result<(), ExportError> {
+    let value = to_value(regex_tree, text)?;
+    let keys = regex_tree.field_keys();
+
+    if keys.is_empty() {
+        write_row(&mut writer, ["value"])?;
+        for record in records(&value) {
+            write_row(&mut writer, [cell(Some(record))])?;
+        }
+    } else {
+        write_row(&mut writer, keys.iter().copied())?;
+        for record in records(&value) {
+            write_row(&mut writer, keys.iter().map(|key| cell(record.get(key))))?;
+        }
+    }
+    Ok(())
+}
+
+fn cell(value: Option<&Value>) -> String {
+    match value {
+        None => String::new(),
+        Some(value) => match value.as_str() {
+            Some(string) => string.to_owned(),
+            None => serde_json::to_string(value).unwrap_or_default(),
+        },
+    }
+}
+
+/// Write a row, quoting cells that contain a delimiter, quote or line break
+fn write_row<S: AsRef<str>>(
+    writer: &mut impl Write,
+    cells: impl IntoIterator<Item = S>,
+) -> io::Result<()> {
+    for (index, cell) in cells.into_iter().enumerate() {
+        let cell = cell.as_ref();
+        if index > 0 {
+            write!(writer, ",")?;
+        }
+        if cell.contains([',', '"', '\n', '\r']) {
+            write!(writer, "\"{}\"", cell.replace('"', "\"\""))?;
+        } else {
+            write!(writer, "{cell}")?;
+        }
+    }
+    writeln!(writer)
+}
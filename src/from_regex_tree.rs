@@ -0,0 +1,51 @@
+use serde::Deserialize;
+
+use crate::{from_regex_tree_and_str, Error, RegexTree};
+
+/// A type with a canonical regex tree for parsing it from text.
+///
+/// Implement this once for a type, and callers can parse it without building
+/// the tree themselves. The `derive` feature's `#[derive(RegexTreeFrom)]`
+/// implements it automatically.
+///
+/// The tree is rebuilt, and its regexes recompiled, on every call. When
+/// parsing many inputs, build it once with
+/// [`regex_tree`](FromRegexTree::regex_tree) and use
+/// [`from_regex_tree_and_str`] instead.
+///
+/// ## Example
+/// ```
+/// # use recursive_regex::{FromRegexTree, RegexTree};
+/// # use serde::Deserialize;
+/// #[derive(Deserialize, Debug, PartialEq, Eq)]
+/// struct Point {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// impl FromRegexTree<'_> for Point {
+///     fn regex_tree() -> RegexTree {
+///         RegexTree::leaf(r"\((?P<x>-?\d+), (?P<y>-?\d+)\)")
+///     }
+/// }
+///
+/// assert_eq!(Point::from_regex_str("(1, -2)").unwrap(), Point { x: 1, y: -2 });
+/// assert_eq!(
+///     Point::all_from_regex_str("(1, 2) (3, 4)").unwrap(),
+///     vec![Point { x: 1, y: 2 }, Point { x: 3, y: 4 }]
+/// );
+/// ```
+pub trait FromRegexTree<'de>: Deserialize<'de> {
+    /// The canonical regex tree for this type
+    fn regex_tree() -> RegexTree;
+
+    /// Parse a single value from the first match of the tree in `text`
+    fn from_regex_str(text: &'de str) -> Result<Self, Error> {
+        from_regex_tree_and_str(&Self::regex_tree(), text)
+    }
+
+    /// Parse a value from every match of the tree in `text`
+    fn all_from_regex_str(text: &'de str) -> Result<Vec<Self>, Error> {
+        from_regex_tree_and_str(&Self::regex_tree(), text)
+    }
+}
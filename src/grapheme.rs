@@ -0,0 +1,83 @@
+use std::fmt;
+
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A single extended grapheme cluster: what a reader sees as one character,
+/// such as `é` written with a combining accent, or an emoji with a skin tone
+/// modifier, even when it is made of several `char`s.
+///
+/// It deserializes like a `char`, but keeps every `char` of the grapheme. With
+/// this crate, use [`DeserializeOptions::graphemes`](crate::DeserializeOptions::graphemes)
+/// so that graphemes of several `char`s are accepted.
+///
+/// ## Example
+/// ```
+/// # use recursive_regex::{from_regex_tree_and_str_with_options, DeserializeOptions, Grapheme, RegexTree};
+/// let regex_tree = RegexTree::leaf(r"\S+");
+/// let options = DeserializeOptions::new().graphemes();
+/// let reactions: Vec<Grapheme> =
+///     from_regex_tree_and_str_with_options(&regex_tree, "👍🏽 a", &options).unwrap();
+/// assert_eq!(reactions[0].as_str(), "👍🏽");
+/// assert_eq!(reactions[1].as_str(), "a");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Grapheme(String);
+
+impl Grapheme {
+    /// The grapheme, if `text` is exactly one grapheme cluster
+    pub fn new(text: &str) -> Option<Self> {
+        let mut graphemes = text.graphemes(true);
+        match (graphemes.next(), graphemes.next()) {
+            (Some(grapheme), None) => Some(Self(grapheme.to_owned())),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl fmt::Display for Grapheme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Grapheme {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_char(GraphemeVisitor)
+    }
+}
+
+struct GraphemeVisitor;
+
+impl<'de> Visitor<'de> for GraphemeVisitor {
+    type Value = Grapheme;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a single grapheme cluster")
+    }
+
+    fn visit_char<E>(self, c: char) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Grapheme(c.to_string()))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Grapheme::new(v).ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &self))
+    }
+}
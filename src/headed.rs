@@ -0,0 +1,138 @@
+use serde::de::{self, MapAccess};
+
+use crate::just_string::JustStrDeserializer;
+use crate::node_captures::strip_line_ending;
+use crate::options::DeserializeOptions;
+use crate::string::StrDeserializer;
+use crate::trace::trace_event;
+use crate::{Error, PathSegment, RegexTree};
+
+/// A section of a headed node's text, with byte offsets within the originally parsed string
+struct Section<'t> {
+    name: &'t str,
+    name_start: usize,
+    content: &'t str,
+    content_start: usize,
+}
+
+/// Deserialize the sections of a headed node as a map from their names to their contents. See
+/// [`headed`](RegexTree::headed).
+pub(crate) struct HeadedMapAccess<'r, 't> {
+    regex_tree: &'r RegexTree,
+    options: &'r DeserializeOptions,
+    sections: std::vec::IntoIter<Section<'t>>,
+    /// Section whose name was given last, for its content to be given next
+    last: Option<Section<'t>>,
+}
+
+impl<'r, 't> HeadedMapAccess<'r, 't> {
+    /// Find the sections of `text`, which starts at byte offset `start` of the originally parsed
+    /// string
+    pub(crate) fn new(
+        regex_tree: &'r RegexTree,
+        options: &'r DeserializeOptions,
+        text: &'t str,
+        start: usize,
+    ) -> Result<Self, Error> {
+        let mut captures_iter = regex_tree.captures_iter(text);
+        let headers: Vec<_> = std::iter::from_fn(|| captures_iter.try_next())
+            .filter_map(Result::ok)
+            .collect();
+        trace_event!(
+            pattern = regex_tree.pattern(),
+            sections = headers.len(),
+            start,
+            end = start + text.len(),
+            "splitting text at headers"
+        );
+
+        let preamble = &text[..headers.first().map_or(text.len(), |header| header.start())];
+        if !preamble.trim().is_empty() {
+            return Err(Error::with_span(
+                "text before the first header is in no section",
+                start,
+                start + preamble.len(),
+            ));
+        }
+
+        let ends = headers
+            .iter()
+            .skip(1)
+            .map(|header| header.start())
+            .chain([text.len()]);
+        let sections = headers
+            .iter()
+            .zip(ends)
+            .map(|(header, end)| {
+                let name = header
+                    .iter()
+                    .skip(1)
+                    .flatten()
+                    .next()
+                    .unwrap_or_else(|| header.whole_match());
+                let content = &text[header.end()..end];
+                let content = content
+                    .strip_prefix("\r\n")
+                    .or_else(|| content.strip_prefix('\n'))
+                    .unwrap_or(content);
+                Section {
+                    name: name.as_str(),
+                    name_start: start + name.start(),
+                    content: strip_line_ending(content),
+                    content_start: start + end - content.len(),
+                }
+            })
+            .collect::<Vec<_>>();
+        Ok(Self {
+            regex_tree,
+            options,
+            sections: sections.into_iter(),
+            last: None,
+        })
+    }
+}
+
+impl<'de, 'r> MapAccess<'de> for HeadedMapAccess<'r, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        let Some(section) = self.sections.next() else {
+            return Ok(None);
+        };
+        let name =
+            JustStrDeserializer::with_options(section.name, section.name_start, self.options);
+        self.last = Some(section);
+        seed.deserialize(name).map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let section = self
+            .last
+            .take()
+            .ok_or_else(|| <Error as de::Error>::custom("value requested before its key"))?;
+        let value = match self.regex_tree.section_child(section.name) {
+            Some(child) => seed.deserialize(StrDeserializer::from_regex_tree_and_offset_str(
+                child,
+                self.options,
+                section.content,
+                section.content_start,
+            )),
+            None => seed.deserialize(JustStrDeserializer::with_options(
+                section.content,
+                section.content_start,
+                self.options,
+            )),
+        };
+        value.map_err(|error| error.within(PathSegment::Name(section.name.to_owned())))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.sections.len())
+    }
+}
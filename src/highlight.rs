@@ -0,0 +1,73 @@
+use crate::{MatchNode, RegexTree};
+
+/// ANSI escape that ends any styling
+const RESET: &str = "\x1b[0m";
+
+/// ANSI escape for text that no match of the root covers
+const GAP: &str = "\x1b[2m";
+
+/// ANSI escapes for the matches at each depth of the tree, starting with the
+/// root's, which repeat for deeper trees
+const DEPTHS: &[&str] = &[
+    "\x1b[34m", "\x1b[32m", "\x1b[33m", "\x1b[35m", "\x1b[36m", "\x1b[31m",
+];
+
+/// Render `text` for a terminal with ANSI colors showing how a regex tree
+/// matches it, for developing a tree against sample data. Text that no match
+/// of the root covers is dim, matches of the root are blue, and matches of
+/// children are green, yellow, magenta, cyan and red by how deep they are,
+/// with the colors repeating beyond that. Text of a match that none of its
+/// children's matches cover keeps the color of the match.
+///
+/// The structure comes from [`RegexTree::matches`], so nothing is
+/// deserialized and text that would fail to deserialize is still shown.
+///
+/// ## Example
+/// ```
+/// # use recursive_regex::{highlight, RegexTree};
+/// let regex_tree = RegexTree::root(r"(?P<name>\w+): (?P<nums>[\d ]*)")
+///     .with_child("nums", RegexTree::leaf(r"\d+"))
+///     .build();
+///
+/// let highlighted = highlight(&regex_tree, "a: 1 2;");
+/// assert_eq!(
+///     highlighted,
+///     "\x1b[34ma: \x1b[0m\x1b[32m1\x1b[0m\x1b[34m \x1b[0m\x1b[32m2\x1b[0m\x1b[2m;\x1b[0m"
+/// );
+/// println!("{highlighted}");
+/// ```
+pub fn highlight(regex_tree: &RegexTree, text: &str) -> String {
+    // Depth of the deepest match covering each byte, if any match does
+    let mut depths = vec![None; text.len()];
+    for root_match in regex_tree.matches(text) {
+        paint(&root_match, 0, &mut depths);
+    }
+
+    let mut highlighted = String::with_capacity(text.len() * 2);
+    let mut start = 0;
+    while start < text.len() {
+        let depth = depths[start];
+        let end = depths[start..]
+            .iter()
+            .position(|other| *other != depth)
+            .map_or(text.len(), |len| start + len);
+        highlighted.push_str(match depth {
+            Some(depth) => DEPTHS[depth % DEPTHS.len()],
+            None => GAP,
+        });
+        highlighted.push_str(&text[start..end]);
+        highlighted.push_str(RESET);
+        start = end;
+    }
+    highlighted
+}
+
+/// Mark the bytes of a match and of the matches of its children with their depths
+fn paint(node: &MatchNode, depth: usize, depths: &mut [Option<usize>]) {
+    depths[node.range()].fill(Some(depth));
+    for group in node.groups() {
+        for child_match in group.matches().unwrap_or_default() {
+            paint(child_match, depth + 1, depths);
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use std::ops::Range;
+
+use serde::Deserialize;
+
+use crate::options::DEFAULT_OPTIONS;
+use crate::{
+    from_regex_tree_and_str_range_with_options, DeserializeOptions, Error, RegexTree, Spanned,
+};
+
+/// A change to a text: the bytes in a range of the old text replaced by some
+/// number of new bytes, for [`reparse_records`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Edit {
+    range: Range<usize>,
+    inserted_len: usize,
+}
+
+impl Edit {
+    /// The bytes of the old text in `range` were replaced by `inserted_len`
+    /// bytes. An insertion has an empty range, and a deletion inserts nothing.
+    pub fn new(range: Range<usize>, inserted_len: usize) -> Self {
+        Self {
+            range,
+            inserted_len,
+        }
+    }
+
+    /// Range of the old text that was replaced
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// Number of bytes that replaced the range
+    pub fn inserted_len(&self) -> usize {
+        self.inserted_len
+    }
+
+    /// Where a byte offset into the old text is in the new text. Offsets up
+    /// to the start of the range stay put, and offsets within it move to the
+    /// end of the inserted bytes.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::Edit;
+    /// // "a=1, b=2" becomes "a=10, b=2"
+    /// let edit = Edit::new(3..3, 1);
+    /// assert_eq!(edit.shift(2), 2);
+    /// assert_eq!(edit.shift(5), 6);
+    /// ```
+    pub fn shift(&self, offset: usize) -> usize {
+        match offset <= self.range.start {
+            true => offset,
+            false => {
+                offset.max(self.range.end) - self.range.end + self.range.start + self.inserted_len
+            }
+        }
+    }
+}
+
+/// Bring records deserialized from a text up to date after an edit to the
+/// text, deserializing only the records around the edit again. `records`
+/// are the records of the old text, as each match of the root of the regex
+/// tree, in order, and `text` is the new text. Returns the range of
+/// `records` that was deserialized again; the rest are kept.
+///
+/// The records around an edit are found from their spans. The text from the
+/// last record before the edit up to the first record after it is matched on
+/// its own with [`from_regex_tree_and_str_range`](crate::from_regex_tree_and_str_range),
+/// so those two unchanged records are deserialized again too. Only if either
+/// of them comes out differently, such as a match now running on into the
+/// edit, is the text widened to the record beyond, and so on until the
+/// records at each end are stable. A tree whose matches depend on text far
+/// from them, such as a pattern that skips ahead over other records, may
+/// still need a full parse.
+///
+/// Records after the edit are kept with their spans shifted to where they
+/// are in the new text. Spans within those records, such as of fields, are
+/// not shifted; [`Edit::shift`] maps them. If deserializing fails, `records`
+/// are left as they were.
+///
+/// ## Example
+/// ```
+/// # use recursive_regex::{from_regex_tree_and_str, reparse_records, Edit, RegexTree, Spanned};
+/// let regex_tree = RegexTree::lines(r"(?P<key>\w+)=(?P<value>\d+)").build();
+///
+/// let old = "a=1\nb=2\nc=3\nd=4";
+/// let mut records: Vec<Spanned<(String, u32)>> = from_regex_tree_and_str(&regex_tree, old).unwrap();
+///
+/// // "c=3" becomes "c=30"
+/// let new = "a=1\nb=2\nc=30\nd=4";
+/// let reparsed = reparse_records(&regex_tree, new, &mut records, &Edit::new(11..11, 1)).unwrap();
+///
+/// assert_eq!(reparsed, 1..4);
+/// assert_eq!(records[2].value(), &("c".to_owned(), 30));
+/// assert_eq!(records[3].substring(new), "d=4");
+/// ```
+pub fn reparse_records<'t, T>(
+    regex_tree: &RegexTree,
+    text: &'t str,
+    records: &mut Vec<Spanned<T>>,
+    edit: &Edit,
+) -> Result<Range<usize>, Error>
+where
+    T: Deserialize<'t>,
+{
+    reparse_records_with_options(regex_tree, text, records, edit, &DEFAULT_OPTIONS)
+}
+
+/// Like [`reparse_records`], but with [`DeserializeOptions`] that change how
+/// the text is deserialized. Record spans are expected to include the
+/// [base offset](DeserializeOptions::with_base_offset), as spans deserialized
+/// with the same options do.
+pub fn reparse_records_with_options<'t, T>(
+    regex_tree: &RegexTree,
+    text: &'t str,
+    records: &mut Vec<Spanned<T>>,
+    edit: &Edit,
+    options: &DeserializeOptions,
+) -> Result<Range<usize>, Error>
+where
+    T: Deserialize<'t>,
+{
+    let base = options.base_offset();
+    let span = |record: &Spanned<T>| {
+        record.begin().saturating_sub(base)..record.end().saturating_sub(base)
+    };
+    let shifted = |span: Range<usize>| edit.shift(span.start)..edit.shift(span.end);
+
+    // Records that touch the edit, and so may change, even if only by growing into it
+    let range = edit.range();
+    let affected_start = records.partition_point(|record| span(record).end < range.start);
+    let affected_end = records.partition_point(|record| span(record).start <= range.end);
+
+    // Along with an unchanged record either side, which should come out the same
+    let mut lo = affected_start.saturating_sub(1);
+    let mut hi = (affected_end + 1).min(records.len());
+    loop {
+        let start = match lo {
+            0 => 0,
+            _ => span(&records[lo]).start,
+        };
+        let end = match hi == records.len() {
+            true => text.len(),
+            false => shifted(span(&records[hi - 1])).end,
+        };
+        let reparsed: Vec<Spanned<T>> =
+            from_regex_tree_and_str_range_with_options(regex_tree, text, start..end, options)?;
+
+        let first_stable = lo == 0
+            || reparsed
+                .first()
+                .is_some_and(|first| span(first) == span(&records[lo]));
+        let last_stable = hi == records.len()
+            || reparsed
+                .last()
+                .is_some_and(|last| span(last) == shifted(span(&records[hi - 1])));
+        if first_stable && last_stable {
+            let reparsed_len = reparsed.len();
+            let after: Vec<_> = records
+                .drain(hi..)
+                .map(|record| {
+                    let Range { start, end } = shifted(span(&record));
+                    Spanned::new_raw(record.into_inner(), start + base, end + base)
+                })
+                .collect();
+            records.truncate(lo);
+            records.extend(reparsed);
+            records.extend(after);
+            return Ok(lo..lo + reparsed_len);
+        }
+        if !first_stable {
+            lo -= 1;
+        }
+        if !last_stable {
+            hi += 1;
+        }
+    }
+}
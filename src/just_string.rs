@@ -1,56 +1,214 @@
-use regex::Match;
+use crate::pattern::Match;
+use std::borrow::Cow;
 use std::fmt::Display;
 use std::str::FromStr;
-
-use crate::spanned::{
-    SpannedDeserializer, SPANNED_BEGIN, SPANNED_END, SPANNED_NAME, SPANNED_VALUE,
-};
-use serde::de::value::Error;
-use serde::de::Error as ErrorTrait;
-use serde::{de, serde_if_integer128};
+use std::time::Duration;
+
+use crate::datetime::{self, DURATION_FIELDS, DURATION_NAME};
+use crate::matched::{is_matched, MatchedDeserializer};
+use crate::options::DeserializeOptions;
+use crate::regex_tree::ValueType;
+use crate::spanned::{spanned_keys, SpanPolicy, SpannedDeserializer};
+use crate::trace::trace_event;
+use crate::{Error, Number, RegexTree};
+use serde::de;
+use serde::de::value::{MapDeserializer, SeqDeserializer};
 
 /// Deserialize just a string, in the sense that regular expressions are no
 /// longer needed to complete parsing. This should be invoked near the end of
 /// (nearly) all deserialization to take the final capture groups and turn them
 /// into numbers, `bool`s, `&str`s, or whatever other type was requested.
-pub struct JustStrDeserializer<'t> {
+///
+/// ## Example
+/// ```
+/// # use recursive_regex::JustStrDeserializer;
+/// # use serde::Deserialize;
+/// let number = u32::deserialize(JustStrDeserializer::from_str("640")).unwrap();
+/// assert_eq!(number, 640);
+///
+/// // Errors span the text, as though it started at byte 10 of a document
+/// let error = u32::deserialize(JustStrDeserializer::new("wide", 10)).unwrap_err();
+/// assert_eq!(error.span(), Some(10..14));
+/// ```
+pub struct JustStrDeserializer<'r, 't> {
     text: &'t str,
-    /// Byte offset of the start of `text` within the originally parsed string
-    start: usize,
+    /// Byte offsets of the start and end of what spans cover within the originally parsed
+    /// string, which is `text` unless the span policy is for the text before trimming
+    span: (usize, usize),
+    /// Node whose match or text this is, if any, whose options say how to parse the text
+    node: Option<&'r RegexTree>,
+    /// Text to parse in place of `text`, which it was translated into by a value map
+    mapped: Option<&'r str>,
+    /// Type the text must be of, from the capture group it came from
+    value_type: Option<&'r ValueType>,
+    /// Text to parse in place of `text`, which is `text` with its escapes resolved, or why they
+    /// are invalid. `None` if there were no escapes to resolve.
+    unescaped: Option<Result<String, String>>,
+    /// Whether a `char` may be a whole grapheme cluster
+    #[cfg(feature = "unicode-segmentation")]
+    graphemes: bool,
+    /// Whether text that reads as a number or bool is one when no type is asked for
+    infer_types: bool,
 }
 
-impl<'t> JustStrDeserializer<'t> {
+impl<'r, 't> JustStrDeserializer<'r, 't> {
+    /// Create a new deserializer for `text`, which starts at byte offset
+    /// `start` of the string it came from, with the default options
     pub fn new(text: &'t str, start: usize) -> Self {
-        Self { text, start }
+        Self::with_options(text, start, &crate::options::DEFAULT_OPTIONS)
     }
 
-    /// Create a new deserializer from a `Match`
-    pub fn from_match(re_match: Match<'t>, start: usize) -> Self {
+    /// Create a new deserializer for the whole of `text`, with the default
+    /// options
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(text: &'t str) -> Self {
+        Self::new(text, 0)
+    }
+
+    /// Create a new deserializer, applying any options that affect the text
+    pub fn with_options(text: &'t str, start: usize, options: &DeserializeOptions) -> Self {
+        let original = (start, start + text.len());
+        let (text, start) = if options.is_trim() {
+            let trimmed_start = text.trim_start();
+            (
+                trimmed_start.trim_end(),
+                start + (text.len() - trimmed_start.len()),
+            )
+        } else {
+            (text, start)
+        };
+        let span = match options.span_policy() {
+            SpanPolicy::Transformed => (start, start + text.len()),
+            SpanPolicy::Original => original,
+        };
         Self {
-            text: re_match.as_str(),
-            start,
+            text,
+            span,
+            node: None,
+            mapped: None,
+            value_type: None,
+            unescaped: None,
+            #[cfg(feature = "unicode-segmentation")]
+            graphemes: options.is_graphemes(),
+            infer_types: options.is_infer_types(),
         }
     }
 
-    fn parse_bool(self) -> Result<bool, Error> {
-        match self.text.to_lowercase().as_str() {
-            "false" | "f" | "no" | "n" | "0" => Ok(false),
-            "true" | "t" | "yes" | "y" | "1" => Ok(true),
-            whole_match => Err(Error::custom(format!(
-                "got {whole_match:?} but expecting a bool"
-            ))),
+    /// Create a new deserializer from a `Match`
+    pub(crate) fn from_match(
+        re_match: Match<'t>,
+        start: usize,
+        options: &DeserializeOptions,
+    ) -> Self {
+        Self::with_options(re_match.as_str(), start, options)
+    }
+
+    /// Parse the text as the options of the node it came from say, such as with a datetime format.
+    /// Text from a capture group without a child has no node.
+    pub fn with_node(mut self, node: &'r RegexTree) -> Self {
+        self.node = Some(node);
+        self.unescaped = node
+            .escapes()
+            .and_then(|escapes| match escapes.unescape(self.text) {
+                Ok(Cow::Borrowed(_)) => None,
+                Ok(Cow::Owned(unescaped)) => Some(Ok(unescaped)),
+                Err(message) => Some(Err(message)),
+            });
+        self
+    }
+
+    /// Parse the text as the capture group it came from says: as the text its value map
+    /// translates it into, if it has one, and as its type, if it has one. See
+    /// [`with_value_map`](crate::regex_tree::Builder::with_value_map) and
+    /// [`with_type`](crate::regex_tree::Builder::with_type).
+    pub(crate) fn with_group(mut self, regex_tree: &'r RegexTree, name: &str) -> Self {
+        self.mapped = regex_tree.mapped_value(name, self.text);
+        self.value_type = regex_tree.value_type(name);
+        self
+    }
+
+    /// Text to parse, after any value map or unescaping. Spans still cover the original text.
+    fn value(&self) -> Result<&str, Error> {
+        match (self.mapped, &self.unescaped) {
+            (Some(mapped), _) => Ok(mapped),
+            (None, Some(Ok(unescaped))) => Ok(unescaped),
+            (None, Some(Err(message))) => Err(self.error(message.clone())),
+            (None, None) => Ok(self.text),
+        }
+    }
+
+    /// Text to show in errors and traces, which is the text to parse if it is valid
+    fn shown(&self) -> &str {
+        self.value().unwrap_or(self.text)
+    }
+
+    /// Trace the text being parsed as `kind`
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    fn trace(&self, kind: &str) {
+        trace_event!(
+            kind,
+            text = self.shown(),
+            start = self.span.0,
+            end = self.span.1,
+            "parsing value"
+        );
+    }
+
+    /// Fail if the text is not of the capture group's type, if it has one
+    fn check_type(&self) -> Result<(), Error> {
+        let value = self.value()?;
+        let valid = match self.value_type {
+            Some(ValueType::Int) => matches!(
+                infer_number(value),
+                Some(Number::Unsigned(_) | Number::Signed(_))
+            ),
+            Some(ValueType::Float) => parse_float(value).is_some(),
+            Some(ValueType::Bool) => parse_bool(value).is_some(),
+            // Datetimes are checked as they are parsed
+            Some(ValueType::String | ValueType::Datetime(_)) | None => true,
+        };
+        match (valid, self.value_type) {
+            (false, Some(ValueType::Int)) => {
+                Err(self.error(format!("got {value:?} but expecting an int")))
+            }
+            (false, Some(value_type)) => {
+                Err(self.error(format!("got {value:?} but expecting a {value_type}")))
+            }
+            _ => Ok(()),
         }
     }
 
+    fn parse_bool(self) -> Result<bool, Error> {
+        self.trace("bool");
+        self.check_type()?;
+        let value = self.value()?;
+        parse_bool(value).ok_or_else(|| {
+            self.error(format!(
+                "got {:?} but expecting a bool",
+                value.to_lowercase()
+            ))
+        })
+    }
+
     fn parse_char(self) -> Result<char, Error> {
-        let mut chars = self.text.chars();
+        self.trace("char");
+        self.check_type()?;
+        let mut chars = self.value()?.chars();
         let first_char = chars.next();
         match first_char {
             Some(first_char) if chars.next().is_none() => Ok(first_char),
-            _ => Err(Error::custom(format!(
-                "got {} but expecting a single char",
-                self.text
-            ))),
+            _ => Err(self.error(format!("got {} but expecting a single char", self.shown()))),
+        }
+    }
+
+    #[cfg(feature = "unicode-segmentation")]
+    fn parse_grapheme<'a>(&self, text: &'a str) -> Result<&'a str, Error> {
+        use unicode_segmentation::UnicodeSegmentation;
+
+        let mut graphemes = text.graphemes(true);
+        match (graphemes.next(), graphemes.next()) {
+            (Some(grapheme), None) => Ok(grapheme),
+            _ => Err(self.error(format!("got {text} but expecting a single grapheme"))),
         }
     }
 
@@ -58,27 +216,108 @@ impl<'t> JustStrDeserializer<'t> {
     where
         T::Err: Display,
     {
-        self.text
+        self.trace(std::any::type_name::<T>());
+        self.check_type()?;
+        self.value()?
             .parse::<T>()
-            .map_err(|err| Error::custom(format!("parsing error: {err}")))
+            .map_err(|err| self.error(format!("parsing error: {err}")))
+    }
+
+    /// Format the text is parsed with as a date or time: that of the capture group's type, or
+    /// else that of the node it came from
+    fn datetime_format(&self) -> Option<&'r str> {
+        match self.value_type {
+            Some(ValueType::Datetime(format)) => Some(format),
+            _ => self.node.and_then(RegexTree::datetime_format),
+        }
+    }
+
+    /// The text in the standard form of the date or time it holds, if there is a datetime format
+    fn parse_datetime(&self) -> Result<Option<String>, Error> {
+        let Some(format) = self.datetime_format() else {
+            return Ok(None);
+        };
+        #[cfg(feature = "chrono")]
+        {
+            datetime::normalize(self.value()?, format)
+                .map(Some)
+                .map_err(|err| self.error(format!("parsing error: {err}")))
+        }
+        #[cfg(not(feature = "chrono"))]
+        {
+            Err(self.error(format!(
+                "parsing with datetime format {format:?} requires the `chrono` feature"
+            )))
+        }
+    }
+
+    /// The bytes the text encodes, if the node it came from has a decoding
+    fn decode(&self) -> Result<Option<Vec<u8>>, Error> {
+        let Some(decoding) = self.node.and_then(RegexTree::decoding) else {
+            return Ok(None);
+        };
+        decoding.decode(self.value()?).map(Some).ok_or_else(|| {
+            self.error(format!(
+                "got {} but expecting {decoding} bytes",
+                self.shown()
+            ))
+        })
+    }
+
+    fn parse_duration(&self) -> Result<Duration, Error> {
+        datetime::parse_duration(self.value()?)
+            .ok_or_else(|| self.error(format!("got {} but expecting a duration", self.shown())))
+    }
+
+    /// Error spanning the text
+    fn error(&self, message: String) -> Error {
+        Error::with_span(message, self.span.0, self.span.1)
     }
 }
 
-impl<'de> de::Deserializer<'de> for JustStrDeserializer<'de> {
+impl<'de, 'r> de::Deserializer<'de> for JustStrDeserializer<'r, 'de> {
     type Error = Error;
 
     fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.deserialize_any(visitor)
+        visitor.visit_unit()
     }
 
-    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        unimplemented!()
+        match self.value_type {
+            Some(ValueType::Int) => {
+                self.check_type()?;
+                return match infer_number(self.value()?) {
+                    Some(Number::Signed(number)) => visitor.visit_i64(number),
+                    _ => visitor.visit_u64(self.parse()?),
+                };
+            }
+            Some(ValueType::Float) => return visitor.visit_f64(self.parse()?),
+            Some(ValueType::Bool) => return visitor.visit_bool(self.parse_bool()?),
+            Some(ValueType::String | ValueType::Datetime(_)) => {
+                return self.deserialize_str(visitor)
+            }
+            None => {}
+        }
+        if self.infer_types && self.datetime_format().is_none() {
+            match self.shown() {
+                "true" => return visitor.visit_bool(true),
+                "false" => return visitor.visit_bool(false),
+                _ => {}
+            }
+            match infer_number(self.shown()) {
+                Some(Number::Unsigned(number)) => return visitor.visit_u64(number),
+                Some(Number::Signed(number)) => return visitor.visit_i64(number),
+                Some(Number::Float(number)) => return visitor.visit_f64(number),
+                None => {}
+            }
+        }
+        self.deserialize_str(visitor)
     }
 
     fn deserialize_tuple_struct<V>(
@@ -100,11 +339,17 @@ impl<'de> de::Deserializer<'de> for JustStrDeserializer<'de> {
         self.deserialize_seq(visitor)
     }
 
-    fn deserialize_seq<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        unimplemented!()
+        match self.decode()? {
+            Some(bytes) => visitor.visit_seq(SeqDeserializer::new(bytes.into_iter())),
+            None => Err(self.error(format!(
+                "got {} but expecting a sequence, which needs a child regex",
+                self.shown()
+            ))),
+        }
     }
 
     fn deserialize_struct<V>(
@@ -116,9 +361,18 @@ impl<'de> de::Deserializer<'de> for JustStrDeserializer<'de> {
     where
         V: de::Visitor<'de>,
     {
-        if name == SPANNED_NAME && fields == [SPANNED_BEGIN, SPANNED_END, SPANNED_VALUE] {
-            let end = self.start + self.text.len();
-            visitor.visit_map(SpannedDeserializer::new(self.start, end, self))
+        if let Some(keys) = spanned_keys(name, fields) {
+            let (start, end) = self.span;
+            visitor.visit_map(SpannedDeserializer::new(keys, start, end, self))
+        } else if is_matched(name, fields) {
+            visitor.visit_map(MatchedDeserializer::new(self.text, self))
+        } else if name == DURATION_NAME && fields == DURATION_FIELDS {
+            let duration = self.parse_duration()?;
+            let entries = [
+                ("secs", duration.as_secs()),
+                ("nanos", duration.subsec_nanos().into()),
+            ];
+            visitor.visit_map(MapDeserializer::new(entries.into_iter()))
         } else {
             self.deserialize_map(visitor)
         }
@@ -128,7 +382,10 @@ impl<'de> de::Deserializer<'de> for JustStrDeserializer<'de> {
     where
         V: de::Visitor<'de>,
     {
-        unimplemented!()
+        Err(self.error(format!(
+            "got {} but expecting a map, which needs named capture groups",
+            self.shown()
+        )))
     }
 
     fn deserialize_enum<V>(
@@ -140,7 +397,7 @@ impl<'de> de::Deserializer<'de> for JustStrDeserializer<'de> {
     where
         V: de::Visitor<'de>,
     {
-        unimplemented!()
+        Err(self.error("enums are not supported".to_owned()))
     }
 
     fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -178,13 +435,11 @@ impl<'de> de::Deserializer<'de> for JustStrDeserializer<'de> {
         visitor.visit_i64(self.parse()?)
     }
 
-    serde_if_integer128! {
-        fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-        where
-            V: de::Visitor<'de>
-        {
-            visitor.visit_i128(self.parse()?)
-        }
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_i128(self.parse()?)
     }
 
     fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -215,13 +470,11 @@ impl<'de> de::Deserializer<'de> for JustStrDeserializer<'de> {
         visitor.visit_u64(self.parse()?)
     }
 
-    serde_if_integer128! {
-        fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-        where
-            V: de::Visitor<'de>
-        {
-            visitor.visit_u128(self.parse()?)
-        }
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_u128(self.parse()?)
     }
 
     fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -242,6 +495,24 @@ impl<'de> de::Deserializer<'de> for JustStrDeserializer<'de> {
     where
         V: de::Visitor<'de>,
     {
+        // A grapheme of several chars can only be given to the visitor as a string
+        #[cfg(feature = "unicode-segmentation")]
+        if self.graphemes {
+            if self.mapped.is_some() || self.unescaped.is_some() {
+                let grapheme = self.parse_grapheme(self.value()?)?;
+                let mut chars = grapheme.chars();
+                return match (chars.next(), chars.next()) {
+                    (Some(c), None) => visitor.visit_char(c),
+                    _ => visitor.visit_str(grapheme),
+                };
+            }
+            let grapheme = self.parse_grapheme(self.text)?;
+            let mut chars = grapheme.chars();
+            return match (chars.next(), chars.next()) {
+                (Some(c), None) => visitor.visit_char(c),
+                _ => visitor.visit_borrowed_str(grapheme),
+            };
+        }
         visitor.visit_char(self.parse_char()?)
     }
 
@@ -263,7 +534,18 @@ impl<'de> de::Deserializer<'de> for JustStrDeserializer<'de> {
     where
         V: de::Visitor<'de>,
     {
-        visitor.visit_borrowed_str(self.text)
+        self.trace("str");
+        self.check_type()?;
+        // Only text left as it is can be borrowed
+        if let Some(datetime) = self.parse_datetime()? {
+            return visitor.visit_string(datetime);
+        }
+        match (self.mapped, self.unescaped) {
+            (Some(mapped), _) => visitor.visit_str(mapped),
+            (None, Some(Ok(unescaped))) => visitor.visit_string(unescaped),
+            (None, Some(Err(message))) => Err(Error::with_span(message, self.span.0, self.span.1)),
+            (None, None) => visitor.visit_borrowed_str(self.text),
+        }
     }
 
     fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -277,7 +559,17 @@ impl<'de> de::Deserializer<'de> for JustStrDeserializer<'de> {
     where
         V: de::Visitor<'de>,
     {
-        visitor.visit_borrowed_bytes(self.text.as_bytes())
+        self.trace("bytes");
+        self.check_type()?;
+        if let Some(bytes) = self.decode()? {
+            return visitor.visit_byte_buf(bytes);
+        }
+        match (self.mapped, self.unescaped) {
+            (Some(mapped), _) => visitor.visit_bytes(mapped.as_bytes()),
+            (None, Some(Ok(unescaped))) => visitor.visit_byte_buf(unescaped.into_bytes()),
+            (None, Some(Err(message))) => Err(Error::with_span(message, self.span.0, self.span.1)),
+            (None, None) => visitor.visit_borrowed_bytes(self.text.as_bytes()),
+        }
     }
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -317,6 +609,64 @@ impl<'de> de::Deserializer<'de> for JustStrDeserializer<'de> {
     }
 }
 
+/// The bool `text` holds, in any of the forms `bool` fields accept
+fn parse_bool(text: &str) -> Option<bool> {
+    match text.to_lowercase().as_str() {
+        "false" | "f" | "no" | "n" | "0" => Some(false),
+        "true" | "t" | "yes" | "y" | "1" => Some(true),
+        _ => None,
+    }
+}
+
+/// The finite number `text` holds, if any
+fn parse_float(text: &str) -> Option<f64> {
+    text.parse::<f64>().ok().filter(|number| number.is_finite())
+}
+
+/// The number `text` holds, if it is written as a JSON number that fits in 64 bits
+fn infer_number(text: &str) -> Option<Number> {
+    let digits = |text: &str| text.bytes().take_while(u8::is_ascii_digit).count();
+
+    let unsigned = text.strip_prefix('-').unwrap_or(text);
+    let integer = digits(unsigned);
+    if integer == 0 || (integer > 1 && unsigned.starts_with('0')) {
+        return None;
+    }
+    let mut rest = &unsigned[integer..];
+    let mut float = false;
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let fraction_len = digits(fraction);
+        if fraction_len == 0 {
+            return None;
+        }
+        rest = &fraction[fraction_len..];
+        float = true;
+    }
+    if let Some(exponent) = rest.strip_prefix(['e', 'E']) {
+        let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+        let exponent_len = digits(exponent);
+        if exponent_len == 0 {
+            return None;
+        }
+        rest = &exponent[exponent_len..];
+        float = true;
+    }
+    if !rest.is_empty() {
+        return None;
+    }
+
+    if float {
+        text.parse()
+            .ok()
+            .filter(|number: &f64| number.is_finite())
+            .map(Number::Float)
+    } else if let Ok(number) = text.parse() {
+        Some(Number::Unsigned(number))
+    } else {
+        text.parse().ok().map(Number::Signed)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::JustStrDeserializer;
@@ -415,4 +765,40 @@ mod test {
         Some("hello world")
     );
     test_type!((), test_unit, "yf78iy f37y", ());
+
+    #[test]
+    fn infer_number() {
+        use super::infer_number;
+        use crate::Number;
+
+        for (text, expected) in [("0", 0), ("42", 42)] {
+            assert!(matches!(infer_number(text), Some(Number::Unsigned(n)) if n == expected));
+        }
+        assert!(matches!(infer_number("-7"), Some(Number::Signed(-7))));
+        for (text, expected) in [
+            ("1.5", 1.5),
+            ("-0.25", -0.25),
+            ("2e3", 2000.0),
+            ("1E-2", 0.01),
+        ] {
+            assert!(matches!(infer_number(text), Some(Number::Float(n)) if n == expected));
+        }
+        for text in [
+            "",
+            "-",
+            "007",
+            "+1",
+            "1.",
+            ".5",
+            "1e",
+            "1x",
+            " 1",
+            "NaN",
+            "inf",
+            "1e999",
+            "99999999999999999999",
+        ] {
+            assert!(infer_number(text).is_none(), "{text:?}");
+        }
+    }
 }
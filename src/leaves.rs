@@ -0,0 +1,159 @@
+use std::fmt;
+
+use crate::{RegexTree, Spanned};
+
+/// Location of a value within the recursion of a regex tree: which match of
+/// each node, and which capture group it was in.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodePath(Vec<PathSegment>);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PathSegment {
+    /// Index of a match among all matches of a node's regex
+    Index(usize),
+    /// Name of a capture group
+    Name(String),
+}
+
+impl NodePath {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.0
+    }
+
+    pub fn push(&mut self, segment: PathSegment) {
+        self.0.push(segment);
+    }
+
+    pub(crate) fn push_front(&mut self, segment: PathSegment) {
+        self.0.insert(0, segment);
+    }
+
+    pub fn pop(&mut self) -> Option<PathSegment> {
+        self.0.pop()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Display for NodePath {
+    /// Formats like `[1].tags[0]`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, segment) in self.0.iter().enumerate() {
+            match segment {
+                PathSegment::Index(index) => write!(f, "[{index}]")?,
+                PathSegment::Name(name) if i == 0 => write!(f, "{name}")?,
+                PathSegment::Name(name) => write!(f, ".{name}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromIterator<PathSegment> for NodePath {
+    fn from_iter<I: IntoIterator<Item = PathSegment>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+/// Walk the full recursion of a regex tree over some text and yield every
+/// leaf, meaning every capture that no child breaks down further, with its
+/// path and span.
+///
+/// A match with no named capture groups is a leaf as a whole. This mirrors
+/// what deserialization sees, but needs no types to deserialize into, which
+/// suits generic consumers such as indexers or anonymizers.
+///
+/// ## Example
+/// ```
+/// # use recursive_regex::{leaves, RegexTree};
+/// let regex_tree = RegexTree::root(r"(?P<name>\w+): (?P<nums>.*)")
+///     .with_child("nums", RegexTree::leaf(r"\d+"))
+///     .build();
+///
+/// let found: Vec<_> = leaves(&regex_tree, "a: 1 2\nb: 3")
+///     .map(|(path, value)| (path.to_string(), *value.value(), value.begin()))
+///     .collect();
+/// assert_eq!(
+///     found,
+///     vec![
+///         ("[0].name".to_owned(), "a", 0),
+///         ("[0].nums[0]".to_owned(), "1", 3),
+///         ("[0].nums[1]".to_owned(), "2", 5),
+///         ("[1].name".to_owned(), "b", 7),
+///         ("[1].nums[0]".to_owned(), "3", 10),
+///     ]
+/// );
+/// ```
+pub fn leaves<'t>(
+    regex_tree: &RegexTree,
+    text: &'t str,
+) -> impl Iterator<Item = (NodePath, Spanned<&'t str>)> {
+    let mut found = Vec::new();
+    collect(regex_tree, text, 0, &mut NodePath::new(), &mut found);
+    found.into_iter()
+}
+
+fn collect<'t>(
+    regex_tree: &RegexTree,
+    text: &'t str,
+    start: usize,
+    path: &mut NodePath,
+    found: &mut Vec<(NodePath, Spanned<&'t str>)>,
+) {
+    for (index, captures) in regex_tree.captures_iter(text).enumerate() {
+        path.push(PathSegment::Index(index));
+
+        if let Some(child) = regex_tree.child_index(0) {
+            collect(
+                child,
+                captures.as_str(),
+                start + captures.start(),
+                path,
+                found,
+            );
+            path.pop();
+            continue;
+        }
+
+        let has_named = captures.names().flatten().next().is_some();
+        let values = regex_tree.group_values(&captures);
+        for (index, (name, re_match)) in captures.names().zip(captures.iter()).enumerate() {
+            let (Some(name), Some(re_match)) = (name, re_match) else {
+                continue;
+            };
+            path.push(PathSegment::Name(name.to_owned()));
+            let group_start = start + captures.offset() + re_match.start();
+            match regex_tree.group_child(index, Some(name), values.as_ref()) {
+                Some(child) => collect(child, re_match.as_str(), group_start, path, found),
+                None => found.push((
+                    path.clone(),
+                    Spanned::new_raw(
+                        re_match.as_str(),
+                        group_start,
+                        group_start + re_match.as_str().len(),
+                    ),
+                )),
+            }
+            path.pop();
+        }
+
+        if !has_named {
+            found.push((
+                path.clone(),
+                Spanned::new_raw(
+                    captures.as_str(),
+                    start + captures.start(),
+                    start + captures.end(),
+                ),
+            ));
+        }
+
+        path.pop();
+    }
+}
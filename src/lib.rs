@@ -1,21 +1,119 @@
 #![doc = include_str!("../README.md")]
 
-use serde::de::value::Error;
+use std::ops::Range;
+
 use serde::Deserialize;
 
+#[cfg(feature = "async")]
+mod async_reader;
+mod budget;
+mod chunked;
+#[cfg(feature = "regex")]
+mod compile_stats;
+mod datetime;
+mod decoding;
+mod detect;
+mod error;
+#[cfg(feature = "export")]
+mod export;
+mod from_regex_tree;
+#[cfg(feature = "unicode-segmentation")]
+mod grapheme;
+mod headed;
+#[cfg(feature = "highlight")]
+mod highlight;
+mod incremental;
 mod just_string;
+mod leaves;
+mod lossy;
+mod match_stats;
+mod match_tree;
+mod matched;
 mod multi_capture;
+mod near_miss;
+mod node_captures;
+mod options;
+mod pattern;
+mod records;
 pub mod regex_tree;
+mod replace;
+mod schema;
+mod shared;
 mod single_capture;
+mod snapshot;
 mod spanned;
 mod string;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod trace;
+mod transcode;
 mod uncaptured;
+mod unescape;
+mod validate;
+mod value;
+mod warnings;
 
+#[cfg(feature = "derive")]
+pub use recursive_regex_derive::{regex_tree, regex_tree_static, RegexTreeFrom};
+#[cfg(feature = "regex")]
 pub use regex;
+#[cfg(all(feature = "regex-lite", not(feature = "regex")))]
+pub use regex_lite as regex;
+
+#[cfg(not(any(feature = "regex", feature = "regex-lite")))]
+compile_error!("one of the `regex` or `regex-lite` features must be enabled");
+
+#[doc(hidden)]
+pub mod __private {
+    pub use serde;
+}
 
+#[cfg(feature = "async")]
+pub use crate::async_reader::{
+    from_regex_tree_and_async_reader, from_regex_tree_and_async_reader_with_options,
+};
+pub use crate::chunked::ChunkedDeserializer;
+#[cfg(feature = "regex")]
+pub use crate::compile_stats::{CompileStats, NodeStats};
+pub use crate::detect::{
+    from_regex_trees_and_str, from_regex_trees_and_str_with_options, rank_regex_trees, TreeFit,
+};
+pub use crate::error::Error;
+#[cfg(feature = "export")]
+pub use crate::export::{write_csv, write_ndjson, ExportError};
+pub use crate::from_regex_tree::FromRegexTree;
+#[cfg(feature = "unicode-segmentation")]
+pub use crate::grapheme::Grapheme;
+#[cfg(feature = "highlight")]
+pub use crate::highlight::highlight;
+pub use crate::incremental::{reparse_records, reparse_records_with_options, Edit};
+pub use crate::just_string::JustStrDeserializer;
+pub use crate::leaves::{leaves, NodePath, PathSegment};
+pub use crate::lossy::{
+    from_regex_tree_and_bytes_lossy, from_regex_tree_and_bytes_lossy_with_options,
+    from_regex_tree_and_os_str_lossy,
+};
+pub use crate::match_stats::{MatchStats, NodeMatchStats};
+pub use crate::match_tree::{MatchGroup, MatchNode, MatchesIter};
+pub use crate::matched::Matched;
+pub use crate::options::DeserializeOptions;
+pub use crate::records::{
+    records_from_regex_tree_and_str, records_from_regex_tree_and_str_with_options, Records,
+};
 pub use crate::regex_tree::RegexTree;
-pub use crate::spanned::Spanned;
+pub use crate::replace::replace_matches;
+pub use crate::schema::Schema;
+pub use crate::shared::SharedDeserializer;
+pub use crate::snapshot::debug_snapshot;
+pub use crate::spanned::{SpanPolicy, Spanned};
 pub use crate::string::StrDeserializer;
+pub use crate::transcode::{transcode, transcode_with_options, TranscodeError};
+pub use crate::uncaptured::{Gap, Uncaptured};
+pub use crate::validate::{validate, ValidationIssue, ValidationReport};
+pub use crate::value::{to_value, to_value_with_options, Number, Value};
+pub use crate::warnings::Warning;
+
+use crate::options::DEFAULT_OPTIONS;
 
 /// Primary entry point to the library.
 ///
@@ -41,9 +139,125 @@ where
     T::deserialize(deserializer)
 }
 
+/// Like [`from_regex_tree_and_str`], but with [`DeserializeOptions`] that
+/// change how the text is deserialized.
+pub fn from_regex_tree_and_str_with_options<'t, 'r, T>(
+    regex_tree: &'r RegexTree,
+    text: &'t str,
+    options: &'r DeserializeOptions,
+) -> Result<T, Error>
+where
+    T: Deserialize<'t>,
+{
+    let deserializer =
+        StrDeserializer::from_regex_tree_and_str_with_options(regex_tree, text, options);
+    T::deserialize(deserializer).map_err(|error| error.in_source(options))
+}
+
+/// Deserialize only the bytes of `text` within `range`, as if the rest of
+/// the text were not there, while reporting spans as byte offsets into the
+/// whole text. This re-parses a changed region of a document without
+/// slicing it and fixing up offsets by hand.
+///
+/// Fails if the range is outside the text or does not start and end on
+/// character boundaries.
+///
+/// ## Example
+/// ```
+/// # use recursive_regex::{from_regex_tree_and_str_range, RegexTree, Spanned};
+/// let text = "a=1\nb=2\nc=x";
+/// let regex_tree = RegexTree::lines(r"(?P<key>\w)=(?P<value>\w+)").build();
+///
+/// let entries: Vec<(Spanned<&str>, u32)> =
+///     from_regex_tree_and_str_range(&regex_tree, text, 4..7).unwrap();
+/// assert_eq!(entries.len(), 1);
+/// assert_eq!(&text[entries[0].0.begin()..entries[0].0.end()], "b");
+///
+/// let error =
+///     from_regex_tree_and_str_range::<Vec<(&str, u32)>>(&regex_tree, text, 8..11).unwrap_err();
+/// assert_eq!(error.span(), Some(10..11));
+/// ```
+pub fn from_regex_tree_and_str_range<'t, 'r, T>(
+    regex_tree: &'r RegexTree,
+    text: &'t str,
+    range: Range<usize>,
+) -> Result<T, Error>
+where
+    T: Deserialize<'t>,
+{
+    from_regex_tree_and_str_range_with_options(regex_tree, text, range, &DEFAULT_OPTIONS)
+}
+
+/// Like [`from_regex_tree_and_str_range`], but with [`DeserializeOptions`]
+/// that change how the text is deserialized. Spans are offset by the range's
+/// start on top of any [base offset](DeserializeOptions::with_base_offset).
+pub fn from_regex_tree_and_str_range_with_options<'t, 'r, T>(
+    regex_tree: &'r RegexTree,
+    text: &'t str,
+    range: Range<usize>,
+    options: &'r DeserializeOptions,
+) -> Result<T, Error>
+where
+    T: Deserialize<'t>,
+{
+    let Some(region) = text.get(range.clone()) else {
+        let message = match range.end <= text.len() && range.start <= range.end {
+            true => format!("range {range:?} does not start and end on character boundaries"),
+            false => format!(
+                "range {range:?} is not within the text of {} bytes",
+                text.len()
+            ),
+        };
+        return Err(<Error as serde::de::Error>::custom(message).in_source(options));
+    };
+    let options = options
+        .clone()
+        .with_base_offset(options.base_offset() + range.start);
+    from_regex_tree_and_str_with_options(regex_tree, region, &options)
+}
+
 pub fn get_uncaptured<'r, 't: 'r>(
     regex_tree: &'r RegexTree,
     text: &'t str,
 ) -> impl Iterator<Item = &'t str> + 'r {
-    uncaptured::get_uncaptured(text, regex_tree.matches_iter(text))
+    uncaptured::uncaptured_ranges(text, regex_tree.captures_iter(text)).map(|range| &text[range])
+}
+
+/// Like [`get_uncaptured`], but with the span of each piece of text that the
+/// root of the regex tree does not match.
+///
+/// ## Example
+/// ```
+/// # use recursive_regex::{get_uncaptured_spanned, RegexTree};
+/// let regex_tree = RegexTree::leaf(r"\d+");
+/// let uncaptured: Vec<_> = get_uncaptured_spanned(&regex_tree, "1, 2").collect();
+/// assert_eq!(uncaptured[0].value(), &", ");
+/// assert_eq!((uncaptured[0].begin(), uncaptured[0].end()), (1, 3));
+/// ```
+pub fn get_uncaptured_spanned<'r, 't: 'r>(
+    regex_tree: &'r RegexTree,
+    text: &'t str,
+) -> impl Iterator<Item = Spanned<&'t str>> + 'r {
+    uncaptured::uncaptured_ranges(text, regex_tree.captures_iter(text))
+        .map(|range| Spanned::new_raw(&text[range.clone()], range.start, range.end))
+}
+
+/// Every piece of text that the root of the regex tree does not match, as
+/// [`get_uncaptured`] finds them, along with the line and column each starts
+/// at, so unconsumed text can be reported by where it is.
+///
+/// ## Example
+/// ```
+/// # use recursive_regex::{get_gaps, RegexTree};
+/// let regex_tree = RegexTree::leaf(r"\w+=\w+");
+/// let gaps = get_gaps(&regex_tree, "a=1\nb=2 oops\nc=3");
+/// assert_eq!(gaps[1].text(), " oops\n");
+/// assert_eq!(gaps[1].range(), 7..13);
+/// assert_eq!((gaps[1].line(), gaps[1].column()), (2, 4));
+/// ```
+pub fn get_gaps<'t>(regex_tree: &RegexTree, text: &'t str) -> Vec<Gap<'t>> {
+    uncaptured::gaps(
+        text,
+        uncaptured::uncaptured_ranges(text, regex_tree.captures_iter(text)),
+    )
 }
@@ -0,0 +1,93 @@
+use std::borrow::Cow;
+use std::ffi::OsStr;
+
+use serde::de::DeserializeOwned;
+
+use crate::options::DEFAULT_OPTIONS;
+use crate::{from_regex_tree_and_str_with_options, DeserializeOptions, Error, RegexTree};
+
+/// Stands in for each byte that is not part of valid UTF-8
+const SUBSTITUTE: char = '\u{1A}';
+
+/// Deserialize text that is mostly UTF-8 but may hold the odd invalid byte,
+/// such as a log file with a stray Latin-1 character, without the bytes
+/// regex backend.
+///
+/// Unlike [`String::from_utf8_lossy`], each invalid byte becomes the one byte
+/// ASCII substitute character, U+001A, rather than the three byte U+FFFD.
+/// Every byte of the text is therefore at the same offset as in `bytes`, so
+/// the spans of errors and [`Spanned`](crate::Spanned) values index straight
+/// into the input. Valid input is not copied.
+///
+/// ## Example
+/// ```
+/// # use recursive_regex::{from_regex_tree_and_bytes_lossy, RegexTree, Spanned};
+/// let bytes = b"caf\xe9 42\nok 7";
+/// let regex_tree = RegexTree::lines(r"(?P<word>\S+) (?P<count>\d+)").build();
+///
+/// let records: Vec<(Spanned<String>, u32)> =
+///     from_regex_tree_and_bytes_lossy(&regex_tree, bytes).unwrap();
+/// assert_eq!(*records[0].0.value(), "caf\u{1A}");
+/// assert_eq!((records[1].0.begin(), records[1].0.end()), (8, 10));
+/// assert_eq!(&bytes[8..10], b"ok");
+/// ```
+pub fn from_regex_tree_and_bytes_lossy<T>(regex_tree: &RegexTree, bytes: &[u8]) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    from_regex_tree_and_bytes_lossy_with_options(regex_tree, bytes, &DEFAULT_OPTIONS)
+}
+
+/// Like [`from_regex_tree_and_bytes_lossy`], but with [`DeserializeOptions`]
+/// that change how the text is deserialized.
+pub fn from_regex_tree_and_bytes_lossy_with_options<T>(
+    regex_tree: &RegexTree,
+    bytes: &[u8],
+    options: &DeserializeOptions,
+) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    let text = decode_lossy(bytes);
+    from_regex_tree_and_str_with_options(regex_tree, &text, options)
+}
+
+/// Deserialize an OS string, such as a path or an environment variable, as
+/// with [`from_regex_tree_and_bytes_lossy`]. Spans are offsets into
+/// [`OsStr::as_encoded_bytes`], which on Unix are the string's own bytes.
+///
+/// ## Example
+/// ```
+/// # use recursive_regex::{from_regex_tree_and_os_str_lossy, RegexTree};
+/// # use std::path::Path;
+/// let path = Path::new("logs/2024-05-01.txt");
+/// let regex_tree = RegexTree::leaf(r"(\d{4})-(\d\d)-(\d\d)");
+///
+/// let dates: Vec<(u16, u8, u8)> =
+///     from_regex_tree_and_os_str_lossy(&regex_tree, path.as_os_str()).unwrap();
+/// assert_eq!(dates, vec![(2024, 5, 1)]);
+/// ```
+pub fn from_regex_tree_and_os_str_lossy<T>(regex_tree: &RegexTree, text: &OsStr) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    from_regex_tree_and_bytes_lossy(regex_tree, text.as_encoded_bytes())
+}
+
+/// Decode `bytes` a valid run at a time, replacing each invalid byte with [`SUBSTITUTE`]
+fn decode_lossy(bytes: &[u8]) -> Cow<'_, str> {
+    let mut chunks = bytes.utf8_chunks();
+    let Some(first) = chunks.next() else {
+        return Cow::Borrowed("");
+    };
+    if first.invalid().is_empty() {
+        return Cow::Borrowed(first.valid());
+    }
+
+    let mut text = String::with_capacity(bytes.len());
+    for chunk in std::iter::once(first).chain(chunks) {
+        text.push_str(chunk.valid());
+        text.extend(chunk.invalid().iter().map(|_| SUBSTITUTE));
+    }
+    Cow::Owned(text)
+}
@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::RegexTree;
+
+/// How the nodes of a regex tree matched while deserializing, as gathered
+/// with [`DeserializeOptions::collect_stats`](crate::DeserializeOptions::collect_stats).
+/// Useful for profiling a tree, and for finding nodes that match less text
+/// than expected across a large corpus.
+///
+/// Nodes are told apart by where their patterns live in memory, so look up
+/// statistics with the same tree the text was deserialized with, not a
+/// clone of it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MatchStats {
+    nodes: HashMap<usize, NodeMatchStats>,
+}
+
+impl MatchStats {
+    /// Statistics for a single node, or `None` if it was never run
+    pub fn node(&self, regex_tree: &RegexTree) -> Option<&NodeMatchStats> {
+        self.nodes.get(&regex_tree.node_id())
+    }
+
+    /// Statistics for every node of the tree that was run, with the child
+    /// names leading to it. Parents come before their children, and siblings
+    /// are ordered by child name, then conditional children appear as
+    /// `name?n` and the default child as `*`.
+    pub fn nodes(&self, regex_tree: &RegexTree) -> Vec<(Vec<String>, NodeMatchStats)> {
+        let mut nodes = Vec::new();
+        self.collect(regex_tree, &mut Vec::new(), &mut nodes);
+        nodes
+    }
+
+    fn collect(
+        &self,
+        regex_tree: &RegexTree,
+        path: &mut Vec<String>,
+        nodes: &mut Vec<(Vec<String>, NodeMatchStats)>,
+    ) {
+        if let Some(stats) = self.node(regex_tree) {
+            nodes.push((path.clone(), *stats));
+        }
+        for (name, child) in regex_tree.child_nodes() {
+            path.push(name);
+            self.collect(child, path, nodes);
+            path.pop();
+        }
+    }
+}
+
+/// How a single node of a regex tree matched while deserializing. A node
+/// runs once each time its patterns search some text, which for a child is
+/// once per capture group it recurses on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NodeMatchStats {
+    runs: usize,
+    matches: usize,
+    bytes_searched: usize,
+    bytes_matched: usize,
+    time: Duration,
+}
+
+impl NodeMatchStats {
+    /// Number of times the node searched some text
+    pub fn runs(&self) -> usize {
+        self.runs
+    }
+
+    /// Number of matches found across all runs
+    pub fn matches(&self) -> usize {
+        self.matches
+    }
+
+    /// Total length, in bytes, of the text the node searched
+    pub fn bytes_searched(&self) -> usize {
+        self.bytes_searched
+    }
+
+    /// Total length, in bytes, of the matches found
+    pub fn bytes_matched(&self) -> usize {
+        self.bytes_matched
+    }
+
+    /// Bytes searched that were not part of any match, such as text between
+    /// matches or text where the node did not match at all
+    pub fn bytes_skipped(&self) -> usize {
+        self.bytes_searched.saturating_sub(self.bytes_matched)
+    }
+
+    /// Time spent finding the node's matches, not counting its children or
+    /// parsing values
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+}
+
+/// Shared place [`MatchStats`] are gathered into. Clones of the options that
+/// hold it gather into the same place.
+#[derive(Clone, Default)]
+pub(crate) struct StatsCollector(Arc<Mutex<MatchStats>>);
+
+impl StatsCollector {
+    /// Record that `regex_tree` starts searching `searched` bytes of text
+    pub fn start_run(&self, regex_tree: &RegexTree, searched: usize) {
+        self.update(regex_tree, |node| {
+            node.runs += 1;
+            node.bytes_searched += searched;
+        });
+    }
+
+    /// Time `search` for the next match of `regex_tree`, and record the length of the match it
+    /// finds, as given by `matched`
+    pub fn record_search<T>(
+        &self,
+        regex_tree: &RegexTree,
+        search: impl FnOnce() -> T,
+        matched: impl FnOnce(&T) -> Option<usize>,
+    ) -> T {
+        let started = Instant::now();
+        let found = search();
+        let time = started.elapsed();
+        let matched = matched(&found);
+        self.update(regex_tree, |node| {
+            node.time += time;
+            if let Some(matched) = matched {
+                node.matches += 1;
+                node.bytes_matched += matched;
+            }
+        });
+        found
+    }
+
+    fn update(&self, regex_tree: &RegexTree, update: impl FnOnce(&mut NodeMatchStats)) {
+        let mut stats = self
+            .0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        update(stats.nodes.entry(regex_tree.node_id()).or_default());
+    }
+
+    pub fn snapshot(&self) -> MatchStats {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+}
+
+impl fmt::Debug for StatsCollector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("StatsCollector").finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for StatsCollector {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for StatsCollector {}
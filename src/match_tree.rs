@@ -0,0 +1,183 @@
+use std::collections::VecDeque;
+use std::ops::Range;
+
+use crate::node_captures::{NodeCaptureMatches, NodeCaptures};
+use crate::RegexTree;
+
+/// Iterator over the matches of a regex tree node, each with the full
+/// structure beneath it, as returned by [`RegexTree::matches`].
+pub struct MatchesIter<'r, 't> {
+    regex_tree: &'r RegexTree,
+    captures: NodeCaptureMatches<'r, 't>,
+    /// Byte offset of the start of the text within the originally parsed string
+    start: usize,
+}
+
+impl<'r, 't> MatchesIter<'r, 't> {
+    pub(crate) fn new(regex_tree: &'r RegexTree, text: &'t str, start: usize) -> Self {
+        Self {
+            regex_tree,
+            captures: regex_tree.captures_iter(text),
+            start,
+        }
+    }
+
+    /// Only the last `n` of the remaining matches, in order. The text is still
+    /// searched to its end, but only the captures of the last `n` matches are
+    /// kept while searching, and children are only run on those, so this is
+    /// far cheaper than collecting every match to take the last few.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::RegexTree;
+    /// let regex_tree = RegexTree::leaf(r"\d+");
+    ///
+    /// let last: Vec<_> = regex_tree.matches("1 2 3 4").take_last(2).map(|m| m.as_str()).collect();
+    /// assert_eq!(last, vec!["3", "4"]);
+    /// ```
+    pub fn take_last(self, n: usize) -> impl ExactSizeIterator<Item = MatchNode<'r, 't>> {
+        let mut kept = VecDeque::with_capacity(n.saturating_add(1).min(1024));
+        for captures in self.captures {
+            kept.push_back(captures);
+            if kept.len() > n {
+                kept.pop_front();
+            }
+        }
+        let (regex_tree, start) = (self.regex_tree, self.start);
+        kept.into_iter()
+            .map(move |captures| MatchNode::new(regex_tree, &captures, start))
+    }
+}
+
+impl<'r, 't> Iterator for MatchesIter<'r, 't> {
+    type Item = MatchNode<'r, 't>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let captures = self.captures.next()?;
+        Some(MatchNode::new(self.regex_tree, &captures, self.start))
+    }
+
+    /// Matches passed over are not built, so `skip` does not run children on them
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let captures = self.captures.nth(n)?;
+        Some(MatchNode::new(self.regex_tree, &captures, self.start))
+    }
+}
+
+/// A single match of a regex tree node, along with its capture groups and
+/// the matches of any children run on them. All spans are byte offsets into
+/// the originally parsed string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchNode<'r, 't> {
+    pattern: &'r str,
+    text: &'t str,
+    start: usize,
+    groups: Vec<MatchGroup<'r, 't>>,
+}
+
+impl<'r, 't> MatchNode<'r, 't> {
+    fn new(regex_tree: &'r RegexTree, captures: &NodeCaptures<'r, 't>, start: usize) -> Self {
+        let offset = start + captures.offset();
+        let values = regex_tree.group_values(captures);
+        let groups = captures
+            .names()
+            .zip(captures.iter())
+            .enumerate()
+            .filter_map(|(index, (name, re_match))| {
+                let re_match = re_match?;
+                let group_start = offset + re_match.start();
+                let matches = regex_tree
+                    .group_child(index, name, values.as_ref())
+                    .map(|child| MatchesIter::new(child, re_match.as_str(), group_start).collect());
+                Some(MatchGroup {
+                    index,
+                    name,
+                    text: re_match.as_str(),
+                    start: group_start,
+                    matches,
+                })
+            })
+            .collect();
+        Self {
+            pattern: captures.pattern(),
+            text: captures.as_str(),
+            start: start + captures.start(),
+            groups,
+        }
+    }
+
+    /// The pattern of the node that made this match, which may be a fallback
+    pub fn pattern(&self) -> &'r str {
+        self.pattern
+    }
+
+    pub fn as_str(&self) -> &'t str {
+        self.text
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.start + self.text.len()
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.start()..self.end()
+    }
+
+    /// Capture groups that took part in the match, in index order, starting
+    /// with group 0 for the whole match
+    pub fn groups(&self) -> &[MatchGroup<'r, 't>] {
+        &self.groups
+    }
+
+    /// The participating capture group with the given name, if any
+    pub fn group(&self, name: &str) -> Option<&MatchGroup<'r, 't>> {
+        self.groups.iter().find(|group| group.name == Some(name))
+    }
+}
+
+/// A capture group that took part in a match
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchGroup<'r, 't> {
+    index: usize,
+    name: Option<&'r str>,
+    text: &'t str,
+    start: usize,
+    matches: Option<Vec<MatchNode<'r, 't>>>,
+}
+
+impl<'r, 't> MatchGroup<'r, 't> {
+    /// Index of the group within its pattern. Group 0 is the whole match.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn name(&self) -> Option<&'r str> {
+        self.name
+    }
+
+    pub fn as_str(&self) -> &'t str {
+        self.text
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.start + self.text.len()
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.start()..self.end()
+    }
+
+    /// Matches of the child run on this group, or `None` if no child applies
+    /// to it
+    pub fn matches(&self) -> Option<&[MatchNode<'r, 't>]> {
+        self.matches.as_deref()
+    }
+}
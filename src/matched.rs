@@ -0,0 +1,181 @@
+use std::marker::PhantomData;
+use std::{fmt, mem};
+
+use serde::de::value::BorrowedStrDeserializer;
+use serde::de::{self, Deserialize, Deserializer};
+
+pub(crate) const MATCHED_NAME: &str = "  __MATCHED";
+pub(crate) const MATCHED_TEXT: &str = "  __MATCHED_TEXT";
+pub(crate) const MATCHED_VALUE: &str = "  __MATCHED_VALUE";
+
+/// A deserialized value along with the exact text it was parsed from, such
+/// as for logging a record or writing it back out unchanged.
+///
+/// The text is that of the whole match for an element of a sequence, such as
+/// `Vec<Matched<Record>>`, the text of the capture group for a group's value,
+/// and otherwise all of the text the regex tree node was run over, as with
+/// [`Spanned`](crate::Spanned).
+///
+/// The text is a `String` by default. Use `Matched<T, &str>` to borrow it
+/// from the input instead.
+///
+/// ## Example
+/// ```
+/// # use recursive_regex::{from_regex_tree_and_str, Matched, RegexTree};
+/// # use serde::Deserialize;
+/// #[derive(Deserialize)]
+/// struct Reading {
+///     sensor: String,
+///     value: f64,
+/// }
+///
+/// let regex_tree = RegexTree::lines(r"(?P<sensor>\w+)\s*=\s*(?P<value>\S+)").build();
+/// let readings: Vec<Matched<Reading, &str>> =
+///     from_regex_tree_and_str(&regex_tree, "a = 1.5\nb=2").unwrap();
+/// assert_eq!(readings[0].value().value, 1.5);
+/// assert_eq!(*readings[0].text(), "a = 1.5");
+/// assert_eq!(*readings[1].text(), "b=2");
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Matched<T, S = String> {
+    text: S,
+    value: T,
+}
+
+impl<T, S> Matched<T, S> {
+    pub fn new(value: T, text: S) -> Self {
+        Self { text, value }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+
+    /// The value and the text it was parsed from
+    pub fn into_parts(self) -> (T, S) {
+        (self.value, self.text)
+    }
+
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    pub fn text(&self) -> &S {
+        &self.text
+    }
+}
+
+impl<'de, T, S> Deserialize<'de> for Matched<T, S>
+where
+    T: Deserialize<'de>,
+    S: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_struct(
+            MATCHED_NAME,
+            &[MATCHED_TEXT, MATCHED_VALUE],
+            MatchedVisitor(PhantomData),
+        )
+    }
+}
+
+struct MatchedVisitor<T, S>(PhantomData<(T, S)>);
+
+impl<'de, T, S> de::Visitor<'de> for MatchedVisitor<T, S>
+where
+    T: Deserialize<'de>,
+    S: Deserialize<'de>,
+{
+    type Value = Matched<T, S>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a regex match with its text")
+    }
+
+    fn visit_map<A>(self, mut visitor: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        fn check_key<'de, A>(visitor: &mut A, expected: &str, name: &str) -> Result<(), A::Error>
+        where
+            A: de::MapAccess<'de>,
+        {
+            match visitor.next_key::<&str>()? {
+                Some(key) if key == expected => Ok(()),
+                _ => Err(de::Error::custom(format!("`Matched` {name} key not found"))),
+            }
+        }
+
+        check_key(&mut visitor, MATCHED_TEXT, "text")?;
+        let text: S = visitor.next_value()?;
+
+        check_key(&mut visitor, MATCHED_VALUE, "value")?;
+        let value: T = visitor.next_value()?;
+
+        Ok(Matched { text, value })
+    }
+}
+
+/// Whether `name` and `fields` are those of [`Matched`]
+pub(crate) fn is_matched(name: &str, fields: &[&str]) -> bool {
+    name == MATCHED_NAME && fields == [MATCHED_TEXT, MATCHED_VALUE]
+}
+
+/// Map of a [`Matched`] value: the text it came from, then the value deserialized from `T`
+pub(crate) struct MatchedDeserializer<'de, T, E> {
+    state: MatchedState<'de, T>,
+    error: PhantomData<E>,
+}
+
+enum MatchedState<'de, T> {
+    Text(&'de str, T),
+    Value(T),
+    None,
+}
+
+impl<'de, T, E> MatchedDeserializer<'de, T, E> {
+    pub fn new(text: &'de str, value: T) -> Self {
+        Self {
+            state: MatchedState::Text(text, value),
+            error: PhantomData,
+        }
+    }
+}
+
+impl<'de, T, E> de::MapAccess<'de> for MatchedDeserializer<'de, T, E>
+where
+    T: Deserializer<'de, Error = E>,
+    E: de::Error,
+{
+    type Error = E;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        let key = match self.state {
+            MatchedState::Text(..) => MATCHED_TEXT,
+            MatchedState::Value(..) => MATCHED_VALUE,
+            MatchedState::None => return Ok(None),
+        };
+        seed.deserialize(BorrowedStrDeserializer::new(key))
+            .map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        match mem::replace(&mut self.state, MatchedState::None) {
+            MatchedState::Text(text, value) => {
+                self.state = MatchedState::Value(value);
+                seed.deserialize(BorrowedStrDeserializer::new(text))
+            }
+            MatchedState::Value(value) => seed.deserialize(value),
+            MatchedState::None => Err(E::custom("no more values for `Matched`")),
+        }
+    }
+}
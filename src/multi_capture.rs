@@ -1,30 +1,242 @@
-use crate::regex::CaptureMatches;
+use std::collections::VecDeque;
+use std::time::Instant;
+
 use serde::de;
-use serde::de::value::Error;
 use serde::de::SeqAccess;
 
+use crate::near_miss::NearMiss;
+use crate::node_captures::{NodeCaptureMatches, NodeCaptures, Unmatched};
+use crate::options::DeserializeOptions;
 use crate::single_capture::SingleCaptureDeserializer;
-use crate::RegexTree;
+use crate::string::StrDeserializer;
+use crate::trace::trace_span;
+use crate::{Error, PathSegment, RegexTree};
 
 pub struct MultiCaptureSeqAccess<'r, 't> {
     regex_tree: &'r RegexTree,
-    captures: CaptureMatches<'r, 't>,
+    options: &'r DeserializeOptions,
+    captures: NodeCaptureMatches<'r, 't>,
     /// Byte offset of the start of the string `capture` is over within the originally parsed string
     start: usize,
+    /// The string `captures` is over
+    text: &'t str,
+    /// Number of matches deserialized so far
+    count: usize,
+    /// Whether having no matches at all is an error, whatever the node's count
+    require_match: bool,
+    /// Whether every match has been seen, after which there are no more elements
+    exhausted: bool,
+    /// Exact number of matches there must be, for a tuple or array
+    len: Option<usize>,
+    /// Whether the text has been counted against the scan limit
+    scanned: bool,
+    /// Byte offset within the text of the end of the last match
+    last_end: usize,
+    /// Number of matches at the end of the text to deserialize, if not all of them
+    take_last: Option<usize>,
+    /// The last matches of the text, once it has been searched for them
+    kept: Option<VecDeque<Result<NodeCaptures<'r, 't>, Unmatched>>>,
+    /// Number of matches passed over before the first that is deserialized
+    skipped: usize,
+    /// Number of matches still to pass over, for [`DeserializeOptions::skip`]
+    skip: usize,
+    /// Number of matches to deserialize at most, for [`DeserializeOptions::take`]
+    take: Option<usize>,
 }
 
 impl<'r, 't> MultiCaptureSeqAccess<'r, 't> {
     pub fn from_regex_tree_and_captures(
         regex_tree: &'r RegexTree,
-        captures: CaptureMatches<'r, 't>,
+        options: &'r DeserializeOptions,
+        captures: NodeCaptureMatches<'r, 't>,
+        text: &'t str,
         start: usize,
     ) -> Self {
+        if let Some(stats) = options.stats_collector() {
+            stats.start_run(regex_tree, text.len());
+        }
         Self {
             regex_tree,
+            options,
             captures,
+            text,
             start,
+            count: 0,
+            require_match: false,
+            exhausted: false,
+            len: None,
+            scanned: false,
+            last_end: 0,
+            take_last: None,
+            kept: None,
+            skipped: 0,
+            skip: 0,
+            take: None,
+        }
+    }
+
+    /// Byte offset of the end of the string `captures` is over within the originally parsed string
+    fn end(&self) -> usize {
+        self.start + self.text.len()
+    }
+
+    /// Apply the options that select which matches of the root of a tree are deserialized:
+    /// [`DeserializeOptions::take_last`], [`DeserializeOptions::skip`],
+    /// [`DeserializeOptions::take`] and [`DeserializeOptions::require_match`]
+    pub fn root(mut self) -> Self {
+        self.take_last = self.options.last_matches();
+        self.skip = self.options.skipped_matches();
+        self.take = self.options.max_matches();
+        self.require_match = self.options.is_require_match();
+        self
+    }
+
+    /// Fail unless there are exactly `len` matches, for a tuple or array. Check for matches
+    /// beyond them with [`check_no_more`](Self::check_no_more) once the elements are
+    /// deserialized.
+    pub fn exact_len(mut self, len: usize) -> Self {
+        self.len = Some(len);
+        self
+    }
+
+    /// Fail if there are matches left over after the elements of a tuple or array, counting them
+    /// all for the error
+    pub fn check_no_more(&mut self) -> Result<(), Error> {
+        let Some(len) = self.len else {
+            return Ok(());
+        };
+        let mut found = self.count;
+        while !self.exhausted {
+            self.check_limits()?;
+            match self.next_capture()? {
+                Some(capture) => {
+                    capture.map_err(|unmatched| self.unmatched_error(unmatched))?;
+                    found += 1;
+                }
+                None => self.exhausted = true,
+            }
+        }
+        match found > len {
+            true => Err(self.len_error(found)),
+            false => Ok(()),
+        }
+    }
+
+    /// Count the matches there are to deserialize, searching the text within the same limits as
+    /// deserializing them
+    pub fn count_matches(mut self) -> Result<usize, Error> {
+        let mut found = 0;
+        while self.take.is_none_or(|take| found < take) {
+            self.check_limits()?;
+            match self.next_capture()? {
+                Some(_) => found += 1,
+                None => break,
+            }
+        }
+        Ok(found)
+    }
+
+    fn len_error(&self, found: usize) -> Error {
+        let len = self.len.unwrap_or_default();
+        let message =
+            format!("expected {len} matches for a tuple of {len} elements but found {found}");
+        Error::with_span(message, self.start, self.end())
+    }
+
+    /// Fail if the deadline has passed or the scan limit is used up, before searching further.
+    /// See [`DeserializeOptions::with_deadline`] and [`DeserializeOptions::with_scan_limit`].
+    fn check_limits(&mut self) -> Result<(), Error> {
+        if !self.scanned {
+            self.scanned = true;
+            if let Some(budget) = self.options.scan_budget() {
+                if !budget.charge(self.text.len()) {
+                    let message = format!("scan limit of {} bytes exceeded", budget.limit());
+                    return Err(Error::with_span(message, self.start, self.end()));
+                }
+            }
+        }
+        match self.options.deadline() {
+            Some(deadline) if Instant::now() >= deadline => Err(Error::with_span(
+                "deadline passed before the text was deserialized",
+                self.start + self.last_end,
+                self.end(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// The next match to deserialize, after passing over those to skip
+    fn next_capture(&mut self) -> Result<Option<Result<NodeCaptures<'r, 't>, Unmatched>>, Error> {
+        while self.skip > 0 {
+            self.skip -= 1;
+            if self.next_kept()?.is_none() {
+                return Ok(None);
+            }
+            self.skipped += 1;
+            self.check_limits()?;
+        }
+        self.next_kept()
+    }
+
+    /// The next match, of those kept. When only the last matches are, the whole text is searched
+    /// first, keeping just those.
+    fn next_kept(&mut self) -> Result<Option<Result<NodeCaptures<'r, 't>, Unmatched>>, Error> {
+        let Some(n) = self.take_last else {
+            return Ok(self.search());
+        };
+        if self.kept.is_none() {
+            let mut kept = VecDeque::with_capacity(n.saturating_add(1).min(1024));
+            while let Some(capture) = self.search() {
+                kept.push_back(capture);
+                if kept.len() > n {
+                    kept.pop_front();
+                    self.skipped += 1;
+                }
+                self.check_limits()?;
+            }
+            self.kept = Some(kept);
+        }
+        Ok(self.kept.as_mut().and_then(VecDeque::pop_front))
+    }
+
+    /// Search for the next match, recording statistics if they are being collected
+    fn search(&mut self) -> Option<Result<NodeCaptures<'r, 't>, Unmatched>> {
+        match self.options.stats_collector() {
+            Some(stats) => stats.record_search(
+                self.regex_tree,
+                || self.captures.try_next(),
+                |capture| match capture {
+                    Some(Ok(capture)) => Some(capture.end() - capture.start()),
+                    _ => None,
+                },
+            ),
+            None => self.captures.try_next(),
+        }
+    }
+
+    fn no_match_error(&self) -> Error {
+        let patterns: Vec<String> = self
+            .regex_tree
+            .patterns()
+            .map(|pattern| format!("`{pattern}`"))
+            .collect();
+        let message = format!("no match of {} in the text", patterns.join(" or "));
+        if !self.options.is_near_misses() {
+            return Error::with_span(message, self.start, self.end());
+        }
+        match NearMiss::find(self.regex_tree, self.text) {
+            Some(near_miss) => Error::with_span(
+                format!("{message}; {near_miss}"),
+                self.start + near_miss.start(),
+                self.start + near_miss.end(),
+            ),
+            None => Error::with_span(message, self.start, self.end()),
         }
     }
+
+    fn unmatched_error(&self, unmatched: Unmatched) -> Error {
+        unmatched.error(self.start, self.end())
+    }
 }
 
 impl<'de, 'r> SeqAccess<'de> for MultiCaptureSeqAccess<'r, 'de> {
@@ -34,17 +246,91 @@ impl<'de, 'r> SeqAccess<'de> for MultiCaptureSeqAccess<'r, 'de> {
     where
         T: de::DeserializeSeed<'de>,
     {
-        self.captures
-            .next()
-            .map(|capture| {
-                seed.deserialize(
-                    SingleCaptureDeserializer::from_regex_tree_and_single_capture(
-                        self.regex_tree,
-                        capture.iter(),
-                        self.start,
-                    ),
-                )
-            })
-            .transpose()
+        if self.exhausted {
+            return Ok(None);
+        }
+        if self.take.is_some_and(|take| self.count >= take) {
+            self.exhausted = true;
+            return Ok(None);
+        }
+        self.check_limits()?;
+        let count = self.regex_tree.count();
+        let capture = self.next_capture()?;
+        self.exhausted = capture.is_none();
+        // Matches passed over for `take_last` or `skip` still count towards the node's count
+        let found = self.skipped + self.count;
+        let capture = match capture {
+            Some(capture) => capture.map_err(|unmatched| self.unmatched_error(unmatched))?,
+            None if found == 0 && self.require_match => return Err(self.no_match_error()),
+            None if self.len.is_some_and(|len| self.count < len) => {
+                return Err(self.len_error(self.count));
+            }
+            None if found < count.min() || count.max().is_some_and(|max| found > max) => {
+                let message = format!("expected {count} matches but found {found}");
+                return Err(Error::with_span(message, self.start, self.end()));
+            }
+            None => return Ok(None),
+        };
+
+        self.count += 1;
+        self.last_end = capture.end();
+        let index = self.skipped + self.count - 1;
+        if count.max().is_some_and(|max| index >= max) {
+            return Err(Error::with_span(
+                format!("expected {count} matches but found more"),
+                self.start + capture.start(),
+                self.start + capture.end(),
+            ));
+        }
+
+        trace_span!(
+            "match",
+            index,
+            start = self.start + capture.start(),
+            end = self.start + capture.end(),
+        );
+        let value = deserialize_capture(
+            self.regex_tree,
+            self.options,
+            &capture,
+            index,
+            self.start,
+            seed,
+        );
+        value.map(Some).map_err(|error| {
+            // Errors raised once the record's data is buffered, such as when no variant of an
+            // untagged enum fits, cover the whole record
+            error
+                .or_span(self.start + capture.start(), self.start + capture.end())
+                .within(PathSegment::Index(index))
+        })
+    }
+}
+
+/// Deserialize match `index` of a node deserialized as a sequence. A child for group 0, or for
+/// the index of a section, recurses on the whole match.
+pub(crate) fn deserialize_capture<'de, 'r, T>(
+    regex_tree: &'r RegexTree,
+    options: &'r DeserializeOptions,
+    capture: &NodeCaptures<'r, 'de>,
+    index: usize,
+    start: usize,
+    seed: T,
+) -> Result<T::Value, Error>
+where
+    T: de::DeserializeSeed<'de>,
+{
+    match regex_tree.match_child(index) {
+        Some(child) => seed.deserialize(StrDeserializer::from_regex_tree_and_offset_str(
+            child,
+            options,
+            capture.as_str(),
+            start + capture.start(),
+        )),
+        None => seed.deserialize(
+            SingleCaptureDeserializer::from_regex_tree_and_single_capture(
+                regex_tree, options, capture, start,
+            ),
+        ),
     }
 }
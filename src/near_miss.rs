@@ -0,0 +1,98 @@
+use std::fmt;
+
+use crate::RegexTree;
+
+/// The longest start of one of a node's patterns that matches somewhere in
+/// text the whole pattern does not match, for
+/// [`DeserializeOptions::near_misses`](crate::DeserializeOptions::near_misses).
+/// The text after the match is where the rest of the pattern failed.
+pub(crate) struct NearMiss<'t> {
+    /// The start of the pattern, with any groups it leaves open closed
+    prefix: String,
+    /// Text the start of the pattern matches
+    text: &'t str,
+    /// Byte offset of `text` within the text searched
+    start: usize,
+}
+
+impl<'t> NearMiss<'t> {
+    /// Find the near miss of `regex_tree`'s patterns in `text` that matches the most text, trying
+    /// each start of each pattern from longest to shortest. This compiles many patterns, so it
+    /// is only done once deserialization has failed.
+    pub fn find(regex_tree: &RegexTree, text: &'t str) -> Option<Self> {
+        regex_tree
+            .compiled_patterns()
+            .iter()
+            .filter_map(|pattern| {
+                let source = pattern.as_str();
+                let ends = source.char_indices().rev().map(|(index, _)| index);
+                ends.filter_map(|end| {
+                    let prefix = close_groups(&source[..end])?;
+                    let found = regex_tree
+                        .compile_like(pattern, &prefix)?
+                        .captures(text)?
+                        .get(0)?;
+                    (!found.as_str().is_empty()).then(|| Self {
+                        prefix,
+                        text: found.as_str(),
+                        start: found.start(),
+                    })
+                })
+                .next()
+            })
+            .max_by_key(|near_miss| near_miss.text.len())
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.start + self.text.len()
+    }
+}
+
+impl fmt::Display for NearMiss<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "the longest start of a pattern that matches is `{}`, which matches {:?}",
+            self.prefix, self.text
+        )
+    }
+}
+
+/// Close the groups left open by the start of a pattern, or `None` if it ends
+/// partway through an escape or a character class, or just after opening a
+/// group, which would only add an empty group
+fn close_groups(prefix: &str) -> Option<String> {
+    let mut open = Vec::new();
+    let mut class_depth = 0usize;
+    let mut chars = prefix.char_indices();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next()?;
+            }
+            '[' => class_depth += 1,
+            ']' if class_depth > 0 => class_depth -= 1,
+            '(' if class_depth == 0 => open.push(index),
+            ')' if class_depth == 0 => {
+                open.pop()?;
+            }
+            _ => {}
+        }
+    }
+    if class_depth > 0 {
+        return None;
+    }
+    if let Some(&innermost) = open.last() {
+        let opener = &prefix[innermost + 1..];
+        if opener.is_empty() || opener.starts_with('?') && opener.ends_with([':', '>', '=', '!']) {
+            return None;
+        }
+    }
+    let mut closed = prefix.to_owned();
+    closed.extend(std::iter::repeat_n(')', open.len()));
+    Some(closed)
+}
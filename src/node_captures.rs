@@ -0,0 +1,659 @@
+use std::fmt;
+
+use crate::pattern::{CaptureNames, Captures, Match, Pattern, SubCaptureMatches};
+#[cfg(feature = "regex")]
+use crate::regex::RegexSet;
+use crate::regex::{CaptureMatches, Matches, Regex};
+use crate::regex_tree::{MatchPolicy, Split};
+use crate::{Error, RegexTree};
+
+/// Captures from one of the patterns of a regex tree node, along with the
+/// pattern that produced them. Patterns of the same node may have different
+/// capture groups, so the names must come from the pattern that matched.
+pub struct NodeCaptures<'r, 't> {
+    regex: &'r Pattern,
+    captures: Captures<'t>,
+    /// Byte offset of the text the regex ran on within the text of the node, such as the start of
+    /// a line. Positions of the captures themselves are relative to the text the regex ran on.
+    offset: usize,
+}
+
+impl<'r, 't> NodeCaptures<'r, 't> {
+    pub fn new(regex: &'r Pattern, captures: Captures<'t>) -> Self {
+        Self {
+            regex,
+            captures,
+            offset: 0,
+        }
+    }
+
+    fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn names(&self) -> CaptureNames<'r> {
+        self.regex.capture_names()
+    }
+
+    pub fn iter(&self) -> SubCaptureMatches<'_, 't> {
+        self.captures.iter()
+    }
+
+    /// Number of capture groups of the pattern, including group 0
+    pub fn captures_len(&self) -> usize {
+        self.regex.captures_len()
+    }
+
+    /// Source of the pattern that produced the captures
+    pub fn pattern(&self) -> &'r str {
+        self.regex.as_str()
+    }
+
+    /// The whole match, capture group 0
+    pub fn whole_match(&self) -> Match<'t> {
+        self.captures.get(0).expect("group 0 is the whole match")
+    }
+
+    /// Pieces of the whole match outside every capture group that took part, in order
+    pub fn uncaptured(&self) -> Vec<Match<'t>> {
+        let whole_match = self.whole_match();
+        let mut groups: Vec<_> = self
+            .iter()
+            .skip(1)
+            .flatten()
+            .map(|group| group.range())
+            .collect();
+        groups.sort_by_key(|group| group.start);
+        let mut pieces = Vec::new();
+        let mut end = whole_match.start();
+        for group in groups {
+            let start = group.start.min(whole_match.end());
+            if start > end {
+                pieces.push(whole_match.slice(end..start));
+            }
+            end = end.max(group.end);
+        }
+        if whole_match.end() > end {
+            pieces.push(whole_match.slice(end..whole_match.end()));
+        }
+        pieces
+    }
+
+    /// Text of the whole match
+    pub fn as_str(&self) -> &'t str {
+        self.whole_match().as_str()
+    }
+
+    /// Start of the whole match within the text of the node
+    pub fn start(&self) -> usize {
+        self.offset + self.whole_match_range().start
+    }
+
+    /// End of the whole match within the text of the node
+    pub fn end(&self) -> usize {
+        self.offset + self.whole_match_range().end
+    }
+
+    fn whole_match_range(&self) -> std::ops::Range<usize> {
+        self.whole_match().range()
+    }
+}
+
+/// The patterns of a regex tree node, the primary pattern followed by any
+/// fallbacks, along with a set of all of them when there is more than one.
+/// One pass of the set finds which patterns match at all, so patterns that
+/// cannot match are skipped instead of being run for their captures.
+#[derive(Clone, Copy)]
+pub struct Patterns<'r> {
+    regexes: &'r [Pattern],
+    #[cfg(feature = "regex")]
+    set: Option<&'r RegexSet>,
+}
+
+impl<'r> Patterns<'r> {
+    pub fn new(regexes: &'r [Pattern]) -> Self {
+        Self {
+            regexes,
+            #[cfg(feature = "regex")]
+            set: None,
+        }
+    }
+
+    #[cfg(feature = "regex")]
+    pub fn with_set(self, set: Option<&'r RegexSet>) -> Self {
+        Self { set, ..self }
+    }
+
+    /// Captures of the first pattern that matches `text`
+    pub fn captures<'t>(self, text: &'t str) -> Option<NodeCaptures<'r, 't>> {
+        self.candidates(text).into_iter().find_map(|regex| {
+            regex
+                .captures(text)
+                .map(|captures| NodeCaptures::new(regex, captures))
+        })
+    }
+
+    /// Index of the first pattern that matches `text`
+    pub fn position(self, text: &str) -> Option<usize> {
+        #[cfg(feature = "regex")]
+        if let Some(set) = self.set {
+            return set.matches(text).iter().next();
+        }
+        self.regexes.iter().position(|regex| regex.is_match(text))
+    }
+
+    /// Patterns that may match somewhere in `text`, in order
+    #[cfg_attr(not(feature = "regex"), allow(unused_variables))]
+    fn candidates(self, text: &str) -> Vec<&'r Pattern> {
+        #[cfg(feature = "regex")]
+        if let Some(set) = self.set {
+            return set
+                .matches(text)
+                .iter()
+                .map(|index| &self.regexes[index])
+                .collect();
+        }
+        self.regexes.iter().collect()
+    }
+}
+
+/// Iterator over successive captures of a regex tree node.
+///
+/// With a single pattern and the default [`MatchPolicy`], this is just the
+/// regex crate's iterator. With fallback patterns, at every position the
+/// earliest match of any pattern is taken, preferring earlier patterns when
+/// two matches begin at the same spot.
+/// For a split node, there is one match per piece. For a node that must match
+/// its whole text, there is exactly one match.
+pub enum NodeCaptureMatches<'r, 't> {
+    Single(CaptureMatches<'r, 't>, &'r Pattern),
+    Stepped(SteppedCaptureMatches<'r, 't>),
+    Pieces(PieceCaptureMatches<'r, 't>),
+    /// The match of the whole text, if it has not been taken yet
+    Whole(Option<Option<NodeCaptures<'r, 't>>>),
+    /// Matches of a node with filters, skipping those it does not keep
+    Filtered(Box<NodeCaptureMatches<'r, 't>>, &'r RegexTree),
+}
+
+impl<'r, 't> NodeCaptureMatches<'r, 't> {
+    pub fn new(patterns: Patterns<'r>, policy: MatchPolicy, text: &'t str) -> Self {
+        match (patterns.regexes, policy) {
+            ([pattern @ Pattern::Regex(regex)], MatchPolicy::Search) => {
+                Self::Single(regex.captures_iter(text), pattern)
+            }
+            _ => Self::Stepped(SteppedCaptureMatches::new(patterns, policy, text)),
+        }
+    }
+
+    pub fn whole(captures: Option<NodeCaptures<'r, 't>>) -> Self {
+        Self::Whole(Some(captures))
+    }
+
+    pub fn split(patterns: Patterns<'r>, split: &'r Split, text: &'t str) -> Self {
+        Self::Pieces(PieceCaptureMatches::new(patterns, split, text))
+    }
+
+    pub fn filtered(captures_iter: Self, regex_tree: &'r RegexTree) -> Self {
+        Self::Filtered(Box::new(captures_iter), regex_tree)
+    }
+
+    /// Like `next`, but report text that was required to match and did not instead of skipping it
+    pub fn try_next(&mut self) -> Option<Result<NodeCaptures<'r, 't>, Unmatched>> {
+        match self {
+            Self::Single(captures_iter, pattern) => captures_iter
+                .next()
+                .map(|captures| Ok(NodeCaptures::new(pattern, Captures::Regex(captures)))),
+            Self::Stepped(stepped) => stepped.next_match(),
+            Self::Pieces(pieces) => pieces.next_piece(),
+            Self::Whole(whole) => whole
+                .take()
+                .map(|captures| captures.ok_or(Unmatched::Whole)),
+            Self::Filtered(captures_iter, regex_tree) => loop {
+                match captures_iter.try_next()? {
+                    Ok(captures) if !regex_tree.is_kept(&captures) => continue,
+                    next => return Some(next),
+                }
+            },
+        }
+    }
+}
+
+impl<'r, 't> Iterator for NodeCaptureMatches<'r, 't> {
+    type Item = NodeCaptures<'r, 't>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Ok(captures) = self.try_next()? {
+                return Some(captures);
+            }
+        }
+    }
+}
+
+/// Captures found by searching with every pattern from a position that moves
+/// along the text according to a [`MatchPolicy`]
+pub struct SteppedCaptureMatches<'r, 't> {
+    /// Patterns that may still match. A pattern that fails to match after some position will not
+    /// match after any later position either, so it is dropped.
+    patterns: Vec<&'r Pattern>,
+    policy: MatchPolicy,
+    text: &'t str,
+    /// Byte offset to resume searching from
+    position: usize,
+    /// End of the last match, used to skip empty matches directly after it the same way the regex
+    /// crate does
+    last_end: Option<usize>,
+    /// Match found after a gap, to be returned after the gap is reported
+    pending: Option<NodeCaptures<'r, 't>>,
+    /// Whether the end of the text has been reached
+    done: bool,
+}
+
+impl<'r, 't> SteppedCaptureMatches<'r, 't> {
+    fn new(patterns: Patterns<'r>, policy: MatchPolicy, text: &'t str) -> Self {
+        Self {
+            patterns: patterns.candidates(text),
+            policy,
+            text,
+            position: 0,
+            last_end: None,
+            pending: None,
+            done: false,
+        }
+    }
+
+    /// Earliest match of any pattern at or after `position`
+    fn earliest(&mut self) -> Option<NodeCaptures<'r, 't>> {
+        let mut earliest: Option<NodeCaptures<'r, 't>> = None;
+        let mut matched = Vec::with_capacity(self.patterns.len());
+        for &regex in &self.patterns {
+            let Some(captures) = self.captures_at(regex, self.position) else {
+                continue;
+            };
+            matched.push(regex);
+            // Keep the first of equal starts, so earlier patterns win ties
+            if earliest
+                .as_ref()
+                .is_none_or(|earliest| captures.start() < earliest.start())
+            {
+                earliest = Some(captures);
+            }
+        }
+        self.patterns = matched;
+        earliest
+    }
+
+    fn captures_at(&self, regex: &'r Pattern, mut position: usize) -> Option<NodeCaptures<'r, 't>> {
+        loop {
+            let captures = NodeCaptures::new(regex, regex.captures_at(self.text, position)?);
+            let is_empty = captures.start() == captures.end();
+            if !is_empty || Some(captures.end()) != self.last_end {
+                return Some(captures);
+            }
+            position = self.next_char_boundary(position)?;
+        }
+    }
+
+    fn next_char_boundary(&self, position: usize) -> Option<usize> {
+        self.text[position..]
+            .chars()
+            .next()
+            .map(|c| position + c.len_utf8())
+    }
+
+    fn next_match(&mut self) -> Option<Result<NodeCaptures<'r, 't>, Unmatched>> {
+        if let Some(captures) = self.pending.take() {
+            return Some(Ok(self.advance(captures)));
+        }
+        if self.done {
+            return None;
+        }
+
+        let Some(captures) = self.earliest() else {
+            self.done = true;
+            let has_gap =
+                self.policy == MatchPolicy::Consecutive && self.position < self.text.len();
+            return has_gap.then(|| Err(self.gap(self.text.len())));
+        };
+
+        if self.policy == MatchPolicy::Consecutive && captures.start() > self.position {
+            let gap = self.gap(captures.start());
+            self.pending = Some(captures);
+            return Some(Err(gap));
+        }
+        Some(Ok(self.advance(captures)))
+    }
+
+    /// Move past a match that is about to be returned
+    fn advance(&mut self, captures: NodeCaptures<'r, 't>) -> NodeCaptures<'r, 't> {
+        match self.policy {
+            MatchPolicy::Search | MatchPolicy::Consecutive => {
+                self.position = captures.end();
+                self.last_end = Some(captures.end());
+            }
+            MatchPolicy::Overlapping => match self.next_char_boundary(captures.start()) {
+                Some(position) => self.position = position,
+                None => self.done = true,
+            },
+        }
+        captures
+    }
+
+    fn gap(&self, end: usize) -> Unmatched {
+        Unmatched::Gap {
+            start: self.position,
+            end,
+        }
+    }
+}
+
+/// Text that a node's patterns were required to match, but did not
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unmatched {
+    /// A piece of the text of a split node
+    Piece {
+        /// What the pieces are called, such as "line"
+        kind: &'static str,
+        /// Number of the piece, starting from 1
+        number: usize,
+        /// Byte offset of the start of the piece within the text of the node
+        start: usize,
+        /// Byte offset of the end of the piece within the text of the node
+        end: usize,
+    },
+    /// The whole text of a node that must match all of it
+    Whole,
+    /// Text between consecutive matches, or after the last one
+    Gap {
+        /// Byte offset of the start of the gap within the text of the node
+        start: usize,
+        /// Byte offset of the end of the gap within the text of the node
+        end: usize,
+    },
+}
+
+impl Unmatched {
+    /// Error spanning the unmatched text, for a node whose text spans `start..end` of the
+    /// originally parsed string
+    pub fn error(self, start: usize, end: usize) -> Error {
+        let (error_start, error_end) = match self {
+            Self::Piece {
+                start: piece_start,
+                end: piece_end,
+                ..
+            }
+            | Self::Gap {
+                start: piece_start,
+                end: piece_end,
+            } => (start + piece_start, start + piece_end),
+            Self::Whole => (start, end),
+        };
+        Error::with_span(self, error_start, error_end)
+    }
+
+    /// Byte offset of the start of the unmatched text within the text of the node
+    pub fn start(&self) -> usize {
+        match self {
+            Self::Piece { start, .. } | Self::Gap { start, .. } => *start,
+            Self::Whole => 0,
+        }
+    }
+}
+
+impl fmt::Display for Unmatched {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Piece { kind, number, .. } => write!(f, "{kind} {number} does not match"),
+            Self::Whole => write!(f, "regular expression does not match the whole text"),
+            Self::Gap { .. } => write!(f, "text between matches is not matched"),
+        }
+    }
+}
+
+/// Iterator over the captures of each piece of some text. Each piece is
+/// matched on its own, so patterns never see more than one piece.
+pub struct PieceCaptureMatches<'r, 't> {
+    patterns: Patterns<'r>,
+    pieces: Pieces<'r, 't>,
+    /// Number of pieces already matched
+    number: usize,
+}
+
+/// Byte offset and text of a piece
+type Piece<'t> = (usize, &'t str);
+
+enum Pieces<'r, 't> {
+    /// Lines end with `\n` or `\r\n`, and a line ending at the end of the text does not begin
+    /// another line
+    Lines {
+        text: &'t str,
+        /// Byte offset of the start of the next line
+        position: usize,
+    },
+    /// Pieces are separated by matches of a delimiter. Empty text has no pieces, but otherwise
+    /// empty pieces are kept.
+    Delimited {
+        text: &'t str,
+        delimiters: Delimiters<'r, 't>,
+        /// Byte offset of the start of the next piece, or `None` once there are no more pieces
+        position: Option<usize>,
+    },
+    /// Each piece begins at a match of a pattern and runs up to the next, without a final line
+    /// ending. Text before the first match is a piece unless it is only whitespace.
+    Frames {
+        text: &'t str,
+        starts: Matches<'r, 't>,
+        /// Byte offset of the start of the next piece, or `None` once there are no more pieces
+        position: Option<usize>,
+    },
+    /// Runs of lines that are not blank, without their final line ending
+    Paragraphs {
+        text: &'t str,
+        /// Byte offset from which to look for the next paragraph
+        position: usize,
+    },
+    /// Each piece is the first match of the next region pattern after the previous piece
+    Sections {
+        text: &'t str,
+        regions: std::slice::Iter<'r, Regex>,
+        /// Byte offset from which to look for the next section, or `None` once one was missing
+        position: Option<usize>,
+    },
+}
+
+impl<'r, 't> PieceCaptureMatches<'r, 't> {
+    fn new(patterns: Patterns<'r>, split: &'r Split, text: &'t str) -> Self {
+        let pieces = match split {
+            Split::Lines => Pieces::Lines { text, position: 0 },
+            Split::Delimiter(delimiter) => Pieces::Delimited {
+                text,
+                delimiters: Delimiters::Regex(delimiter.find_iter(text)),
+                position: (!text.is_empty()).then_some(0),
+            },
+            Split::Literal(delimiter) => Pieces::Delimited {
+                text,
+                delimiters: Delimiters::Literal(text.match_indices(delimiter.as_str())),
+                position: (!text.is_empty()).then_some(0),
+            },
+            Split::Frames(start) => Pieces::Frames {
+                text,
+                starts: start.find_iter(text),
+                position: (!text.trim().is_empty()).then_some(0),
+            },
+            Split::Paragraphs => Pieces::Paragraphs { text, position: 0 },
+            Split::Sections(regions) => Pieces::Sections {
+                text,
+                regions: regions.iter(),
+                position: Some(0),
+            },
+        };
+        Self {
+            patterns,
+            pieces,
+            number: 0,
+        }
+    }
+
+    fn next_piece(&mut self) -> Option<Result<NodeCaptures<'r, 't>, Unmatched>> {
+        let next = self.pieces.next()?;
+        self.number += 1;
+        let (start, piece) = match next {
+            Ok(piece) => piece,
+            Err((start, end)) => {
+                return Some(Err(Unmatched::Piece {
+                    kind: self.pieces.kind(),
+                    number: self.number,
+                    start,
+                    end,
+                }))
+            }
+        };
+
+        let captures = self
+            .patterns
+            .captures(piece)
+            .map(|captures| captures.with_offset(start));
+        Some(captures.ok_or(Unmatched::Piece {
+            kind: self.pieces.kind(),
+            number: self.number,
+            start,
+            end: start + piece.len(),
+        }))
+    }
+}
+
+impl<'r, 't> Pieces<'r, 't> {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Lines { .. } => "line",
+            Self::Delimited { .. } => "item",
+            Self::Frames { .. } => "frame",
+            Self::Paragraphs { .. } => "paragraph",
+            Self::Sections { .. } => "section",
+        }
+    }
+
+    /// Byte offset and text of the next piece, or the byte offsets of the text where a piece was
+    /// expected but not found
+    fn next(&mut self) -> Option<Result<Piece<'t>, (usize, usize)>> {
+        match self {
+            Self::Lines { text, position } => {
+                let start = *position;
+                if start >= text.len() {
+                    return None;
+                }
+                let rest = &text[start..];
+                let (line, next) = match rest.find('\n') {
+                    Some(newline) => (&rest[..newline], start + newline + 1),
+                    None => (rest, text.len()),
+                };
+                *position = next;
+                Some(Ok((start, line.strip_suffix('\r').unwrap_or(line))))
+            }
+            Self::Delimited {
+                text,
+                delimiters,
+                position,
+            } => {
+                let start = (*position)?;
+                match delimiters.next() {
+                    Some((delimiter_start, delimiter_end)) => {
+                        *position = Some(delimiter_end);
+                        Some(Ok((start, &text[start..delimiter_start])))
+                    }
+                    None => {
+                        *position = None;
+                        Some(Ok((start, &text[start..])))
+                    }
+                }
+            }
+            Self::Frames {
+                text,
+                starts,
+                position,
+            } => loop {
+                let start = (*position)?;
+                // Starts at the start of the previous frame or at the end of the text would make
+                // empty frames
+                let end = starts
+                    .by_ref()
+                    .map(|found| found.start())
+                    .find(|&next| next > start && next < text.len());
+                *position = end;
+                let frame = &text[start..end.unwrap_or(text.len())];
+                // Only text before the first start may be blank, and then it is no frame
+                if start == 0 && frame.trim().is_empty() {
+                    continue;
+                }
+                return Some(Ok((start, strip_line_ending(frame))));
+            },
+            Self::Paragraphs { text, position } => {
+                let mut start = None;
+                let mut end = *position;
+                while end < text.len() {
+                    let rest = &text[end..];
+                    let next = rest
+                        .find('\n')
+                        .map_or(text.len(), |newline| end + newline + 1);
+                    match (rest[..next - end].trim().is_empty(), start) {
+                        (true, Some(_)) => break,
+                        (true, None) => {}
+                        (false, None) => start = Some(end),
+                        (false, Some(_)) => {}
+                    }
+                    end = next;
+                }
+                *position = end;
+                let start = start?;
+                Some(Ok((start, strip_line_ending(&text[start..end]))))
+            }
+            Self::Sections {
+                text,
+                regions,
+                position,
+            } => {
+                let start = (*position)?;
+                let region = regions.next()?;
+                match region.find_at(text, start) {
+                    Some(found) => {
+                        *position = Some(found.end());
+                        Some(Ok((found.start(), found.as_str())))
+                    }
+                    None => {
+                        *position = None;
+                        Some(Err((start, text.len())))
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The text without a final `\n` or `\r\n`
+pub(crate) fn strip_line_ending(text: &str) -> &str {
+    let text = text.strip_suffix('\n').unwrap_or(text);
+    text.strip_suffix('\r').unwrap_or(text)
+}
+
+/// Matches of the delimiter between pieces
+enum Delimiters<'r, 't> {
+    Regex(Matches<'r, 't>),
+    Literal(std::str::MatchIndices<'t, &'r str>),
+}
+
+impl Delimiters<'_, '_> {
+    /// Byte offsets of the start and end of the next delimiter
+    fn next(&mut self) -> Option<(usize, usize)> {
+        match self {
+            Self::Regex(matches) => matches.next().map(|found| (found.start(), found.end())),
+            Self::Literal(matches) => matches
+                .next()
+                .map(|(start, found)| (start, start + found.len())),
+        }
+    }
+}
@@ -0,0 +1,581 @@
+use std::time::Instant;
+
+use crate::budget::ScanBudget;
+use crate::match_stats::{MatchStats, StatsCollector};
+use crate::spanned::SpanPolicy;
+use crate::warnings::{Warning, WarningCollector};
+
+/// Settings that apply to a whole deserialization rather than to a single
+/// node of a regex tree, for
+/// [`from_regex_tree_and_str_with_options`](crate::from_regex_tree_and_str_with_options).
+///
+/// ## Example
+/// ```
+/// # use recursive_regex::{from_regex_tree_and_str_with_options, DeserializeOptions, RegexTree};
+/// let regex_tree = RegexTree::leaf(r"[^,]+");
+/// let options = DeserializeOptions::new().trim();
+/// let nums: Vec<u32> =
+///     from_regex_tree_and_str_with_options(&regex_tree, "1, 2 ,3", &options).unwrap();
+/// assert_eq!(nums, vec![1, 2, 3]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeserializeOptions {
+    trim: bool,
+    strict: bool,
+    require_match: bool,
+    /// Number of matches at the end of the text to keep, of the root of the tree
+    take_last: Option<usize>,
+    /// Number of matches of the root to pass over, and to deserialize after them
+    skip: usize,
+    take: Option<usize>,
+    near_misses: bool,
+    strict_unit: bool,
+    #[cfg(feature = "unicode-segmentation")]
+    graphemes: bool,
+    infer_types: bool,
+    /// Byte offset of the text within the whole document it came from
+    base_offset: usize,
+    span_policy: SpanPolicy,
+    /// Name of the document the text came from, for errors
+    source_name: Option<String>,
+    stats: Option<StatsCollector>,
+    warnings: Option<WarningCollector>,
+    /// Time after which matching stops with an error
+    deadline: Option<Instant>,
+    scan_budget: Option<ScanBudget>,
+}
+
+/// Options used when none are given
+pub(crate) static DEFAULT_OPTIONS: DeserializeOptions = DeserializeOptions::new();
+
+impl DeserializeOptions {
+    pub const fn new() -> Self {
+        Self {
+            trim: false,
+            strict: false,
+            require_match: false,
+            take_last: None,
+            skip: 0,
+            take: None,
+            near_misses: false,
+            strict_unit: false,
+            #[cfg(feature = "unicode-segmentation")]
+            graphemes: false,
+            infer_types: false,
+            base_offset: 0,
+            span_policy: SpanPolicy::Transformed,
+            source_name: None,
+            stats: None,
+            warnings: None,
+            deadline: None,
+            scan_budget: None,
+        }
+    }
+
+    /// Trim whitespace from the text of leaves before parsing them. Spans
+    /// cover the trimmed text.
+    pub fn trim(mut self) -> Self {
+        self.trim = true;
+        self
+    }
+
+    /// Fail when a match has a named capture group that is not a field of the
+    /// struct it is deserialized into, rather than ignoring the group. This
+    /// catches typos in group names and fields that were forgotten.
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    /// Fail when the root of the regex tree matches nowhere in the text while
+    /// deserializing a sequence, rather than giving an empty sequence. A typo
+    /// in a pattern then shows up as an error instead of as missing data.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str_with_options, DeserializeOptions, RegexTree};
+    /// let regex_tree = RegexTree::leaf(r"(?P<key>\w+)=(?P<value>\w+)");
+    /// let options = DeserializeOptions::new().require_match();
+    ///
+    /// let error = from_regex_tree_and_str_with_options::<Vec<(String, String)>>(
+    ///     &regex_tree,
+    ///     "a: 1",
+    ///     &options,
+    /// )
+    /// .unwrap_err();
+    /// assert_eq!(error.span(), Some(0..4));
+    /// ```
+    pub fn require_match(mut self) -> Self {
+        self.require_match = true;
+        self
+    }
+
+    /// Deserialize only the last `n` matches of the root of a tree, such as
+    /// the latest records of a large log, rather than every match. The text is
+    /// still searched from the start, but only the last `n` matches are kept
+    /// while searching, and only they are deserialized, so nothing is built
+    /// for the matches before them. Indices in the paths of errors count every
+    /// match, including those passed over.
+    ///
+    /// This applies to the matches of the root deserialized as a sequence,
+    /// and to [`records_from_regex_tree_and_str_with_options`](crate::records_from_regex_tree_and_str_with_options).
+    /// See [`MatchesIter::take_last`](crate::MatchesIter::take_last) for the
+    /// same without deserializing.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str_with_options, DeserializeOptions, RegexTree};
+    /// let regex_tree = RegexTree::lines(r"(?P<level>\w+): (?P<message>.*)").build();
+    /// let log = "INFO: started\nWARN: slow\nINFO: done";
+    ///
+    /// let options = DeserializeOptions::new().take_last(2);
+    /// let latest: Vec<(String, String)> =
+    ///     from_regex_tree_and_str_with_options(&regex_tree, log, &options).unwrap();
+    /// assert_eq!(latest[0], ("WARN".to_owned(), "slow".to_owned()));
+    /// assert_eq!(latest.len(), 2);
+    /// ```
+    pub fn take_last(mut self, n: usize) -> Self {
+        self.take_last = Some(n);
+        self
+    }
+
+    /// Pass over the first `n` matches of the root of a tree without
+    /// deserializing them or running their children. Together with
+    /// [`take`](Self::take), this pages through a large text a few records at
+    /// a time. Indices in the paths of errors count the matches passed over.
+    ///
+    /// Like [`take_last`](Self::take_last), this applies to the matches of the
+    /// root deserialized as a sequence and to
+    /// [`records_from_regex_tree_and_str_with_options`](crate::records_from_regex_tree_and_str_with_options),
+    /// and counts after `take_last` when both are set.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str_with_options, DeserializeOptions, RegexTree};
+    /// let regex_tree = RegexTree::leaf(r"\d+");
+    ///
+    /// let options = DeserializeOptions::new().skip(2).take(2);
+    /// let page: Vec<u32> =
+    ///     from_regex_tree_and_str_with_options(&regex_tree, "1 2 3 4 5", &options).unwrap();
+    /// assert_eq!(page, vec![3, 4]);
+    /// ```
+    pub fn skip(mut self, n: usize) -> Self {
+        self.skip = n;
+        self
+    }
+
+    /// Deserialize at most `n` matches of the root of a tree, after any
+    /// passed over with [`skip`](Self::skip). The text after the last of them
+    /// is not searched, so the root's [`Count`](crate::regex_tree::Count) is
+    /// only checked against the matches that were found.
+    pub fn take(mut self, n: usize) -> Self {
+        self.take = Some(n);
+        self
+    }
+
+    /// Only deserialize `()` and unit structs from text that a node's
+    /// patterns match, rather than from any text at all. A field of type `()`
+    /// then validates the text it is given, such as asserting that a marker
+    /// is present, without keeping anything from it.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str_with_options, DeserializeOptions, RegexTree};
+    /// #[derive(Debug, serde::Deserialize)]
+    /// struct Entry {
+    ///     name: String,
+    ///     signed: (),
+    /// }
+    ///
+    /// let regex_tree = RegexTree::root(r"(?P<name>\w+) (?P<signed>.*)")
+    ///     .with_child("signed", RegexTree::leaf(r"-- signed"))
+    ///     .build();
+    /// let options = DeserializeOptions::new().strict_unit();
+    ///
+    /// let entry: Entry =
+    ///     from_regex_tree_and_str_with_options(&regex_tree, "release -- signed", &options).unwrap();
+    /// assert_eq!(entry.name, "release");
+    /// assert!(from_regex_tree_and_str_with_options::<Entry>(&regex_tree, "release draft", &options).is_err());
+    /// ```
+    pub fn strict_unit(mut self) -> Self {
+        self.strict_unit = true;
+        self
+    }
+
+    /// When a node's patterns match nowhere in its text, explain how close
+    /// they came: the error names the longest start of a pattern that does
+    /// match and spans the text it matches, so the rest of the pattern is what
+    /// failed just after. Finding this compiles many variations of the
+    /// patterns, but only once deserialization has already failed.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str_with_options, DeserializeOptions, RegexTree};
+    /// #[derive(Debug, serde::Deserialize)]
+    /// struct Version {
+    ///     major: u32,
+    ///     minor: u32,
+    /// }
+    ///
+    /// let regex_tree = RegexTree::leaf(r"v(?P<major>\d+)\.(?P<minor>\d+)");
+    /// let options = DeserializeOptions::new().near_misses();
+    /// let error =
+    ///     from_regex_tree_and_str_with_options::<Version>(&regex_tree, "v1-2", &options).unwrap_err();
+    /// assert_eq!(
+    ///     error.message(),
+    ///     "regular expression does not match; the longest start of a pattern that \
+    ///      matches is `v(?P<major>\\d+)`, which matches \"v1\""
+    /// );
+    /// assert_eq!(error.span(), Some(0..2));
+    /// ```
+    pub fn near_misses(mut self) -> Self {
+        self.near_misses = true;
+        self
+    }
+
+    /// Let a `char` be deserialized from a single extended grapheme cluster,
+    /// such as an emoji with a skin tone modifier, rather than only from a
+    /// single `char`. A grapheme of several `char`s is given to the type as a
+    /// string, which [`Grapheme`](crate::Grapheme) accepts but `char` itself
+    /// cannot.
+    #[cfg(feature = "unicode-segmentation")]
+    pub fn graphemes(mut self) -> Self {
+        self.graphemes = true;
+        self
+    }
+
+    /// When deserializing into a type that takes whatever it is given, such
+    /// as [`Value`](crate::Value) or `serde_json::Value`, give the text of a
+    /// leaf as a number when it is written as a JSON number, or as a bool when
+    /// it is `true` or `false`. Other text, including integers with leading
+    /// zeros and integers too large for 64 bits, is still given as a string,
+    /// so nothing is lost. Typed fields parse their text as usual.
+    ///
+    /// This is off by default, so dynamic output is all strings unless it is
+    /// asked for.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{to_value_with_options, DeserializeOptions, Number, RegexTree};
+    /// let regex_tree = RegexTree::leaf(r"(?P<id>\w+)=(?P<value>\S+)");
+    /// let options = DeserializeOptions::default().infer_types();
+    ///
+    /// let value = to_value_with_options(&regex_tree, "007=1.5 x=-2 y=true", &options).unwrap();
+    /// let records = value.as_seq().unwrap();
+    /// assert_eq!(records[0].get("id").unwrap().as_str(), Some("007"));
+    /// assert_eq!(records[0].get("value").unwrap().as_number(), Some(Number::Float(1.5)));
+    /// assert_eq!(records[1].get("value").unwrap().as_number(), Some(Number::Signed(-2)));
+    /// assert_eq!(records[2].get("value").unwrap().as_bool(), Some(true));
+    /// ```
+    pub fn infer_types(mut self) -> Self {
+        self.infer_types = true;
+        self
+    }
+
+    /// Choose which text spans cover when the text of a value was changed
+    /// before it was parsed, such as by trimming. See [`SpanPolicy`].
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str_with_options, DeserializeOptions, RegexTree, SpanPolicy, Spanned};
+    /// # use serde::Deserialize;
+    /// #[derive(Deserialize)]
+    /// struct Pair {
+    ///     left: u32,
+    ///     right: Spanned<u32>,
+    /// }
+    ///
+    /// let regex_tree = RegexTree::leaf(r"(?P<left>[^,]+),(?P<right>.+)");
+    ///
+    /// let options = DeserializeOptions::new().trim();
+    /// let pair: Pair = from_regex_tree_and_str_with_options(&regex_tree, "1, 2 ", &options).unwrap();
+    /// assert_eq!((pair.right.begin(), pair.right.end()), (3, 4));
+    ///
+    /// let options = options.with_span_policy(SpanPolicy::Original);
+    /// let pair: Pair = from_regex_tree_and_str_with_options(&regex_tree, "1, 2 ", &options).unwrap();
+    /// assert_eq!((pair.right.begin(), pair.right.end()), (2, 5));
+    /// ```
+    pub fn with_span_policy(mut self, span_policy: SpanPolicy) -> Self {
+        self.span_policy = span_policy;
+        self
+    }
+
+    /// Report spans as byte offsets into a larger document that the text
+    /// starts `offset` bytes into, rather than into the text itself. This
+    /// applies to [`Spanned`](crate::Spanned) values and to the spans of
+    /// errors, so text cut from the middle of a big file or out of another
+    /// format can point back at where it came from.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str_with_options, DeserializeOptions, RegexTree, Spanned};
+    /// let document = "header\nvalues: 1 2 x";
+    /// let body = &document[15..];
+    ///
+    /// let regex_tree = RegexTree::leaf(r"\S+");
+    /// let options = DeserializeOptions::new().with_base_offset(15);
+    ///
+    /// let values: Vec<Spanned<&str>> =
+    ///     from_regex_tree_and_str_with_options(&regex_tree, body, &options).unwrap();
+    /// assert_eq!(values[1].substring(document), "2");
+    ///
+    /// let error = from_regex_tree_and_str_with_options::<Vec<u32>>(&regex_tree, body, &options)
+    ///     .unwrap_err();
+    /// assert_eq!(&document[error.span().unwrap()], "x");
+    /// ```
+    pub fn with_base_offset(mut self, offset: usize) -> Self {
+        self.base_offset = offset;
+        self
+    }
+
+    /// Name the document the text came from, such as its file name, so that
+    /// errors say where they happened. See [`Error::source_name`].
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str_with_options, DeserializeOptions, RegexTree};
+    /// let regex_tree = RegexTree::leaf(r"\S+");
+    /// let options = DeserializeOptions::new().with_source_name("values.txt");
+    ///
+    /// let error =
+    ///     from_regex_tree_and_str_with_options::<Vec<u32>>(&regex_tree, "1 x", &options)
+    ///         .unwrap_err();
+    /// assert_eq!(error.source_name(), Some("values.txt"));
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "values.txt: [1]: parsing error: invalid digit found in string"
+    /// );
+    /// ```
+    pub fn with_source_name(mut self, name: impl Into<String>) -> Self {
+        self.source_name = Some(name.into());
+        self
+    }
+
+    /// Gather statistics on how each node of the regex tree matches: how
+    /// often it ran and matched, how much of its text it matched or skipped,
+    /// and how long finding matches took. Read them with
+    /// [`stats`](DeserializeOptions::stats).
+    ///
+    /// Statistics add up over every deserialization with these options,
+    /// including with clones of them, so a whole corpus can be profiled at
+    /// once. Timing each search has a small cost, so this is off by default.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str_with_options, DeserializeOptions, RegexTree};
+    /// let regex_tree = RegexTree::root(r"(?P<name>\w+): (?P<nums>.*)")
+    ///     .with_child("nums", RegexTree::leaf(r"\d+"))
+    ///     .build();
+    /// let options = DeserializeOptions::new().collect_stats();
+    ///
+    /// let _: Vec<(String, Vec<u32>)> =
+    ///     from_regex_tree_and_str_with_options(&regex_tree, "a: 1 2\nb: 3", &options).unwrap();
+    ///
+    /// let stats = options.stats().unwrap();
+    /// let nums = stats.node(regex_tree.child("nums").unwrap()).unwrap();
+    /// assert_eq!(nums.runs(), 2);
+    /// assert_eq!(nums.matches(), 3);
+    /// assert_eq!(nums.bytes_skipped(), 1);
+    /// ```
+    pub fn collect_stats(mut self) -> Self {
+        self.stats = Some(StatsCollector::default());
+        self
+    }
+
+    /// Gather [`Warning`]s about text that was matched but never used, such
+    /// as named capture groups that the struct they are deserialized into has
+    /// no field for. Read them with [`warnings`](DeserializeOptions::warnings).
+    /// Unlike [`strict`](DeserializeOptions::strict), deserialization still
+    /// succeeds, so a pattern and a type drifting apart can be noticed without
+    /// rejecting data.
+    ///
+    /// Warnings add up over every deserialization with these options,
+    /// including with clones of them.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str_with_options, DeserializeOptions, RegexTree, Warning};
+    /// #[derive(serde::Deserialize)]
+    /// struct Entry {
+    ///     name: String,
+    /// }
+    ///
+    /// let regex_tree = RegexTree::leaf(r"(?P<name>\w+)=(?P<value>\d+)");
+    /// let options = DeserializeOptions::new().collect_warnings();
+    ///
+    /// let _: Vec<Entry> =
+    ///     from_regex_tree_and_str_with_options(&regex_tree, "a=1", &options).unwrap();
+    /// assert_eq!(
+    ///     options.warnings().unwrap(),
+    ///     vec![Warning::IgnoredGroup { group: "value".to_owned(), span: 2..3 }]
+    /// );
+    /// ```
+    pub fn collect_warnings(mut self) -> Self {
+        self.warnings = Some(WarningCollector::default());
+        self
+    }
+
+    /// Stop with an error once `deadline` has passed, rather than going on
+    /// searching for matches. The deadline is checked before each search for
+    /// the next match of a node, so a single search is never interrupted, but
+    /// text with a great many matches or deeply nested ones is cut short.
+    /// This suits services parsing untrusted text with trees given by users.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str_with_options, DeserializeOptions, RegexTree};
+    /// # use std::time::{Duration, Instant};
+    /// let regex_tree = RegexTree::leaf(r"\d+");
+    ///
+    /// let options = DeserializeOptions::new().with_deadline(Instant::now() + Duration::from_secs(1));
+    /// let nums: Vec<u32> = from_regex_tree_and_str_with_options(&regex_tree, "1 2 3", &options).unwrap();
+    /// assert_eq!(nums, vec![1, 2, 3]);
+    ///
+    /// let options = DeserializeOptions::new().with_deadline(Instant::now());
+    /// let error = from_regex_tree_and_str_with_options::<Vec<u32>>(&regex_tree, "1 2 3", &options)
+    ///     .unwrap_err();
+    /// assert_eq!(error.message(), "deadline passed before the text was deserialized");
+    /// ```
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Stop with an error once nodes have been run on more than `bytes` bytes
+    /// of text in total, rather than going on searching for matches. Each
+    /// node counts the whole of the text it is run on, so text searched again
+    /// by a child is counted again, and deeply nested trees use up the limit
+    /// sooner. This bounds the work done on untrusted text with a tree given
+    /// by a user.
+    ///
+    /// The count adds up over every deserialization with these options,
+    /// including with clones of them, so give each document its own options.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str_with_options, DeserializeOptions, RegexTree};
+    /// let regex_tree = RegexTree::root(r"(?P<name>\w+): (?P<nums>.*)")
+    ///     .with_child("nums", RegexTree::leaf(r"\d+"))
+    ///     .build();
+    /// let text = "a: 1 2\nb: 3";
+    ///
+    /// // The root searches 11 bytes, and its child 3 and then 1 more
+    /// let options = DeserializeOptions::new().with_scan_limit(15);
+    /// let _: Vec<(String, Vec<u32>)> =
+    ///     from_regex_tree_and_str_with_options(&regex_tree, text, &options).unwrap();
+    ///
+    /// let options = DeserializeOptions::new().with_scan_limit(14);
+    /// let error =
+    ///     from_regex_tree_and_str_with_options::<Vec<(String, Vec<u32>)>>(&regex_tree, text, &options)
+    ///         .unwrap_err();
+    /// assert_eq!(error.message(), "scan limit of 14 bytes exceeded");
+    /// assert_eq!(error.span(), Some(10..11));
+    /// ```
+    pub fn with_scan_limit(mut self, bytes: usize) -> Self {
+        self.scan_budget = Some(ScanBudget::new(bytes));
+        self
+    }
+
+    pub fn is_trim(&self) -> bool {
+        self.trim
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    pub fn is_require_match(&self) -> bool {
+        self.require_match
+    }
+
+    /// Number of matches at the end of the text to deserialize, if not all of them. See
+    /// [`take_last`](Self::take_last).
+    pub fn last_matches(&self) -> Option<usize> {
+        self.take_last
+    }
+
+    /// Number of matches of the root passed over. See [`skip`](Self::skip).
+    pub fn skipped_matches(&self) -> usize {
+        self.skip
+    }
+
+    /// Number of matches of the root to deserialize at most. See [`take`](Self::take).
+    pub fn max_matches(&self) -> Option<usize> {
+        self.take
+    }
+
+    pub fn is_near_misses(&self) -> bool {
+        self.near_misses
+    }
+
+    pub fn is_strict_unit(&self) -> bool {
+        self.strict_unit
+    }
+
+    #[cfg(feature = "unicode-segmentation")]
+    pub fn is_graphemes(&self) -> bool {
+        self.graphemes
+    }
+
+    pub fn is_infer_types(&self) -> bool {
+        self.infer_types
+    }
+
+    pub fn base_offset(&self) -> usize {
+        self.base_offset
+    }
+
+    pub fn span_policy(&self) -> SpanPolicy {
+        self.span_policy
+    }
+
+    pub fn source_name(&self) -> Option<&str> {
+        self.source_name.as_deref()
+    }
+
+    pub fn is_collect_stats(&self) -> bool {
+        self.stats.is_some()
+    }
+
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    pub fn scan_limit(&self) -> Option<usize> {
+        self.scan_budget.as_ref().map(ScanBudget::limit)
+    }
+
+    /// Bytes of text nodes have been run on so far, if a
+    /// [scan limit](DeserializeOptions::with_scan_limit) is set
+    pub fn bytes_scanned(&self) -> Option<usize> {
+        self.scan_budget.as_ref().map(ScanBudget::scanned)
+    }
+
+    pub(crate) fn scan_budget(&self) -> Option<&ScanBudget> {
+        self.scan_budget.as_ref()
+    }
+
+    /// Statistics gathered so far, if
+    /// [`collect_stats`](DeserializeOptions::collect_stats) is set
+    pub fn stats(&self) -> Option<MatchStats> {
+        self.stats.as_ref().map(StatsCollector::snapshot)
+    }
+
+    pub(crate) fn stats_collector(&self) -> Option<&StatsCollector> {
+        self.stats.as_ref()
+    }
+
+    pub fn is_collect_warnings(&self) -> bool {
+        self.warnings.is_some()
+    }
+
+    /// Warnings gathered so far, if
+    /// [`collect_warnings`](DeserializeOptions::collect_warnings) is set
+    pub fn warnings(&self) -> Option<Vec<Warning>> {
+        self.warnings.as_ref().map(WarningCollector::snapshot)
+    }
+
+    pub(crate) fn warning_collector(&self) -> Option<&WarningCollector> {
+        self.warnings.as_ref()
+    }
+}
@@ -0,0 +1,282 @@
+use std::ops::Range;
+
+use crate::regex::{self, Regex};
+
+/// A compiled pattern of a regex tree node. Patterns are matched with the
+/// regex crate unless they opt into `fancy-regex`, which adds backreferences
+/// and lookaround at the cost of backtracking, or are fixed-width columns,
+/// which need no regex at all.
+///
+/// A fancy pattern that gives up while matching, such as by exceeding its
+/// backtracking limit, is treated as not matching.
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    Regex(Regex),
+    #[cfg(feature = "fancy")]
+    Fancy(fancy_regex::Regex),
+    Columns(Columns),
+}
+
+/// Fixed byte ranges of a record that are each a capture group, for
+/// [`RegexTree::fixed_width`](crate::RegexTree::fixed_width). The match is
+/// the whole rest of the text, so a record should be split off first, such
+/// as by line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Columns {
+    /// Name of each capture group, starting with the unnamed group 0
+    names: Vec<Option<String>>,
+    /// Byte range of each column within the record
+    ranges: Vec<Range<usize>>,
+    /// Description of the columns, in place of a pattern's source
+    source: String,
+}
+
+impl Columns {
+    pub fn new(columns: impl IntoIterator<Item = (Option<String>, Range<usize>)>) -> Self {
+        let (names, ranges): (Vec<_>, Vec<_>) = columns.into_iter().unzip();
+        let described: Vec<String> = names
+            .iter()
+            .zip(&ranges)
+            .map(|(name, range)| match name {
+                Some(name) => format!("{name}: {range:?}"),
+                None => format!("{range:?}"),
+            })
+            .collect();
+        Self {
+            names: std::iter::once(None).chain(names).collect(),
+            ranges,
+            source: format!("columns [{}]", described.join(", ")),
+        }
+    }
+
+    /// Captures of the record starting at `position`, which runs to the end of the text. A column
+    /// past the end of the record does not take part, and one that runs over the end is cut
+    /// short. A column that would split a character fails the match.
+    fn captures_at<'t>(&self, text: &'t str, position: usize) -> Option<Vec<Option<Match<'t>>>> {
+        let whole = Match {
+            text: text.get(position..)?,
+            start: position,
+        };
+        let columns = self.ranges.iter().map(|range| {
+            let start = position + range.start;
+            let end = (position + range.end).min(text.len());
+            if start > end {
+                return Some(None);
+            }
+            let column = text.get(start..end)?;
+            Some(Some(Match {
+                text: column,
+                start,
+            }))
+        });
+        std::iter::once(Some(Some(whole))).chain(columns).collect()
+    }
+}
+
+/// Patterns are equal when they have the same source and engine. Nodes given compiled regexes,
+/// whose flags their source may not show, are told apart by the comparison of their
+/// [`RegexTree`](crate::RegexTree).
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Regex(a), Self::Regex(b)) => a.as_str() == b.as_str(),
+            #[cfg(feature = "fancy")]
+            (Self::Fancy(a), Self::Fancy(b)) => a.as_str() == b.as_str(),
+            (Self::Columns(a), Self::Columns(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Pattern {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Regex(regex) => regex.as_str(),
+            #[cfg(feature = "fancy")]
+            Self::Fancy(regex) => regex.as_str(),
+            Self::Columns(columns) => &columns.source,
+        }
+    }
+
+    /// The pattern, if it is matched with the regex crate
+    #[cfg(feature = "regex")]
+    pub fn as_regex(&self) -> Option<&Regex> {
+        match self {
+            Self::Regex(regex) => Some(regex),
+            #[cfg(feature = "fancy")]
+            Self::Fancy(_) => None,
+            Self::Columns(_) => None,
+        }
+    }
+
+    /// Number of capture groups, including group 0
+    pub fn captures_len(&self) -> usize {
+        match self {
+            Self::Regex(regex) => regex.captures_len(),
+            #[cfg(feature = "fancy")]
+            Self::Fancy(regex) => regex.captures_len(),
+            Self::Columns(columns) => columns.names.len(),
+        }
+    }
+
+    pub fn capture_names(&self) -> CaptureNames<'_> {
+        match self {
+            Self::Regex(regex) => CaptureNames::Regex(regex.capture_names()),
+            #[cfg(feature = "fancy")]
+            Self::Fancy(regex) => CaptureNames::Fancy(regex.capture_names()),
+            Self::Columns(columns) => CaptureNames::Columns(columns.names.iter()),
+        }
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        match self {
+            Self::Regex(regex) => regex.is_match(text),
+            #[cfg(feature = "fancy")]
+            Self::Fancy(regex) => regex.is_match(text).unwrap_or(false),
+            Self::Columns(columns) => columns.captures_at(text, 0).is_some(),
+        }
+    }
+
+    pub fn captures<'t>(&self, text: &'t str) -> Option<Captures<'t>> {
+        self.captures_at(text, 0)
+    }
+
+    /// Captures of the first match starting at or after `position`. The text
+    /// before `position` is still seen by anchors and lookbehind.
+    pub fn captures_at<'t>(&self, text: &'t str, position: usize) -> Option<Captures<'t>> {
+        match self {
+            Self::Regex(regex) => regex.captures_at(text, position).map(Captures::Regex),
+            #[cfg(feature = "fancy")]
+            Self::Fancy(regex) => regex
+                .captures_from_pos(text, position)
+                .ok()
+                .flatten()
+                .map(Captures::Fancy),
+            Self::Columns(columns) => columns.captures_at(text, position).map(Captures::Columns),
+        }
+    }
+}
+
+/// Captures of one match of a [`Pattern`]
+pub enum Captures<'t> {
+    Regex(regex::Captures<'t>),
+    #[cfg(feature = "fancy")]
+    Fancy(fancy_regex::Captures<'t>),
+    Columns(Vec<Option<Match<'t>>>),
+}
+
+impl<'t> Captures<'t> {
+    pub fn get(&self, index: usize) -> Option<Match<'t>> {
+        match self {
+            Self::Regex(captures) => captures.get(index).map(Match::from),
+            #[cfg(feature = "fancy")]
+            Self::Fancy(captures) => captures.get(index).map(Match::from),
+            Self::Columns(columns) => columns.get(index).copied().flatten(),
+        }
+    }
+
+    pub fn iter(&self) -> SubCaptureMatches<'_, 't> {
+        match self {
+            Self::Regex(captures) => SubCaptureMatches::Regex(captures.iter()),
+            #[cfg(feature = "fancy")]
+            Self::Fancy(captures) => SubCaptureMatches::Fancy(captures.iter()),
+            Self::Columns(columns) => SubCaptureMatches::Columns(columns.iter()),
+        }
+    }
+}
+
+/// Text matched by a whole pattern or one of its capture groups
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match<'t> {
+    text: &'t str,
+    start: usize,
+}
+
+impl<'t> Match<'t> {
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.start + self.text.len()
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.start()..self.end()
+    }
+
+    pub fn as_str(&self) -> &'t str {
+        self.text
+    }
+
+    /// The part of this match over `range`, given in the same offsets as the match
+    pub fn slice(&self, range: Range<usize>) -> Self {
+        Self {
+            text: &self.text[range.start - self.start..range.end - self.start],
+            start: range.start,
+        }
+    }
+}
+
+impl<'t> From<regex::Match<'t>> for Match<'t> {
+    fn from(re_match: regex::Match<'t>) -> Self {
+        Self {
+            text: re_match.as_str(),
+            start: re_match.start(),
+        }
+    }
+}
+
+#[cfg(feature = "fancy")]
+impl<'t> From<fancy_regex::Match<'t>> for Match<'t> {
+    fn from(re_match: fancy_regex::Match<'t>) -> Self {
+        Self {
+            text: re_match.as_str(),
+            start: re_match.start(),
+        }
+    }
+}
+
+/// Names of the capture groups of a [`Pattern`], starting with the unnamed
+/// group 0
+pub enum CaptureNames<'r> {
+    Regex(regex::CaptureNames<'r>),
+    #[cfg(feature = "fancy")]
+    Fancy(fancy_regex::CaptureNames<'r>),
+    Columns(std::slice::Iter<'r, Option<String>>),
+}
+
+impl<'r> Iterator for CaptureNames<'r> {
+    type Item = Option<&'r str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Regex(names) => names.next(),
+            #[cfg(feature = "fancy")]
+            Self::Fancy(names) => names.next(),
+            Self::Columns(names) => names.next().map(Option::as_deref),
+        }
+    }
+}
+
+/// Matches of each capture group of [`Captures`], in order, with `None` for
+/// groups that did not take part
+pub enum SubCaptureMatches<'c, 't> {
+    Regex(regex::SubCaptureMatches<'c, 't>),
+    #[cfg(feature = "fancy")]
+    Fancy(fancy_regex::SubCaptureMatches<'c, 't>),
+    Columns(std::slice::Iter<'c, Option<Match<'t>>>),
+}
+
+impl<'c, 't> Iterator for SubCaptureMatches<'c, 't> {
+    type Item = Option<Match<'t>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Regex(matches) => matches.next().map(|re_match| re_match.map(Match::from)),
+            #[cfg(feature = "fancy")]
+            Self::Fancy(matches) => matches.next().map(|re_match| re_match.map(Match::from)),
+            Self::Columns(matches) => matches.next().copied(),
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use std::marker::PhantomData;
+
+use serde::de::SeqAccess;
+use serde::Deserialize;
+
+use crate::multi_capture::MultiCaptureSeqAccess;
+use crate::options::{DeserializeOptions, DEFAULT_OPTIONS};
+use crate::{Error, RegexTree};
+
+/// Deserialize each match of the root of a regex tree as its own record,
+/// where deserializing `Vec<T>` would fail entirely on the first bad one.
+/// Each record is a `Result`, and the records after an error are still
+/// found, so messy text can be read for whatever parses.
+///
+/// Collect the records into a `Vec<Result<T, Error>>` to keep every error in
+/// its place, flatten them to skip the bad ones, or use
+/// [`split_errors`](Records::split_errors) to get the values and errors
+/// apart. Errors carry their path and span as usual.
+///
+/// ## Example
+/// ```
+/// # use recursive_regex::{records_from_regex_tree_and_str, RegexTree};
+/// let regex_tree = RegexTree::leaf(r"\S+");
+///
+/// let skipped: Vec<u32> = records_from_regex_tree_and_str(&regex_tree, "1 x 3")
+///     .flatten()
+///     .collect();
+/// assert_eq!(skipped, vec![1, 3]);
+///
+/// let (values, errors) =
+///     records_from_regex_tree_and_str::<u32>(&regex_tree, "1 x 3").split_errors();
+/// assert_eq!(values, vec![1, 3]);
+/// assert_eq!(errors[0].to_string(), "[1]: parsing error: invalid digit found in string");
+/// assert_eq!(errors[0].span(), Some(2..3));
+/// ```
+pub fn records_from_regex_tree_and_str<'t, 'r, T>(
+    regex_tree: &'r RegexTree,
+    text: &'t str,
+) -> Records<'r, 't, T>
+where
+    T: Deserialize<'t>,
+{
+    records_from_regex_tree_and_str_with_options(regex_tree, text, &DEFAULT_OPTIONS)
+}
+
+/// Like [`records_from_regex_tree_and_str`], but with [`DeserializeOptions`]
+/// that change how the text is deserialized.
+pub fn records_from_regex_tree_and_str_with_options<'t, 'r, T>(
+    regex_tree: &'r RegexTree,
+    text: &'t str,
+    options: &'r DeserializeOptions,
+) -> Records<'r, 't, T>
+where
+    T: Deserialize<'t>,
+{
+    let seq_access = MultiCaptureSeqAccess::from_regex_tree_and_captures(
+        regex_tree,
+        options,
+        regex_tree.captures_iter(text),
+        text,
+        options.base_offset(),
+    )
+    .root();
+    Records {
+        seq_access,
+        options,
+        records: PhantomData,
+    }
+}
+
+/// Iterator over the records of a text, from
+/// [`records_from_regex_tree_and_str`]
+pub struct Records<'r, 't, T> {
+    seq_access: MultiCaptureSeqAccess<'r, 't>,
+    options: &'r DeserializeOptions,
+    records: PhantomData<fn() -> T>,
+}
+
+impl<'t, T: Deserialize<'t>> Records<'_, 't, T> {
+    /// Separate the records that deserialized from the errors of those that
+    /// did not, keeping each in order
+    pub fn split_errors(self) -> (Vec<T>, Vec<Error>) {
+        let mut values = Vec::new();
+        let mut errors = Vec::new();
+        for record in self {
+            match record {
+                Ok(value) => values.push(value),
+                Err(error) => errors.push(error),
+            }
+        }
+        (values, errors)
+    }
+}
+
+impl<'t, T: Deserialize<'t>> Iterator for Records<'_, 't, T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = self.seq_access.next_element_seed(PhantomData).transpose()?;
+        Some(record.map_err(|error| error.in_source(self.options)))
+    }
+}
@@ -1,10 +1,21 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
 
-use regex::Matches;
 #[cfg(feature = "deserialize-regex-tree")]
 use serde::Deserialize;
 
-use crate::regex::{CaptureMatches, CaptureNames, Captures, Regex};
+#[cfg(feature = "regex")]
+use crate::compile_stats::CompileStats;
+use crate::match_tree::MatchesIter;
+use crate::node_captures::{NodeCaptureMatches, NodeCaptures, Patterns};
+use crate::pattern::{Columns, Match, Pattern};
+use crate::regex::{self, Regex, RegexBuilder};
+#[cfg(feature = "regex")]
+use crate::regex::{RegexSet, RegexSetBuilder};
+use crate::schema::Schema;
+use crate::spanned::is_reserved_key;
 
 /// A regex tree is a recursive regular expression. Once the root regex of a
 /// tree matches a string, if any of its named capture groups match the name of
@@ -66,94 +77,3038 @@ use crate::regex::{CaptureMatches, CaptureNames, Captures, Regex};
 /// in the opinion list were pairs (boolean number), perhaps indicating
 /// belief and strength of belief, the opinion_list could have another child
 /// to break up each space-separated pair into a logical tuple.
+///
+/// ## Fallback patterns
+/// A node may carry more than one pattern, added with
+/// [`with_fallback`](Builder::with_fallback). This is useful when a field
+/// appears in several formats, such as dates written in two layouts. The
+/// patterns are tried in order: for a single match, the first pattern that
+/// matches is used, and when matching repeatedly, at each position the
+/// earliest match is taken, with earlier patterns winning ties. Each pattern
+/// may have its own capture groups, and children apply to any of them.
+///
+/// A node with many fallbacks, such as one per record shape of a log file,
+/// first checks all of its patterns at once with a
+/// [`RegexSet`](crate::regex::RegexSet), and only extracts captures with
+/// patterns that match. This works best with [line mode](#line-mode), where
+/// each line is checked on its own.
+///
+/// ## Line mode
+/// Many inputs hold one record per line. A node constructed with
+/// [`lines`](RegexTree::lines) splits its text into lines and matches its
+/// patterns against each line on its own, instead of searching across the
+/// whole text. Patterns never see a line break, so `^` and `$` anchor to the
+/// line and `.*` cannot run into the next record. When deserializing a
+/// sequence, every line must match, and the first line that does not is an
+/// error rather than being skipped. Lines end with `\n` or `\r\n`.
+///
+/// Splitting on a delimiter other than a line break is done with
+/// [`split`](RegexTree::split).
+///
+/// ```
+/// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+/// let regex_tree = RegexTree::lines(r"^(\w+)=(\d+)$").build();
+/// let pairs: Vec<(&str, &str, u32)> =
+///     from_regex_tree_and_str(&regex_tree, "a=1\nb=2\n").unwrap();
+/// assert_eq!(pairs, vec![("a=1", "a", 1), ("b=2", "b", 2)]);
+///
+/// let error = from_regex_tree_and_str::<Vec<(&str, &str, u32)>>(&regex_tree, "a=1\nb\n");
+/// assert!(error.is_err());
+/// ```
+///
+/// ## Fancy patterns
+/// With the `fancy` feature, a pattern may be matched with
+/// [fancy-regex](https://docs.rs/fancy-regex) instead, for syntax the regex
+/// crate does not support, such as backreferences and lookaround. Such
+/// patterns are opted into one at a time with [`fancy`](RegexTree::fancy) and
+/// [`with_fancy_fallback`](Builder::with_fancy_fallback), so the rest of the
+/// tree keeps the regex crate's guarantee of linear time matching. Fancy
+/// patterns may backtrack, and a match that exceeds fancy-regex's backtracking
+/// limit is treated as no match.
+///
+/// ## Cloning and comparing
+/// A tree is `Send` and `Sync`, and cloning it is cheap: patterns compiled by
+/// the regex crate share their programs with their clones, so mostly just the
+/// nodes are copied. Trees can then be kept in configuration structs, cloned
+/// per thread, and logged with their `Debug` output.
+///
+/// Trees are equal when their patterns have the same source and engine and
+/// their structure and settings are the same. Conditions and filters cannot
+/// be compared, so they are only equal to themselves and their clones. Nor
+/// can a compiled [`Regex`] given to the builder, which may have been built
+/// with flags that its source does not show, so a node given one is likewise
+/// only equal to itself and its clones.
+///
+/// ```
+/// # use recursive_regex::RegexTree;
+/// let regex_tree = RegexTree::root(r"(?P<nums>.*)")
+///     .with_child("nums", RegexTree::leaf(r"\d+"))
+///     .build();
+/// assert_eq!(regex_tree.clone(), regex_tree);
+/// assert_ne!(RegexTree::leaf(r"\d+"), RegexTree::leaf(r"\w+"));
+/// ```
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "deserialize-regex-tree", derive(Deserialize))]
+#[cfg_attr(
+    feature = "deserialize-regex-tree",
+    derive(Deserialize),
+    serde(try_from = "RegexTreeDef")
+)]
 pub struct RegexTree {
-    #[cfg_attr(feature = "deserialize-regex-tree", serde(with = "serde_regex"))]
-    regex: Regex,
-    #[cfg_attr(feature = "deserialize-regex-tree", serde(default))]
-    children: HashMap<String, RegexTree>,
+    /// The primary pattern followed by any fallbacks. Never empty.
+    patterns: Vec<Pattern>,
+    /// Set of all the patterns, when there are several, to find which of them match in one pass.
+    /// Built along with the node. `regex-lite` has no sets, so with it the patterns are always
+    /// tried one by one.
+    #[cfg(feature = "regex")]
+    pattern_set: Option<RegexSet>,
+    /// How the text is split into pieces for the patterns to match one at a time, if it is.
+    /// Otherwise, the patterns are searched for across the whole text.
+    split: Option<Split>,
+    /// Whether the patterns match headers, with the text after each header up to the next being
+    /// a section keyed by the header. See [`headed`](RegexTree::headed).
+    headed: bool,
+    /// Whether the patterns must match the whole text, or each whole piece of a split node. The
+    /// patterns are anchored when the node is built.
+    full_match: bool,
+    /// How matches are searched for when they are not split or whole
+    match_policy: MatchPolicy,
+    children: Children,
+    /// Children for capture groups that apply only when their condition holds, in the order they
+    /// are tried
+    conditional_children: Vec<ConditionalChild>,
+    /// Child for named capture groups without one of their own
+    default_child: Option<Box<RegexTree>>,
+    /// Conditions a match must meet to be kept, all of which must hold
+    filters: Vec<Filter>,
+    options: NodeOptions,
+    /// Options the patterns were compiled with, to compile variations of them
+    compile_options: CompileOptions,
+    /// Identity of the node if it was given any compiled regexes, which may have been built with
+    /// settings their source does not show, so the node is only equal to itself and its clones
+    compiled: Option<Arc<()>>,
+}
+
+/// The pattern set is built from the patterns, so it is left out
+impl PartialEq for RegexTree {
+    fn eq(&self, other: &Self) -> bool {
+        self.patterns == other.patterns
+            && self.split == other.split
+            && self.headed == other.headed
+            && self.full_match == other.full_match
+            && self.match_policy == other.match_policy
+            && self.children == other.children
+            && self.conditional_children == other.conditional_children
+            && self.default_child == other.default_child
+            && self.filters == other.filters
+            && self.options == other.options
+            && self.compile_options == other.compile_options
+            && match (&self.compiled, &other.compiled) {
+                (None, None) => true,
+                (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+                _ => false,
+            }
+    }
+}
+
+/// Children of a node by the name of the capture group they recurse on. With the
+/// `preserve-order` feature, they keep the order they were added in.
+#[cfg(feature = "preserve-order")]
+type Children<T = RegexTree> = indexmap::IndexMap<String, T>;
+#[cfg(not(feature = "preserve-order"))]
+type Children<T = RegexTree> = HashMap<String, T>;
+
+/// How a split node breaks its text into pieces
+#[derive(Debug, Clone)]
+pub(crate) enum Split {
+    Lines,
+    Delimiter(Regex),
+    Literal(String),
+    /// Each piece begins at a match of the regex
+    Frames(Regex),
+    /// Pieces are runs of lines that are not blank
+    Paragraphs,
+    /// Each piece is the first match of the next regex after the previous piece
+    Sections(Vec<Regex>),
+}
+
+/// Regexes are equal when their sources are. Nodes given compiled regexes, whose flags their
+/// source may not show, are told apart by the node's identity.
+impl PartialEq for Split {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Lines, Self::Lines) | (Self::Paragraphs, Self::Paragraphs) => true,
+            (Self::Delimiter(a), Self::Delimiter(b)) | (Self::Frames(a), Self::Frames(b)) => {
+                a.as_str() == b.as_str()
+            }
+            (Self::Literal(a), Self::Literal(b)) => a == b,
+            (Self::Sections(a), Self::Sections(b)) => {
+                a.iter().map(Regex::as_str).eq(b.iter().map(Regex::as_str))
+            }
+            _ => false,
+        }
+    }
+}
+
+/// How a node searches its text for successive matches, for
+/// [`with_match_policy`](Builder::with_match_policy). This has no effect on
+/// split nodes or nodes that must match their whole text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "deserialize-regex-tree",
+    derive(Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum MatchPolicy {
+    /// Each match is the next non-overlapping match after the last, skipping
+    /// any text in between
+    #[default]
+    Search,
+    /// Each match must start where the last ended, and the first at the start
+    /// of the text. When deserializing, any text between matches or after the
+    /// last is an error, which suits strict tokenizing.
+    Consecutive,
+    /// Each match is the leftmost match starting after the start of the last,
+    /// so matches may overlap
+    Overlapping,
+}
+
+/// How a node's patterns are compiled. The flags are set with the builder
+/// rather than written inline in every pattern, and inline flags in a pattern
+/// still override them. The limits are in bytes, and the regex crate's
+/// defaults apply when they are not set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "deserialize-regex-tree",
+    derive(Deserialize),
+    serde(default)
+)]
+pub(crate) struct CompileOptions {
+    pub(crate) case_insensitive: bool,
+    pub(crate) multi_line: bool,
+    pub(crate) dot_matches_new_line: bool,
+    pub(crate) size_limit: Option<usize>,
+    pub(crate) dfa_size_limit: Option<usize>,
+}
+
+impl CompileOptions {
+    fn regex(&self, pattern: &str) -> Result<Regex, regex::Error> {
+        let mut builder = RegexBuilder::new(pattern);
+        builder
+            .case_insensitive(self.case_insensitive)
+            .multi_line(self.multi_line)
+            .dot_matches_new_line(self.dot_matches_new_line);
+        if let Some(size_limit) = self.size_limit {
+            builder.size_limit(size_limit);
+        }
+        #[cfg(feature = "regex")]
+        if let Some(dfa_size_limit) = self.dfa_size_limit {
+            builder.dfa_size_limit(dfa_size_limit);
+        }
+        builder.build()
+    }
+
+    /// Compile a pattern with these options. Flags are written inline, as fancy-regex's builder
+    /// does not set them all, nor apply them to lookaround. The size limits apply to the regex
+    /// that fancy-regex delegates to.
+    #[cfg(feature = "fancy")]
+    fn fancy_regex(&self, pattern: &str) -> Result<fancy_regex::Regex, regex::Error> {
+        let flags: String = [
+            (self.case_insensitive, 'i'),
+            (self.multi_line, 'm'),
+            (self.dot_matches_new_line, 's'),
+        ]
+        .into_iter()
+        .filter_map(|(set, flag)| set.then_some(flag))
+        .collect();
+        let pattern = if flags.is_empty() {
+            pattern.to_owned()
+        } else {
+            format!("(?{flags}){pattern}")
+        };
+        let mut builder = fancy_regex::RegexBuilder::new(&pattern);
+        if let Some(size_limit) = self.size_limit {
+            builder.delegate_size_limit(size_limit);
+        }
+        if let Some(dfa_size_limit) = self.dfa_size_limit {
+            builder.delegate_dfa_size_limit(dfa_size_limit);
+        }
+        builder
+            .build()
+            .map_err(|err| regex::Error::Syntax(err.to_string()))
+    }
+
+    #[cfg(feature = "regex")]
+    fn regex_set<'p>(
+        &self,
+        patterns: impl IntoIterator<Item = &'p str>,
+    ) -> Result<RegexSet, regex::Error> {
+        let mut builder = RegexSetBuilder::new(patterns);
+        builder
+            .case_insensitive(self.case_insensitive)
+            .multi_line(self.multi_line)
+            .dot_matches_new_line(self.dot_matches_new_line);
+        if let Some(size_limit) = self.size_limit {
+            builder.size_limit(size_limit);
+        }
+        if let Some(dfa_size_limit) = self.dfa_size_limit {
+            builder.dfa_size_limit(dfa_size_limit);
+        }
+        builder.build()
+    }
+}
+
+/// Settings of a single node that do not affect which text it matches
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(
+    feature = "deserialize-regex-tree",
+    derive(Deserialize),
+    serde(default)
+)]
+struct NodeOptions {
+    duplicate_keys: DuplicateKeys,
+    empty_captures: EmptyCaptures,
+    count: Count,
+    datetime_format: Option<String>,
+    decoding: Option<Decoding>,
+    /// Escapes resolved in this node's text before it is parsed
+    unescape: Option<Escapes>,
+    /// Keys to deserialize capture groups under instead of their own, by
+    /// group name or key
+    aliases: HashMap<String, String>,
+    /// Case to convert the keys of groups without an alias to
+    rename_all: Option<Case>,
+    /// Separator splitting keys into the path of the nested maps they are
+    /// deserialized into
+    key_separator: Option<String>,
+    /// Key of the group whose text picks the variant of an enum
+    tag: Option<String>,
+    /// Groups deserialized as whether they took part in a match, by group
+    /// name or key
+    flags: HashSet<String>,
+    /// Groups deserialized from every match of the node when it is
+    /// deserialized as a single map, by group name or key
+    collect_all: HashSet<String>,
+    /// Tokens to translate the text of capture groups into before parsing,
+    /// by group name or key
+    value_maps: HashMap<String, HashMap<String, String>>,
+    /// Types expected of the text of capture groups, by group name or key
+    types: HashMap<String, ValueType>,
+    /// Whether an integer is deserialized from this node as its number of
+    /// matches, rather than parsed from its text
+    count_matches: bool,
+}
+
+/// How many matches a node is expected to have when it is deserialized as a
+/// sequence, for [`with_count`](Builder::with_count). Deserialization fails
+/// when the count is outside the expected range.
+///
+/// In a serialized regex tree, this is written as `count: {min: 1, max: 3}`,
+/// where either bound may be left out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "deserialize-regex-tree",
+    derive(Deserialize),
+    serde(default)
+)]
+pub struct Count {
+    min: usize,
+    max: Option<usize>,
+}
+
+impl Count {
+    /// Any number of matches, including none. This is the default.
+    pub fn any() -> Self {
+        Self::default()
+    }
+
+    pub fn at_least(min: usize) -> Self {
+        Self { min, max: None }
+    }
+
+    pub fn at_most(max: usize) -> Self {
+        Self {
+            min: 0,
+            max: Some(max),
+        }
+    }
+
+    pub fn exactly(count: usize) -> Self {
+        Self::between(count, count)
+    }
+
+    /// Between `min` and `max` matches, inclusive
+    pub fn between(min: usize, max: usize) -> Self {
+        Self {
+            min,
+            max: Some(max),
+        }
+    }
+
+    pub fn min(&self) -> usize {
+        self.min
+    }
+
+    pub fn max(&self) -> Option<usize> {
+        self.max
+    }
+
+    /// Whether `count` matches would be accepted
+    pub fn contains(&self, count: usize) -> bool {
+        self.min <= count && self.max.is_none_or(|max| count <= max)
+    }
+}
+
+impl fmt::Display for Count {
+    /// Formats like `at least 1` or `between 2 and 4`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.min, self.max) {
+            (0, None) => write!(f, "any number of"),
+            (min, None) => write!(f, "at least {min}"),
+            (0, Some(max)) => write!(f, "at most {max}"),
+            (min, Some(max)) if min == max => write!(f, "exactly {min}"),
+            (min, Some(max)) => write!(f, "between {min} and {max}"),
+        }
+    }
+}
+
+/// How a node treats several capture groups with the same key, for
+/// [`with_duplicate_keys`](Builder::with_duplicate_keys).
+///
+/// The regex crate does not allow two groups to share a name, so a group
+/// named like `tag[1]` or `tag[second]` is keyed as `tag` instead. This also
+/// allows the same key to appear in each branch of an alternation, where
+/// only one of the groups will take part in any match. A child for `tag`
+/// applies to every group keyed as `tag`, unless there is a child for the
+/// group's full name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "deserialize-regex-tree",
+    derive(Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum DuplicateKeys {
+    /// Fail to deserialize when more than one group with the same key takes
+    /// part in a match
+    #[default]
+    Error,
+    /// Use the first participating group, in pattern order
+    FirstWins,
+    /// Use the last participating group, in pattern order
+    LastWins,
+    /// Deserialize every participating group with the key as a sequence. The
+    /// value is a sequence even when only one group takes part.
+    Collect,
+}
+
+/// A key of a map a match is deserialized into, with its path, a slice of a
+/// full key, and whether it is the prefix of other keys rather than a key of
+/// its own. See [`with_nested_keys`](Builder::with_nested_keys).
+pub(crate) type NestedKey<'a> = (&'a str, &'a str, bool);
+
+/// How a capture group that takes part in a match but matches no text is
+/// deserialized, for [`with_empty_captures`](Builder::with_empty_captures).
+/// Groups that do not take part in a match are always missing: an `Option`
+/// is `None`, and a struct field without a default is an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "deserialize-regex-tree",
+    derive(Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum EmptyCaptures {
+    /// Deserialize the group from its empty text, so an `Option<String>` is
+    /// `Some("")`
+    #[default]
+    Empty,
+    /// Treat the group as if it did not take part in the match, so an
+    /// `Option<String>` is `None`
+    Missing,
+}
+
+/// How a node's text is decoded when it is deserialized as bytes, such as
+/// into `Vec<u8>`, for [`with_decoding`](Builder::with_decoding).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "deserialize-regex-tree",
+    derive(Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Decoding {
+    /// Two hex digits per byte, in either case
+    Hex,
+    /// Base64 with the standard or URL-safe alphabet, with or without padding
+    Base64,
+    /// Percent-encoding as in URLs, where `%` and two hex digits is a byte and
+    /// anything else stands for itself
+    Percent,
+}
+
+impl fmt::Display for Decoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Hex => write!(f, "hex"),
+            Self::Base64 => write!(f, "base64"),
+            Self::Percent => write!(f, "percent-encoded"),
+        }
+    }
+}
+
+/// Escapes resolved in a node's text before it is parsed, for
+/// [`with_unescape`](Builder::with_unescape). An invalid escape is an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "deserialize-regex-tree",
+    derive(Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Escapes {
+    /// `\\`, `\"`, `\'`, `\n`, `\r`, `\t`, `\0`, and `\u{...}` with up to
+    /// six hex digits, as in Rust and many log formats
+    Backslash,
+    /// `""` for a single `"`, as in quoted CSV fields and SQL identifiers
+    DoubledQuotes,
+    /// The escapes of JSON strings: `\\`, `\"`, `\/`, `\b`, `\f`, `\n`, `\r`,
+    /// `\t`, and `\uXXXX`, with surrogate pairs for chars beyond the basic
+    /// multilingual plane
+    Json,
+}
+
+impl fmt::Display for Escapes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Backslash => write!(f, "backslash"),
+            Self::DoubledQuotes => write!(f, "doubled quotes"),
+            Self::Json => write!(f, "JSON"),
+        }
+    }
+}
+
+/// Type expected of the text of a capture group, for
+/// [`with_type`](Builder::with_type). Text that does not read as the type is
+/// an error, whatever it is deserialized as. Without a Rust type to guide it,
+/// such as when deserializing a [`Value`](crate::Value), the group becomes a
+/// value of the type rather than a string.
+///
+/// In a serialized regex tree, this is written as `int`, `float`, `bool`,
+/// `string` or `datetime(format)`, such as `types: {port: "int"}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "deserialize-regex-tree",
+    derive(Deserialize),
+    serde(try_from = "String")
+)]
+pub enum ValueType {
+    /// An integer, with an optional `-` sign, that fits in 64 bits
+    Int,
+    /// A finite number, with or without a fraction or exponent
+    Float,
+    /// A bool, written as any of the forms `bool` fields accept, such as
+    /// `true`, `no` or `1`
+    Bool,
+    /// Any text, which is never inferred to be a number or bool
+    String,
+    /// A date, time, or both, in a [`chrono` format](chrono::format::strftime),
+    /// as with [`with_datetime_format`](Builder::with_datetime_format). Only
+    /// usable with the `chrono` feature.
+    Datetime(String),
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Int => write!(f, "int"),
+            Self::Float => write!(f, "float"),
+            Self::Bool => write!(f, "bool"),
+            Self::String => write!(f, "string"),
+            Self::Datetime(format) => write!(f, "datetime({format})"),
+        }
+    }
+}
+
+#[cfg(feature = "deserialize-regex-tree")]
+impl TryFrom<String> for ValueType {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        match name.as_str() {
+            "int" => Ok(Self::Int),
+            "float" => Ok(Self::Float),
+            "bool" => Ok(Self::Bool),
+            "string" => Ok(Self::String),
+            _ => match name
+                .strip_prefix("datetime(")
+                .and_then(|format| format.strip_suffix(')'))
+            {
+                Some(format) => Ok(Self::Datetime(format.to_owned())),
+                None => Err(format!(
+                    "unknown type {name:?}, expected `int`, `float`, `bool`, `string` or `datetime(format)`"
+                )),
+            },
+        }
+    }
+}
+
+/// Case convention for the keys of capture groups, for
+/// [`with_rename_all`](Builder::with_rename_all). A key is split into words at
+/// underscores, hyphens, and lowercase letters or digits followed by
+/// uppercase letters, then written out in the case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "deserialize-regex-tree", derive(Deserialize))]
+pub enum Case {
+    /// `like_this`
+    #[cfg_attr(feature = "deserialize-regex-tree", serde(rename = "snake_case"))]
+    Snake,
+    /// `like-this`
+    #[cfg_attr(feature = "deserialize-regex-tree", serde(rename = "kebab-case"))]
+    Kebab,
+    /// `likeThis`
+    #[cfg_attr(feature = "deserialize-regex-tree", serde(rename = "camelCase"))]
+    Camel,
+    /// `LikeThis`
+    #[cfg_attr(feature = "deserialize-regex-tree", serde(rename = "PascalCase"))]
+    Pascal,
+    /// `LIKE_THIS`
+    #[cfg_attr(
+        feature = "deserialize-regex-tree",
+        serde(rename = "SCREAMING_SNAKE_CASE")
+    )]
+    ScreamingSnake,
+}
+
+impl Case {
+    /// Write `key` in this case
+    pub fn convert(self, key: &str) -> String {
+        let mut words: Vec<String> = Vec::new();
+        let mut previous: Option<char> = None;
+        for c in key.chars() {
+            if c == '_' || c == '-' {
+                previous = None;
+                continue;
+            }
+            let starts_word = match previous {
+                None => true,
+                Some(previous) => {
+                    c.is_uppercase() && (previous.is_lowercase() || previous.is_ascii_digit())
+                }
+            };
+            if starts_word {
+                words.push(String::new());
+            }
+            words.last_mut().expect("a word was started").push(c);
+            previous = Some(c);
+        }
+
+        let mut converted = String::with_capacity(key.len());
+        for (index, word) in words.iter().enumerate() {
+            let separator = match self {
+                Self::Snake | Self::ScreamingSnake => "_",
+                Self::Kebab => "-",
+                Self::Camel | Self::Pascal => "",
+            };
+            if index > 0 {
+                converted.push_str(separator);
+            }
+            let capitalize = match self {
+                Self::Snake | Self::Kebab => false,
+                Self::Camel => index > 0,
+                Self::Pascal => true,
+                Self::ScreamingSnake => {
+                    converted.push_str(&word.to_uppercase());
+                    continue;
+                }
+            };
+            let mut chars = word.chars();
+            if let Some(first) = chars.next() {
+                if capitalize {
+                    converted.extend(first.to_uppercase());
+                } else {
+                    converted.extend(first.to_lowercase());
+                }
+                converted.push_str(&chars.as_str().to_lowercase());
+            }
+        }
+        converted
+    }
+}
+
+/// The named capture groups that took part in one match of a node, for
+/// choosing a child with [`with_child_if`](Builder::with_child_if)
+pub struct GroupValues<'r, 't> {
+    values: Vec<(&'r str, &'t str)>,
+}
+
+impl<'r, 't> GroupValues<'r, 't> {
+    pub(crate) fn new(
+        names: impl Iterator<Item = Option<&'r str>>,
+        values: impl Iterator<Item = Option<&'t str>>,
+    ) -> Self {
+        let values = names
+            .zip(values)
+            .filter_map(|(name, value)| Some((name?, value?)))
+            .collect();
+        Self { values }
+    }
+
+    /// Text of the capture group with the given name, or else the first with
+    /// the given key. `None` if no such group took part in the match.
+    pub fn name(&self, name: &str) -> Option<&'t str> {
+        let find = |matches: &dyn Fn(&str) -> bool| {
+            self.values
+                .iter()
+                .find(|(group, _)| matches(group))
+                .map(|(_, value)| *value)
+        };
+        find(&|group| group == name).or_else(|| find(&|group| group_key(group) == name))
+    }
+}
+
+/// Condition under which a child applies, given the groups of the match
+type Condition = Arc<dyn Fn(&GroupValues) -> bool + Send + Sync>;
+
+/// A child for a capture group that applies only when its condition holds.
+/// See [`with_child_if`](Builder::with_child_if).
+#[derive(Clone)]
+struct ConditionalChild {
+    /// Name or key of the capture group
+    group: String,
+    condition: Condition,
+    child: RegexTree,
+}
+
+/// Conditions cannot be compared, so only the same condition is equal
+impl PartialEq for ConditionalChild {
+    fn eq(&self, other: &Self) -> bool {
+        self.group == other.group
+            && Arc::ptr_eq(&self.condition, &other.condition)
+            && self.child == other.child
+    }
+}
+
+impl fmt::Debug for ConditionalChild {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ConditionalChild")
+            .field("group", &self.group)
+            .field("child", &self.child)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Condition a match must meet to be kept. See [`with_filter`](Builder::with_filter).
+#[derive(Clone)]
+struct Filter(Condition);
+
+impl PartialEq for Filter {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl fmt::Debug for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Filter").finish_non_exhaustive()
+    }
+}
+
+/// The key a capture group is deserialized under: its name without any
+/// trailing `[...]`. See [`DuplicateKeys`].
+pub(crate) fn group_key(name: &str) -> &str {
+    match name.find('[') {
+        Some(index) if index > 0 && name.ends_with(']') => &name[..index],
+        _ => name,
+    }
+}
+
+/// Serialized form of a regex tree. The patterns are compiled only once the
+/// compile options are known, so limits apply to untrusted patterns, and a
+/// pattern that fails to compile is a deserialization error.
+///
+/// A node may instead be a reference to a definition, written as
+/// `{"$ref": "name"}`, which is replaced by the definition of that name in
+/// the `definitions` of the node or of any node above it, the nearest first.
+/// A reference cannot have settings of its own.
+#[cfg(feature = "deserialize-regex-tree")]
+#[derive(Clone, Deserialize)]
+struct RegexTreeDef {
+    #[serde(default)]
+    regex: Option<String>,
+    /// Name of the definition this node is
+    #[serde(default, rename = "$ref")]
+    reference: Option<String>,
+    /// Nodes that this node and those below it can refer to by name
+    #[serde(default)]
+    definitions: HashMap<String, RegexTreeDef>,
+    #[serde(default)]
+    fallbacks: Vec<String>,
+    #[serde(default)]
+    lines: bool,
+    #[serde(default)]
+    split: Option<String>,
+    #[serde(default)]
+    split_literal: Option<String>,
+    #[serde(default)]
+    frames: Option<String>,
+    #[serde(default)]
+    paragraphs: bool,
+    #[serde(default)]
+    sections: Option<Vec<String>>,
+    /// Whether `regex` matches headers of keyed sections
+    #[serde(default)]
+    headed: bool,
+    #[serde(default)]
+    full_match: bool,
+    #[serde(default)]
+    match_policy: MatchPolicy,
+    #[serde(default)]
+    children: Children<RegexTreeDef>,
+    #[serde(default)]
+    default_child: Option<Box<RegexTreeDef>>,
+    /// Whether the patterns are compiled with fancy-regex
+    #[cfg(feature = "fancy")]
+    #[serde(default)]
+    fancy: bool,
+    #[serde(flatten)]
+    compile_options: CompileOptions,
+    #[serde(flatten)]
+    options: NodeOptions,
+}
+
+#[cfg(feature = "deserialize-regex-tree")]
+impl TryFrom<RegexTreeDef> for RegexTree {
+    type Error = BuildError;
+
+    fn try_from(def: RegexTreeDef) -> Result<Self, Self::Error> {
+        def.build(&mut Expansion::default())
+    }
+}
+
+/// Most nodes a deserialized tree can have once its references are replaced. Definitions that
+/// refer to others more than once would otherwise grow exponentially with how deeply they nest.
+#[cfg(feature = "deserialize-regex-tree")]
+const MAX_EXPANDED_NODES: usize = 10_000;
+
+/// State of building a deserialized tree as its references are replaced
+#[cfg(feature = "deserialize-regex-tree")]
+#[derive(Default)]
+struct Expansion<'d> {
+    /// Definitions of the nodes above the one being built, outermost first
+    scopes: Vec<&'d HashMap<String, RegexTreeDef>>,
+    /// Names of the definitions being built, to catch those that refer to themselves
+    expanding: Vec<String>,
+    /// Definitions built so far, with their number of nodes, so each is only built once
+    built: HashMap<*const RegexTreeDef, (RegexTree, usize)>,
+    /// Number of nodes built so far, counting each copy of a definition
+    nodes: usize,
+}
+
+#[cfg(feature = "deserialize-regex-tree")]
+impl Expansion<'_> {
+    /// Count `nodes` more nodes, failing if there are too many
+    fn add_nodes(&mut self, nodes: usize) -> Result<(), BuildError> {
+        self.nodes += nodes;
+        match self.nodes > MAX_EXPANDED_NODES {
+            true => Err(BuildError::TooManyNodes {
+                limit: MAX_EXPANDED_NODES,
+            }),
+            false => Ok(()),
+        }
+    }
+}
+
+#[cfg(feature = "deserialize-regex-tree")]
+impl RegexTreeDef {
+    /// Build the node and the nodes below it
+    fn build<'d>(&'d self, expansion: &mut Expansion<'d>) -> Result<RegexTree, BuildError> {
+        expansion.scopes.push(&self.definitions);
+        let regex_tree = match (&self.reference, &self.regex) {
+            (Some(name), _) => match self.settings() {
+                keys if keys.is_empty() => self.build_reference(name, expansion),
+                keys => Err(BuildError::RefWithSettings {
+                    name: name.clone(),
+                    keys,
+                }),
+            },
+            (None, Some(regex)) => self.build_node(regex, expansion),
+            (None, None) => Err(BuildError::MissingRegex),
+        };
+        expansion.scopes.pop();
+        regex_tree
+    }
+
+    /// Build the definition named `name`, seeing only the definitions in scope where it is
+    /// defined. A definition is built the first time it is referred to, and copied after that.
+    fn build_reference<'d>(
+        &self,
+        name: &str,
+        expansion: &mut Expansion<'d>,
+    ) -> Result<RegexTree, BuildError> {
+        let (depth, definition) = expansion
+            .scopes
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(depth, scope)| Some((depth, scope.get(name)?)))
+            .ok_or_else(|| BuildError::UnknownDefinition {
+                name: name.to_owned(),
+            })?;
+        if let Some((regex_tree, nodes)) = expansion.built.get(&(definition as *const _)) {
+            let (regex_tree, nodes) = (regex_tree.clone(), *nodes);
+            expansion.add_nodes(nodes)?;
+            return Ok(regex_tree);
+        }
+        if expansion.expanding.iter().any(|expanded| expanded == name) {
+            return Err(BuildError::CyclicDefinition {
+                name: name.to_owned(),
+            });
+        }
+
+        let scopes = expansion.scopes[..=depth].to_vec();
+        let scopes = std::mem::replace(&mut expansion.scopes, scopes);
+        expansion.expanding.push(name.to_owned());
+        let before = expansion.nodes;
+        let regex_tree = definition.build(expansion);
+        expansion.expanding.pop();
+        expansion.scopes = scopes;
+
+        let regex_tree = regex_tree?;
+        let nodes = expansion.nodes - before;
+        expansion
+            .built
+            .insert(definition, (regex_tree.clone(), nodes));
+        Ok(regex_tree)
+    }
+
+    /// Keys of the settings given to this node besides its definitions, which a reference
+    /// cannot have as they would be lost when it is replaced. Settings left at their defaults
+    /// are not counted.
+    fn settings(&self) -> Vec<String> {
+        let options = &self.options;
+        let defaults = NodeOptions::default();
+        let compile_options = &self.compile_options;
+        [
+            ("regex", self.regex.is_some()),
+            ("fallbacks", !self.fallbacks.is_empty()),
+            ("lines", self.lines),
+            ("split", self.split.is_some()),
+            ("split_literal", self.split_literal.is_some()),
+            ("frames", self.frames.is_some()),
+            ("paragraphs", self.paragraphs),
+            ("sections", self.sections.is_some()),
+            ("headed", self.headed),
+            ("full_match", self.full_match),
+            ("match_policy", self.match_policy != MatchPolicy::default()),
+            ("children", !self.children.is_empty()),
+            ("default_child", self.default_child.is_some()),
+            #[cfg(feature = "fancy")]
+            ("fancy", self.fancy),
+            ("case_insensitive", compile_options.case_insensitive),
+            ("multi_line", compile_options.multi_line),
+            ("dot_matches_new_line", compile_options.dot_matches_new_line),
+            ("size_limit", compile_options.size_limit.is_some()),
+            ("dfa_size_limit", compile_options.dfa_size_limit.is_some()),
+            (
+                "duplicate_keys",
+                options.duplicate_keys != defaults.duplicate_keys,
+            ),
+            (
+                "empty_captures",
+                options.empty_captures != defaults.empty_captures,
+            ),
+            ("count", options.count != defaults.count),
+            ("datetime_format", options.datetime_format.is_some()),
+            ("decoding", options.decoding.is_some()),
+            ("unescape", options.unescape.is_some()),
+            ("aliases", !options.aliases.is_empty()),
+            ("rename_all", options.rename_all.is_some()),
+            ("key_separator", options.key_separator.is_some()),
+            ("tag", options.tag.is_some()),
+            ("flags", !options.flags.is_empty()),
+            ("collect_all", !options.collect_all.is_empty()),
+            ("value_maps", !options.value_maps.is_empty()),
+            ("types", !options.types.is_empty()),
+            ("count_matches", options.count_matches),
+        ]
+        .into_iter()
+        .filter(|(_, set)| *set)
+        .map(|(key, _)| key.to_owned())
+        .collect()
+    }
+
+    fn build_node<'d>(
+        &'d self,
+        regex: &str,
+        expansion: &mut Expansion<'d>,
+    ) -> Result<RegexTree, BuildError> {
+        expansion.add_nodes(1)?;
+        let compile_options = self.compile_options;
+        let patterns = std::iter::once(regex)
+            .chain(self.fallbacks.iter().map(String::as_str))
+            .map(|pattern| {
+                let pattern = anchor(pattern, self.full_match);
+                #[cfg(feature = "fancy")]
+                if self.fancy {
+                    return compile_options.fancy_regex(&pattern).map(Pattern::Fancy);
+                }
+                compile_options.regex(&pattern).map(Pattern::Regex)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let split = if let Some(delimiter) = &self.split {
+            Some(Split::Delimiter(compile_options.regex(delimiter)?))
+        } else if let Some(delimiter) = &self.split_literal {
+            Some(Split::Literal(delimiter.clone()))
+        } else if let Some(start) = &self.frames {
+            Some(Split::Frames(compile_options.regex(start)?))
+        } else if self.paragraphs {
+            Some(Split::Paragraphs)
+        } else if let Some(regions) = &self.sections {
+            let regions = regions
+                .iter()
+                .map(|region| compile_options.regex(region))
+                .collect::<Result<_, _>>()?;
+            Some(Split::Sections(regions))
+        } else if self.lines {
+            Some(Split::Lines)
+        } else {
+            None
+        };
+        let children = self
+            .children
+            .iter()
+            .map(|(name, child)| Ok((name.clone(), child.build(expansion)?)))
+            .collect::<Result<_, BuildError>>()?;
+        let default_child = match &self.default_child {
+            Some(child) => Some(Box::new(child.build(expansion)?)),
+            None => None,
+        };
+        let mut regex_tree = RegexTree {
+            #[cfg(feature = "regex")]
+            pattern_set: pattern_set(&patterns, compile_options),
+            patterns,
+            full_match: self.full_match,
+            match_policy: self.match_policy,
+            split,
+            headed: self.headed,
+            children,
+            conditional_children: Vec::new(),
+            default_child,
+            filters: Vec::new(),
+            options: self.options.clone(),
+            compile_options,
+            compiled: None,
+        };
+        let aliased = regex_tree.aliased_groups();
+        regex_tree.rename_groups();
+        regex_tree.check_names(&aliased)?;
+        Ok(regex_tree)
+    }
 }
 
 impl RegexTree {
     /// Begin construction of a regex tree with children. See
     /// [`Builder`](Builder).
     pub fn root(regex: impl ToRegex) -> Builder {
-        Builder::new(regex.to_regex())
+        Builder::new(PatternSource::Regex(RegexSource::new(regex)))
+    }
+
+    /// Begin construction of a regex tree whose pattern is matched with
+    /// fancy-regex, which supports backreferences and lookaround. See
+    /// [Fancy patterns](RegexTree#fancy-patterns). The pattern is compiled
+    /// when the node is built, so an invalid pattern is a [`BuildError`].
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// // A price is a number after a currency sign, which is not captured
+    /// let regex_tree = RegexTree::fancy(r"(?<=\$)\d+").build();
+    /// let prices: Vec<u32> = from_regex_tree_and_str(&regex_tree, "$5 and 7 and $12").unwrap();
+    /// assert_eq!(prices, vec![5, 12]);
+    /// ```
+    #[cfg(feature = "fancy")]
+    pub fn fancy(regex: &str) -> Builder {
+        Builder::new(PatternSource::Fancy(regex.to_owned()))
+    }
+
+    /// Construct a regex tree with a child for each named capture group in
+    /// `children`. The same as adding each child with
+    /// [`with_child`](Builder::with_child), for when the children are already
+    /// in a collection.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// #[derive(serde::Deserialize)]
+    /// struct Player {
+    ///     name: String,
+    ///     scores: Vec<u32>,
+    /// }
+    ///
+    /// let regex_tree = RegexTree::new(
+    ///     r"(?P<name>\w+): (?P<scores>.*)",
+    ///     [("scores", RegexTree::leaf(r"\d+"))],
+    /// );
+    /// let player: Player = from_regex_tree_and_str(&regex_tree, "ada: 3 5").unwrap();
+    /// assert_eq!(player.scores, vec![3, 5]);
+    /// ```
+    pub fn new<N: Into<String>>(
+        regex: impl ToRegex,
+        children: impl IntoIterator<Item = (N, RegexTree)>,
+    ) -> Self {
+        children
+            .into_iter()
+            .fold(Self::root(regex), |builder, (name, child)| {
+                builder.with_child(name, child)
+            })
+            .build()
     }
 
     /// Construct a regex tree with no children.
     pub fn leaf(regex: impl ToRegex) -> Self {
-        Self {
-            regex: regex.to_regex(),
-            children: HashMap::new(),
+        Self::root(regex).build()
+    }
+
+    /// Begin construction of a regex tree whose regex is matched against each
+    /// line of its text in turn, rather than searched for across the whole
+    /// text. See [Line mode](RegexTree#line-mode).
+    pub fn lines(regex: impl ToRegex) -> Builder {
+        let mut builder = Self::root(regex);
+        builder.regex_tree.split = Some(Split::Lines);
+        builder
+    }
+
+    /// Begin construction of a regex tree that splits its text on each match
+    /// of `delimiter` and treats each piece as a match of its own. This is
+    /// clearer and faster than writing a regex to match the items of a simple
+    /// list. For a literal delimiter containing special characters, use
+    /// [`regex::escape`](crate::regex::escape).
+    ///
+    /// Empty text has no pieces, but otherwise empty pieces are kept, such as
+    /// after a trailing delimiter. Add a child for group 0 with
+    /// [`with_child_index`](Builder::with_child_index) to recurse on each
+    /// piece.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// let regex_tree = RegexTree::split(r"\s*,\s*").build();
+    /// let items: Vec<&str> = from_regex_tree_and_str(&regex_tree, "a, b ,c").unwrap();
+    /// assert_eq!(items, vec!["a", "b", "c"]);
+    ///
+    /// let regex_tree = RegexTree::split(",")
+    ///     .with_child_index(0, RegexTree::leaf(r"\d+"))
+    ///     .build();
+    /// let items: Vec<Vec<u32>> = from_regex_tree_and_str(&regex_tree, "1 2,3").unwrap();
+    /// assert_eq!(items, vec![vec![1, 2], vec![3]]);
+    /// ```
+    pub fn split(delimiter: impl ToRegex) -> Builder {
+        let mut builder = Self::root(r"(?s).*");
+        builder.split = Some(SplitSource::Delimiter(RegexSource::new(delimiter)));
+        builder
+    }
+
+    /// Begin construction of a regex tree that splits its text on each
+    /// occurrence of the literal string `delimiter`, like
+    /// [`split`](RegexTree::split) but without compiling a regex or escaping
+    /// special characters.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// let regex_tree = RegexTree::split_literal("|").build();
+    /// let items: Vec<&str> = from_regex_tree_and_str(&regex_tree, "a|b||c").unwrap();
+    /// assert_eq!(items, vec!["a", "b", "", "c"]);
+    /// ```
+    pub fn split_literal(delimiter: impl Into<String>) -> Builder {
+        let mut builder = Self::root(r"(?s).*");
+        builder.regex_tree.split = Some(Split::Literal(delimiter.into()));
+        builder
+    }
+
+    /// Begin construction of a regex tree that frames its text into records,
+    /// each beginning at a match of `start` and running up to the next, such
+    /// as log entries that begin with a timestamp and continue over several
+    /// lines. Finding where records begin is often far simpler, and faster
+    /// over large inputs, than one pattern that matches whole records.
+    ///
+    /// A line ending at the end of a frame is not part of it. Text before the
+    /// first match is a frame of its own unless it is only whitespace. Add a
+    /// child for group 0 with [`with_child_index`](Builder::with_child_index)
+    /// to parse each frame.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// #[derive(serde::Deserialize)]
+    /// struct Entry {
+    ///     time: String,
+    ///     message: String,
+    /// }
+    ///
+    /// let regex_tree = RegexTree::frames(r"(?m)^\d\d:\d\d ")
+    ///     .with_child_index(0, RegexTree::leaf(r"(?s)(?P<time>\S+) (?P<message>.*)"))
+    ///     .build();
+    /// let text = "10:00 started\n10:05 failed:\n  at main\n10:06 stopped\n";
+    /// let entries: Vec<Entry> = from_regex_tree_and_str(&regex_tree, text).unwrap();
+    /// assert_eq!(entries.len(), 3);
+    /// assert_eq!(entries[1].message, "failed:\n  at main");
+    /// ```
+    pub fn frames(start: impl ToRegex) -> Builder {
+        let mut builder = Self::root(r"(?s).*");
+        builder.split = Some(SplitSource::Frames(RegexSource::new(start)));
+        builder
+    }
+
+    /// Begin construction of a regex tree for a document made of sections of
+    /// different shapes, such as a header, then records, then a footer. Each
+    /// section is the first match of its region pattern after the end of the
+    /// previous section, and text between sections is skipped. A section
+    /// whose pattern does not match is an error.
+    ///
+    /// Deserialize the tree as a tuple with an element per section, and give
+    /// each section a child with [`with_child_index`](Builder::with_child_index)
+    /// using the section's index to parse it. A section without a child is
+    /// just its text.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// #[derive(serde::Deserialize)]
+    /// struct Header {
+    ///     title: String,
+    /// }
+    ///
+    /// #[derive(serde::Deserialize)]
+    /// struct Record {
+    ///     name: String,
+    ///     size: u32,
+    /// }
+    ///
+    /// #[derive(serde::Deserialize)]
+    /// struct Footer {
+    ///     total: u32,
+    /// }
+    ///
+    /// let regex_tree = RegexTree::sections([r"\A.*\n", r"(?m)(?:^[a-z]+ \d+\n)*", r"TOTAL \d+"])
+    ///     .with_child_index(0, RegexTree::leaf(r"TITLE (?P<title>.*)"))
+    ///     .with_child_index(1, RegexTree::leaf(r"(?P<name>\w+) (?P<size>\d+)"))
+    ///     .with_child_index(2, RegexTree::leaf(r"TOTAL (?P<total>\d+)"))
+    ///     .build();
+    /// let text = "TITLE sizes\na 1\nb 2\nTOTAL 3";
+    /// let (header, records, footer): (Header, Vec<Record>, Footer) =
+    ///     from_regex_tree_and_str(&regex_tree, text).unwrap();
+    /// assert_eq!(header.title, "sizes");
+    /// assert_eq!(records.len(), 2);
+    /// assert_eq!(footer.total, 3);
+    /// ```
+    pub fn sections<R: ToRegex>(regions: impl IntoIterator<Item = R>) -> Builder {
+        let mut builder = Self::root(r"(?s).*");
+        let regions = regions.into_iter().map(RegexSource::new).collect();
+        builder.split = Some(SplitSource::Sections(regions));
+        builder
+    }
+
+    /// Begin construction of a regex tree for a document of sections that each
+    /// begin with a header naming them, such as an INI file or a desktop
+    /// entry. `header` matches the headers, and the text of its first capture
+    /// group that takes part, or of the whole header if it has none, is the
+    /// name of the section. The section is the text after the header up to
+    /// the next header, without the line endings around it.
+    ///
+    /// Deserialize the tree as a map or struct from the names of the sections
+    /// to their contents. Each section is parsed by the child with its name,
+    /// or else by the [default child](Builder::with_default_child), or is
+    /// just its text. Text before the first header must be blank.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// #[derive(serde::Deserialize)]
+    /// struct Config {
+    ///     server: Vec<(String, String)>,
+    ///     ports: Vec<u16>,
+    /// }
+    ///
+    /// let regex_tree = RegexTree::headed(r"(?m)^\[(?P<section>\w+)\]$")
+    ///     .with_child("ports", RegexTree::leaf(r"\d+"))
+    ///     .with_default_child(RegexTree::leaf(r"(?m)^(\w+) = (.*)$"))
+    ///     .build();
+    /// let text = "[server]\nhost = example.com\nuser = ada\n\n[ports]\n80\n443\n";
+    /// let config: Config = from_regex_tree_and_str(&regex_tree, text).unwrap();
+    /// assert_eq!(config.server[0], ("host".to_owned(), "example.com".to_owned()));
+    /// assert_eq!(config.ports, vec![80, 443]);
+    /// ```
+    pub fn headed(header: impl ToRegex) -> Builder {
+        let mut builder = Self::root(header);
+        builder.regex_tree.headed = true;
+        builder
+    }
+
+    /// Begin construction of a regex tree that frames its text into
+    /// paragraphs: runs of lines separated by one or more blank lines, which
+    /// may hold whitespace. Blank lines at the start or end make no empty
+    /// paragraphs, and a paragraph does not include its last line ending. Add
+    /// a child for group 0 with [`with_child_index`](Builder::with_child_index)
+    /// to parse each paragraph.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// let regex_tree = RegexTree::paragraphs()
+    ///     .with_child_index(0, RegexTree::leaf(r"\w+"))
+    ///     .build();
+    /// let words: Vec<Vec<&str>> =
+    ///     from_regex_tree_and_str(&regex_tree, "\na b\nc\n\n  \nd\n").unwrap();
+    /// assert_eq!(words, vec![vec!["a", "b", "c"], vec!["d"]]);
+    /// ```
+    pub fn paragraphs() -> Builder {
+        let mut builder = Self::root(r"(?s).*");
+        builder.regex_tree.split = Some(Split::Paragraphs);
+        builder
+    }
+
+    /// Begin construction of a regex tree for a fixed-width format, where each
+    /// line of the text is a record and each field sits in a fixed range of
+    /// bytes of the line. Field `n` is capture group `n + 1`, so records
+    /// deserialize as tuples; use
+    /// [`fixed_width_named`](RegexTree::fixed_width_named) to deserialize
+    /// structs. No regex is involved, so columns are cut exactly where they
+    /// say.
+    ///
+    /// A field past the end of a short line is missing, and one that runs
+    /// over the end is cut short. A line on which a field would split a
+    /// character does not match. Padding is kept, so pair this with
+    /// [`DeserializeOptions::trim`](crate::DeserializeOptions::trim) for
+    /// padded fields.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str_with_options, DeserializeOptions, RegexTree};
+    /// let regex_tree = RegexTree::fixed_width([0..8, 8..12]).build();
+    /// let text = "apples     3\npears     12";
+    ///
+    /// let options = DeserializeOptions::default().trim();
+    /// let stock: Vec<(String, u32)> =
+    ///     from_regex_tree_and_str_with_options(&regex_tree, text, &options).unwrap();
+    /// assert_eq!(stock, vec![("apples".to_owned(), 3), ("pears".to_owned(), 12)]);
+    /// ```
+    pub fn fixed_width(columns: impl IntoIterator<Item = Range<usize>>) -> Builder {
+        let columns = columns.into_iter().map(|range| (None, range));
+        Self::columns(Columns::new(columns))
+    }
+
+    /// Like [`fixed_width`](RegexTree::fixed_width), but each field is a
+    /// named capture group, so it can be deserialized as a struct field or
+    /// given a child by name.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// #[derive(Debug, PartialEq, serde::Deserialize)]
+    /// struct Entry {
+    ///     date: String,
+    ///     code: u32,
+    /// }
+    ///
+    /// let regex_tree = RegexTree::fixed_width_named([("date", 0..8), ("code", 8..11)]).build();
+    /// let entries: Vec<Entry> = from_regex_tree_and_str(&regex_tree, "20240101042").unwrap();
+    /// assert_eq!(entries, vec![Entry { date: "20240101".to_owned(), code: 42 }]);
+    /// ```
+    pub fn fixed_width_named<N: Into<String>>(
+        columns: impl IntoIterator<Item = (N, Range<usize>)>,
+    ) -> Builder {
+        let columns = columns
+            .into_iter()
+            .map(|(name, range)| (Some(name.into()), range));
+        Self::columns(Columns::new(columns))
+    }
+
+    fn columns(columns: Columns) -> Builder {
+        let mut builder = Builder::new(PatternSource::Columns(columns));
+        builder.regex_tree.split = Some(Split::Lines);
+        builder
+    }
+
+    pub(crate) fn captures<'r, 't>(&'r self, text: &'t str) -> Option<NodeCaptures<'r, 't>> {
+        if self.split.is_some() || (!self.filters.is_empty() && !self.full_match) {
+            // A single match of a split node comes from its first piece, and of a filtered node
+            // from its first match that is kept
+            return self.captures_iter(text).try_next()?.ok();
+        }
+        self.pattern_list()
+            .captures(text)
+            .filter(|captures| self.is_kept(captures))
+    }
+
+    /// Whether a match meets every [filter](Builder::with_filter) of the node
+    pub(crate) fn is_kept(&self, captures: &NodeCaptures) -> bool {
+        if self.filters.is_empty() {
+            return true;
+        }
+        let values = captures.iter().map(|re_match| Some(re_match?.as_str()));
+        let values = GroupValues::new(captures.names(), values);
+        self.filters
+            .iter()
+            .all(|Filter(condition)| condition(&values))
+    }
+
+    fn pattern_list(&self) -> Patterns<'_> {
+        let patterns = Patterns::new(&self.patterns);
+        #[cfg(feature = "regex")]
+        let patterns = patterns.with_set(self.pattern_set.as_ref());
+        patterns
+    }
+
+    pub(crate) fn captures_iter<'r, 't>(&'r self, text: &'t str) -> NodeCaptureMatches<'r, 't> {
+        let captures_iter = match &self.split {
+            Some(split) => NodeCaptureMatches::split(self.pattern_list(), split, text),
+            // The match of the whole text is already filtered
+            None if self.full_match => return NodeCaptureMatches::whole(self.captures(text)),
+            None => NodeCaptureMatches::new(self.pattern_list(), self.match_policy, text),
+        };
+        match self.filters.is_empty() {
+            true => captures_iter,
+            false => NodeCaptureMatches::filtered(captures_iter, self),
+        }
+    }
+
+    /// Iterate over the matches of this tree in `text`, each with its capture
+    /// groups and the matches of children beneath them, without deserializing.
+    /// This suits syntax highlighting, debugging, and other processing that
+    /// needs the structure of the matches rather than typed values.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::RegexTree;
+    /// let regex_tree = RegexTree::root(r"(?P<name>\w+): (?P<nums>.*)")
+    ///     .with_child("nums", RegexTree::leaf(r"\d+"))
+    ///     .build();
+    ///
+    /// let matches: Vec<_> = regex_tree.matches("a: 1 2\nb: 3").collect();
+    /// assert_eq!(matches.len(), 2);
+    ///
+    /// let nums = matches[0].group("nums").unwrap();
+    /// assert_eq!(nums.range(), 3..6);
+    /// let spans: Vec<_> = nums.matches().unwrap().iter().map(|m| m.range()).collect();
+    /// assert_eq!(spans, vec![3..4, 5..6]);
+    /// ```
+    pub fn matches<'r, 't>(&'r self, text: &'t str) -> MatchesIter<'r, 't> {
+        MatchesIter::new(self, text, 0)
+    }
+
+    /// The source pattern of this node's primary regex
+    pub fn pattern(&self) -> &str {
+        self.patterns[0].as_str()
+    }
+
+    /// The source patterns of this node's regexes: the primary pattern
+    /// followed by any fallbacks, in the order they are tried.
+    pub fn patterns(&self) -> impl Iterator<Item = &str> {
+        self.patterns.iter().map(Pattern::as_str)
+    }
+
+    /// This node's patterns, as compiled
+    pub(crate) fn compiled_patterns(&self) -> &[Pattern] {
+        &self.patterns
+    }
+
+    /// The flags and limits this node's patterns were compiled with
+    #[cfg(feature = "regex")]
+    pub(crate) fn compile_options(&self) -> CompileOptions {
+        self.compile_options
+    }
+
+    /// Whether the node was given any regexes already compiled, whose settings are unknown
+    #[cfg(feature = "regex")]
+    pub(crate) fn is_given_compiled(&self) -> bool {
+        self.compiled.is_some()
+    }
+
+    /// Compile `source` with the same engine as `pattern` and this node's
+    /// flags, if it is valid
+    pub(crate) fn compile_like(&self, pattern: &Pattern, source: &str) -> Option<Pattern> {
+        match pattern {
+            Pattern::Regex(_) => self.compile_options.regex(source).ok().map(Pattern::Regex),
+            #[cfg(feature = "fancy")]
+            Pattern::Fancy(_) => self
+                .compile_options
+                .fancy_regex(source)
+                .ok()
+                .map(Pattern::Fancy),
+            Pattern::Columns(_) => None,
+        }
+    }
+
+    /// Index into [`patterns`](RegexTree::patterns) of the pattern used when
+    /// this node matches `text` once, or `None` if no pattern matches.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::RegexTree;
+    /// let date = RegexTree::root(r"(?P<year>\d{4})-(?P<month>\d\d)")
+    ///     .with_fallback(r"(?P<month>\d\d)/(?P<year>\d{4})")
+    ///     .build();
+    /// assert_eq!(date.matching_pattern("2015-08"), Some(0));
+    /// assert_eq!(date.matching_pattern("08/2015"), Some(1));
+    /// assert_eq!(date.matching_pattern("August"), None);
+    /// ```
+    pub fn matching_pattern(&self, text: &str) -> Option<usize> {
+        self.pattern_list().position(text)
+    }
+
+    /// Names of the capture groups of this node's primary regex, in order,
+    /// starting with the implicit unnamed group 0.
+    pub fn capture_names(&self) -> impl Iterator<Item = Option<&str>> {
+        self.patterns[0].capture_names()
+    }
+
+    /// Number of capture groups of this node's primary regex, including group 0
+    pub(crate) fn captures_len(&self) -> usize {
+        self.patterns[0].captures_len()
+    }
+
+    /// Names of the named capture groups of all of this node's patterns. Names
+    /// shared between patterns are repeated.
+    pub(crate) fn patterns_capture_names(&self) -> impl Iterator<Item = &str> {
+        self.patterns
+            .iter()
+            .flat_map(|regex| regex.capture_names().flatten())
+    }
+
+    /// Keys the named capture groups of all of this node's patterns are
+    /// deserialized under, without repeats, in the order they first appear
+    pub(crate) fn group_keys(&self) -> Vec<&str> {
+        let mut seen = HashSet::new();
+        self.patterns_capture_names()
+            .map(group_key)
+            .filter(|key| seen.insert(*key))
+            .collect()
+    }
+
+    /// Keys of the map a match of this node is deserialized into, after any
+    /// aliases, without repeats, in the order they first appear. A nested key
+    /// appears as its first part. See [`with_nested_keys`](Builder::with_nested_keys).
+    pub(crate) fn field_keys(&self) -> Vec<&str> {
+        self.nested_keys(None)
+            .into_iter()
+            .map(|(key, _, _)| key)
+            .collect()
+    }
+
+    /// Keys of the outermost map a match of this node is deserialized into,
+    /// or of the map nested under the key path `prefix`, without repeats
+    pub(crate) fn nested_keys(&self, prefix: Option<&str>) -> Vec<NestedKey<'_>> {
+        let separator = self.key_separator();
+        let mut seen = HashSet::new();
+        self.patterns_capture_names()
+            .map(|name| self.field_key(name))
+            .filter_map(|key| {
+                let rest = match prefix {
+                    Some(prefix) => key.strip_prefix(prefix)?.strip_prefix(separator?)?,
+                    None => key,
+                };
+                let (part, nested) = match separator
+                    .and_then(|separator| rest.split_once(separator))
+                {
+                    Some((part, inner)) if !part.is_empty() && !inner.is_empty() => (part, true),
+                    _ => (rest, false),
+                };
+                let path = &key[..key.len() - rest.len() + part.len()];
+                Some((part, path, nested))
+            })
+            .filter(|(part, _, _)| seen.insert(*part))
+            .collect()
+    }
+
+    /// Separator splitting keys into the path of the nested maps they are
+    /// deserialized into, if any. See [`with_nested_keys`](Builder::with_nested_keys).
+    pub(crate) fn key_separator(&self) -> Option<&str> {
+        self.options.key_separator.as_deref()
+    }
+
+    /// Key of the group whose text picks the variant of an enum, if any. See
+    /// [`with_tag`](Builder::with_tag).
+    pub(crate) fn tag(&self) -> Option<&str> {
+        self.options.tag.as_deref()
+    }
+
+    /// The key a capture group is deserialized under: its alias, if it or its
+    /// key has one, or else its key. See
+    /// [`with_alias`](Builder::with_alias).
+    pub(crate) fn field_key<'a>(&'a self, name: &'a str) -> &'a str {
+        let key = group_key(name);
+        if self.options.aliases.is_empty() {
+            return key;
         }
+        self.options
+            .aliases
+            .get(name)
+            .or_else(|| self.options.aliases.get(key))
+            .map_or(key, String::as_str)
     }
 
-    pub(crate) fn captures<'t>(&self, text: &'t str) -> Option<Captures<'t>> {
-        self.regex.captures(text)
+    /// Names and keys of the groups given aliases on purpose, rather than by renaming, which
+    /// must be taken before the node's groups are renamed
+    fn aliased_groups(&self) -> HashSet<String> {
+        self.options.aliases.keys().cloned().collect()
     }
 
-    pub(crate) fn captures_iter<'r, 't>(&'r self, text: &'t str) -> CaptureMatches<'r, 't> {
-        self.regex.captures_iter(text)
+    /// Whether `name` is the name of a child that can be used: a group key, or the index of a
+    /// group, or any name for a headed node.
+    pub(crate) fn is_child_name(&self, name: &str) -> bool {
+        // Sections are parsed by the children for their names
+        if self.headed {
+            return true;
+        }
+        let captures_len = match &self.split {
+            Some(Split::Sections(regions)) => self.captures_len().max(regions.len()),
+            _ => self.captures_len(),
+        };
+        self.group_keys().contains(&name)
+            || name.parse().is_ok_and(|index: usize| index < captures_len)
     }
 
-    pub(crate) fn matches_iter<'r, 't>(&'r self, text: &'t str) -> Matches<'r, 't> {
-        self.regex.find_iter(text)
+    /// Check that every child is named after a group, and that no two groups share a key by
+    /// accident or use a reserved key. Groups in `aliased` were aliased on purpose, so sharing
+    /// a key between them is how several groups fill one field.
+    fn check_names(&self, aliased: &HashSet<String>) -> Result<(), BuildError> {
+        let mut children: Vec<&str> = self
+            .children
+            .keys()
+            .map(String::as_str)
+            .filter(|name| !self.is_child_name(name))
+            .chain(
+                self.conditional_children
+                    .iter()
+                    .map(|conditional| conditional.group.as_str())
+                    .filter(|name| !self.group_keys().contains(name)),
+            )
+            .collect();
+        children.sort_unstable();
+        if let Some(child) = children.first() {
+            return Err(BuildError::UnknownChild {
+                child: (*child).to_owned(),
+            });
+        }
+
+        let mut keys: Vec<(&str, Vec<&str>, bool)> = Vec::new();
+        for name in self.patterns_capture_names() {
+            let group = group_key(name);
+            let key = self.field_key(name);
+            if is_reserved_key(key) {
+                return Err(BuildError::ReservedKey {
+                    key: key.to_owned(),
+                });
+            }
+            let on_purpose = aliased.contains(name) || aliased.contains(group);
+            match keys.iter_mut().find(|(other, _, _)| *other == key) {
+                Some((_, groups, all_on_purpose)) => {
+                    if !groups.contains(&group) {
+                        groups.push(group);
+                        *all_on_purpose &= on_purpose;
+                    }
+                }
+                None => keys.push((key, vec![group], on_purpose)),
+            }
+        }
+        match keys
+            .into_iter()
+            .find(|(_, groups, on_purpose)| groups.len() > 1 && !on_purpose)
+        {
+            Some((key, groups, _)) => Err(BuildError::DuplicateKey {
+                key: key.to_owned(),
+                groups: groups.into_iter().map(str::to_owned).collect(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Alias every group key without an alias to the key in the node's case,
+    /// if it has one
+    fn rename_groups(&mut self) {
+        let Some(case) = self.options.rename_all else {
+            return;
+        };
+        let renamed: Vec<(String, String)> = self
+            .group_keys()
+            .into_iter()
+            .map(|key| (key.to_owned(), case.convert(key)))
+            .collect();
+        for (key, converted) in renamed {
+            self.options.aliases.entry(key).or_insert(converted);
+        }
+    }
+
+    /// Name of the first capture group of this node's patterns that is
+    /// deserialized under `field`, if any
+    pub(crate) fn field_group(&self, field: &str) -> Option<&str> {
+        self.patterns_capture_names()
+            .find(|name| self.field_key(name) == field)
     }
 
-    pub(crate) fn names(&self) -> CaptureNames {
-        self.regex.capture_names()
+    /// The child that recurses on the capture group with the given name, if
+    /// there is one.
+    pub fn child(&self, name: &str) -> Option<&RegexTree> {
+        self.children.get(name)
     }
 
-    pub(crate) fn child(&self, name: &str) -> Option<&RegexTree> {
+    /// The child that recurses on the capture group with the given index, if
+    /// there is one. See [`with_child_index`](Builder::with_child_index).
+    pub fn child_index(&self, index: usize) -> Option<&RegexTree> {
+        // This is looked up for every match, so the index is written out without allocating
+        if self.children.is_empty() {
+            return None;
+        }
+        let mut digits = [0; 20];
+        let mut start = digits.len();
+        let mut rest = index;
+        loop {
+            start -= 1;
+            digits[start] = b'0' + (rest % 10) as u8;
+            rest /= 10;
+            if rest == 0 {
+                break;
+            }
+        }
+        let name = std::str::from_utf8(&digits[start..]).expect("digits are ASCII");
         self.children.get(name)
     }
+
+    /// The child for a capture group: the first conditional child for its name or key whose
+    /// condition holds for `values`, then by its name, then by its key (see [`DuplicateKeys`]),
+    /// then by its index, then the default child if the group is named. Without `values`,
+    /// conditional children are skipped.
+    pub(crate) fn group_child(
+        &self,
+        index: usize,
+        name: Option<&str>,
+        values: Option<&GroupValues>,
+    ) -> Option<&RegexTree> {
+        if let (Some(name), Some(values)) = (name, values) {
+            let conditional = self.conditional_children.iter().find(|conditional| {
+                (conditional.group == name || conditional.group == group_key(name))
+                    && (conditional.condition)(values)
+            });
+            if let Some(conditional) = conditional {
+                return Some(&conditional.child);
+            }
+        }
+        let default_child = || name.and(self.default_child());
+        if self.children.is_empty() {
+            return default_child();
+        }
+        let by_name = |name: &str| {
+            let key = group_key(name);
+            match self.child(name) {
+                None if key != name => self.child(key),
+                child => child,
+            }
+        };
+        name.and_then(by_name)
+            .or_else(|| self.child_index(index))
+            .or_else(default_child)
+    }
+
+    /// Values of the named groups of a match, for choosing conditional
+    /// children, or `None` if the node has none
+    pub(crate) fn group_values<'r, 't>(
+        &'r self,
+        captures: &NodeCaptures<'r, 't>,
+    ) -> Option<GroupValues<'r, 't>> {
+        if self.conditional_children.is_empty() {
+            return None;
+        }
+        let values = captures.iter().map(|re_match| Some(re_match?.as_str()));
+        Some(GroupValues::new(captures.names(), values))
+    }
+
+    /// Every child of this node with the name it appears under in paths: children ordered by
+    /// name, then conditional children as `name?n`, then the default child as `*`
+    pub(crate) fn child_nodes(&self) -> Vec<(String, &RegexTree)> {
+        let mut children: Vec<_> = self
+            .children()
+            .map(|(name, child)| (name.to_owned(), child))
+            .collect();
+        children.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let conditional_children = self
+            .conditional_children()
+            .enumerate()
+            .map(|(index, (name, child))| (format!("{name}?{index}"), child));
+        let default_child = self.default_child().map(|child| ("*".to_owned(), child));
+        children.extend(conditional_children.chain(default_child));
+        children
+    }
+
+    /// Identifies this node among the nodes of live trees. The patterns are on the heap, so this
+    /// stays the same when the tree is moved, but not when it is cloned.
+    pub(crate) fn node_id(&self) -> usize {
+        self.patterns.as_ptr() as usize
+    }
+
+    /// Iterate over the conditional children of this node along with the
+    /// names of the capture groups they recurse on, in the order they are
+    /// tried. See [`with_child_if`](Builder::with_child_if).
+    pub fn conditional_children(&self) -> impl Iterator<Item = (&str, &RegexTree)> {
+        self.conditional_children
+            .iter()
+            .map(|conditional| (conditional.group.as_str(), &conditional.child))
+    }
+
+    /// The child for named capture groups that have no child of their own, if
+    /// there is one. See [`with_default_child`](Builder::with_default_child).
+    pub fn default_child(&self) -> Option<&RegexTree> {
+        self.default_child.as_deref()
+    }
+
+    /// Whether a capture group is deserialized as whether it took part in a
+    /// match. See [`with_flag`](Builder::with_flag).
+    pub(crate) fn is_flag(&self, name: &str) -> bool {
+        let flags = &self.options.flags;
+        !flags.is_empty()
+            && (flags.contains(name)
+                || flags.contains(group_key(name))
+                || flags.contains(self.field_key(name)))
+    }
+
+    /// Whether the node has keys collected across its matches. See
+    /// [`with_collect_all`](Builder::with_collect_all).
+    pub(crate) fn is_collecting(&self) -> bool {
+        !self.options.collect_all.is_empty()
+    }
+
+    /// Whether a capture group, or the key of a field, is collected across the node's matches.
+    /// See [`with_collect_all`](Builder::with_collect_all).
+    pub(crate) fn is_collected(&self, name: &str) -> bool {
+        let collect_all = &self.options.collect_all;
+        !collect_all.is_empty()
+            && (collect_all.contains(name)
+                || collect_all.contains(group_key(name))
+                || collect_all.contains(self.field_key(name)))
+    }
+
+    /// Text that a capture group's value map translates its text into, if it
+    /// has a map with an entry for the text. See
+    /// [`with_value_map`](Builder::with_value_map).
+    pub(crate) fn mapped_value(&self, name: &str, text: &str) -> Option<&str> {
+        let maps = &self.options.value_maps;
+        if maps.is_empty() {
+            return None;
+        }
+        let map = maps
+            .get(name)
+            .or_else(|| maps.get(group_key(name)))
+            .or_else(|| maps.get(self.field_key(name)))?;
+        map.get(text).map(String::as_str)
+    }
+
+    /// Type expected of a capture group's text, if it has one. See
+    /// [`with_type`](Builder::with_type).
+    pub(crate) fn value_type(&self, name: &str) -> Option<&ValueType> {
+        let types = &self.options.types;
+        if types.is_empty() {
+            return None;
+        }
+        types
+            .get(name)
+            .or_else(|| types.get(group_key(name)))
+            .or_else(|| types.get(self.field_key(name)))
+    }
+
+    /// Whether integers are deserialized from this node as its number of
+    /// matches. See [`with_counted_child`](Builder::with_counted_child).
+    pub(crate) fn is_count_matches(&self) -> bool {
+        self.options.count_matches
+    }
+
+    pub(crate) fn duplicate_keys(&self) -> DuplicateKeys {
+        self.options.duplicate_keys
+    }
+
+    /// Whether a participating capture group's match is deserialized, rather than treated as if
+    /// the group did not take part. See [`with_empty_captures`](Builder::with_empty_captures).
+    pub(crate) fn is_present(&self, re_match: &Match) -> bool {
+        self.options.empty_captures == EmptyCaptures::Empty || re_match.start() != re_match.end()
+    }
+
+    /// How the text is split into pieces for the patterns to match, if it is
+    pub(crate) fn split_kind(&self) -> Option<&Split> {
+        self.split.as_ref()
+    }
+
+    /// Whether the node's patterns match the headers of keyed sections. See
+    /// [`headed`](RegexTree::headed).
+    pub(crate) fn is_headed(&self) -> bool {
+        self.headed
+    }
+
+    /// The child that parses the section of a headed node with the given name: the child with
+    /// that name, or else the default child
+    pub(crate) fn section_child(&self, name: &str) -> Option<&RegexTree> {
+        self.children.get(name).or(self.default_child.as_deref())
+    }
+
+    /// The child that runs on the whole of the match at `index`, in place of its capture groups:
+    /// the child for group 0, or for a node split into sections, the child for the section's
+    /// index
+    pub(crate) fn match_child(&self, index: usize) -> Option<&RegexTree> {
+        match self.split {
+            Some(Split::Sections(_)) => self.child_index(index),
+            _ => self.child_index(0),
+        }
+    }
+
+    /// Whether the patterns must match the whole text, or each whole piece of a split node
+    pub(crate) fn is_full_match(&self) -> bool {
+        self.full_match
+    }
+
+    /// How many matches this node is expected to have when deserialized as a
+    /// sequence
+    pub fn count(&self) -> Count {
+        self.options.count
+    }
+
+    /// Format that this node's text is parsed with when it is deserialized as
+    /// a date or time, if any
+    pub fn datetime_format(&self) -> Option<&str> {
+        self.options.datetime_format.as_deref()
+    }
+
+    /// How this node's text is decoded when it is deserialized as bytes, if
+    /// it is
+    pub fn decoding(&self) -> Option<Decoding> {
+        self.options.decoding
+    }
+
+    /// Escapes resolved in this node's text before it is parsed, if any. See
+    /// [`with_unescape`](Builder::with_unescape).
+    pub fn escapes(&self) -> Option<Escapes> {
+        self.options.unescape
+    }
+
+    /// Iterate over the children of this node along with the names of the
+    /// capture groups they recurse on. With the `preserve-order` feature, they
+    /// are in the order they were added, or the order they appear in a
+    /// deserialized tree. Otherwise, order is unspecified.
+    pub fn children(&self) -> impl Iterator<Item = (&str, &RegexTree)> {
+        self.children
+            .iter()
+            .map(|(name, child)| (name.as_str(), child))
+    }
+
+    /// Number of levels in the tree. A leaf has depth 1.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::RegexTree;
+    /// let regex_tree = RegexTree::root(r"(?P<nums>.*)")
+    ///     .with_child("nums", RegexTree::leaf(r"\d+"))
+    ///     .build();
+    /// assert_eq!(regex_tree.depth(), 2);
+    /// assert_eq!(regex_tree.child("nums").unwrap().depth(), 1);
+    /// ```
+    pub fn depth(&self) -> usize {
+        1 + self
+            .children
+            .values()
+            .chain(self.conditional_children().map(|(_, child)| child))
+            .chain(self.default_child())
+            .map(RegexTree::depth)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// A description of the tree's structure, for documenting the format it
+    /// expects. It displays as indented text, and
+    /// [`to_dot`](crate::Schema::to_dot) renders it as a Graphviz graph.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::RegexTree;
+    /// let regex_tree = RegexTree::lines(r"(?P<name>\w+): (?P<nums>.*)")
+    ///     .with_child("nums", RegexTree::leaf(r"\d+"))
+    ///     .build();
+    /// assert_eq!(
+    ///     regex_tree.schema().to_string(),
+    ///     "pattern `(?P<name>\\w+): (?P<nums>.*)`
+    /// split lines
+    /// groups
+    ///   1 name
+    ///   2 nums -> nums
+    /// child nums
+    ///   pattern `\\d+`
+    /// "
+    /// );
+    /// ```
+    pub fn schema(&self) -> Schema<'_> {
+        Schema::new(self)
+    }
+
+    /// Compile every regex in the tree again, with the flags and size limits
+    /// of its node, and gather statistics on them, such as program size and
+    /// memory usage. This is intended for checking
+    /// user-provided trees against a quota before using them.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::RegexTree;
+    /// let regex_tree = RegexTree::root(r"(?P<nums>.*)")
+    ///     .with_child("nums", RegexTree::leaf(r"\d+"))
+    ///     .build();
+    /// let stats = regex_tree.compile_stats();
+    /// assert_eq!(stats.nodes().len(), 2);
+    /// assert_eq!(stats.nodes()[1].path(), ["nums"]);
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn compile_stats(&self) -> CompileStats {
+        CompileStats::from_regex_tree(self)
+    }
+
+    /// Heap memory, in bytes, used by the compiled regexes of the whole tree.
+    /// Shorthand for `compile_stats().memory_usage()`.
+    #[cfg(feature = "regex")]
+    pub fn memory_usage(&self) -> usize {
+        self.compile_stats().memory_usage()
+    }
 }
 
 pub struct Builder {
-    regex: Regex,
-    children: HashMap<String, RegexTree>,
+    regex_tree: RegexTree,
+    compile_options: CompileOptions,
+    /// The primary pattern followed by any fallbacks, compiled when the node is built
+    patterns: Vec<PatternSource>,
+    /// The regexes that split the node's text, compiled when the node is built
+    split: Option<SplitSource>,
 }
 
 impl Builder {
-    fn new(regex: Regex) -> Self {
+    fn new(pattern: PatternSource) -> Self {
         Self {
-            regex,
-            children: HashMap::new(),
+            regex_tree: RegexTree {
+                patterns: Vec::new(),
+                #[cfg(feature = "regex")]
+                pattern_set: None,
+                split: None,
+                headed: false,
+                full_match: false,
+                match_policy: MatchPolicy::default(),
+                children: Children::new(),
+                conditional_children: Vec::new(),
+                default_child: None,
+                filters: Vec::new(),
+                options: NodeOptions::default(),
+                compile_options: CompileOptions::default(),
+                compiled: None,
+            },
+            compile_options: CompileOptions::default(),
+            patterns: vec![pattern],
+            split: None,
         }
     }
 
+    /// Record that the node was given a compiled regex, which cannot be compared by its source
+    fn mark_compiled(&mut self) {
+        self.regex_tree.compiled.get_or_insert_with(Arc::default);
+    }
+
+    /// Add a pattern to try if the patterns before it do not match. See
+    /// [Fallback patterns](RegexTree#fallback-patterns).
+    pub fn with_fallback(mut self, regex: impl ToRegex) -> Self {
+        self.patterns
+            .push(PatternSource::Regex(RegexSource::new(regex)));
+        self
+    }
+
+    /// Add a pattern to try if the patterns before it do not match, matched
+    /// with fancy-regex. See [Fancy patterns](RegexTree#fancy-patterns).
+    #[cfg(feature = "fancy")]
+    pub fn with_fancy_fallback(mut self, regex: &str) -> Self {
+        self.patterns.push(PatternSource::Fancy(regex.to_owned()));
+        self
+    }
+
     /// Add a child with the given name to the regex tree under construction
     pub fn with_child(mut self, name: impl Into<String>, child: RegexTree) -> Self {
-        self.children.insert(name.into(), child);
+        self.regex_tree.children.insert(name.into(), child);
+        self
+    }
+
+    /// Add a child with the given name whose number of matches is the value
+    /// of an integer field, rather than text parsed as a number. This suits
+    /// tallies, such as the number of error tokens in a line. The child is
+    /// otherwise like any other, so other types, such as a `Vec` of its
+    /// matches, are deserialized from it as usual.
+    ///
+    /// In a serialized regex tree, the child sets `count_matches: true`.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// #[derive(serde::Deserialize)]
+    /// struct Build {
+    ///     target: String,
+    ///     errors: usize,
+    /// }
+    ///
+    /// let regex_tree = RegexTree::lines(r"(?P<target>\w+): (?P<errors>.*)")
+    ///     .with_counted_child("errors", RegexTree::leaf(r"\bE\d+"))
+    ///     .build();
+    /// let builds: Vec<Build> =
+    ///     from_regex_tree_and_str(&regex_tree, "app: E1 W7 E4\nlib: W2").unwrap();
+    /// assert_eq!(builds[0].errors, 2);
+    /// assert_eq!(builds[1].errors, 0);
+    /// ```
+    pub fn with_counted_child(self, name: impl Into<String>, mut child: RegexTree) -> Self {
+        child.options.count_matches = true;
+        self.with_child(name, child)
+    }
+
+    /// Add a child for the capture group with the given index. This allows
+    /// recursing into unnamed groups, such as those of third-party patterns.
+    /// The child is stored under the index written in decimal, which cannot
+    /// clash with a group name.
+    ///
+    /// Unnamed groups are only seen when deserializing a match as a sequence or
+    /// tuple, since maps and structs need names for keys.
+    ///
+    /// A child for group 0, the whole match, is different: it runs on each
+    /// whole match in place of the match's own capture groups. See
+    /// [`with_whole_match_child`](Builder::with_whole_match_child).
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// let regex_tree = RegexTree::root(r"(\w+)=(.*)")
+    ///     .with_child_index(2, RegexTree::leaf(r"\d+"))
+    ///     .build();
+    /// // Group 0, the whole match, comes first when the tuple has room for it
+    /// let pairs: Vec<(&str, &str, Vec<u32>)> =
+    ///     from_regex_tree_and_str(&regex_tree, "a=1,2\nb=3").unwrap();
+    /// assert_eq!(pairs, vec![("a=1,2", "a", vec![1, 2]), ("b=3", "b", vec![3])]);
+    /// ```
+    pub fn with_child_index(self, index: usize, child: RegexTree) -> Self {
+        self.with_child(index.to_string(), child)
+    }
+
+    /// Add a child that runs on the whole of each match, in place of the
+    /// match's own capture groups. This suits pipelines that first grab a
+    /// block of text, then parse inside it. Shorthand for
+    /// [`with_child_index`](Builder::with_child_index) with index 0.
+    ///
+    /// The child is used whether the node is deserialized as a sequence, as
+    /// an option, or as a single struct or map.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// #[derive(serde::Deserialize)]
+    /// struct Block {
+    ///     name: String,
+    ///     size: u32,
+    /// }
+    ///
+    /// let regex_tree = RegexTree::root(r"(?s)BEGIN.*?END")
+    ///     .with_whole_match_child(
+    ///         RegexTree::leaf(r"(?m)^name: (?P<name>\w+)$(?s:.*)^size: (?P<size>\d+)$"),
+    ///     )
+    ///     .build();
+    ///
+    /// let text = "BEGIN\nname: a\nsize: 1\nEND\nBEGIN\nname: b\nsize: 2\nEND";
+    /// let blocks: Vec<Block> = from_regex_tree_and_str(&regex_tree, text).unwrap();
+    /// assert_eq!(blocks[1].name, "b");
+    /// assert_eq!(blocks[1].size, 2);
+    /// ```
+    pub fn with_whole_match_child(self, child: RegexTree) -> Self {
+        self.with_child_index(0, child)
+    }
+
+    /// Add a child for the capture group with the given name or key that
+    /// applies only to matches where `condition` holds. The condition is
+    /// given the values of the match's named groups, so the child can depend
+    /// on a sibling group, such as a type column that decides how a payload
+    /// is structured.
+    ///
+    /// Conditional children are tried in the order they were added, before
+    /// any other child. When none of their conditions hold, the group's usual
+    /// child applies, if it has one. [`validate`](crate::validate) only sees
+    /// the usual child, as it has no match to decide with. Conditions are
+    /// closures, so trees read with the `deserialize-regex-tree` feature
+    /// cannot have conditional children.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// # use std::collections::HashMap;
+    /// #[derive(Debug, PartialEq, serde::Deserialize)]
+    /// struct Event<'a> {
+    ///     kind: &'a str,
+    ///     #[serde(borrow)]
+    ///     payload: HashMap<String, &'a str>,
+    /// }
+    ///
+    /// let regex_tree = RegexTree::lines(r"(?P<kind>\w+) (?P<payload>.*)")
+    ///     .with_child_if(
+    ///         "payload",
+    ///         |groups| groups.name("kind") == Some("move"),
+    ///         RegexTree::leaf(r"(?P<x>-?\d+),(?P<y>-?\d+)"),
+    ///     )
+    ///     .with_child_if(
+    ///         "payload",
+    ///         |groups| groups.name("kind") == Some("say"),
+    ///         RegexTree::leaf(r"(?P<words>.+)"),
+    ///     )
+    ///     .build();
+    ///
+    /// let events: Vec<Event> = from_regex_tree_and_str(&regex_tree, "move 1,-2\nsay hi there").unwrap();
+    /// assert_eq!(events[0].payload["y"], "-2");
+    /// assert_eq!(events[1].payload["words"], "hi there");
+    /// ```
+    pub fn with_child_if(
+        mut self,
+        name: impl Into<String>,
+        condition: impl Fn(&GroupValues) -> bool + Send + Sync + 'static,
+        child: RegexTree,
+    ) -> Self {
+        self.regex_tree.conditional_children.push(ConditionalChild {
+            group: name.into(),
+            condition: Arc::new(condition),
+            child,
+        });
+        self
+    }
+
+    /// Keep only the matches of the node for which `filter` holds, given
+    /// the text of their named capture groups. Other matches are skipped as
+    /// though the patterns had not matched there, so they are left out of
+    /// sequences, and a single value comes from the first match that is kept.
+    /// With several filters, a match must pass all of them. This drops
+    /// records such as comments without complicating the pattern.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// let regex_tree = RegexTree::lines(r"(?P<key>\S+)=(?P<value>.*)")
+    ///     .with_filter(|groups| !groups.name("key").is_some_and(|key| key.starts_with('#')))
+    ///     .build();
+    ///
+    /// let settings: Vec<(String, String)> =
+    ///     from_regex_tree_and_str(&regex_tree, "width=80\n#height=24\ncolor=auto").unwrap();
+    /// assert_eq!(
+    ///     settings,
+    ///     vec![
+    ///         ("width".to_owned(), "80".to_owned()),
+    ///         ("color".to_owned(), "auto".to_owned()),
+    ///     ]
+    /// );
+    /// ```
+    pub fn with_filter(
+        mut self,
+        filter: impl Fn(&GroupValues) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.regex_tree.filters.push(Filter(Arc::new(filter)));
+        self
+    }
+
+    /// Add a child for every named capture group that has no child of its
+    /// own, by name, key, or index. This suits patterns whose groups all
+    /// share a format, such as columns that each hold a pair of numbers.
+    /// Groups deserialized as strings still get their whole text.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// #[derive(Debug, PartialEq, serde::Deserialize)]
+    /// struct Point {
+    ///     x: i32,
+    ///     y: i32,
+    /// }
+    ///
+    /// #[derive(Debug, PartialEq, serde::Deserialize)]
+    /// struct Segment<'a> {
+    ///     name: &'a str,
+    ///     from: Point,
+    ///     to: Point,
+    ///     via: Vec<Point>,
+    /// }
+    ///
+    /// let regex_tree = RegexTree::root(r"(?P<name>\w+) (?P<from>\S+) (?P<to>\S+) \[(?P<via>.*)\]")
+    ///     .with_default_child(RegexTree::leaf(r"(?P<x>-?\d+),(?P<y>-?\d+)"))
+    ///     .build();
+    ///
+    /// let segment: Segment = from_regex_tree_and_str(&regex_tree, "a 0,0 3,-4 [1,1 2,0]").unwrap();
+    /// assert_eq!(segment.name, "a");
+    /// assert_eq!(segment.to, Point { x: 3, y: -4 });
+    /// assert_eq!(segment.via, vec![Point { x: 1, y: 1 }, Point { x: 2, y: 0 }]);
+    /// ```
+    pub fn with_default_child(mut self, child: RegexTree) -> Self {
+        self.regex_tree.default_child = Some(Box::new(child));
+        self
+    }
+
+    /// Choose how several capture groups with the same key are handled. See
+    /// [`DuplicateKeys`].
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// # use recursive_regex::regex_tree::DuplicateKeys;
+    /// # use std::collections::HashMap;
+    /// let regex_tree = RegexTree::root(r"(?P<tag[0]>\w+), (?P<tag[1]>\w+)")
+    ///     .with_duplicate_keys(DuplicateKeys::Collect)
+    ///     .build();
+    /// let tags: HashMap<String, Vec<&str>> =
+    ///     from_regex_tree_and_str(&regex_tree, "red, blue").unwrap();
+    /// assert_eq!(tags["tag"], vec!["red", "blue"]);
+    /// ```
+    pub fn with_duplicate_keys(mut self, duplicate_keys: DuplicateKeys) -> Self {
+        self.regex_tree.options.duplicate_keys = duplicate_keys;
+        self
+    }
+
+    /// Choose whether a capture group that takes part in a match but matches
+    /// no text is deserialized from its empty text, or as if it did not take
+    /// part. See [`EmptyCaptures`]. Flag groups are unaffected, since they
+    /// are about whether a group took part.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// # use recursive_regex::regex_tree::EmptyCaptures;
+    /// #[derive(serde::Deserialize)]
+    /// struct Field<'a> {
+    ///     name: &'a str,
+    ///     value: Option<&'a str>,
+    /// }
+    ///
+    /// let pattern = r"(?P<name>\w+)(?:=(?P<value>\w*))?";
+    /// let regex_tree = RegexTree::root(pattern).build();
+    /// let fields: Vec<Field> = from_regex_tree_and_str(&regex_tree, "a=1 b= c").unwrap();
+    /// let values: Vec<_> = fields.iter().map(|field| field.value).collect();
+    /// assert_eq!(values, vec![Some("1"), Some(""), None]);
+    ///
+    /// let regex_tree = RegexTree::root(pattern)
+    ///     .with_empty_captures(EmptyCaptures::Missing)
+    ///     .build();
+    /// let fields: Vec<Field> = from_regex_tree_and_str(&regex_tree, "a=1 b= c").unwrap();
+    /// let values: Vec<_> = fields.iter().map(|field| field.value).collect();
+    /// assert_eq!(values, vec![Some("1"), None, None]);
+    /// ```
+    pub fn with_empty_captures(mut self, empty_captures: EmptyCaptures) -> Self {
+        self.regex_tree.options.empty_captures = empty_captures;
+        self
+    }
+
+    /// Deserialize a capture group under `key` instead of its own name, for
+    /// keys that are not valid group names, such as ones with hyphens. `group`
+    /// is a capture group's name, or a key shared by several groups (see
+    /// [`DuplicateKeys`]). Children are still attached by the group's name.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// #[derive(serde::Deserialize)]
+    /// struct Header {
+    ///     #[serde(rename = "content-type")]
+    ///     content_type: String,
+    ///     #[serde(rename = "content-length")]
+    ///     content_length: Vec<u32>,
+    /// }
+    ///
+    /// let regex_tree = RegexTree::root(r"type: (?P<ty>\S+), length: (?P<len>.*)")
+    ///     .with_alias("ty", "content-type")
+    ///     .with_alias("len", "content-length")
+    ///     .with_child("len", RegexTree::leaf(r"\d+"))
+    ///     .build();
+    /// let header: Header = from_regex_tree_and_str(&regex_tree, "type: text, length: 1 2").unwrap();
+    /// assert_eq!(header.content_type, "text");
+    /// assert_eq!(header.content_length, vec![1, 2]);
+    /// ```
+    pub fn with_alias(mut self, group: impl Into<String>, key: impl Into<String>) -> Self {
+        self.regex_tree
+            .options
+            .aliases
+            .insert(group.into(), key.into());
+        self
+    }
+
+    /// Deserialize groups whose keys share a part before `separator` into a
+    /// map nested under that part, so a struct field can be filled from
+    /// several groups of one match without a child pattern. Keys with several
+    /// separators nest several levels deep. Keys are split after any
+    /// [aliases](Builder::with_alias) and [renaming](Builder::with_rename_all).
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// #[derive(serde::Deserialize)]
+    /// struct Person {
+    ///     name: String,
+    ///     addr: Address,
+    /// }
+    ///
+    /// #[derive(serde::Deserialize)]
+    /// struct Address {
+    ///     street: String,
+    ///     city: String,
+    /// }
+    ///
+    /// let regex_tree = RegexTree::root(r"(?P<name>\w+), (?P<addr__street>[^,]+), (?P<addr__city>\w+)")
+    ///     .with_nested_keys("__")
+    ///     .build();
+    /// let person: Person = from_regex_tree_and_str(&regex_tree, "Ada, 12 Main St, Leeds").unwrap();
+    /// assert_eq!(person.addr.street, "12 Main St");
+    /// assert_eq!(person.addr.city, "Leeds");
+    /// ```
+    pub fn with_nested_keys(mut self, separator: impl Into<String>) -> Self {
+        self.regex_tree.options.key_separator = Some(separator.into());
+        self
+    }
+
+    /// Deserialize an enum from the node by taking the variant named by the
+    /// text of the group under `key`, such as a type column, and filling the
+    /// variant from the rest of the match. A struct variant takes the other
+    /// groups as its fields, a newtype variant is deserialized from the match
+    /// as a whole, and a unit variant takes nothing more.
+    ///
+    /// Write the enum without `#[serde(tag = ...)]`. Serde buffers the fields
+    /// of such an internally tagged enum as text before it knows the variant,
+    /// so they can only be strings, while this parses each field as its type.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// #[derive(Debug, PartialEq, serde::Deserialize)]
+    /// enum Event {
+    ///     Login { user: String },
+    ///     Move { x: i32, y: i32 },
+    ///     Logout,
+    /// }
+    ///
+    /// let regex_tree = RegexTree::lines(
+    ///     r"(?P<kind>\w+)(?: user=(?P<user>\w+)| x=(?P<x>-?\d+) y=(?P<y>-?\d+))?",
+    /// )
+    /// .with_tag("kind")
+    /// .build();
+    /// let events: Vec<Event> =
+    ///     from_regex_tree_and_str(&regex_tree, "Login user=ada\nMove x=1 y=-2\nLogout").unwrap();
+    /// assert_eq!(
+    ///     events,
+    ///     vec![
+    ///         Event::Login { user: "ada".to_owned() },
+    ///         Event::Move { x: 1, y: -2 },
+    ///         Event::Logout,
+    ///     ]
+    /// );
+    /// ```
+    pub fn with_tag(mut self, key: impl Into<String>) -> Self {
+        self.regex_tree.options.tag = Some(key.into());
+        self
+    }
+
+    /// Deserialize a capture group as `true` if it took part in the match and
+    /// `false` if it did not, whatever text it matched. This suits optional
+    /// markers, such as a trailing `(?P<deleted> \(deleted\))?`, which would
+    /// otherwise have to match text that looks like a bool. `group` is a
+    /// capture group's name, or a key shared by several groups, including
+    /// through an alias, which is `true` if any of them took part.
+    ///
+    /// The key is always present, so the field needs no default. Other
+    /// groups still parse their text as a bool as usual.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// #[derive(Debug, PartialEq, serde::Deserialize)]
+    /// struct Task {
+    ///     name: String,
+    ///     done: bool,
+    /// }
+    ///
+    /// let regex_tree = RegexTree::lines(r"- (?P<done>\[x\] )?(?P<name>.*)")
+    ///     .with_flag("done")
+    ///     .build();
+    /// let tasks: Vec<Task> = from_regex_tree_and_str(&regex_tree, "- [x] write\n- test").unwrap();
+    /// assert_eq!(
+    ///     tasks,
+    ///     vec![
+    ///         Task { name: "write".to_owned(), done: true },
+    ///         Task { name: "test".to_owned(), done: false },
+    ///     ]
+    /// );
+    /// ```
+    pub fn with_flag(mut self, group: impl Into<String>) -> Self {
+        self.regex_tree.options.flags.insert(group.into());
+        self
+    }
+
+    /// Gather the capture groups under `group` from every match of this node
+    /// into a sequence, when the node is deserialized as a single struct or
+    /// map rather than as a sequence of them. Other keys come from the first
+    /// match as usual. This suits values scattered through a document, such
+    /// as every `#tag` in a post. `group` is a group name or key.
+    ///
+    /// A struct field for the key is an empty sequence when no group under it
+    /// took part in any match, and the node need not match at all if every
+    /// field is collected.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// #[derive(serde::Deserialize)]
+    /// struct Post {
+    ///     title: String,
+    ///     tags: Vec<String>,
+    /// }
+    ///
+    /// let regex_tree = RegexTree::root(r"(?m)^# (?P<title>.*)$|#(?P<tags>\w+)")
+    ///     .with_collect_all("tags")
+    ///     .build();
+    /// let post: Post =
+    ///     from_regex_tree_and_str(&regex_tree, "# Notes\nsee #rust and #serde").unwrap();
+    /// assert_eq!(post.title, "Notes");
+    /// assert_eq!(post.tags, vec!["rust", "serde"]);
+    /// ```
+    pub fn with_collect_all(mut self, group: impl Into<String>) -> Self {
+        self.regex_tree.options.collect_all.insert(group.into());
+        self
+    }
+
+    /// Translate the text of a capture group through `map` before parsing
+    /// it, such as month names into numbers or log levels into integers.
+    /// Text with no entry in the map is parsed as it is. `group` is a group
+    /// name or key. Errors still span the text in the input, and a
+    /// translated value cannot be borrowed from it, so it needs an owned
+    /// type such as `String` rather than `&str`.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// #[derive(serde::Deserialize)]
+    /// struct Date {
+    ///     day: u32,
+    ///     month: u32,
+    /// }
+    ///
+    /// let regex_tree = RegexTree::root(r"(?P<day>\d+) (?P<month>\w+)")
+    ///     .with_value_map("month", [("Jan", "1"), ("Feb", "2"), ("Mar", "3")])
+    ///     .build();
+    /// let dates: Vec<Date> = from_regex_tree_and_str(&regex_tree, "3 Feb, 14 Mar, 5 6").unwrap();
+    /// assert_eq!(dates[0].month, 2);
+    /// assert_eq!(dates[1].month, 3);
+    /// assert_eq!(dates[2].month, 6);
+    /// ```
+    pub fn with_value_map<K, V>(
+        mut self,
+        group: impl Into<String>,
+        map: impl IntoIterator<Item = (K, V)>,
+    ) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.regex_tree
+            .options
+            .value_maps
+            .entry(group.into())
+            .or_default()
+            .extend(
+                map.into_iter()
+                    .map(|(key, value)| (key.into(), value.into())),
+            );
+        self
+    }
+
+    /// Expect the text of a capture group to be of a type, such as an
+    /// integer, failing on text that is not. `group` is a group name or key.
+    /// This mostly suits trees loaded from configuration with no Rust type to
+    /// deserialize into, where the group then becomes a number, bool or
+    /// date rather than a string. See [`ValueType`].
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{to_value, RegexTree};
+    /// # use recursive_regex::regex_tree::ValueType;
+    /// let regex_tree = RegexTree::root(r"(?P<host>[\w.]+):(?P<port>\w+)")
+    ///     .with_type("port", ValueType::Int)
+    ///     .build();
+    /// let value = to_value(&regex_tree, "localhost:8080").unwrap();
+    /// let port = value.as_seq().unwrap()[0].get("port").unwrap();
+    /// assert_eq!(serde_json::to_string(port).unwrap(), "8080");
+    ///
+    /// let error = to_value(&regex_tree, "localhost:http").unwrap_err();
+    /// assert_eq!(error.to_string(), "[0].port: got \"http\" but expecting an int");
+    /// ```
+    pub fn with_type(mut self, group: impl Into<String>, value_type: ValueType) -> Self {
+        self.regex_tree
+            .options
+            .types
+            .insert(group.into(), value_type);
+        self
+    }
+
+    /// Convert the key of each capture group to `case`, so a struct with
+    /// `#[serde(rename_all = "...")]` can be filled from groups named in
+    /// another case. Groups with an alias from
+    /// [`with_alias`](Builder::with_alias) keep it. See [`Case`].
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// # use recursive_regex::regex_tree::Case;
+    /// #[derive(serde::Deserialize)]
+    /// #[serde(rename_all = "camelCase")]
+    /// struct Request {
+    ///     http_method: String,
+    ///     status_code: u16,
+    /// }
+    ///
+    /// let regex_tree = RegexTree::root(r"(?P<http_method>[A-Z]+) (?P<status_code>\d+)")
+    ///     .with_rename_all(Case::Camel)
+    ///     .build();
+    /// let requests: Vec<Request> = from_regex_tree_and_str(&regex_tree, "GET 200").unwrap();
+    /// assert_eq!(requests[0].http_method, "GET");
+    /// assert_eq!(requests[0].status_code, 200);
+    /// ```
+    pub fn with_rename_all(mut self, case: Case) -> Self {
+        self.regex_tree.options.rename_all = Some(case);
+        self
+    }
+
+    /// Constrain how many matches this node may have when it is deserialized
+    /// as a sequence, such as to check the number of records while parsing
+    /// them. A violation is an error spanning the node's text when there are
+    /// too few matches, or the first extra match when there are too many.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// # use recursive_regex::regex_tree::Count;
+    /// let regex_tree = RegexTree::root(r"\d+").with_count(Count::exactly(3)).build();
+    /// let parsed: Vec<u32> = from_regex_tree_and_str(&regex_tree, "1 2 3").unwrap();
+    /// assert_eq!(parsed, vec![1, 2, 3]);
+    ///
+    /// let error = from_regex_tree_and_str::<Vec<u32>>(&regex_tree, "1 2 3 4").unwrap_err();
+    /// assert_eq!(error.to_string(), "expected exactly 3 matches but found more");
+    /// assert_eq!(error.span(), Some(6..7));
+    /// ```
+    pub fn with_count(mut self, count: Count) -> Self {
+        self.regex_tree.options.count = count;
+        self
+    }
+
+    /// Parse this node's text as a date, time, or both, with a
+    /// [`chrono` format string](chrono::format::strftime), when it is
+    /// deserialized as a string. The text is handed on in the standard form
+    /// that `chrono` types deserialize from, so fields such as
+    /// `DateTime<FixedOffset>` or `NaiveDate` can be filled straight from
+    /// a log's own format.
+    ///
+    /// Formats with an offset give an RFC 3339 date and time. Otherwise, the
+    /// result is a `NaiveDateTime`, `NaiveDate`, or `NaiveTime`, depending on
+    /// what the format holds.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// use chrono::NaiveDate;
+    ///
+    /// let regex_tree = RegexTree::root(r"\d+/\d+/\d+")
+    ///     .with_datetime_format("%d/%m/%Y")
+    ///     .build();
+    /// let dates: Vec<NaiveDate> = from_regex_tree_and_str(&regex_tree, "1/2/2024 15/3/2024").unwrap();
+    /// assert_eq!(dates[1], NaiveDate::from_ymd_opt(2024, 3, 15).unwrap());
+    /// ```
+    #[cfg(feature = "chrono")]
+    pub fn with_datetime_format(mut self, format: impl Into<String>) -> Self {
+        self.regex_tree.options.datetime_format = Some(format.into());
+        self
+    }
+
+    /// Decode this node's text when it is deserialized as bytes, so fields
+    /// like `Vec<u8>` receive the payload of an encoded blob rather than the
+    /// text itself. Deserializing the node as a sequence or as bytes then
+    /// decodes its text, instead of matching its patterns. See [`Decoding`].
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// # use recursive_regex::regex_tree::Decoding;
+    /// #[derive(serde::Deserialize)]
+    /// struct Packet {
+    ///     id: u32,
+    ///     payload: Vec<u8>,
+    /// }
+    ///
+    /// let regex_tree = RegexTree::root(r"(?P<id>\d+):(?P<payload>\S*)")
+    ///     .with_child(
+    ///         "payload",
+    ///         RegexTree::root(".*").with_decoding(Decoding::Hex).build(),
+    ///     )
+    ///     .build();
+    /// let packets: Vec<Packet> = from_regex_tree_and_str(&regex_tree, "1:cafe 2:00").unwrap();
+    /// assert_eq!(packets[0].payload, vec![0xca, 0xfe]);
+    /// assert_eq!(packets[1].payload, vec![0]);
+    /// ```
+    pub fn with_decoding(mut self, decoding: Decoding) -> Self {
+        self.regex_tree.options.decoding = Some(decoding);
+        self
+    }
+
+    /// Resolve escapes in this node's text before it is parsed, so quoted
+    /// strings deserialize to what they stand for rather than how they are
+    /// written. Numbers, dates and bytes are parsed from the unescaped text
+    /// too. See [`Escapes`].
+    ///
+    /// Text without escapes is still borrowed, so a `Cow<str>` field marked
+    /// `#[serde(borrow)]` only allocates for text that had escapes resolved.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// # use recursive_regex::regex_tree::Escapes;
+    /// # use std::borrow::Cow;
+    /// #[derive(serde::Deserialize)]
+    /// struct Setting<'a> {
+    ///     key: &'a str,
+    ///     #[serde(borrow)]
+    ///     value: Cow<'a, str>,
+    /// }
+    ///
+    /// let regex_tree = RegexTree::root(r#"(?P<key>\w+)="(?P<value>(?:[^"\\]|\\.)*)""#)
+    ///     .with_child(
+    ///         "value",
+    ///         RegexTree::root(".*").with_unescape(Escapes::Backslash).build(),
+    ///     )
+    ///     .build();
+    /// let text = r#"name="plain" motd="say \"hi\"\n""#;
+    /// let settings: Vec<Setting> = from_regex_tree_and_str(&regex_tree, text).unwrap();
+    /// assert!(matches!(settings[0].value, Cow::Borrowed("plain")));
+    /// assert_eq!(settings[1].value, "say \"hi\"\n");
+    /// assert!(matches!(settings[1].value, Cow::Owned(_)));
+    /// ```
+    pub fn with_unescape(mut self, escapes: Escapes) -> Self {
+        self.regex_tree.options.unescape = Some(escapes);
+        self
+    }
+
+    /// Choose how this node searches for successive matches. See
+    /// [`MatchPolicy`].
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// # use recursive_regex::regex_tree::MatchPolicy;
+    /// let regex_tree = RegexTree::root(r"\w+\s*")
+    ///     .with_match_policy(MatchPolicy::Consecutive)
+    ///     .build();
+    /// assert!(from_regex_tree_and_str::<Vec<&str>>(&regex_tree, "let x").is_ok());
+    ///
+    /// let error = from_regex_tree_and_str::<Vec<&str>>(&regex_tree, "let x = 1").unwrap_err();
+    /// assert_eq!(error.span(), Some(6..8));
+    ///
+    /// let regex_tree = RegexTree::root(r"\d\d")
+    ///     .with_match_policy(MatchPolicy::Overlapping)
+    ///     .build();
+    /// let pairs: Vec<&str> = from_regex_tree_and_str(&regex_tree, "1234").unwrap();
+    /// assert_eq!(pairs, vec!["12", "23", "34"]);
+    /// ```
+    pub fn with_match_policy(mut self, match_policy: MatchPolicy) -> Self {
+        self.regex_tree.match_policy = match_policy;
+        self
+    }
+
+    /// Require the patterns of this node to match the whole of the text they
+    /// are run on, so text with garbage around a match is rejected instead of
+    /// yielding the match. For a split node, every piece must be matched whole.
+    ///
+    /// When deserializing a sequence, a node that must match its whole text
+    /// has exactly one element, and it is an error if the text does not
+    /// match, rather than an empty sequence.
+    ///
+    /// The patterns are anchored with `\A(?:...)\z`, so the anchors do not
+    /// depend on multi-line mode, and [`patterns`](RegexTree::patterns) will
+    /// include the anchors. Anchoring compiles the patterns from their
+    /// source, so patterns given as a compiled [`Regex`] are an error, as any
+    /// settings they were built with would be lost. Give them as strings,
+    /// with inline flags such as `(?i)`.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// #[derive(serde::Deserialize)]
+    /// struct Version {
+    ///     major: u32,
+    ///     minor: u32,
+    /// }
+    ///
+    /// let regex_tree = RegexTree::root(r"(?P<major>\d+)\.(?P<minor>\d+)")
+    ///     .with_full_match(true)
+    ///     .build();
+    /// assert!(from_regex_tree_and_str::<Version>(&regex_tree, "1.2").is_ok());
+    /// assert!(from_regex_tree_and_str::<Version>(&regex_tree, "v1.2-beta").is_err());
+    /// ```
+    pub fn with_full_match(mut self, full_match: bool) -> Self {
+        self.regex_tree.full_match = full_match;
+        self
+    }
+
+    /// Match letters in this node's patterns without regard to case, as
+    /// with the inline flag `(?i)`
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    /// let regex_tree = RegexTree::root(r"yes|no").with_case_insensitive(true).build();
+    /// let answers: Vec<&str> = from_regex_tree_and_str(&regex_tree, "Yes NO no").unwrap();
+    /// assert_eq!(answers, vec!["Yes", "NO", "no"]);
+    /// ```
+    pub fn with_case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.compile_options.case_insensitive = case_insensitive;
+        self
+    }
+
+    /// Make `^` and `$` in this node's patterns match at the start and end of
+    /// lines, as with the inline flag `(?m)`
+    pub fn with_multi_line(mut self, multi_line: bool) -> Self {
+        self.compile_options.multi_line = multi_line;
+        self
+    }
+
+    /// Allow `.` in this node's patterns to match `\n`, as with the inline
+    /// flag `(?s)`
+    pub fn with_dot_matches_new_line(mut self, dot_matches_new_line: bool) -> Self {
+        self.compile_options.dot_matches_new_line = dot_matches_new_line;
+        self
+    }
+
+    /// Limit the size, in bytes, of each of this node's compiled patterns.
+    /// Patterns that would be larger fail to build, which protects against
+    /// patterns that blow up when compiled, such as from untrusted input. See
+    /// [`RegexBuilder::size_limit`].
+    ///
+    /// Patterns passed to the builder as strings are compiled only when the
+    /// node is built, with this limit, which may be above the regex crate's
+    /// default. Patterns given as a compiled [`Regex`] cannot be compiled
+    /// again, so they make building fail, as they do with the flags above.
+    /// Use [`try_build`](Builder::try_build) to handle the error.
+    pub fn with_size_limit(mut self, bytes: usize) -> Self {
+        self.compile_options.size_limit = Some(bytes);
+        self
+    }
+
+    /// Limit the size, in bytes, of the cache of the lazy DFA used to match
+    /// each of this node's patterns. See [`RegexBuilder::dfa_size_limit`].
+    /// `regex-lite` has no lazy DFA, so with it this has no effect.
+    pub fn with_dfa_size_limit(mut self, bytes: usize) -> Self {
+        self.compile_options.dfa_size_limit = Some(bytes);
         self
     }
 
     /// Finish construction and create the regex tree
+    ///
+    /// ## Panics
+    /// Panics if a pattern is invalid or exceeds the
+    /// [size limit](Builder::with_size_limit), or if the names of the node's children or keys are wrong. See
+    /// [`BuildError`]. Use [`try_build`](Builder::try_build) to handle this
+    /// instead.
     pub fn build(self) -> RegexTree {
-        RegexTree {
-            regex: self.regex,
-            children: self.children,
+        self.try_build().unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Finish construction and create the regex tree, or return the error
+    /// from compiling a pattern that is invalid or exceeds the
+    /// [size limit](Builder::with_size_limit), or from checking the names of
+    /// the node's children and keys against its capture groups.
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::RegexTree;
+    /// # use recursive_regex::regex_tree::BuildError;
+    /// let result = RegexTree::root(r"\w{100}").with_size_limit(1000).try_build();
+    /// assert!(matches!(result, Err(BuildError::Regex(_))));
+    ///
+    /// // A typo in the name of a child would otherwise leave it unused
+    /// let result = RegexTree::root(r"(?P<nums>.*)")
+    ///     .with_child("numbers", RegexTree::leaf(r"\d+"))
+    ///     .try_build();
+    /// assert_eq!(
+    ///     result.unwrap_err().to_string(),
+    ///     "child `numbers` does not match any capture group"
+    /// );
+    /// ```
+    pub fn try_build(mut self) -> Result<RegexTree, BuildError> {
+        let compile_options = self.compile_options;
+        let full_match = self.regex_tree.full_match;
+        // A set compiles the patterns again from their text, which would lose the settings
+        // compiled regexes were built with
+        let given_compiled = self.patterns.iter().any(PatternSource::is_compiled);
+        if given_compiled || self.split.as_ref().is_some_and(SplitSource::is_compiled) {
+            self.mark_compiled();
+        }
+        self.regex_tree.patterns = self
+            .patterns
+            .into_iter()
+            .map(|pattern| pattern.compile(compile_options, full_match))
+            .collect::<Result<_, _>>()?;
+        if let Some(split) = self.split {
+            self.regex_tree.split = Some(split.compile(compile_options)?);
         }
+        #[cfg(feature = "regex")]
+        if !given_compiled {
+            self.regex_tree.pattern_set = pattern_set(&self.regex_tree.patterns, compile_options);
+        }
+        self.regex_tree.compile_options = compile_options;
+        let aliased = self.regex_tree.aliased_groups();
+        self.regex_tree.rename_groups();
+        self.regex_tree.check_names(&aliased)?;
+        Ok(self.regex_tree)
+    }
+}
+
+/// Error from building a regex tree. Besides patterns that fail to compile,
+/// names that do not fit together are errors, since they would otherwise give
+/// a tree that silently deserializes the wrong thing.
+#[derive(Debug, Clone)]
+pub enum BuildError {
+    /// A pattern failed to compile, such as by exceeding its size limit
+    Regex(regex::Error),
+    /// A pattern was given as a compiled [`Regex`] to a node that compiles its
+    /// patterns again, to [match the whole text](Builder::with_full_match) or
+    /// with flags or limits, which would lose the settings it was built with
+    CompiledRegex,
+    /// A child is named after a capture group that none of the node's patterns
+    /// have, nor is it a capture group's index
+    UnknownChild { child: String },
+    /// Capture groups with different keys are deserialized under the same key
+    /// after aliases and renaming, without each having been aliased to it on
+    /// purpose
+    DuplicateKey { key: String, groups: Vec<String> },
+    /// A capture group is deserialized under a key reserved for the fields of
+    /// [`Spanned`](crate::Spanned)
+    ReservedKey { key: String },
+    /// A node of a deserialized tree has neither a pattern nor a reference to
+    /// a definition
+    MissingRegex,
+    /// A node of a deserialized tree that refers to a definition has settings
+    /// of its own, such as a pattern or children, which would be lost when it
+    /// is replaced by the definition
+    RefWithSettings { name: String, keys: Vec<String> },
+    /// A node of a deserialized tree refers to a definition that is not in
+    /// scope
+    UnknownDefinition { name: String },
+    /// A definition in a deserialized tree refers to itself, directly or
+    /// through other definitions, so it would never end
+    CyclicDefinition { name: String },
+    /// A deserialized tree has more nodes than the limit once its references
+    /// to definitions are replaced
+    TooManyNodes { limit: usize },
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Regex(err) => write!(f, "{err}"),
+            Self::CompiledRegex => write!(
+                f,
+                "a compiled regex cannot be compiled again with the node's settings; \
+                 give its pattern as a string instead"
+            ),
+            Self::UnknownChild { child } => {
+                write!(f, "child `{child}` does not match any capture group")
+            }
+            Self::DuplicateKey { key, groups } => write!(
+                f,
+                "capture groups `{}` are all deserialized under key `{key}`",
+                groups.join("`, `")
+            ),
+            Self::ReservedKey { key } => write!(f, "key {key:?} is reserved"),
+            Self::MissingRegex => write!(f, "missing field `regex`"),
+            Self::RefWithSettings { name, keys } => write!(
+                f,
+                "reference to `{name}` cannot also have `{}`",
+                keys.join("`, `")
+            ),
+            Self::UnknownDefinition { name } => write!(f, "no definition named `{name}`"),
+            Self::CyclicDefinition { name } => write!(f, "definition `{name}` refers to itself"),
+            Self::TooManyNodes { limit } => write!(
+                f,
+                "tree has more than {limit} nodes once references to definitions are replaced"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Regex(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<regex::Error> for BuildError {
+    fn from(err: regex::Error) -> Self {
+        Self::Regex(err)
+    }
+}
+
+/// Set of the patterns of a node, if there is more than one. The set is only an optimization, so
+/// if it is too large to compile, the patterns are tried one by one instead. The patterns' text
+/// does not carry the node's flags, so they are applied again. Fancy patterns cannot be in a set,
+/// so nodes with any have none, and neither do nodes given compiled regexes, whose settings their
+/// text may not show.
+#[cfg(feature = "regex")]
+fn pattern_set(patterns: &[Pattern], compile_options: CompileOptions) -> Option<RegexSet> {
+    if patterns.len() > 1 {
+        let regexes = patterns
+            .iter()
+            .map(Pattern::as_regex)
+            .collect::<Option<Vec<_>>>()?;
+        compile_options
+            .regex_set(regexes.into_iter().map(Regex::as_str))
+            .ok()
+    } else {
+        None
+    }
+}
+
+/// Anchor a pattern to match only the whole of the text, if it must
+fn anchor(pattern: &str, full_match: bool) -> String {
+    if full_match {
+        format!(r"\A(?:{pattern})\z")
+    } else {
+        pattern.to_owned()
     }
 }
 
 pub trait ToRegex {
     /// Convert to regex. Expected to panic upon failure.
     fn to_regex(self) -> Regex;
+
+    /// The pattern to compile when the node is built, with the node's flags
+    /// and limits, or `None` if the regex is already compiled, and so may have
+    /// been built with settings that its source does not show
+    fn uncompiled(&self) -> Option<&str> {
+        None
+    }
 }
 
-impl<'a> ToRegex for &'a str {
+impl ToRegex for &str {
     fn to_regex(self) -> Regex {
         Regex::new(self).unwrap()
     }
+
+    fn uncompiled(&self) -> Option<&str> {
+        Some(self)
+    }
 }
 
 impl ToRegex for String {
     fn to_regex(self) -> Regex {
         Regex::new(&self).unwrap()
     }
+
+    fn uncompiled(&self) -> Option<&str> {
+        Some(self)
+    }
 }
 
 impl ToRegex for Regex {
@@ -161,3 +3116,98 @@ impl ToRegex for Regex {
         self
     }
 }
+
+/// A regex given to the builder, compiled when the node is built so that it
+/// gets the node's flags and limits, and an invalid pattern is a
+/// [`BuildError`] rather than a panic
+enum RegexSource {
+    Source(String),
+    /// Given already compiled, so it can only be used as it is
+    Compiled(Regex),
+}
+
+impl RegexSource {
+    fn new(regex: impl ToRegex) -> Self {
+        match regex.uncompiled().map(str::to_owned) {
+            Some(source) => Self::Source(source),
+            None => Self::Compiled(regex.to_regex()),
+        }
+    }
+
+    fn is_compiled(&self) -> bool {
+        matches!(self, Self::Compiled(_))
+    }
+
+    /// Compile the regex with `options`, anchored if it must match the whole text. A regex given
+    /// compiled cannot be compiled again without losing the settings it was built with.
+    fn compile(self, options: CompileOptions, full_match: bool) -> Result<Regex, BuildError> {
+        match self {
+            Self::Source(source) => Ok(options.regex(&anchor(&source, full_match))?),
+            Self::Compiled(regex) if options == CompileOptions::default() && !full_match => {
+                Ok(regex)
+            }
+            Self::Compiled(_) => Err(BuildError::CompiledRegex),
+        }
+    }
+}
+
+/// A pattern given to the builder, compiled when the node is built
+enum PatternSource {
+    Regex(RegexSource),
+    #[cfg(feature = "fancy")]
+    Fancy(String),
+    Columns(Columns),
+}
+
+impl PatternSource {
+    fn is_compiled(&self) -> bool {
+        match self {
+            Self::Regex(regex) => regex.is_compiled(),
+            _ => false,
+        }
+    }
+
+    fn compile(self, options: CompileOptions, full_match: bool) -> Result<Pattern, BuildError> {
+        match self {
+            Self::Regex(regex) => regex.compile(options, full_match).map(Pattern::Regex),
+            #[cfg(feature = "fancy")]
+            Self::Fancy(source) => Ok(Pattern::Fancy(
+                options.fancy_regex(&anchor(&source, full_match))?,
+            )),
+            // Columns always match the whole text from where they start
+            Self::Columns(columns) => Ok(Pattern::Columns(columns)),
+        }
+    }
+}
+
+/// The regexes of a split node given to the builder, compiled when the node is built with the
+/// node's flags and limits, as they are for a deserialized tree
+enum SplitSource {
+    Delimiter(RegexSource),
+    Frames(RegexSource),
+    Sections(Vec<RegexSource>),
+}
+
+impl SplitSource {
+    fn is_compiled(&self) -> bool {
+        match self {
+            Self::Delimiter(regex) | Self::Frames(regex) => regex.is_compiled(),
+            Self::Sections(regions) => regions.iter().any(RegexSource::is_compiled),
+        }
+    }
+
+    /// Compile the regexes with `options`. They find pieces within the text rather than match
+    /// all of it, so are never anchored.
+    fn compile(self, options: CompileOptions) -> Result<Split, BuildError> {
+        Ok(match self {
+            Self::Delimiter(regex) => Split::Delimiter(regex.compile(options, false)?),
+            Self::Frames(regex) => Split::Frames(regex.compile(options, false)?),
+            Self::Sections(regions) => Split::Sections(
+                regions
+                    .into_iter()
+                    .map(|region| region.compile(options, false))
+                    .collect::<Result<_, _>>()?,
+            ),
+        })
+    }
+}
@@ -0,0 +1,43 @@
+use crate::{MatchNode, RegexTree};
+
+/// Rebuild text with each match of a regex tree replaced by the output of
+/// `replace`, keeping the text between matches as it is.
+///
+/// The callback sees the full structure of each match, so it can rewrite a
+/// record from its capture groups and the matches of their children. This
+/// suits redaction and migrating data from one format to another.
+///
+/// With [overlapping](crate::regex_tree::MatchPolicy::Overlapping) matches, a
+/// match that starts within one already replaced is left out.
+///
+/// ## Example
+/// ```
+/// # use recursive_regex::{replace_matches, RegexTree};
+/// let regex_tree = RegexTree::root(r"(?P<user>\w+)@(?P<host>[\w.]+)").build();
+///
+/// let masked = replace_matches(&regex_tree, "mail ann@example.com or bo@test.org", |found| {
+///     format!("***@{}", found.group("host").unwrap().as_str())
+/// });
+/// assert_eq!(masked, "mail ***@example.com or ***@test.org");
+/// ```
+pub fn replace_matches<'r, 't, F>(
+    regex_tree: &'r RegexTree,
+    text: &'t str,
+    mut replace: F,
+) -> String
+where
+    F: FnMut(MatchNode<'r, 't>) -> String,
+{
+    let mut replaced = String::with_capacity(text.len());
+    let mut last_end = 0;
+    for found in regex_tree.matches(text) {
+        if found.start() < last_end {
+            continue;
+        }
+        replaced.push_str(&text[last_end..found.start()]);
+        last_end = found.end();
+        replaced.push_str(&replace(found));
+    }
+    replaced.push_str(&text[last_end..]);
+    replaced
+}
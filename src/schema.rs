@@ -0,0 +1,179 @@
+use std::fmt::{self, Write};
+
+use crate::regex_tree::{Count, Split};
+use crate::RegexTree;
+
+/// Description of the structure of a regex tree, from
+/// [`RegexTree::schema`]: each node's patterns, how it splits its text, its
+/// capture groups and which of them recurse into which children.
+///
+/// It displays as indented text, with each child under its parent, and
+/// [`to_dot`](Schema::to_dot) renders it as a Graphviz graph. Siblings are
+/// ordered by child name, then conditional children appear as `name?n` and
+/// the default child as `*`.
+#[derive(Debug, Clone, Copy)]
+pub struct Schema<'r> {
+    regex_tree: &'r RegexTree,
+}
+
+impl<'r> Schema<'r> {
+    pub(crate) fn new(regex_tree: &'r RegexTree) -> Self {
+        Self { regex_tree }
+    }
+
+    /// Render the tree as a Graphviz `digraph`, with a node per regex tree
+    /// node labelled with its patterns, and an edge to each child labelled
+    /// with the capture group it recurses on
+    ///
+    /// ## Example
+    /// ```
+    /// # use recursive_regex::RegexTree;
+    /// let regex_tree = RegexTree::root(r"(?P<nums>.*)")
+    ///     .with_child("nums", RegexTree::leaf(r"\d+"))
+    ///     .build();
+    /// let dot = regex_tree.schema().to_dot();
+    /// assert!(dot.starts_with("digraph regex_tree {"));
+    /// assert!(dot.contains(r#"n1 [label="\\d+"];"#));
+    /// assert!(dot.contains(r#"n0 -> n1 [label="nums"];"#));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph regex_tree {\n    node [shape=box];\n");
+        let mut next_id = 0;
+        write_dot(self.regex_tree, &mut next_id, &mut dot);
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl fmt::Display for Schema<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_node(self.regex_tree, 0, f)
+    }
+}
+
+fn write_node(regex_tree: &RegexTree, depth: usize, f: &mut fmt::Formatter) -> fmt::Result {
+    let indent = "  ".repeat(depth);
+    let mut patterns = regex_tree.patterns();
+    if let Some(pattern) = patterns.next() {
+        writeln!(f, "{indent}pattern `{pattern}`")?;
+    }
+    for fallback in patterns {
+        writeln!(f, "{indent}fallback `{fallback}`")?;
+    }
+    match regex_tree.split_kind() {
+        Some(Split::Lines) => writeln!(f, "{indent}split lines")?,
+        Some(Split::Delimiter(delimiter)) => {
+            writeln!(f, "{indent}split on `{}`", delimiter.as_str())?
+        }
+        Some(Split::Literal(delimiter)) => writeln!(f, "{indent}split on {delimiter:?}")?,
+        Some(Split::Frames(start)) => {
+            writeln!(f, "{indent}frames starting at `{}`", start.as_str())?
+        }
+        Some(Split::Paragraphs) => writeln!(f, "{indent}split paragraphs")?,
+        Some(Split::Sections(regions)) => {
+            let regions: Vec<_> = regions
+                .iter()
+                .map(|region| format!("`{}`", region.as_str()))
+                .collect();
+            writeln!(f, "{indent}sections {}", regions.join(", "))?
+        }
+        None => {}
+    }
+    if regex_tree.is_headed() {
+        writeln!(f, "{indent}headed sections")?;
+    }
+    if regex_tree.is_full_match() {
+        writeln!(f, "{indent}full match")?;
+    }
+    if regex_tree.count() != Count::any() {
+        writeln!(f, "{indent}count {}", regex_tree.count())?;
+    }
+    if let Some(format) = regex_tree.datetime_format() {
+        writeln!(f, "{indent}datetime format `{format}`")?;
+    }
+    if let Some(decoding) = regex_tree.decoding() {
+        writeln!(f, "{indent}decoding {decoding}")?;
+    }
+    if let Some(escapes) = regex_tree.escapes() {
+        writeln!(f, "{indent}unescape {escapes}")?;
+    }
+
+    let groups = groups(regex_tree);
+    if !groups.is_empty() {
+        writeln!(f, "{indent}groups")?;
+        for (index, name, child) in groups {
+            write!(f, "{indent}  {index}")?;
+            if let Some(name) = name {
+                write!(f, " {name}")?;
+            }
+            match child {
+                Some(child) => writeln!(f, " -> {child}")?,
+                None => writeln!(f)?,
+            }
+        }
+    }
+
+    for (name, child) in regex_tree.child_nodes() {
+        writeln!(f, "{indent}child {name}")?;
+        write_node(child, depth + 1, f)?;
+    }
+    Ok(())
+}
+
+/// The capture groups of the node's primary pattern, with the name of the child each recurses
+/// into, if any. Group 0, the whole match, is only listed if it has a child.
+fn groups(regex_tree: &RegexTree) -> Vec<(usize, Option<&str>, Option<String>)> {
+    regex_tree
+        .capture_names()
+        .enumerate()
+        .map(|(index, name)| {
+            let child = child_name(regex_tree, index, name);
+            (index, name, child)
+        })
+        .filter(|(index, _, child)| *index > 0 || child.is_some())
+        .collect()
+}
+
+/// Name of the child a group recurses into, as in [`RegexTree::child_nodes`]. Conditional
+/// children apply first when their condition holds, so they are listed along with the child
+/// used otherwise.
+fn child_name(regex_tree: &RegexTree, index: usize, name: Option<&str>) -> Option<String> {
+    let child = regex_tree.group_child(index, name, None);
+    let fallback = child.and_then(|child| {
+        regex_tree
+            .child_nodes()
+            .into_iter()
+            .find(|(_, node)| std::ptr::eq(*node, child))
+            .map(|(child_name, _)| child_name)
+    });
+    let conditional = regex_tree
+        .conditional_children()
+        .enumerate()
+        .filter(|(_, (group, _))| name == Some(*group))
+        .map(|(index, (group, _))| format!("{group}?{index}"));
+    let names: Vec<String> = conditional.chain(fallback).collect();
+    (!names.is_empty()).then(|| names.join(" or "))
+}
+
+fn write_dot(regex_tree: &RegexTree, next_id: &mut usize, dot: &mut String) -> usize {
+    let id = *next_id;
+    *next_id += 1;
+    let label = regex_tree.patterns().collect::<Vec<_>>().join("\n");
+    let _ = writeln!(dot, "    n{id} [label=\"{}\"];", escape_dot(&label));
+    for (name, child) in regex_tree.child_nodes() {
+        let child_id = write_dot(child, next_id, dot);
+        let _ = writeln!(
+            dot,
+            "    n{id} -> n{child_id} [label=\"{}\"];",
+            escape_dot(&name)
+        );
+    }
+    id
+}
+
+/// Escape text for a quoted Graphviz string, where a newline is written as `\n`
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,121 @@
+use std::sync::Arc;
+
+use serde::Deserialize;
+
+use crate::{
+    from_regex_tree_and_str_with_options, records_from_regex_tree_and_str_with_options,
+    to_value_with_options, ChunkedDeserializer, DeserializeOptions, Error, Records, RegexTree,
+    Value,
+};
+
+// Trees are shared across threads, so losing either bound would be a breaking change
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<RegexTree>();
+    assert_send_sync::<DeserializeOptions>();
+    assert_send_sync::<SharedDeserializer>();
+};
+
+/// Deserializer that owns a regex tree behind an [`Arc`], along with its
+/// options. It has no lifetime, so it can be stored in application state or
+/// moved into a spawned thread or task, and cloning it shares the same tree
+/// rather than recompiling it.
+///
+/// The text, and values borrowed from it, only need to live as long as each
+/// call.
+///
+/// ## Example
+/// ```
+/// # use std::sync::Arc;
+/// # use recursive_regex::{RegexTree, SharedDeserializer};
+/// let regex_tree = Arc::new(RegexTree::leaf(r"\d+"));
+/// let deserializer = SharedDeserializer::new(regex_tree);
+///
+/// let workers: Vec<_> = ["1 2", "3 4"]
+///     .into_iter()
+///     .map(|text| {
+///         let deserializer = deserializer.clone();
+///         std::thread::spawn(move || deserializer.deserialize_str::<Vec<u32>>(text))
+///     })
+///     .collect();
+/// let sums: Vec<u32> = workers
+///     .into_iter()
+///     .map(|worker| worker.join().unwrap().unwrap().iter().sum())
+///     .collect();
+/// assert_eq!(sums, vec![3, 7]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SharedDeserializer {
+    regex_tree: Arc<RegexTree>,
+    options: DeserializeOptions,
+}
+
+impl SharedDeserializer {
+    /// Deserializer for a tree that is already shared, or for a tree to share
+    /// from now on
+    pub fn new(regex_tree: impl Into<Arc<RegexTree>>) -> Self {
+        Self {
+            regex_tree: regex_tree.into(),
+            options: DeserializeOptions::new(),
+        }
+    }
+
+    /// Use [`DeserializeOptions`] that change how text is deserialized
+    pub fn with_options(mut self, options: DeserializeOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// The shared regex tree
+    pub fn regex_tree(&self) -> &Arc<RegexTree> {
+        &self.regex_tree
+    }
+
+    /// The options text is deserialized with
+    pub fn options(&self) -> &DeserializeOptions {
+        &self.options
+    }
+
+    /// Deserialize `text`, as with [`from_regex_tree_and_str`](crate::from_regex_tree_and_str)
+    pub fn deserialize_str<'t, T>(&self, text: &'t str) -> Result<T, Error>
+    where
+        T: Deserialize<'t>,
+    {
+        from_regex_tree_and_str_with_options(&self.regex_tree, text, &self.options)
+    }
+
+    /// Deserialize each match of the root as its own record, as with
+    /// [`records_from_regex_tree_and_str`](crate::records_from_regex_tree_and_str)
+    pub fn records<'s, 't, T>(&'s self, text: &'t str) -> Records<'s, 't, T>
+    where
+        T: Deserialize<'t>,
+    {
+        records_from_regex_tree_and_str_with_options(&self.regex_tree, text, &self.options)
+    }
+
+    /// Deserialize `text` without a Rust type to hold it, as with
+    /// [`to_value`](crate::to_value)
+    pub fn to_value(&self, text: &str) -> Result<Value, Error> {
+        to_value_with_options(&self.regex_tree, text, &self.options)
+    }
+
+    /// Start deserializing text that arrives in pieces, as with [`ChunkedDeserializer`]
+    pub fn chunked<T>(&self) -> ChunkedDeserializer<'_, T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        ChunkedDeserializer::with_options(&self.regex_tree, &self.options)
+    }
+}
+
+impl From<Arc<RegexTree>> for SharedDeserializer {
+    fn from(regex_tree: Arc<RegexTree>) -> Self {
+        Self::new(regex_tree)
+    }
+}
+
+impl From<RegexTree> for SharedDeserializer {
+    fn from(regex_tree: RegexTree) -> Self {
+        Self::new(regex_tree)
+    }
+}
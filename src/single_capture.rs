@@ -1,59 +1,96 @@
-use std::iter::Zip;
+use std::iter::{Enumerate, Zip};
+use std::vec;
 
-use crate::regex::{CaptureNames, Match, SubCaptureMatches};
-use serde::de::value::{Error, StringDeserializer};
+use crate::pattern::{CaptureNames, Match, SubCaptureMatches};
+use serde::de;
+use serde::de::value::UnitDeserializer;
 use serde::de::{MapAccess, SeqAccess};
 use serde::Deserializer;
-use serde::{de, serde_if_integer128};
 
+use crate::datetime::{DURATION_FIELDS, DURATION_NAME};
 use crate::just_string::JustStrDeserializer;
-use crate::spanned::{
-    SpannedDeserializer, SPANNED_BEGIN, SPANNED_END, SPANNED_NAME, SPANNED_VALUE,
-};
+use crate::matched::{is_matched, MatchedDeserializer};
+use crate::node_captures::NodeCaptures;
+use crate::options::DeserializeOptions;
+use crate::regex_tree::{DuplicateKeys, GroupValues};
+use crate::spanned::{spanned_keys, KeyDeserializer, SpannedDeserializer};
 use crate::string::StrDeserializer;
-use crate::RegexTree;
+use crate::trace::trace_span;
+use crate::uncaptured::{UncapturedDeserializer, UNCAPTURED_NAME};
+use crate::{Error, PathSegment, RegexTree, Warning};
 
 pub struct SingleCaptureDeserializer<'r, 'c, 't> {
     regex_tree: &'r RegexTree,
-    capture: SubCaptureMatches<'c, 't>,
-    /// Byte offset of the start of the string `capture` is over within the originally parsed string
+    options: &'r DeserializeOptions,
+    captures: &'c NodeCaptures<'r, 't>,
+    /// Byte offset of the start of the string `captures` are over within the originally parsed
+    /// string
     start: usize,
+    /// Key left out of maps, as it was already used to pick an enum's variant
+    tag: Option<&'r str>,
 }
 
 impl<'r, 'c, 't> SingleCaptureDeserializer<'r, 'c, 't> {
     pub fn from_regex_tree_and_single_capture(
         regex_tree: &'r RegexTree,
-        capture: SubCaptureMatches<'c, 't>,
+        options: &'r DeserializeOptions,
+        capture: &'c NodeCaptures<'r, 't>,
         start: usize,
     ) -> Self {
         Self {
             regex_tree,
-            capture,
-            start,
+            options,
+            captures: capture,
+            start: start + capture.offset(),
+            tag: None,
         }
     }
 
-    fn whole_match(mut self) -> Match<'t> {
-        // capture group 0 is the whole match
-        self.capture.next().unwrap().unwrap()
+    fn just_str(self) -> JustStrDeserializer<'r, 't> {
+        let whole_match = self.captures.whole_match();
+        JustStrDeserializer::from_match(whole_match, self.start + whole_match.start(), self.options)
+            .with_node(self.regex_tree)
     }
 
-    fn whole_match_cloned(&self) -> Match<'t> {
-        // capture group 0 is the whole match
-        self.capture.clone().next().unwrap().unwrap()
+    fn map_access(self, fields: &[&'static str]) -> SingleCaptureMapAccess<'r, 't> {
+        SingleCaptureMapAccess::from_regex_tree_and_captures(
+            self.regex_tree,
+            self.options,
+            self.captures,
+            self.start,
+        )
+        .with_absent_fields(fields, self.captures)
+        .without_key(self.tag)
     }
 
-    fn just_str(self) -> JustStrDeserializer<'t> {
-        let start = self.start;
-        let whole_match = self.whole_match();
-        JustStrDeserializer::from_match(whole_match, start + whole_match.start())
+    fn seq_access(self) -> SingleCaptureSeqAccess<'r, 'c, 't> {
+        SingleCaptureSeqAccess::from_regex_tree_and_captures(
+            self.regex_tree,
+            self.options,
+            self.captures,
+            self.start,
+        )
     }
 
     fn start_end(&self) -> (usize, usize) {
-        let whole_match = self.whole_match_cloned();
-        let length = whole_match.as_str().len();
-        let start = self.start + whole_match.start();
-        (start, start + length)
+        let whole_match = self.captures.whole_match();
+        (
+            self.start + whole_match.start(),
+            self.start + whole_match.end(),
+        )
+    }
+
+    /// Match of the first participating group under `key`
+    fn key_match(&self, key: &str) -> Option<Match<'t>> {
+        self.captures
+            .names()
+            .zip(self.captures.iter())
+            .find_map(
+                |(name, re_match)| match self.regex_tree.field_key(name?) == key {
+                    true => re_match,
+                    false => None,
+                },
+            )
     }
 }
 
@@ -64,26 +101,47 @@ impl<'de, 'r, 'c> Deserializer<'de> for SingleCaptureDeserializer<'r, 'c, 'de> {
     where
         V: de::Visitor<'de>,
     {
-        self.deserialize_any(visitor)
+        visitor.visit_unit()
     }
 
-    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        unimplemented!()
+        // Named capture groups make a map. Otherwise, the only data is the whole match.
+        if self.captures.names().flatten().next().is_some() {
+            self.deserialize_map(visitor)
+        } else {
+            self.just_str().deserialize_any(visitor)
+        }
     }
 
     fn deserialize_enum<V>(
         self,
         _name: &'static str,
         _variants: &'static [&'static str],
-        _visitor: V,
+        visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        unimplemented!()
+        match self.regex_tree.tag() {
+            Some(tag) => visitor.visit_enum(TaggedEnumAccess {
+                deserializer: Self {
+                    tag: Some(tag),
+                    ..self
+                },
+                tag,
+            }),
+            None => {
+                let (start, end) = self.start_end();
+                Err(Error::with_span(
+                    "enums are not supported without a tag group",
+                    start,
+                    end,
+                ))
+            }
+        }
     }
 
     fn deserialize_struct<V>(
@@ -95,11 +153,18 @@ impl<'de, 'r, 'c> Deserializer<'de> for SingleCaptureDeserializer<'r, 'c, 'de> {
     where
         V: de::Visitor<'de>,
     {
-        if name == SPANNED_NAME && fields == [SPANNED_BEGIN, SPANNED_END, SPANNED_VALUE] {
+        if let Some(keys) = spanned_keys(name, fields) {
             let (start, end) = self.start_end();
-            visitor.visit_map(SpannedDeserializer::new(start, end, self))
+            visitor.visit_map(SpannedDeserializer::new(keys, start, end, self))
+        } else if is_matched(name, fields) {
+            let text = self.captures.whole_match().as_str();
+            visitor.visit_map(MatchedDeserializer::new(text, self))
+        } else if name == DURATION_NAME && fields == DURATION_FIELDS {
+            self.just_str().deserialize_struct(name, fields, visitor)
         } else {
-            self.deserialize_map(visitor)
+            let map_access = self.map_access(fields);
+            map_access.check_fields(fields)?;
+            visitor.visit_map(map_access)
         }
     }
 
@@ -107,12 +172,7 @@ impl<'de, 'r, 'c> Deserializer<'de> for SingleCaptureDeserializer<'r, 'c, 'de> {
     where
         V: de::Visitor<'de>,
     {
-        let deserializer = SingleCaptureMapAccess::from_regex_tree_and_captures(
-            self.regex_tree,
-            self.capture,
-            self.start,
-        );
-        visitor.visit_map(deserializer)
+        visitor.visit_map(self.map_access(&[]))
     }
 
     fn deserialize_tuple_struct<V>(
@@ -127,23 +187,38 @@ impl<'de, 'r, 'c> Deserializer<'de> for SingleCaptureDeserializer<'r, 'c, 'de> {
         self.deserialize_tuple(len, visitor)
     }
 
-    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    /// A tuple has an element for each capture group, whether or not it took part in the match,
+    /// and starts with the whole match only if it has room for it
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.deserialize_seq(visitor)
+        let groups = self.captures.captures_len() - 1;
+        if len != groups && len != groups + 1 {
+            let (start, end) = self.start_end();
+            return Err(Error::with_span(
+                format!("expected {groups} capture groups for a tuple of {len} elements"),
+                start,
+                end,
+            ));
+        }
+
+        let mut seq_access = self.seq_access();
+        seq_access.positional = true;
+        if len == groups {
+            seq_access.named_captures.next();
+        }
+        visitor.visit_seq(seq_access)
     }
 
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        let seq_access = SingleCaptureSeqAccess::from_regex_tree_and_captures(
-            self.regex_tree,
-            self.capture,
-            self.start,
-        );
-        visitor.visit_seq(seq_access)
+        if self.regex_tree.decoding().is_some() {
+            return self.just_str().deserialize_seq(visitor);
+        }
+        visitor.visit_seq(self.seq_access())
     }
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -217,13 +292,11 @@ impl<'de, 'r, 'c> Deserializer<'de> for SingleCaptureDeserializer<'r, 'c, 'de> {
         self.just_str().deserialize_i64(visitor)
     }
 
-    serde_if_integer128! {
-        fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-        where
-            V: de::Visitor<'de>
-        {
-            self.just_str().deserialize_i128(visitor)
-        }
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.just_str().deserialize_i128(visitor)
     }
 
     fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -254,13 +327,11 @@ impl<'de, 'r, 'c> Deserializer<'de> for SingleCaptureDeserializer<'r, 'c, 'de> {
         self.just_str().deserialize_u64(visitor)
     }
 
-    serde_if_integer128! {
-        fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-        where
-            V: de::Visitor<'de>
-        {
-            self.just_str().deserialize_u128(visitor)
-        }
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.just_str().deserialize_u128(visitor)
     }
 
     fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -320,50 +391,320 @@ impl<'de, 'r, 'c> Deserializer<'de> for SingleCaptureDeserializer<'r, 'c, 'de> {
     }
 }
 
-pub struct SingleCaptureMapAccess<'r, 'c, 't> {
+pub struct SingleCaptureMapAccess<'r, 't> {
     regex_tree: &'r RegexTree,
-    named_captures: Zip<CaptureNames<'r>, SubCaptureMatches<'c, 't>>,
-    /// Stores the last returned key with its associated value
-    last_key_value: Option<(&'r str, Match<'t>)>,
-    /// Byte offset of the start of the string `named_captures` is over within the originally parsed
+    options: &'r DeserializeOptions,
+    /// Participating capture groups grouped by key, in order of each key's first group
+    entries: vec::IntoIter<(&'r str, KeyValue<'r, 't>)>,
+    /// Stores the last returned key with its associated values
+    last_key_values: Option<(&'r str, KeyValue<'r, 't>)>,
+    /// Byte offset of the start of the string `entries` are over within the originally parsed
     /// string
     start: usize,
+    /// Byte offsets of the start and end of the whole match within the string `entries` are over
+    whole_match: (usize, usize),
+}
+
+/// A participating capture group's index, name, match, and the child it recurses on, if any
+type NamedMatch<'r, 't> = (usize, &'r str, Match<'t>, Option<&'r RegexTree>);
+
+/// What a key is deserialized from
+enum KeyValue<'r, 't> {
+    /// The participating groups under the key
+    Groups(Vec<NamedMatch<'r, 't>>),
+    /// Whether a flag group took part, by its match if it did. See
+    /// [`with_flag`](crate::regex_tree::Builder::with_flag).
+    Flag(Option<Match<'t>>),
+    /// A struct field without a capture group, which may take the pieces of the match outside
+    /// every group. See [`Uncaptured`](crate::Uncaptured).
+    Absent(Vec<Match<'t>>),
+    /// The keys nested under the key, by the rest of their path. See
+    /// [`with_nested_keys`](crate::regex_tree::Builder::with_nested_keys).
+    Nested(Vec<(&'r str, KeyValue<'r, 't>)>),
+    /// The participating groups under the key in every match of the node, with the byte offset
+    /// of the start of the string each match is over. See
+    /// [`with_collect_all`](crate::regex_tree::Builder::with_collect_all).
+    Collected(Vec<(usize, NamedMatch<'r, 't>)>),
+}
+
+impl KeyValue<'_, '_> {
+    /// Byte offsets of the starts and ends of the groups under the key, where `start` is the
+    /// byte offset of the string the match is over
+    fn spans(&self, start: usize) -> Vec<(usize, usize)> {
+        let span = |re_match: &Match| (start + re_match.start(), start + re_match.end());
+        match self {
+            Self::Groups(values) => values
+                .iter()
+                .map(|(_, _, re_match, _)| span(re_match))
+                .collect(),
+            Self::Flag(re_match) => re_match.iter().map(span).collect(),
+            Self::Absent(pieces) => pieces.iter().map(span).collect(),
+            Self::Nested(entries) => entries
+                .iter()
+                .flat_map(|(_, values)| values.spans(start))
+                .collect(),
+            Self::Collected(values) => values
+                .iter()
+                .map(|(start, (_, _, re_match, _))| {
+                    (start + re_match.start(), start + re_match.end())
+                })
+                .collect(),
+        }
+    }
 }
 
-impl<'r, 'c, 't> SingleCaptureMapAccess<'r, 'c, 't> {
+/// Move the entries whose keys have a part before `separator` into an entry for that part, so
+/// they are deserialized as a nested map, repeating for the rest of their keys
+fn nest<'r, 't>(
+    entries: Vec<(&'r str, KeyValue<'r, 't>)>,
+    separator: &str,
+) -> Vec<(&'r str, KeyValue<'r, 't>)> {
+    let mut nested: Vec<(&str, KeyValue)> = Vec::new();
+    for (key, values) in entries {
+        let (part, rest) = match key.split_once(separator) {
+            Some((part, rest)) if !part.is_empty() && !rest.is_empty() => (part, rest),
+            _ => {
+                nested.push((key, values));
+                continue;
+            }
+        };
+        let entry = nested
+            .iter_mut()
+            .find(|(key, values)| *key == part && matches!(values, KeyValue::Nested(_)));
+        match entry {
+            Some((_, KeyValue::Nested(inner))) => inner.push((rest, values)),
+            _ => nested.push((part, KeyValue::Nested(vec![(rest, values)]))),
+        }
+    }
+    for (_, values) in &mut nested {
+        if let KeyValue::Nested(inner) = values {
+            *inner = nest(std::mem::take(inner), separator);
+        }
+    }
+    nested
+}
+
+impl<'r, 't> SingleCaptureMapAccess<'r, 't> {
     pub fn from_regex_tree_and_captures(
         regex_tree: &'r RegexTree,
-        captures: SubCaptureMatches<'c, 't>,
+        options: &'r DeserializeOptions,
+        captures: &NodeCaptures<'r, 't>,
         start: usize,
     ) -> Self {
-        let names = regex_tree.names();
-        let named_captures = names.zip(captures);
+        let mut entries: Vec<(&str, KeyValue)> = Vec::new();
+        let values = regex_tree.group_values(captures);
+        let named_captures = captures
+            .names()
+            .zip(captures.iter())
+            .enumerate()
+            .filter_map(|(index, (name, re_match))| Some((index, name?, re_match)));
+        for (index, name, re_match) in named_captures {
+            let key = regex_tree.field_key(name);
+            let entry = entries.iter_mut().find(|(entry_key, _)| *entry_key == key);
+            if regex_tree.is_flag(name) {
+                // A flag is present if any of its groups took part
+                match entry {
+                    Some((_, KeyValue::Flag(flag @ None))) => *flag = re_match,
+                    Some(_) => {}
+                    None => entries.push((key, KeyValue::Flag(re_match))),
+                }
+                continue;
+            }
+            let Some(re_match) = re_match.filter(|re_match| regex_tree.is_present(re_match)) else {
+                continue;
+            };
+            let child = regex_tree.group_child(index, Some(name), values.as_ref());
+            let value = (index, name, re_match, child);
+            match entry {
+                Some((_, KeyValue::Groups(values))) => values.push(value),
+                Some(_) => {}
+                None => entries.push((key, KeyValue::Groups(vec![value]))),
+            }
+        }
+
+        if let Some(separator) = regex_tree.key_separator() {
+            entries = nest(entries, separator);
+        }
+
+        let whole_match = captures.whole_match();
         Self {
             regex_tree,
-            named_captures,
-            last_key_value: None,
+            options,
+            entries: entries.into_iter(),
+            last_key_values: None,
             start,
+            whole_match: (whole_match.start(), whole_match.end()),
+        }
+    }
+
+    /// Deserialize a map from the first of `all` the matches of the node, except for keys
+    /// collected across matches, which take the groups under them in every match. `start` is the
+    /// byte offset of the start of the text the node matched `len` bytes of. See
+    /// [`with_collect_all`](crate::regex_tree::Builder::with_collect_all).
+    pub fn from_regex_tree_and_all_captures(
+        regex_tree: &'r RegexTree,
+        options: &'r DeserializeOptions,
+        all: &[NodeCaptures<'r, 't>],
+        start: usize,
+        len: usize,
+        fields: &[&'static str],
+    ) -> Self {
+        let map_access = match all.first() {
+            Some(first) => Self::from_regex_tree_and_captures(
+                regex_tree,
+                options,
+                first,
+                start + first.offset(),
+            )
+            .with_absent_fields(fields, first),
+            None => Self {
+                regex_tree,
+                options,
+                entries: Vec::new().into_iter(),
+                last_key_values: None,
+                start,
+                whole_match: (0, len),
+            },
+        };
+        let mut entries: Vec<_> = map_access
+            .entries
+            .filter(|(key, _)| !regex_tree.is_collected(key))
+            .collect();
+
+        // A struct's collected fields are empty sequences when no group under them took part
+        let mut collected: Vec<(&str, Vec<_>)> = fields
+            .iter()
+            .filter(|field| regex_tree.is_collected(field))
+            .map(|field| (*field, Vec::new()))
+            .collect();
+        for captures in all {
+            let values = regex_tree.group_values(captures);
+            let named_captures = captures
+                .names()
+                .zip(captures.iter())
+                .enumerate()
+                .filter_map(|(index, (name, re_match))| Some((index, name?, re_match?)));
+            for (index, name, re_match) in named_captures {
+                if !regex_tree.is_collected(name)
+                    || regex_tree.is_flag(name)
+                    || !regex_tree.is_present(&re_match)
+                {
+                    continue;
+                }
+                let child = regex_tree.group_child(index, Some(name), values.as_ref());
+                let value = (start + captures.offset(), (index, name, re_match, child));
+                let key = regex_tree.field_key(name);
+                match collected
+                    .iter_mut()
+                    .find(|(entry_key, _)| *entry_key == key)
+                {
+                    Some((_, values)) => values.push(value),
+                    None => collected.push((key, vec![value])),
+                }
+            }
+        }
+        entries.extend(
+            collected
+                .into_iter()
+                .map(|(key, values)| (key, KeyValue::Collected(values))),
+        );
+
+        Self {
+            entries: entries.into_iter(),
+            ..map_access
+        }
+    }
+
+    /// Add an entry for each of the struct's `fields` that none of the node's capture groups are
+    /// for, so that an [`Uncaptured`](crate::Uncaptured) field can be given the text outside of
+    /// the groups
+    pub fn with_absent_fields(
+        mut self,
+        fields: &[&'static str],
+        captures: &NodeCaptures<'_, 't>,
+    ) -> Self {
+        let keys = self.regex_tree.field_keys();
+        let mut absent = fields
+            .iter()
+            .filter(|field| !keys.contains(field))
+            .peekable();
+        if absent.peek().is_none() {
+            return self;
         }
+        let pieces = captures.uncaptured();
+        let mut entries: Vec<_> = self.entries.collect();
+        entries.extend(absent.map(|field| (*field, KeyValue::Absent(pieces.clone()))));
+        self.entries = entries.into_iter();
+        self
     }
 
-    fn last(&mut self) -> Option<(&'r str, Match<'t>)> {
-        self.last_key_value.take()
+    /// Leave out the entry for `key`, if any
+    fn without_key(mut self, key: Option<&str>) -> Self {
+        if let Some(key) = key {
+            let entries: Vec<_> = self
+                .entries
+                .filter(|(entry_key, _)| *entry_key != key)
+                .collect();
+            self.entries = entries.into_iter();
+        }
+        self
+    }
+
+    /// With strict options, fail if any participating group is not one of the struct's `fields`.
+    /// Otherwise, warn of each such group if warnings are collected.
+    pub fn check_fields(&self, fields: &[&str]) -> Result<(), Error> {
+        let mut unknown = self
+            .entries
+            .as_slice()
+            .iter()
+            .filter(|(key, _)| !fields.contains(key))
+            .filter_map(|(key, values)| Some((key, *values.spans(self.start).first()?)));
+        if !self.options.is_strict() {
+            if let Some(warnings) = self.options.warning_collector() {
+                for (key, (start, end)) in unknown {
+                    warnings.push(Warning::IgnoredGroup {
+                        group: (*key).to_owned(),
+                        span: start..end,
+                    });
+                }
+            }
+            return Ok(());
+        }
+        match unknown.next() {
+            Some((key, (start, end))) => Err(Error::with_span(
+                format!("capture group `{key}` is not a field"),
+                start,
+                end,
+            )),
+            None => Ok(()),
+        }
     }
 
-    fn next_key(&mut self) -> Option<String> {
-        self.next().map(|(key, _value)| key.to_owned())
+    fn last(&mut self) -> Option<(&'r str, KeyValue<'r, 't>)> {
+        self.last_key_values.take()
     }
 
-    fn next(&mut self) -> Option<(&'r str, Match<'t>)> {
-        let next = self
-            .named_captures
-            .find_map(|(name, re_match)| name.zip(re_match));
-        self.last_key_value = next;
-        next
+    /// Advance to the next key, returning a deserializer for it spanning all of its groups
+    fn next_key(&mut self) -> Option<KeyDeserializer<'r, Error>> {
+        self.last_key_values = self.entries.next();
+        let (key, values) = self.last_key_values.as_ref()?;
+        let spans = values.spans(self.start);
+        // An absent flag is about the whole match
+        let (start, end) = match (
+            spans.iter().map(|(start, _)| *start).min(),
+            spans.iter().map(|(_, end)| *end).max(),
+        ) {
+            (Some(start), Some(end)) => (start, end),
+            _ => (
+                self.start + self.whole_match.0,
+                self.start + self.whole_match.1,
+            ),
+        };
+        Some(KeyDeserializer::new(key, start, end))
     }
 }
 
-impl<'de, 'r, 'c> MapAccess<'de> for SingleCaptureMapAccess<'r, 'c, 'de> {
+impl<'de, 'r> MapAccess<'de> for SingleCaptureMapAccess<'r, 'de> {
     type Error = Error;
 
     fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
@@ -371,7 +712,6 @@ impl<'de, 'r, 'c> MapAccess<'de> for SingleCaptureMapAccess<'r, 'c, 'de> {
         K: de::DeserializeSeed<'de>,
     {
         self.next_key()
-            .map(StringDeserializer::new)
             .map(|deserializer| seed.deserialize(deserializer))
             .transpose()
     }
@@ -380,26 +720,282 @@ impl<'de, 'r, 'c> MapAccess<'de> for SingleCaptureMapAccess<'r, 'c, 'de> {
     where
         V: de::DeserializeSeed<'de>,
     {
-        let (key, value) = self
+        let (key, values) = self
             .last()
-            .expect("invalid calling order; cannot get next value if there was no next key");
-        match self.regex_tree.child(key) {
-            Some(regex_tree) => seed.deserialize(StrDeserializer::from_regex_tree_and_offset_str(
-                regex_tree,
-                value.as_str(),
-                self.start + value.start(),
-            )),
-            None => seed.deserialize(JustStrDeserializer::from_match(
-                value,
-                self.start + value.start(),
-            )),
+            .ok_or_else(|| <Error as de::Error>::custom("value requested before its key"))?;
+        let mut values = match values {
+            KeyValue::Groups(values) => values,
+            KeyValue::Collected(values) => {
+                return seed
+                    .deserialize(GroupsDeserializer {
+                        regex_tree: self.regex_tree,
+                        options: self.options,
+                        values: values.into_iter(),
+                        index: 0,
+                    })
+                    .map_err(|error| error.within(PathSegment::Name(key.to_owned())));
+            }
+            KeyValue::Nested(entries) => {
+                let nested = NestedDeserializer {
+                    map_access: Self {
+                        regex_tree: self.regex_tree,
+                        options: self.options,
+                        entries: entries.into_iter(),
+                        last_key_values: None,
+                        start: self.start,
+                        whole_match: self.whole_match,
+                    },
+                };
+                return seed
+                    .deserialize(nested)
+                    .map_err(|error| error.within(PathSegment::Name(key.to_owned())));
+            }
+            // Not within the field's path, as a missing field is not
+            KeyValue::Absent(pieces) => {
+                let (_, end) = self.whole_match;
+                return seed.deserialize(AbsentFieldDeserializer {
+                    key,
+                    uncaptured: UncapturedDeserializer::new(self.options, pieces, self.start, end),
+                });
+            }
+            KeyValue::Flag(re_match) => {
+                let (start, end) = match re_match {
+                    Some(re_match) => (re_match.start(), re_match.end()),
+                    None => self.whole_match,
+                };
+                let flag = FlagDeserializer {
+                    present: re_match.is_some(),
+                    start: self.start + start,
+                    end: self.start + end,
+                };
+                return seed
+                    .deserialize(flag)
+                    .map_err(|error| error.within(PathSegment::Name(key.to_owned())));
+            }
+        };
+
+        let value = match self.regex_tree.duplicate_keys() {
+            DuplicateKeys::Collect => seed.deserialize(GroupsDeserializer {
+                regex_tree: self.regex_tree,
+                options: self.options,
+                values: values
+                    .into_iter()
+                    .map(|value| (self.start, value))
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+                index: 0,
+            }),
+            DuplicateKeys::Error if values.len() > 1 => Err(<Error as de::Error>::custom(format!(
+                "capture group key `{key}` matched {} times",
+                values.len()
+            ))),
+            duplicate_keys => {
+                let value = match duplicate_keys {
+                    DuplicateKeys::LastWins => values.pop(),
+                    _ => values.into_iter().next(),
+                };
+                match value {
+                    Some(value) => {
+                        deserialize_group(self.regex_tree, self.options, value, self.start, seed)
+                    }
+                    None => Err(<Error as de::Error>::custom("capture group did not match")),
+                }
+            }
+        };
+        value.map_err(|error| error.within(PathSegment::Name(key.to_owned())))
+    }
+}
+
+/// Deserialize the value of a single capture group, recursing if it has a child
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+fn deserialize_group<'de, 'r, V>(
+    regex_tree: &'r RegexTree,
+    options: &'r DeserializeOptions,
+    (index, name, value, child): NamedMatch<'r, 'de>,
+    start: usize,
+    seed: V,
+) -> Result<V::Value, Error>
+where
+    V: de::DeserializeSeed<'de>,
+{
+    trace_span!(
+        "group",
+        index,
+        name,
+        start = start + value.start(),
+        end = start + value.end(),
+    );
+    match child {
+        Some(regex_tree) => seed.deserialize(StrDeserializer::from_regex_tree_and_offset_str(
+            regex_tree,
+            options,
+            value.as_str(),
+            start + value.start(),
+        )),
+        None => seed.deserialize(
+            JustStrDeserializer::from_match(value, start + value.start(), options)
+                .with_group(regex_tree, name),
+        ),
+    }
+}
+
+/// Deserialize the keys nested under a key as a map, for
+/// [`with_nested_keys`](crate::regex_tree::Builder::with_nested_keys)
+struct NestedDeserializer<'r, 't> {
+    map_access: SingleCaptureMapAccess<'r, 't>,
+}
+
+impl<'r> NestedDeserializer<'r, '_> {
+    /// Span of every group nested under the key
+    fn start_end(&self) -> (usize, usize) {
+        let start = self.map_access.start;
+        let spans: Vec<_> = self
+            .map_access
+            .entries
+            .as_slice()
+            .iter()
+            .flat_map(|(_, values)| values.spans(start))
+            .collect();
+        match (
+            spans.iter().map(|(start, _)| *start).min(),
+            spans.iter().map(|(_, end)| *end).max(),
+        ) {
+            (Some(start), Some(end)) => (start, end),
+            _ => (
+                start + self.map_access.whole_match.0,
+                start + self.map_access.whole_match.1,
+            ),
+        }
+    }
+}
+
+impl<'de, 'r> Deserializer<'de> for NestedDeserializer<'r, 'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_map(self.map_access)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        if let Some(keys) = spanned_keys(name, fields) {
+            let (start, end) = self.start_end();
+            return visitor.visit_map(SpannedDeserializer::new(keys, start, end, self));
         }
+        self.map_access.check_fields(fields)?;
+        visitor.visit_map(self.map_access)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+/// Deserialize every capture group sharing a key as a sequence, for
+/// [`DuplicateKeys::Collect`]
+struct GroupsDeserializer<'r, 't> {
+    regex_tree: &'r RegexTree,
+    options: &'r DeserializeOptions,
+    /// Values with the byte offset of the start of the string each is over
+    values: vec::IntoIter<(usize, NamedMatch<'r, 't>)>,
+    /// Index of the next value
+    index: usize,
+}
+
+impl<'de, 'r> Deserializer<'de> for GroupsDeserializer<'r, 'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_seq(self)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'de, 'r> SeqAccess<'de> for GroupsDeserializer<'r, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        let Some((start, value)) = self.values.next() else {
+            return Ok(None);
+        };
+        let index = self.index;
+        self.index += 1;
+        deserialize_group(self.regex_tree, self.options, value, start, seed)
+            .map(Some)
+            .map_err(|error| error.within(PathSegment::Index(index)))
     }
 }
 
 pub struct SingleCaptureSeqAccess<'r, 'c, 't> {
     regex_tree: &'r RegexTree,
-    named_captures: Zip<CaptureNames<'r>, SubCaptureMatches<'c, 't>>,
+    options: &'r DeserializeOptions,
+    named_captures: Enumerate<Zip<CaptureNames<'r>, SubCaptureMatches<'c, 't>>>,
+    /// Values of the named groups, if the node has conditional children to choose between
+    values: Option<GroupValues<'r, 't>>,
+    /// Whether groups that did not take part in the match are elements, as `None`, rather than
+    /// being skipped, so each element stays at the position of its group
+    positional: bool,
+    /// Number of elements deserialized so far
+    elements: usize,
     /// Byte offset of the start of the string `named_captures` is over within the originally parsed
     /// string
     start: usize,
@@ -408,21 +1004,32 @@ pub struct SingleCaptureSeqAccess<'r, 'c, 't> {
 impl<'r, 'c, 't> SingleCaptureSeqAccess<'r, 'c, 't> {
     pub fn from_regex_tree_and_captures(
         regex_tree: &'r RegexTree,
-        captures: SubCaptureMatches<'c, 't>,
+        options: &'r DeserializeOptions,
+        captures: &'c NodeCaptures<'r, 't>,
         start: usize,
     ) -> Self {
-        let names = regex_tree.names();
-        let named_captures = names.zip(captures);
+        let named_captures = captures.names().zip(captures.iter()).enumerate();
         Self {
             regex_tree,
+            options,
             named_captures,
+            values: regex_tree.group_values(captures),
+            positional: false,
+            elements: 0,
             start,
         }
     }
 
-    fn next(&mut self) -> Option<(Option<&'r str>, Match<'t>)> {
+    fn next(&mut self) -> Option<(usize, Option<&'r str>, Option<Match<'t>>)> {
+        let positional = self.positional;
+        let regex_tree = self.regex_tree;
         self.named_captures
-            .find_map(|(name, re_match)| re_match.map(|re_match| (name, re_match)))
+            .by_ref()
+            .map(|(index, (name, re_match))| {
+                let re_match = re_match.filter(|re_match| regex_tree.is_present(re_match));
+                (index, name, re_match)
+            })
+            .find(|(_, _, re_match)| positional || re_match.is_some())
     }
 }
 
@@ -433,24 +1040,225 @@ impl<'de, 'r, 'c> SeqAccess<'de> for SingleCaptureSeqAccess<'r, 'c, 'de> {
     where
         T: de::DeserializeSeed<'de>,
     {
-        let next = self
-            .next()
-            .map(|(key, value)| (key.and_then(|key| self.regex_tree.child(key)), value));
-        match next {
-            Some((Some(regex_tree), value)) => seed
-                .deserialize(StrDeserializer::from_regex_tree_and_offset_str(
+        let Some((index, name, value)) = self.next() else {
+            return Ok(None);
+        };
+        let element = self.elements;
+        self.elements += 1;
+
+        let value = match (
+            value,
+            self.regex_tree
+                .group_child(index, name, self.values.as_ref()),
+        ) {
+            (None, _) => seed.deserialize(UnitDeserializer::new()),
+            (Some(value), Some(regex_tree)) => {
+                seed.deserialize(StrDeserializer::from_regex_tree_and_offset_str(
                     regex_tree,
+                    self.options,
                     value.as_str(),
                     self.start + value.start(),
                 ))
-                .map(Some),
-            Some((None, value)) => seed
-                .deserialize(JustStrDeserializer::from_match(
+            }
+            (Some(value), None) => {
+                let deserializer = JustStrDeserializer::from_match(
                     value,
                     self.start + value.start(),
-                ))
-                .map(Some),
-            None => Ok(None),
+                    self.options,
+                );
+                match name {
+                    Some(name) => seed.deserialize(deserializer.with_group(self.regex_tree, name)),
+                    None => seed.deserialize(deserializer),
+                }
+            }
+        };
+        value
+            .map(Some)
+            .map_err(|error| error.within(PathSegment::Index(element)))
+    }
+}
+
+/// Deserializes an enum whose variant is named by the text of the group under a key. See
+/// [`with_tag`](crate::regex_tree::Builder::with_tag).
+struct TaggedEnumAccess<'r, 'c, 't> {
+    /// Deserializer of the rest of the match, which leaves out the tag
+    deserializer: SingleCaptureDeserializer<'r, 'c, 't>,
+    tag: &'r str,
+}
+
+impl<'de, 'r, 'c> de::EnumAccess<'de> for TaggedEnumAccess<'r, 'c, 'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let Some(re_match) = self.deserializer.key_match(self.tag) else {
+            let (start, end) = self.deserializer.start_end();
+            return Err(Error::with_span(
+                format!(
+                    "capture group `{}` naming the variant did not match",
+                    self.tag
+                ),
+                start,
+                end,
+            ));
+        };
+        let start = self.deserializer.start + re_match.start();
+        let end = self.deserializer.start + re_match.end();
+        let variant = JustStrDeserializer::from_match(re_match, start, self.deserializer.options)
+            .with_group(self.deserializer.regex_tree, self.tag);
+        let value = seed.deserialize(variant).map_err(|error| {
+            error
+                .or_span(start, end)
+                .within(PathSegment::Name(self.tag.to_owned()))
+        })?;
+        Ok((value, self))
+    }
+}
+
+impl<'de, 'r, 'c> de::VariantAccess<'de> for TaggedEnumAccess<'r, 'c, 'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(self.deserializer)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let (start, end) = self.deserializer.start_end();
+        Err(Error::with_span(
+            "tuple variants are not supported",
+            start,
+            end,
+        ))
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserializer.deserialize_struct("", fields, visitor)
+    }
+}
+
+/// Deserializes a struct field that no capture group is for, which is missing unless it is
+/// [`Uncaptured`](crate::Uncaptured) or an `Option`
+struct AbsentFieldDeserializer<'r, 'o, 't> {
+    key: &'r str,
+    uncaptured: UncapturedDeserializer<'o, 't>,
+}
+
+impl<'de, 'r, 'o> Deserializer<'de> for AbsentFieldDeserializer<'r, 'o, 'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(de::Error::custom(format!("missing field `{}`", self.key)))
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_none()
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match name {
+            UNCAPTURED_NAME => visitor.visit_newtype_struct(self.uncaptured),
+            // A newtype of an `Option` is `None` too
+            _ => visitor.visit_newtype_struct(self),
         }
     }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Deserializes a flag group as whether it took part in the match, possibly
+/// [`Spanned`](crate::Spanned) with its match, or the whole match if it is absent
+struct FlagDeserializer {
+    present: bool,
+    start: usize,
+    end: usize,
+}
+
+impl<'de> Deserializer<'de> for FlagDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_bool(self.present)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match spanned_keys(name, fields) {
+            Some(keys) => {
+                let (start, end) = (self.start, self.end);
+                visitor.visit_map(SpannedDeserializer::new(keys, start, end, self))
+            }
+            None => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
 }
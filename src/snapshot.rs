@@ -0,0 +1,82 @@
+use std::fmt::Write;
+use std::ops::Range;
+
+use crate::{MatchGroup, MatchNode, RegexTree};
+
+/// Number of chars of a match or group shown before the rest is cut off
+const MAX_CHARS: usize = 32;
+
+/// Dump the structure of how a regex tree matches `text` as indented lines,
+/// for snapshot tests of a tree, such as with `insta`. Nothing is
+/// deserialized, so the snapshot shows what a tree matches even where a type
+/// would fail to deserialize it.
+///
+/// Each match of a node is a `match` line, followed by a line for each of its
+/// capture groups that took part, named or `#` and its index for unnamed
+/// groups. The matches of a group's child follow the group, indented one
+/// level more. Group 0, the whole match, is only shown when a child runs on
+/// it. Every line has its byte range, and its text quoted and escaped as in
+/// Rust, with text longer than 32 chars cut off and marked with `...`.
+///
+/// The output depends only on the tree and the text, so it is stable between
+/// runs and changes line by line as a tree is edited.
+///
+/// ## Example
+/// ```
+/// # use recursive_regex::{debug_snapshot, RegexTree};
+/// let regex_tree = RegexTree::root(r"(?P<name>\w+): (?P<nums>.*)")
+///     .with_child("nums", RegexTree::leaf(r"\d+"))
+///     .build();
+///
+/// assert_eq!(
+///     debug_snapshot(&regex_tree, "a: 1 2\nb: 3"),
+///     r#"match 0..6 "a: 1 2"
+///   name 0..1 "a"
+///   nums 3..6 "1 2"
+///     match 3..4 "1"
+///     match 5..6 "2"
+/// match 7..11 "b: 3"
+///   name 7..8 "b"
+///   nums 10..11 "3"
+///     match 10..11 "3"
+/// "#
+/// );
+/// ```
+pub fn debug_snapshot(regex_tree: &RegexTree, text: &str) -> String {
+    let mut snapshot = String::new();
+    for node in regex_tree.matches(text) {
+        write_match(&mut snapshot, &node, 0);
+    }
+    snapshot
+}
+
+fn write_match(snapshot: &mut String, node: &MatchNode, depth: usize) {
+    write_line(snapshot, depth, "match", node.range(), node.as_str());
+    for group in node.groups() {
+        write_group(snapshot, group, depth + 1);
+    }
+}
+
+fn write_group(snapshot: &mut String, group: &MatchGroup, depth: usize) {
+    if group.index() == 0 && group.matches().is_none() {
+        return;
+    }
+    let label = match group.name() {
+        Some(name) => name.to_owned(),
+        None => format!("#{}", group.index()),
+    };
+    write_line(snapshot, depth, &label, group.range(), group.as_str());
+    for node in group.matches().unwrap_or_default() {
+        write_match(snapshot, node, depth + 1);
+    }
+}
+
+fn write_line(snapshot: &mut String, depth: usize, label: &str, range: Range<usize>, text: &str) {
+    let indent = "  ".repeat(depth);
+    let shown = match text.char_indices().nth(MAX_CHARS) {
+        Some((end, _)) => format!("{:?}...", &text[..end]),
+        None => format!("{text:?}"),
+    };
+    // Writing to a string cannot fail
+    let _ = writeln!(snapshot, "{indent}{label} {range:?} {shown}");
+}
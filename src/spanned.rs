@@ -14,6 +14,39 @@ pub(crate) const SPANNED_BEGIN: &str = "  __SPANNED_BEGIN";
 pub(crate) const SPANNED_END: &str = "  __SPANNED_END";
 pub(crate) const SPANNED_VALUE: &str = "  __SPANNED_VALUE";
 
+/// A deserialized value along with the byte range of the text it came from.
+///
+/// What the span covers depends on where the `Spanned` appears:
+/// - as an element of a sequence, such as `Vec<Spanned<Person>>`, it covers
+///   the whole match of the regex that produced the element
+/// - as a capture group's value, it covers the capture group
+/// - as a map key, such as in `HashMap<Spanned<String>, u32>`, it covers the
+///   capture groups with that key
+/// - anywhere else, such as wrapping the whole output, it covers all of the
+///   text the regex tree node was run over
+///
+/// Offsets are relative to the start of the text originally passed in.
+///
+/// ## Other spanned types
+/// With the `serde-spanned` feature, `serde_spanned::Spanned`, which `toml`
+/// re-exports as `toml::Spanned`, may be deserialized in place of this type
+/// and gets the same span. The two also convert into each other with
+/// [`From`], so code can keep one spanned type for values from several
+/// sources.
+///
+/// ```
+/// # #[cfg(feature = "serde-spanned")]
+/// # {
+/// # use recursive_regex::{from_regex_tree_and_str, RegexTree, Spanned};
+/// let regex_tree = RegexTree::leaf(r"\d+");
+/// let nums: Vec<serde_spanned::Spanned<u32>> =
+///     from_regex_tree_and_str(&regex_tree, "1 23").unwrap();
+/// assert_eq!(nums[1].span(), 2..4);
+///
+/// let num: Spanned<u32> = nums[1].clone().into();
+/// assert_eq!((num.begin(), num.end()), (2, 4));
+/// # }
+/// ```
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct Spanned<T> {
     begin: usize,
@@ -74,42 +107,101 @@ impl<'de, T: de::Deserialize<'de>> de::Visitor<'de> for SpannedVisitor<T> {
     where
         A: de::MapAccess<'de>,
     {
-        fn check_key<'de, A>(visitor: &mut A, expected: &str, name: &str)
+        fn check_key<'de, A>(visitor: &mut A, expected: &str, name: &str) -> Result<(), A::Error>
         where
             A: de::MapAccess<'de>,
         {
-            let key_valid = match visitor.next_key::<&str>() {
-                Ok(Some(key)) => std::ptr::eq(key, expected),
-                _ => false,
-            };
-            if !key_valid {
-                panic!("`Spanned` {name} key not found");
+            match visitor.next_key::<&str>()? {
+                Some(key) if key == expected => Ok(()),
+                _ => Err(de::Error::custom(format!("`Spanned` {name} key not found"))),
             }
         }
 
-        check_key(&mut visitor, SPANNED_BEGIN, "begin");
+        check_key(&mut visitor, SPANNED_BEGIN, "begin")?;
         let begin: usize = visitor.next_value()?;
 
-        check_key(&mut visitor, SPANNED_END, "end");
+        check_key(&mut visitor, SPANNED_END, "end")?;
         let end: usize = visitor.next_value()?;
 
-        check_key(&mut visitor, SPANNED_VALUE, "value");
+        check_key(&mut visitor, SPANNED_VALUE, "value")?;
         let value: T = visitor.next_value()?;
 
         Ok(Spanned { begin, end, value })
     }
 }
 
-pub(crate) enum SpannedDeserializer<T, E> {
-    Start(PhantomData<E>, T, usize, usize),
+/// Which text the span of a value parsed from a capture group or match
+/// covers when the text was changed before parsing, for
+/// [`DeserializeOptions::with_span_policy`](crate::DeserializeOptions::with_span_policy).
+/// This applies to [`Spanned`] values and to the spans of errors. A [`Spanned`]
+/// whole match, rather than a value parsed from it, always covers the match.
+///
+/// Spans always count bytes of the original input. Unescaping and value maps
+/// give text that appears nowhere in the input, so under either policy a
+/// value made by them spans the input text it was made from, escapes and all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SpanPolicy {
+    /// The text that was parsed, narrowed by
+    /// [`trim`](crate::DeserializeOptions::trim) if it is set
+    #[default]
+    Transformed,
+    /// The text exactly as the pattern matched it, before trimming
+    Original,
+}
+
+/// Keys of the map a spanned value is deserialized from, if `name` and `fields` are those of
+/// [`Spanned`], or of `serde_spanned::Spanned` with the `serde-spanned` feature
+pub(crate) fn spanned_keys(name: &str, fields: &[&str]) -> Option<[&'static str; 3]> {
+    if name == SPANNED_NAME && fields == [SPANNED_BEGIN, SPANNED_END, SPANNED_VALUE] {
+        return Some([SPANNED_BEGIN, SPANNED_END, SPANNED_VALUE]);
+    }
+    #[cfg(feature = "serde-spanned")]
+    {
+        use serde_spanned::__unstable::{END_FIELD, NAME, START_FIELD, VALUE_FIELD};
+        if name == NAME && fields == [START_FIELD, END_FIELD, VALUE_FIELD] {
+            return Some([START_FIELD, END_FIELD, VALUE_FIELD]);
+        }
+    }
+    None
+}
+
+/// Whether a capture group deserialized under `key` would be mistaken for a field of
+/// [`Spanned`], or of `serde_spanned::Spanned` with the `serde-spanned` feature
+pub(crate) fn is_reserved_key(key: &str) -> bool {
+    if [SPANNED_BEGIN, SPANNED_END, SPANNED_VALUE].contains(&key) {
+        return true;
+    }
+    #[cfg(feature = "serde-spanned")]
+    {
+        use serde_spanned::__unstable::{END_FIELD, START_FIELD, VALUE_FIELD};
+        if [START_FIELD, END_FIELD, VALUE_FIELD].contains(&key) {
+            return true;
+        }
+    }
+    false
+}
+
+pub(crate) struct SpannedDeserializer<T, E> {
+    /// Keys of the start, end, and value, in the order they are given
+    keys: [&'static str; 3],
+    state: SpannedState<T>,
+    error: PhantomData<E>,
+}
+
+enum SpannedState<T> {
+    Start(T, usize, usize),
     End(T, usize),
     Value(T),
     None,
 }
 
 impl<T, E> SpannedDeserializer<T, E> {
-    pub fn new(start: usize, end: usize, value: T) -> Self {
-        Self::Start(PhantomData, value, end, start)
+    pub fn new(keys: [&'static str; 3], start: usize, end: usize, value: T) -> Self {
+        Self {
+            keys,
+            state: SpannedState::Start(value, end, start),
+            error: PhantomData,
+        }
     }
 }
 
@@ -124,11 +216,12 @@ where
     where
         K: de::DeserializeSeed<'de>,
     {
-        let key = match self {
-            Self::Start(..) => Some(SPANNED_BEGIN),
-            Self::End(..) => Some(SPANNED_END),
-            Self::Value(..) => Some(SPANNED_VALUE),
-            Self::None => None,
+        let [start_key, end_key, value_key] = self.keys;
+        let key = match self.state {
+            SpannedState::Start(..) => Some(start_key),
+            SpannedState::End(..) => Some(end_key),
+            SpannedState::Value(..) => Some(value_key),
+            SpannedState::None => None,
         };
 
         key.map(BorrowedStrDeserializer::new)
@@ -140,24 +233,24 @@ where
     where
         V: de::DeserializeSeed<'de>,
     {
-        let data = mem::replace(self, Self::None);
+        let state = mem::replace(&mut self.state, SpannedState::None);
 
-        let (result, data) = match data {
-            Self::Start(_, value, end, start) => (
+        let (result, state) = match state {
+            SpannedState::Start(value, end, start) => (
                 seed.deserialize(start.into_deserializer()),
-                Self::End(value, end),
+                SpannedState::End(value, end),
             ),
-            Self::End(value, end) => (
+            SpannedState::End(value, end) => (
                 seed.deserialize(end.into_deserializer()),
-                Self::Value(value),
+                SpannedState::Value(value),
             ),
-            Self::Value(value) => (seed.deserialize(value), Self::None),
-            Self::None => (
+            SpannedState::Value(value) => (seed.deserialize(value), SpannedState::None),
+            SpannedState::None => (
                 Err(Self::Error::custom("no more values for `Spanned`")),
-                Self::None,
+                SpannedState::None,
             ),
         };
-        let _ = mem::replace(self, data);
+        self.state = state;
 
         result
     }
@@ -171,3 +264,97 @@ impl<T: ser::Serialize> ser::Serialize for Spanned<T> {
         self.value.serialize(serializer)
     }
 }
+
+#[cfg(feature = "serde-spanned")]
+impl<T> From<Spanned<T>> for serde_spanned::Spanned<T> {
+    fn from(spanned: Spanned<T>) -> Self {
+        Self::new(spanned.begin..spanned.end, spanned.value)
+    }
+}
+
+#[cfg(feature = "serde-spanned")]
+impl<T> From<serde_spanned::Spanned<T>> for Spanned<T> {
+    fn from(spanned: serde_spanned::Spanned<T>) -> Self {
+        let span = spanned.span();
+        Self::new_raw(spanned.into_inner(), span.start, span.end)
+    }
+}
+
+/// Deserializer for a map key, which is a capture group key rather than text, but may still be
+/// [`Spanned`] with the span of the text its value came from. The key is passed to visitors as a
+/// `&str`, so struct fields are matched without allocating.
+pub(crate) struct KeyDeserializer<'a, E> {
+    key: &'a str,
+    start: usize,
+    end: usize,
+    error: PhantomData<E>,
+}
+
+impl<'a, E> KeyDeserializer<'a, E> {
+    pub fn new(key: &'a str, start: usize, end: usize) -> Self {
+        Self {
+            key,
+            start,
+            end,
+            error: PhantomData,
+        }
+    }
+}
+
+impl<'de, E: de::Error> Deserializer<'de> for KeyDeserializer<'_, E> {
+    type Error = E;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_str(self.key)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match spanned_keys(name, fields) {
+            Some(keys) => {
+                let (start, end) = (self.start, self.end);
+                visitor.visit_map(SpannedDeserializer::new(keys, start, end, self))
+            }
+            None => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        de::value::StrDeserializer::<E>::new(self.key).deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct seq tuple
+        tuple_struct map identifier ignored_any
+    }
+}
@@ -1,44 +1,220 @@
-use serde::de::value::Error;
-use serde::{de, serde_if_integer128};
+use serde::de;
 
+use crate::datetime::{DURATION_FIELDS, DURATION_NAME};
+use crate::headed::HeadedMapAccess;
 use crate::just_string::JustStrDeserializer;
+use crate::matched::{is_matched, MatchedDeserializer};
 use crate::multi_capture::MultiCaptureSeqAccess;
+use crate::near_miss::NearMiss;
+use crate::node_captures::NodeCaptures;
+use crate::options::{DeserializeOptions, DEFAULT_OPTIONS};
 use crate::single_capture::{SingleCaptureDeserializer, SingleCaptureMapAccess};
-use crate::spanned::{
-    SpannedDeserializer, SPANNED_BEGIN, SPANNED_END, SPANNED_NAME, SPANNED_VALUE,
-};
-use crate::RegexTree;
-
+use crate::spanned::{spanned_keys, SpannedDeserializer};
+use crate::trace::trace_event;
+use crate::{Error, RegexTree};
+
+/// Deserializes text with a regex tree. The functions such as
+/// [`from_regex_tree_and_str`](crate::from_regex_tree_and_str) use this, and
+/// it can be given to anything that takes a [`serde::Deserializer`].
+///
+/// ## Example
+/// ```
+/// # use recursive_regex::{RegexTree, StrDeserializer};
+/// # use serde::Deserialize;
+/// let regex_tree = RegexTree::leaf(r"\d+");
+/// let deserializer = StrDeserializer::new(&regex_tree, "1 2 3");
+/// assert_eq!(Vec::<u32>::deserialize(deserializer).unwrap(), vec![1, 2, 3]);
+/// ```
 pub struct StrDeserializer<'r, 't> {
     regex_tree: &'r RegexTree,
+    options: &'r DeserializeOptions,
     text: &'t str,
     /// Byte offset of the start of `text` within the originally parsed string
     start: usize,
+    /// Whether `regex_tree` is the root of the tree, rather than a child
+    root: bool,
 }
 
 impl<'r, 't> StrDeserializer<'r, 't> {
+    /// Create a new deserializer for `text` with the default options
+    pub fn new(regex_tree: &'r RegexTree, text: &'t str) -> Self {
+        Self::from_regex_tree_and_str(regex_tree, text)
+    }
+
+    /// Create a new deserializer for `text` with the default options. The
+    /// same as [`new`](StrDeserializer::new).
     pub fn from_regex_tree_and_str(regex_tree: &'r RegexTree, text: &'t str) -> Self {
+        Self::from_regex_tree_and_str_with_options(regex_tree, text, &DEFAULT_OPTIONS)
+    }
+
+    /// Create a new deserializer for `text` with [`DeserializeOptions`]
+    pub fn from_regex_tree_and_str_with_options(
+        regex_tree: &'r RegexTree,
+        text: &'t str,
+        options: &'r DeserializeOptions,
+    ) -> Self {
         Self {
             regex_tree,
+            options,
             text,
-            start: 0,
+            start: options.base_offset(),
+            root: true,
         }
     }
 
     pub(crate) fn from_regex_tree_and_offset_str(
         regex_tree: &'r RegexTree,
+        options: &'r DeserializeOptions,
         text: &'t str,
         start: usize,
     ) -> Self {
         Self {
             regex_tree,
+            options,
             text,
             start,
+            root: false,
         }
     }
 
-    fn just_str(self) -> JustStrDeserializer<'t> {
-        JustStrDeserializer::new(self.text, self.start)
+    fn no_match_error(&self) -> Error {
+        let message = "regular expression does not match";
+        if !self.options.is_near_misses() {
+            return <Error as de::Error>::custom(message);
+        }
+        match NearMiss::find(self.regex_tree, self.text) {
+            Some(near_miss) => Error::with_span(
+                format!("{message}; {near_miss}"),
+                self.start + near_miss.start(),
+                self.start + near_miss.end(),
+            ),
+            None => Error::with_span(
+                format!("{message}; no start of a pattern matches"),
+                self.start,
+                self.start + self.text.len(),
+            ),
+        }
+    }
+
+    /// First match of the node in the text, recorded if statistics are collected
+    fn captures(&self) -> Option<NodeCaptures<'r, 't>> {
+        let Some(stats) = self.options.stats_collector() else {
+            return self.regex_tree.captures(self.text);
+        };
+        stats.start_run(self.regex_tree, self.text.len());
+        stats.record_search(
+            self.regex_tree,
+            || self.regex_tree.captures(self.text),
+            |captures| Some(captures.as_ref()?.as_str().len()),
+        )
+    }
+
+    /// Deserialize a map from a single capture, or from every capture if the node collects keys
+    /// across its matches
+    fn map_access(self, fields: &[&'static str]) -> Result<SingleCaptureMapAccess<'r, 't>, Error> {
+        if self.regex_tree.is_collecting() {
+            let mut captures_iter = self.regex_tree.captures_iter(self.text);
+            // Pieces of a split node that do not match have nothing to collect
+            let all: Vec<_> = std::iter::from_fn(|| captures_iter.try_next())
+                .filter_map(Result::ok)
+                .collect();
+            trace_event!(
+                pattern = self.regex_tree.pattern(),
+                matches = all.len(),
+                start = self.start,
+                end = self.start + self.text.len(),
+                "collecting keys across matches"
+            );
+            return Ok(SingleCaptureMapAccess::from_regex_tree_and_all_captures(
+                self.regex_tree,
+                self.options,
+                &all,
+                self.start,
+                self.text.len(),
+                fields,
+            ));
+        }
+        let captures = self.captures().ok_or_else(|| self.no_match_error())?;
+        trace_event!(
+            pattern = self.regex_tree.pattern(),
+            start = self.start + captures.start(),
+            end = self.start + captures.end(),
+            "node matched once"
+        );
+        Ok(SingleCaptureMapAccess::from_regex_tree_and_captures(
+            self.regex_tree,
+            self.options,
+            &captures,
+            self.start + captures.offset(),
+        )
+        .with_absent_fields(fields, &captures))
+    }
+
+    /// Deserializer for the child for group 0 over the first match, if the node has such a child
+    fn whole_match_child(&self) -> Result<Option<Self>, Error> {
+        let Some(child) = self.regex_tree.child_index(0) else {
+            return Ok(None);
+        };
+        let captures = self.captures().ok_or_else(|| self.no_match_error())?;
+        Ok(Some(Self::from_regex_tree_and_offset_str(
+            child,
+            self.options,
+            captures.as_str(),
+            self.start + captures.start(),
+        )))
+    }
+
+    /// Give the number of matches of the node, for a counted child
+    fn deserialize_count<'de, V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let mut captures_iter = self.regex_tree.captures_iter(self.text);
+        let count = std::iter::from_fn(|| captures_iter.try_next())
+            .filter(Result::is_ok)
+            .count();
+        trace_event!(
+            pattern = self.regex_tree.pattern(),
+            count,
+            start = self.start,
+            end = self.start + self.text.len(),
+            "counted matches"
+        );
+        let count = u64::try_from(count).unwrap_or(u64::MAX);
+        visitor
+            .visit_u64(count)
+            .map_err(|error: Error| match error.span() {
+                Some(_) => error,
+                None => Error::with_span(error.message(), self.start, self.start + self.text.len()),
+            })
+    }
+
+    /// Deserialize from many captures
+    fn seq_access(&self) -> MultiCaptureSeqAccess<'r, 't> {
+        trace_event!(
+            pattern = self.regex_tree.pattern(),
+            start = self.start,
+            end = self.start + self.text.len(),
+            "matching node repeatedly"
+        );
+        let captures_iter = self.regex_tree.captures_iter(self.text);
+        let seq_access = MultiCaptureSeqAccess::from_regex_tree_and_captures(
+            self.regex_tree,
+            self.options,
+            captures_iter,
+            self.text,
+            self.start,
+        );
+        if self.root {
+            seq_access.root()
+        } else {
+            seq_access
+        }
+    }
+
+    fn just_str(self) -> JustStrDeserializer<'r, 't> {
+        JustStrDeserializer::with_options(self.text, self.start, self.options)
+            .with_node(self.regex_tree)
     }
 }
 
@@ -49,26 +225,45 @@ impl<'de, 'r> de::Deserializer<'de> for StrDeserializer<'r, 'de> {
     where
         V: de::Visitor<'de>,
     {
-        self.deserialize_any(visitor)
+        visitor.visit_unit()
     }
 
-    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        unimplemented!()
+        // Without a type to guide it, a regex run over text is assumed to match repeatedly, and
+        // headers to key their sections
+        if self.regex_tree.is_headed() {
+            return self.deserialize_map(visitor);
+        }
+        self.deserialize_seq(visitor)
     }
 
     fn deserialize_enum<V>(
         self,
-        _name: &'static str,
-        _variants: &'static [&'static str],
-        _visitor: V,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        unimplemented!()
+        if self.regex_tree.tag().is_none() {
+            return Err(Error::with_span(
+                "enums are not supported without a tag group",
+                self.start,
+                self.start + self.text.len(),
+            ));
+        }
+        let captures = self.captures().ok_or_else(|| self.no_match_error())?;
+        SingleCaptureDeserializer::from_regex_tree_and_single_capture(
+            self.regex_tree,
+            self.options,
+            &captures,
+            self.start,
+        )
+        .deserialize_enum(name, variants, visitor)
     }
 
     fn deserialize_struct<V>(
@@ -80,11 +275,21 @@ impl<'de, 'r> de::Deserializer<'de> for StrDeserializer<'r, 'de> {
     where
         V: de::Visitor<'de>,
     {
-        if name == SPANNED_NAME && fields == [SPANNED_BEGIN, SPANNED_END, SPANNED_VALUE] {
+        if let Some(keys) = spanned_keys(name, fields) {
             let end = self.start + self.text.len();
-            visitor.visit_map(SpannedDeserializer::new(self.start, end, self))
-        } else {
+            visitor.visit_map(SpannedDeserializer::new(keys, self.start, end, self))
+        } else if is_matched(name, fields) {
+            visitor.visit_map(MatchedDeserializer::new(self.text, self))
+        } else if name == DURATION_NAME && fields == DURATION_FIELDS {
+            self.just_str().deserialize_struct(name, fields, visitor)
+        } else if self.regex_tree.is_headed() {
             self.deserialize_map(visitor)
+        } else if let Some(child) = self.whole_match_child()? {
+            child.deserialize_struct(name, fields, visitor)
+        } else {
+            let map_access = self.map_access(fields)?;
+            map_access.check_fields(fields)?;
+            visitor.visit_map(map_access)
         }
     }
 
@@ -92,17 +297,18 @@ impl<'de, 'r> de::Deserializer<'de> for StrDeserializer<'r, 'de> {
     where
         V: de::Visitor<'de>,
     {
-        // Deserialize from a single capture
-        let captures = self
-            .regex_tree
-            .captures(self.text)
-            .ok_or_else(|| <Error as de::Error>::custom("regular expression does not match"))?;
-        let map_access = SingleCaptureMapAccess::from_regex_tree_and_captures(
-            self.regex_tree,
-            captures.iter(),
-            self.start,
-        );
-        visitor.visit_map(map_access)
+        if self.regex_tree.is_headed() {
+            return visitor.visit_map(HeadedMapAccess::new(
+                self.regex_tree,
+                self.options,
+                self.text,
+                self.start,
+            )?);
+        }
+        match self.whole_match_child()? {
+            Some(child) => child.deserialize_map(visitor),
+            None => visitor.visit_map(self.map_access(&[])?),
+        }
     }
 
     fn deserialize_tuple_struct<V>(
@@ -117,43 +323,59 @@ impl<'de, 'r> de::Deserializer<'de> for StrDeserializer<'r, 'de> {
         self.deserialize_tuple(len, visitor)
     }
 
-    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    /// A tuple or array has an element for each match, and there must be exactly as many
+    /// matches as elements
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.deserialize_seq(visitor)
+        if self.regex_tree.decoding().is_some() {
+            return self.just_str().deserialize_tuple(len, visitor);
+        }
+        let mut seq_access = self.seq_access().exact_len(len);
+        let value = visitor.visit_seq(&mut seq_access)?;
+        seq_access.check_no_more()?;
+        Ok(value)
     }
 
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        // Deserialize from many captures
-        let captures_iter = self.regex_tree.captures_iter(self.text);
-        let seq_access = MultiCaptureSeqAccess::from_regex_tree_and_captures(
-            self.regex_tree,
-            captures_iter,
-            self.start,
-        );
-        visitor.visit_seq(seq_access)
+        if self.regex_tree.decoding().is_some() {
+            return self.just_str().deserialize_seq(visitor);
+        }
+        visitor.visit_seq(self.seq_access())
     }
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
+        if self.regex_tree.is_count_matches() {
+            return visitor.visit_some(self);
+        }
         // Deserialize from zero or one captures
-        let captures = self.regex_tree.captures(self.text);
-        match captures {
-            Some(captures) => {
+        let captures = self.captures();
+        match (captures, self.regex_tree.child_index(0)) {
+            (Some(captures), Some(child)) => {
+                visitor.visit_some(Self::from_regex_tree_and_offset_str(
+                    child,
+                    self.options,
+                    captures.as_str(),
+                    self.start + captures.start(),
+                ))
+            }
+            (Some(captures), None) => {
                 let deserializer = SingleCaptureDeserializer::from_regex_tree_and_single_capture(
                     self.regex_tree,
-                    captures.iter(),
+                    self.options,
+                    &captures,
                     self.start,
                 );
                 visitor.visit_some(deserializer)
             }
-            None => visitor.visit_none(),
+            (None, _) => visitor.visit_none(),
         }
     }
 
@@ -172,6 +394,9 @@ impl<'de, 'r> de::Deserializer<'de> for StrDeserializer<'r, 'de> {
     where
         V: de::Visitor<'de>,
     {
+        if self.options.is_strict_unit() && self.captures().is_none() {
+            return Err(self.no_match_error());
+        }
         visitor.visit_unit()
     }
 
@@ -197,6 +422,9 @@ impl<'de, 'r> de::Deserializer<'de> for StrDeserializer<'r, 'de> {
     where
         V: de::Visitor<'de>,
     {
+        if self.regex_tree.is_count_matches() {
+            return self.deserialize_count(visitor);
+        }
         self.just_str().deserialize_i8(visitor)
     }
 
@@ -204,6 +432,9 @@ impl<'de, 'r> de::Deserializer<'de> for StrDeserializer<'r, 'de> {
     where
         V: de::Visitor<'de>,
     {
+        if self.regex_tree.is_count_matches() {
+            return self.deserialize_count(visitor);
+        }
         self.just_str().deserialize_i16(visitor)
     }
 
@@ -211,6 +442,9 @@ impl<'de, 'r> de::Deserializer<'de> for StrDeserializer<'r, 'de> {
     where
         V: de::Visitor<'de>,
     {
+        if self.regex_tree.is_count_matches() {
+            return self.deserialize_count(visitor);
+        }
         self.just_str().deserialize_i32(visitor)
     }
 
@@ -218,22 +452,29 @@ impl<'de, 'r> de::Deserializer<'de> for StrDeserializer<'r, 'de> {
     where
         V: de::Visitor<'de>,
     {
+        if self.regex_tree.is_count_matches() {
+            return self.deserialize_count(visitor);
+        }
         self.just_str().deserialize_i64(visitor)
     }
 
-    serde_if_integer128! {
-        fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-        where
-            V: de::Visitor<'de>
-        {
-            self.just_str().deserialize_i128(visitor)
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        if self.regex_tree.is_count_matches() {
+            return self.deserialize_count(visitor);
         }
+        self.just_str().deserialize_i128(visitor)
     }
 
     fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
+        if self.regex_tree.is_count_matches() {
+            return self.deserialize_count(visitor);
+        }
         self.just_str().deserialize_u8(visitor)
     }
 
@@ -241,6 +482,9 @@ impl<'de, 'r> de::Deserializer<'de> for StrDeserializer<'r, 'de> {
     where
         V: de::Visitor<'de>,
     {
+        if self.regex_tree.is_count_matches() {
+            return self.deserialize_count(visitor);
+        }
         self.just_str().deserialize_u16(visitor)
     }
 
@@ -248,6 +492,9 @@ impl<'de, 'r> de::Deserializer<'de> for StrDeserializer<'r, 'de> {
     where
         V: de::Visitor<'de>,
     {
+        if self.regex_tree.is_count_matches() {
+            return self.deserialize_count(visitor);
+        }
         self.just_str().deserialize_u32(visitor)
     }
 
@@ -255,16 +502,20 @@ impl<'de, 'r> de::Deserializer<'de> for StrDeserializer<'r, 'de> {
     where
         V: de::Visitor<'de>,
     {
+        if self.regex_tree.is_count_matches() {
+            return self.deserialize_count(visitor);
+        }
         self.just_str().deserialize_u64(visitor)
     }
 
-    serde_if_integer128! {
-        fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-        where
-            V: de::Visitor<'de>
-        {
-            self.just_str().deserialize_u128(visitor)
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        if self.regex_tree.is_count_matches() {
+            return self.deserialize_count(visitor);
         }
+        self.just_str().deserialize_u128(visitor)
     }
 
     fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
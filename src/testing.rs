@@ -0,0 +1,157 @@
+//! Assertions for property testing regex trees, such as with `proptest` or
+//! `quickcheck`. Each one panics with a description of what went wrong, so it
+//! can be called straight from the body of a property.
+//!
+//! ## Example
+//! ```
+//! # use recursive_regex::testing::{assert_round_trip, assert_spans_in_bounds};
+//! # use recursive_regex::RegexTree;
+//! # use serde::Deserialize;
+//! #[derive(Debug, PartialEq, Deserialize)]
+//! struct Pair {
+//!     key: String,
+//!     value: u32,
+//! }
+//!
+//! let regex_tree = RegexTree::root(r"(?P<key>\w+)=(?P<value>\d+)").build();
+//!
+//! // In a property, these would come from a generator of valid records
+//! for (key, value) in [("a", 1), ("width", 640)] {
+//!     let text = format!("{key}={value}");
+//!     assert_spans_in_bounds(&regex_tree, &text);
+//!     assert_round_trip(&regex_tree, &text, |pairs: &Vec<Pair>| {
+//!         pairs
+//!             .iter()
+//!             .map(|pair| format!("{}={}", pair.key, pair.value))
+//!             .collect::<Vec<_>>()
+//!             .join("\n")
+//!     });
+//! }
+//! ```
+
+use std::fmt::Debug;
+use std::ops::Range;
+
+use serde::de::DeserializeOwned;
+
+use crate::options::DEFAULT_OPTIONS;
+use crate::{
+    from_regex_tree_and_str_with_options, to_value_with_options, DeserializeOptions, MatchNode,
+    RegexTree, Value,
+};
+
+/// Assert that text deserializes into a `T`, and that the text `to_text`
+/// writes for that value deserializes into an equal `T`, which is returned.
+///
+/// The crate cannot yet write values back out as text, so `to_text` does so
+/// for the tree being tested. Spans differ between the two texts, so `T`
+/// should not contain [`Spanned`](crate::Spanned) or
+/// [`Matched`](crate::Matched) values unless their comparison ignores them.
+#[track_caller]
+pub fn assert_round_trip<T, F>(regex_tree: &RegexTree, text: &str, to_text: F) -> T
+where
+    T: DeserializeOwned + PartialEq + Debug,
+    F: FnOnce(&T) -> String,
+{
+    assert_round_trip_with_options(regex_tree, text, &DEFAULT_OPTIONS, to_text)
+}
+
+/// Like [`assert_round_trip`], but deserializing both texts with
+/// [`DeserializeOptions`]
+#[track_caller]
+pub fn assert_round_trip_with_options<T, F>(
+    regex_tree: &RegexTree,
+    text: &str,
+    options: &DeserializeOptions,
+    to_text: F,
+) -> T
+where
+    T: DeserializeOwned + PartialEq + Debug,
+    F: FnOnce(&T) -> String,
+{
+    let value: T = match from_regex_tree_and_str_with_options(regex_tree, text, options) {
+        Ok(value) => value,
+        Err(err) => panic!("failed to deserialize {text:?}: {err}"),
+    };
+    let written = to_text(&value);
+    let round_tripped: T = match from_regex_tree_and_str_with_options(regex_tree, &written, options)
+    {
+        Ok(value) => value,
+        Err(err) => panic!("failed to deserialize {written:?}, written from {text:?}: {err}"),
+    };
+    assert_eq!(
+        value, round_tripped,
+        "{text:?} changed after being written as {written:?} and deserialized again"
+    );
+    value
+}
+
+/// Assert that every span a regex tree reports for some text lies within it:
+/// the spans of [`Value`]s from [`to_value`](crate::to_value), of the
+/// [matches](RegexTree::matches) and their groups, and of any error.
+#[track_caller]
+pub fn assert_spans_in_bounds(regex_tree: &RegexTree, text: &str) {
+    assert_spans_in_bounds_with_options(regex_tree, text, &DEFAULT_OPTIONS)
+}
+
+/// Like [`assert_spans_in_bounds`], but deserializing with
+/// [`DeserializeOptions`]. Spans of values and errors are checked against the
+/// text shifted by the [base offset](DeserializeOptions::with_base_offset).
+#[track_caller]
+pub fn assert_spans_in_bounds_with_options(
+    regex_tree: &RegexTree,
+    text: &str,
+    options: &DeserializeOptions,
+) {
+    let base = options.base_offset();
+    let bounds = base..base + text.len();
+    match to_value_with_options(regex_tree, text, options) {
+        Ok(value) => check_value(&value, &bounds, text),
+        Err(err) => {
+            if let Some(span) = err.span() {
+                check_span("error", &span, &bounds, text);
+            }
+        }
+    }
+
+    // Matches are always relative to the start of the text
+    for node in regex_tree.matches(text) {
+        check_match(&node, &(0..text.len()), text);
+    }
+}
+
+#[track_caller]
+fn check_value(value: &Value, bounds: &Range<usize>, text: &str) {
+    match value {
+        Value::String(_) | Value::Bool(_) | Value::Number(_) => {}
+        Value::Seq(values) => values
+            .iter()
+            .for_each(|value| check_value(value, bounds, text)),
+        Value::Map(entries) => entries
+            .iter()
+            .for_each(|(_, value)| check_value(value, bounds, text)),
+        Value::Spanned(spanned) => {
+            check_span("value", &(spanned.begin()..spanned.end()), bounds, text);
+            check_value(spanned.value(), bounds, text);
+        }
+    }
+}
+
+#[track_caller]
+fn check_match(node: &MatchNode<'_, '_>, bounds: &Range<usize>, text: &str) {
+    check_span("match", &node.range(), bounds, text);
+    for group in node.groups() {
+        check_span("group", &group.range(), &node.range(), text);
+        for child in group.matches().unwrap_or_default() {
+            check_match(child, &group.range(), text);
+        }
+    }
+}
+
+#[track_caller]
+fn check_span(what: &str, span: &Range<usize>, bounds: &Range<usize>, text: &str) {
+    assert!(
+        bounds.start <= span.start && span.start <= span.end && span.end <= bounds.end,
+        "{what} span {span:?} is not within {bounds:?} of {text:?}"
+    );
+}
@@ -0,0 +1,20 @@
+//! Instrumentation of the deserialization walk with `tracing`, for the
+//! `tracing` feature. Without the feature, the macros expand to nothing.
+
+/// Emit a trace level event
+macro_rules! trace_event {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        ::tracing::trace!(target: "recursive_regex", $($arg)*);
+    };
+}
+
+/// Enter a trace level span until the end of the enclosing block
+macro_rules! trace_span {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        let _span = ::tracing::trace_span!(target: "recursive_regex", $($arg)*).entered();
+    };
+}
+
+pub(crate) use {trace_event, trace_span};
@@ -0,0 +1,130 @@
+use std::fmt;
+
+use serde::ser::SerializeSeq;
+use serde::Serializer;
+
+use crate::multi_capture::MultiCaptureSeqAccess;
+use crate::options::DEFAULT_OPTIONS;
+use crate::{
+    records_from_regex_tree_and_str_with_options, DeserializeOptions, Error, RegexTree, Value,
+};
+
+/// Error from transcoding, either while deserializing the text or from the
+/// serializer
+#[derive(Debug)]
+pub enum TranscodeError<E> {
+    Deserialize(Error),
+    Serialize(E),
+}
+
+impl<E: fmt::Display> fmt::Display for TranscodeError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Deserialize(err) => write!(f, "{err}"),
+            Self::Serialize(err) => write!(f, "cannot serialize: {err}"),
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for TranscodeError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Deserialize(err) => Some(err),
+            Self::Serialize(err) => Some(err),
+        }
+    }
+}
+
+impl<E> From<Error> for TranscodeError<E> {
+    fn from(err: Error) -> Self {
+        Self::Deserialize(err)
+    }
+}
+
+/// Serialize what a regex tree produces from some text with any serializer,
+/// with no Rust type in between. The output is a sequence with an element for
+/// each match of the root, shaped like the [`Value`] from
+/// [`to_value`](crate::to_value): matches with named capture groups become
+/// maps, and everything else becomes strings.
+///
+/// Records are written one at a time as they are matched, so only one is
+/// held in memory and a serializer that writes as it goes, such as for JSON,
+/// streams its output. Transcoding stops at the first record that fails, with
+/// the error for it.
+///
+/// A serializer that is not [human readable](Serializer::is_human_readable),
+/// such as for bincode or CBOR, instead gets text that reads as a number or
+/// bool as one, as with [`DeserializeOptions::infer_types`]. Binary formats
+/// read back numbers by their encoding rather than parsing text, so a string
+/// of digits would not decode as a number. Some of them also need the length
+/// of a sequence before its elements, so the matches are counted first,
+/// keeping to those selected by [`DeserializeOptions::take_last`],
+/// [`DeserializeOptions::skip`] and [`DeserializeOptions::take`]. The
+/// counting searches the text too, so it is charged to the
+/// [scan limit](DeserializeOptions::with_scan_limit) and stops at the
+/// [deadline](DeserializeOptions::with_deadline) like deserializing.
+///
+/// ## Example
+/// ```
+/// # use recursive_regex::{transcode, RegexTree};
+/// let regex_tree = RegexTree::leaf(r"(?P<key>\w+)=(?P<value>\w+)");
+///
+/// let mut out = Vec::new();
+/// transcode(&regex_tree, "a=1 b=2", &mut serde_json::Serializer::new(&mut out)).unwrap();
+/// assert_eq!(
+///     String::from_utf8(out).unwrap(),
+///     r#"[{"key":"a","value":"1"},{"key":"b","value":"2"}]"#
+/// );
+/// ```
+pub fn transcode<S: Serializer>(
+    regex_tree: &RegexTree,
+    text: &str,
+    serializer: S,
+) -> Result<S::Ok, TranscodeError<S::Error>> {
+    transcode_with_options(regex_tree, text, &DEFAULT_OPTIONS, serializer)
+}
+
+/// Like [`transcode`], but with [`DeserializeOptions`] that change how the
+/// text is deserialized
+pub fn transcode_with_options<S: Serializer>(
+    regex_tree: &RegexTree,
+    text: &str,
+    options: &DeserializeOptions,
+    serializer: S,
+) -> Result<S::Ok, TranscodeError<S::Error>> {
+    let inferred;
+    let (options, len) = match serializer.is_human_readable() {
+        true => (options, None),
+        false => {
+            inferred = options.clone().infer_types();
+            (&inferred, Some(count_matches(regex_tree, text, &inferred)?))
+        }
+    };
+
+    let mut seq = serializer
+        .serialize_seq(len)
+        .map_err(TranscodeError::Serialize)?;
+    for record in records_from_regex_tree_and_str_with_options::<Value>(regex_tree, text, options) {
+        seq.serialize_element(&record?)
+            .map_err(TranscodeError::Serialize)?;
+    }
+    seq.end().map_err(TranscodeError::Serialize)
+}
+
+/// Count the records there are to transcode, for serializers that need the length up front
+fn count_matches(
+    regex_tree: &RegexTree,
+    text: &str,
+    options: &DeserializeOptions,
+) -> Result<usize, Error> {
+    MultiCaptureSeqAccess::from_regex_tree_and_captures(
+        regex_tree,
+        options,
+        regex_tree.captures_iter(text),
+        text,
+        options.base_offset(),
+    )
+    .root()
+    .count_matches()
+    .map_err(|error| error.in_source(options))
+}
@@ -1,18 +1,312 @@
 use itertools::Itertools;
-use regex::Matches;
-use std::iter;
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::{fmt, iter, vec};
 
-pub fn get_uncaptured<'r, 't: 'r>(
-    text: &'t str,
-    matches: Matches<'r, 't>,
-) -> impl Iterator<Item = &'t str> + 'r {
+use serde::de::{self, SeqAccess};
+use serde::{Deserialize, Deserializer};
+
+use crate::just_string::JustStrDeserializer;
+use crate::node_captures::NodeCaptureMatches;
+use crate::options::DeserializeOptions;
+use crate::pattern::Match;
+use crate::{Error, PathSegment};
+
+/// Byte ranges of the text between the matches, and before and after them
+pub fn uncaptured_ranges<'r, 't: 'r>(
+    text: &str,
+    matches: NodeCaptureMatches<'r, 't>,
+) -> impl Iterator<Item = Range<usize>> + use<'r, 't> {
     let before = iter::once((0, 0));
-    let between = matches.map(|re_match| (re_match.start(), re_match.end()));
+    let between = matches.map(|captures| (captures.start(), captures.end()));
     let after = iter::once((text.len(), text.len()));
     let all = before.chain(between).chain(after);
 
     all.tuple_windows()
         .map(|((_, end), (start, _))| end..start)
         .filter(|range| !range.is_empty())
-        .map(|range| &text[range])
+}
+
+/// Text that no match of a regex tree's root covers, with where it is, as
+/// returned by [`get_gaps`](crate::get_gaps)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Gap<'t> {
+    text: &'t str,
+    start: usize,
+    line: usize,
+    column: usize,
+}
+
+impl<'t> Gap<'t> {
+    pub fn text(&self) -> &'t str {
+        self.text
+    }
+
+    /// Byte offset of the start of the gap
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Byte offset of the end of the gap
+    pub fn end(&self) -> usize {
+        self.start + self.text.len()
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end()
+    }
+
+    /// Line the gap starts on, counting from 1
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Column the gap starts at, in `char`s from the start of its line,
+    /// counting from 1
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+/// Gaps of the text at each of the ranges, which are in order
+pub fn gaps(text: &str, ranges: impl Iterator<Item = Range<usize>>) -> Vec<Gap<'_>> {
+    let mut line = 1;
+    let mut line_start = 0;
+    let mut counted = 0;
+    ranges
+        .map(|range| {
+            for (index, _) in text[counted..range.start].match_indices('\n') {
+                line += 1;
+                line_start = counted + index + 1;
+            }
+            counted = range.start;
+            Gap {
+                text: &text[range.clone()],
+                start: range.start,
+                line,
+                column: text[line_start..range.start].chars().count() + 1,
+            }
+        })
+        .collect()
+}
+
+pub(crate) const UNCAPTURED_NAME: &str = "  __UNCAPTURED";
+
+/// The text of a match that no capture group took, for a struct field with no
+/// capture group of its own. This makes [`get_uncaptured`](crate::get_uncaptured)
+/// available within a record: the field gets whatever the record's match has
+/// outside of its groups, such as separators, or trailing text a pattern
+/// matches loosely.
+///
+/// An `Uncaptured<String>` gets the pieces joined together, and an
+/// `Uncaptured<Vec<&str>>` gets each piece on its own. A single piece may be
+/// borrowed as an `Uncaptured<&str>`.
+///
+/// A field that has no capture group and is neither `Uncaptured` nor an
+/// `Option` is missing, as usual, so `#[serde(default)]` does not apply to
+/// such fields.
+///
+/// ## Example
+/// ```
+/// # use recursive_regex::{from_regex_tree_and_str, RegexTree, Uncaptured};
+/// # use serde::Deserialize;
+/// #[derive(Deserialize)]
+/// struct Entry<'a> {
+///     key: &'a str,
+///     #[serde(borrow)]
+///     rest: Uncaptured<Vec<&'a str>>,
+/// }
+///
+/// let regex_tree = RegexTree::lines(r"(?P<key>\w+)\s*=.*");
+/// let entries: Vec<Entry> =
+///     from_regex_tree_and_str(&regex_tree.build(), "a = 1 # note\nb=2").unwrap();
+/// assert_eq!(entries[0].key, "a");
+/// assert_eq!(entries[0].rest.value(), &[" = 1 # note"]);
+/// assert_eq!(entries[1].rest.value(), &["=2"]);
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct Uncaptured<T> {
+    value: T,
+}
+
+impl<T> Uncaptured<T> {
+    pub fn new(value: T) -> Self {
+        Self { value }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Uncaptured<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(UNCAPTURED_NAME, UncapturedVisitor(PhantomData))
+    }
+}
+
+struct UncapturedVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> de::Visitor<'de> for UncapturedVisitor<T> {
+    type Value = Uncaptured<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "text outside of capture groups")
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(Uncaptured::new)
+    }
+}
+
+/// Text left over in a match, which can only be borrowed if it is in one piece
+enum Text<'t> {
+    Piece(JustStrDeserializer<'static, 't>),
+    Joined(String),
+}
+
+/// Deserializes the pieces of a match outside its capture groups, as one string or as a
+/// sequence of them
+pub(crate) struct UncapturedDeserializer<'o, 't> {
+    options: &'o DeserializeOptions,
+    pieces: vec::IntoIter<Match<'t>>,
+    /// Byte offset of the start of the string the pieces are over within the originally parsed
+    /// string
+    start: usize,
+    /// Byte offset of the end of the whole match within the string the pieces are over, where
+    /// an empty piece is when nothing is left over
+    end: usize,
+    /// Index of the next piece, as an element of a sequence
+    index: usize,
+}
+
+impl<'o, 't> UncapturedDeserializer<'o, 't> {
+    pub fn new(
+        options: &'o DeserializeOptions,
+        pieces: Vec<Match<'t>>,
+        start: usize,
+        end: usize,
+    ) -> Self {
+        Self {
+            options,
+            pieces: pieces.into_iter(),
+            start,
+            end,
+            index: 0,
+        }
+    }
+
+    /// The only piece, or an empty one, or else all of the pieces joined
+    fn text(mut self) -> Text<'t> {
+        match self.pieces.len() {
+            0 => Text::Piece(JustStrDeserializer::with_options(
+                "",
+                self.start + self.end,
+                self.options,
+            )),
+            1 => {
+                let piece = self.pieces.next().expect("there is one piece");
+                Text::Piece(JustStrDeserializer::from_match(
+                    piece,
+                    self.start + piece.start(),
+                    self.options,
+                ))
+            }
+            _ => Text::Joined(self.pieces.map(|piece| piece.as_str()).collect()),
+        }
+    }
+}
+
+impl<'de, 'o> Deserializer<'de> for UncapturedDeserializer<'o, 'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.text() {
+            Text::Piece(text) => text.deserialize_any(visitor),
+            Text::Joined(joined) => visitor.visit_string(joined),
+        }
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.text() {
+            Text::Piece(text) => text.deserialize_str(visitor),
+            Text::Joined(joined) => visitor.visit_string(joined),
+        }
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_seq(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char
+        bytes byte_buf unit unit_struct tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'de, 'o> SeqAccess<'de> for UncapturedDeserializer<'o, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        let Some(piece) = self.pieces.next() else {
+            return Ok(None);
+        };
+        let index = self.index;
+        self.index += 1;
+        seed.deserialize(JustStrDeserializer::from_match(
+            piece,
+            self.start + piece.start(),
+            self.options,
+        ))
+        .map(Some)
+        .map_err(|error| error.within(PathSegment::Index(index)))
+    }
 }
@@ -0,0 +1,124 @@
+use std::borrow::Cow;
+use std::str::Chars;
+
+use crate::regex_tree::Escapes;
+
+impl Escapes {
+    /// Resolve the escapes in text, borrowing it if it has none, or give why they are invalid
+    pub(crate) fn unescape(self, text: &str) -> Result<Cow<'_, str>, String> {
+        let escape = match self {
+            Self::Backslash | Self::Json => '\\',
+            Self::DoubledQuotes => '"',
+        };
+        if !text.contains(escape) {
+            return Ok(Cow::Borrowed(text));
+        }
+        match self {
+            Self::Backslash => unescape_backslash(text),
+            Self::Json => unescape_json(text),
+            Self::DoubledQuotes => Ok(Cow::Owned(text.replace("\"\"", "\""))),
+        }
+    }
+}
+
+fn unescape_backslash(text: &str) -> Result<Cow<'_, str>, String> {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        let escaped = match chars.next() {
+            Some('\\') => '\\',
+            Some('"') => '"',
+            Some('\'') => '\'',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('0') => '\0',
+            Some('u') => braced_unicode(&mut chars).ok_or("invalid unicode escape")?,
+            Some(other) => return Err(format!("invalid escape `\\{other}`")),
+            None => return Err("escape at the end of the text".to_owned()),
+        };
+        unescaped.push(escaped);
+    }
+    Ok(Cow::Owned(unescaped))
+}
+
+fn unescape_json(text: &str) -> Result<Cow<'_, str>, String> {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        let escaped = match chars.next() {
+            Some('\\') => '\\',
+            Some('"') => '"',
+            Some('/') => '/',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => json_unicode(&mut chars).ok_or("invalid unicode escape")?,
+            Some(other) => return Err(format!("invalid escape `\\{other}`")),
+            None => return Err("escape at the end of the text".to_owned()),
+        };
+        unescaped.push(escaped);
+    }
+    Ok(Cow::Owned(unescaped))
+}
+
+/// The hex digits at the start of `chars`, up to `max` of them, which are consumed
+fn hex(chars: &mut Chars, max: usize) -> Option<u32> {
+    let rest = chars.as_str();
+    let len = rest
+        .bytes()
+        .take(max)
+        .take_while(u8::is_ascii_hexdigit)
+        .count();
+    if len == 0 {
+        return None;
+    }
+    *chars = rest[len..].chars();
+    u32::from_str_radix(&rest[..len], 16).ok()
+}
+
+/// The char of a `\u{...}` escape, after the `u`
+fn braced_unicode(chars: &mut Chars) -> Option<char> {
+    if chars.next()? != '{' {
+        return None;
+    }
+    let code = hex(chars, 6)?;
+    if chars.next()? != '}' {
+        return None;
+    }
+    char::from_u32(code)
+}
+
+/// The char of a `\uXXXX` escape, after the `u`, taking the low half of a surrogate pair from
+/// the escape that follows
+fn json_unicode(chars: &mut Chars) -> Option<char> {
+    let code = four_hex(chars)?;
+    if !(0xd800..0xdc00).contains(&code) {
+        return char::from_u32(code);
+    }
+    if chars.next()? != '\\' || chars.next()? != 'u' {
+        return None;
+    }
+    let low = four_hex(chars)?;
+    if !(0xdc00..0xe000).contains(&low) {
+        return None;
+    }
+    char::from_u32(0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00))
+}
+
+/// Exactly four hex digits
+fn four_hex(chars: &mut Chars) -> Option<u32> {
+    let before = chars.as_str().len();
+    let code = hex(chars, 4)?;
+    (before - chars.as_str().len() == 4).then_some(code)
+}
@@ -0,0 +1,670 @@
+use std::cell::RefCell;
+use std::fmt;
+
+use serde::de::value::Error;
+use serde::de::{self, Deserialize, DeserializeSeed, Error as ErrorTrait};
+
+use crate::datetime::{DURATION_FIELDS, DURATION_NAME};
+use crate::matched::{is_matched, MatchedDeserializer};
+use crate::regex_tree::NestedKey;
+use crate::spanned::{spanned_keys, KeyDeserializer, SpannedDeserializer};
+use crate::uncaptured::UNCAPTURED_NAME;
+use crate::RegexTree;
+
+/// Check that a regex tree fits the type it will be deserialized into, without
+/// parsing any text.
+///
+/// This runs `T`'s `Deserialize` implementation against a probing
+/// deserializer which feeds it placeholder values while comparing the fields
+/// it asks for against the capture groups of the tree. Only the shape of `T`
+/// is checked; whether real text parses into the requested types is not.
+///
+/// ## Example
+/// ```
+/// # use recursive_regex::{validate, RegexTree, ValidationIssue};
+/// # use serde::Deserialize;
+/// #[derive(Deserialize)]
+/// struct Line {
+///     nums: Vec<i32>,
+///     yn: bool,
+/// }
+///
+/// let regex_tree = RegexTree::root(r"(?P<nums>[-\d\s]*) (?P<y_n>true|false)")
+///     .with_child("nums", RegexTree::leaf(r"-?\d+"))
+///     .build();
+///
+/// let report = validate::<Vec<Line>>(&regex_tree);
+/// assert_eq!(
+///     report.issues(),
+///     [
+///         ValidationIssue::MissingCapture { path: vec![], field: "yn".to_owned() },
+///         ValidationIssue::UnusedCapture { path: vec![], group: "y_n".to_owned() },
+///     ]
+/// );
+/// ```
+pub fn validate<'de, T: Deserialize<'de>>(regex_tree: &RegexTree) -> ValidationReport {
+    let issues = RefCell::new(Vec::new());
+    check_children(regex_tree, &mut Vec::new(), &mut issues.borrow_mut());
+
+    let probe = Probe {
+        level: Level::Node(regex_tree),
+        path: Vec::new(),
+        issues: &issues,
+    };
+    // Errors only mean that `T` rejected a placeholder value; anything worth reporting has already
+    // been recorded as an issue
+    let _ = T::deserialize(probe);
+
+    ValidationReport {
+        issues: issues.into_inner(),
+    }
+}
+
+/// Result of [`validate`]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ValidationReport {
+    issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Whether no issues were found
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    pub fn issues(&self) -> &[ValidationIssue] {
+        &self.issues
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for issue in &self.issues {
+            writeln!(f, "{issue}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A mismatch between a regex tree and a type. Each issue carries the path of
+/// struct fields leading to the node where it was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationIssue {
+    /// A struct field has no capture group of the same name, so it will never
+    /// be filled from text
+    MissingCapture { path: Vec<String>, field: String },
+    /// A named capture group is not used by any field of the struct it is
+    /// deserialized into
+    UnusedCapture { path: Vec<String>, group: String },
+    /// A child is named after a capture group that none of its parent's
+    /// patterns have, so it will never be used. Building a tree already fails
+    /// with [`BuildError::UnknownChild`](crate::regex_tree::BuildError::UnknownChild)
+    /// for these.
+    UnknownChild { path: Vec<String>, child: String },
+    /// A struct, map, or sequence is expected from a capture group that has no
+    /// child to break it down further
+    NotNested { path: Vec<String> },
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingCapture { path, field } => write!(
+                f,
+                "{}: field `{field}` has no matching capture group",
+                DisplayPath(path)
+            ),
+            Self::UnusedCapture { path, group } => write!(
+                f,
+                "{}: capture group `{group}` is not used by any field",
+                DisplayPath(path)
+            ),
+            Self::UnknownChild { path, child } => write!(
+                f,
+                "{}: child `{child}` does not match any capture group",
+                DisplayPath(path)
+            ),
+            Self::NotNested { path } => write!(
+                f,
+                "{}: nested data is expected, but the capture group has no child",
+                DisplayPath(path)
+            ),
+        }
+    }
+}
+
+struct DisplayPath<'a>(&'a [String]);
+
+impl fmt::Display for DisplayPath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.is_empty() {
+            write!(f, "<root>")
+        } else {
+            write!(f, "{}", self.0.join("."))
+        }
+    }
+}
+
+fn check_children(
+    regex_tree: &RegexTree,
+    path: &mut Vec<String>,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    let names = regex_tree.group_keys();
+    let mut children: Vec<_> = regex_tree.children().collect();
+    children.sort_by_key(|(name, _)| *name);
+    for (name, child) in children {
+        if !regex_tree.is_child_name(name) {
+            issues.push(ValidationIssue::UnknownChild {
+                path: path.clone(),
+                child: name.to_owned(),
+            });
+        }
+        path.push(name.to_owned());
+        check_children(child, path, issues);
+        path.pop();
+    }
+    for (index, (name, child)) in regex_tree.conditional_children().enumerate() {
+        if !names.contains(&name) {
+            issues.push(ValidationIssue::UnknownChild {
+                path: path.clone(),
+                child: name.to_owned(),
+            });
+        }
+        path.push(format!("{name}?{index}"));
+        check_children(child, path, issues);
+        path.pop();
+    }
+    if let Some(child) = regex_tree.default_child() {
+        path.push("*".to_owned());
+        check_children(child, path, issues);
+        path.pop();
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Level<'r> {
+    /// Text that the node's regex will be run on, like `StrDeserializer`
+    Node(&'r RegexTree),
+    /// A single match of the node's regex, like `SingleCaptureDeserializer`
+    Match(&'r RegexTree),
+    /// The keys of a match nested under a key path, like `NestedDeserializer`
+    Nested(&'r RegexTree, &'r str),
+    /// Text with no regex left to run, like `JustStrDeserializer`
+    Leaf,
+}
+
+struct Probe<'r, 'i> {
+    level: Level<'r>,
+    path: Vec<String>,
+    issues: &'i RefCell<Vec<ValidationIssue>>,
+}
+
+impl<'r, 'i> Probe<'r, 'i> {
+    fn with_level(&self, level: Level<'r>) -> Self {
+        Self {
+            level,
+            path: self.path.clone(),
+            issues: self.issues,
+        }
+    }
+
+    /// Probe for the value deserialized under `key` from a match of `regex_tree`
+    fn field(&self, regex_tree: &'r RegexTree, key: &str) -> Self {
+        self.group(regex_tree, regex_tree.field_group(key).unwrap_or(key))
+    }
+
+    /// The keys of the map at this level, with the path of each and whether keys are nested
+    /// under it, or `None` if the level is not a map
+    fn keys(&self) -> Option<(&'r RegexTree, Vec<NestedKey<'r>>)> {
+        match self.level {
+            Level::Node(regex_tree) | Level::Match(regex_tree) => {
+                Some((regex_tree, regex_tree.nested_keys(None)))
+            }
+            Level::Nested(regex_tree, prefix) => {
+                Some((regex_tree, regex_tree.nested_keys(Some(prefix))))
+            }
+            Level::Leaf => None,
+        }
+    }
+
+    /// Probe for the value deserialized under `key` in a map with the given keys
+    fn key(&self, regex_tree: &'r RegexTree, keys: &[(&'r str, &'r str, bool)], key: &str) -> Self {
+        match keys.iter().find(|(part, _, _)| *part == key) {
+            Some((_, path, true)) => {
+                let mut probe = self.with_level(Level::Nested(regex_tree, path));
+                probe.path.push(key.to_owned());
+                probe
+            }
+            Some((_, path, false)) => self.field(regex_tree, path),
+            None => self.field(regex_tree, key),
+        }
+    }
+
+    /// Probe for the value of the named capture group of `regex_tree`
+    fn group(&self, regex_tree: &'r RegexTree, name: &str) -> Self {
+        let index = regex_tree
+            .capture_names()
+            .position(|group| group == Some(name));
+        self.group_at(regex_tree, index.unwrap_or(usize::MAX), Some(name))
+    }
+
+    /// Probe for the value of the capture group of `regex_tree` with the given
+    /// index and name
+    fn group_at(&self, regex_tree: &'r RegexTree, index: usize, name: Option<&str>) -> Self {
+        let mut path = self.path.clone();
+        path.push(name.map_or_else(|| index.to_string(), str::to_owned));
+        let level = match regex_tree.group_child(index, name, None) {
+            Some(child) => Level::Node(child),
+            None => Level::Leaf,
+        };
+        Self {
+            level,
+            path,
+            issues: self.issues,
+        }
+    }
+
+    /// The sections of a headed node with the given names, each probed with the child that
+    /// parses it, if the level is a headed node
+    fn sections<'k>(
+        &self,
+        names: impl IntoIterator<Item = &'k str>,
+    ) -> Option<Vec<(String, Self)>> {
+        let Level::Node(regex_tree) = self.level else {
+            return None;
+        };
+        if !regex_tree.is_headed() {
+            return None;
+        }
+        let sections = names
+            .into_iter()
+            .map(|name| {
+                let mut probe = self.with_level(match regex_tree.section_child(name) {
+                    Some(child) => Level::Node(child),
+                    None => Level::Leaf,
+                });
+                probe.path.push(name.to_owned());
+                (name.to_owned(), probe)
+            })
+            .collect();
+        Some(sections)
+    }
+
+    /// Probe for the child for group 0, if the level is a node with such a child. Like
+    /// `StrDeserializer`, a single match of the node is handed to the child whole.
+    fn whole_match_child(&self) -> Option<Self> {
+        match self.level {
+            Level::Node(regex_tree) => regex_tree
+                .child_index(0)
+                .map(|child| self.with_level(Level::Node(child))),
+            _ => None,
+        }
+    }
+
+    fn push(&self, issue: ValidationIssue) {
+        self.issues.borrow_mut().push(issue);
+    }
+
+    fn not_nested(self) -> Error {
+        let path = self.path.clone();
+        self.push(ValidationIssue::NotNested { path });
+        Error::custom("nested data expected from a leaf")
+    }
+}
+
+macro_rules! probe_primitive {
+    ($method:ident, $visit:ident, $placeholder:expr) => {
+        fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: de::Visitor<'de>,
+        {
+            visitor.$visit($placeholder)
+        }
+    };
+}
+
+impl<'de, 'r, 'i> de::Deserializer<'de> for Probe<'r, 'i> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    probe_primitive!(deserialize_bool, visit_bool, false);
+    probe_primitive!(deserialize_i8, visit_i8, 0);
+    probe_primitive!(deserialize_i16, visit_i16, 0);
+    probe_primitive!(deserialize_i32, visit_i32, 0);
+    probe_primitive!(deserialize_i64, visit_i64, 0);
+    probe_primitive!(deserialize_i128, visit_i128, 0);
+    probe_primitive!(deserialize_u8, visit_u8, 0);
+    probe_primitive!(deserialize_u16, visit_u16, 0);
+    probe_primitive!(deserialize_u32, visit_u32, 0);
+    probe_primitive!(deserialize_u64, visit_u64, 0);
+    probe_primitive!(deserialize_u128, visit_u128, 0);
+    probe_primitive!(deserialize_f32, visit_f32, 0.0);
+    probe_primitive!(deserialize_f64, visit_f64, 0.0);
+    probe_primitive!(deserialize_char, visit_char, ' ');
+    probe_primitive!(deserialize_str, visit_borrowed_str, "");
+    probe_primitive!(deserialize_string, visit_borrowed_str, "");
+    probe_primitive!(deserialize_identifier, visit_borrowed_str, "");
+    probe_primitive!(deserialize_bytes, visit_borrowed_bytes, b"");
+    probe_primitive!(deserialize_byte_buf, visit_borrowed_bytes, b"");
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.level {
+            Level::Node(regex_tree) => match self.whole_match_child() {
+                Some(child) => visitor.visit_some(child),
+                None => visitor.visit_some(self.with_level(Level::Match(regex_tree))),
+            },
+            Level::Match(_) | Level::Nested(..) | Level::Leaf => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        if name == UNCAPTURED_NAME {
+            // The field is meant to have no capture group
+            if let Some((field, path)) = self.path.split_last() {
+                self.issues.borrow_mut().retain(|issue| {
+                    !matches!(issue, ValidationIssue::MissingCapture { path: issue_path, field: issue_field }
+                        if issue_path == path && issue_field == field)
+                });
+            }
+            return visitor.visit_newtype_struct(UncapturedProbe);
+        }
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.level {
+            Level::Node(regex_tree) => {
+                let element = match regex_tree.child_index(0) {
+                    Some(child) => self.with_level(Level::Node(child)),
+                    None => self.with_level(Level::Match(regex_tree)),
+                };
+                visitor.visit_seq(ProbeSeqAccess {
+                    elements: vec![element].into_iter(),
+                })
+            }
+            Level::Match(regex_tree) => {
+                let elements: Vec<_> = regex_tree
+                    .capture_names()
+                    .enumerate()
+                    .map(|(index, name)| self.group_at(regex_tree, index, name))
+                    .collect();
+                visitor.visit_seq(ProbeSeqAccess {
+                    elements: elements.into_iter(),
+                })
+            }
+            Level::Nested(..) => Err(Error::custom("nested keys are deserialized as a map")),
+            Level::Leaf => Err(self.not_nested()),
+        }
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.level {
+            // A tuple with no room for the whole match starts with the first group
+            Level::Match(regex_tree) if len + 1 == regex_tree.captures_len() => {
+                let elements: Vec<_> = regex_tree
+                    .capture_names()
+                    .enumerate()
+                    .skip(1)
+                    .map(|(index, name)| self.group_at(regex_tree, index, name))
+                    .collect();
+                visitor.visit_seq(ProbeSeqAccess {
+                    elements: elements.into_iter(),
+                })
+            }
+            _ => self.deserialize_seq(visitor),
+        }
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        // Sections are named by the text, so only those with children of their own are known
+        let names = match self.level {
+            Level::Node(regex_tree) => regex_tree.children().map(|(name, _)| name).collect(),
+            _ => Vec::new(),
+        };
+        if let Some(entries) = self.sections(names) {
+            return visitor.visit_map(ProbeMapAccess::new(entries));
+        }
+        if let Some(child) = self.whole_match_child() {
+            return child.deserialize_map(visitor);
+        }
+        match self.keys() {
+            Some((regex_tree, keys)) => {
+                let entries = keys
+                    .iter()
+                    .map(|(key, _, _)| (key.to_string(), self.key(regex_tree, &keys, key)))
+                    .collect();
+                visitor.visit_map(ProbeMapAccess::new(entries))
+            }
+            None => Err(self.not_nested()),
+        }
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        if let Some(keys) = spanned_keys(name, fields) {
+            return visitor.visit_map(SpannedDeserializer::new(keys, 0, 0, self));
+        }
+        if is_matched(name, fields) {
+            return visitor.visit_map(MatchedDeserializer::new("", self));
+        }
+        if name == DURATION_NAME && fields == DURATION_FIELDS {
+            // Durations are parsed from text, so any level will do
+            let entries = [("secs", 0u64), ("nanos", 0)];
+            return visitor.visit_map(de::value::MapDeserializer::new(entries.into_iter()));
+        }
+
+        if let Some(entries) = self.sections(fields.iter().copied()) {
+            return visitor.visit_map(ProbeMapAccess::new(entries));
+        }
+        if let Some(child) = self.whole_match_child() {
+            return child.deserialize_struct(name, fields, visitor);
+        }
+        let Some((regex_tree, keys)) = self.keys() else {
+            return Err(self.not_nested());
+        };
+
+        let names: Vec<&str> = keys.iter().map(|(key, _, _)| *key).collect();
+        for field in fields {
+            if !names.contains(field) {
+                self.push(ValidationIssue::MissingCapture {
+                    path: self.path.clone(),
+                    field: (*field).to_owned(),
+                });
+            }
+        }
+        for name in &names {
+            if !fields.contains(name) {
+                self.push(ValidationIssue::UnusedCapture {
+                    path: self.path.clone(),
+                    group: (*name).to_owned(),
+                });
+            }
+        }
+
+        // Offer every field, even those without a capture group, so the rest of the struct is
+        // probed instead of stopping at the first missing field
+        let entries = fields
+            .iter()
+            .map(|field| ((*field).to_owned(), self.key(regex_tree, &keys, field)))
+            .collect();
+        visitor.visit_map(ProbeMapAccess::new(entries))
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(Error::custom("enums cannot be validated"))
+    }
+}
+
+/// Placeholder for the text outside of capture groups, which is a string or a sequence of them
+struct UncapturedProbe;
+
+impl<'de> de::Deserializer<'de> for UncapturedProbe {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_borrowed_str("")
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_seq(de::value::SeqDeserializer::<_, Error>::new(
+            std::iter::empty::<&str>(),
+        ))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct ProbeSeqAccess<'r, 'i> {
+    elements: std::vec::IntoIter<Probe<'r, 'i>>,
+}
+
+impl<'de, 'r, 'i> de::SeqAccess<'de> for ProbeSeqAccess<'r, 'i> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.elements
+            .next()
+            .map(|element| seed.deserialize(element))
+            .transpose()
+    }
+}
+
+struct ProbeMapAccess<'r, 'i> {
+    entries: std::vec::IntoIter<(String, Probe<'r, 'i>)>,
+    value: Option<Probe<'r, 'i>>,
+}
+
+impl<'r, 'i> ProbeMapAccess<'r, 'i> {
+    fn new(entries: Vec<(String, Probe<'r, 'i>)>) -> Self {
+        Self {
+            entries: entries.into_iter(),
+            value: None,
+        }
+    }
+}
+
+impl<'de, 'r, 'i> de::MapAccess<'de> for ProbeMapAccess<'r, 'i> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        match self.entries.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(KeyDeserializer::new(&key, 0, 0)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| Error::custom("value requested before its key"))?;
+        seed.deserialize(value)
+    }
+}
@@ -0,0 +1,260 @@
+use std::fmt;
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+use crate::options::DEFAULT_OPTIONS;
+use crate::{from_regex_tree_and_str_with_options, DeserializeOptions, Error, RegexTree, Spanned};
+
+/// Whatever a regex tree produces from some text, without a type to guide it,
+/// as returned by [`to_value`].
+///
+/// Nodes become sequences of their matches, matches with named capture groups
+/// become maps, and everything else becomes a string along with its span.
+/// With [`DeserializeOptions::infer_types`], text that reads as a number or
+/// bool becomes one instead of a string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    String(String),
+    Bool(bool),
+    Number(Number),
+    Seq(Vec<Value>),
+    /// Capture groups by key, in pattern order
+    Map(Vec<(String, Value)>),
+    /// A value along with the span of the text it came from. Strings, bools
+    /// and numbers are always wrapped in this when deserialized with a regex
+    /// tree.
+    Spanned(Spanned<Box<Value>>),
+}
+
+/// A number inferred from text by [`DeserializeOptions::infer_types`]. Only
+/// integers that need a sign are `Signed`, and floats are always finite.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Number {
+    Unsigned(u64),
+    Signed(i64),
+    Float(f64),
+}
+
+/// Floats are never NaN when inferred from text
+impl Eq for Number {}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Unsigned(number) => number.fmt(f),
+            Self::Signed(number) => number.fmt(f),
+            Self::Float(number) => number.fmt(f),
+        }
+    }
+}
+
+impl Value {
+    /// The string, if this is a string or a spanned string
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(string) => Some(string),
+            Self::Spanned(spanned) => spanned.value().as_str(),
+            _ => None,
+        }
+    }
+
+    /// The bool, if this is a bool or a spanned bool
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(bool) => Some(*bool),
+            Self::Spanned(spanned) => spanned.value().as_bool(),
+            _ => None,
+        }
+    }
+
+    /// The number, if this is a number or a spanned number
+    pub fn as_number(&self) -> Option<Number> {
+        match self {
+            Self::Number(number) => Some(*number),
+            Self::Spanned(spanned) => spanned.value().as_number(),
+            _ => None,
+        }
+    }
+
+    pub fn as_seq(&self) -> Option<&[Value]> {
+        match self {
+            Self::Seq(seq) => Some(seq),
+            Self::Spanned(spanned) => spanned.value().as_seq(),
+            _ => None,
+        }
+    }
+
+    /// The value of the given key, if this is a map that has it
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Self::Map(entries) => entries
+                .iter()
+                .find_map(|(entry_key, value)| (entry_key == key).then_some(value)),
+            Self::Spanned(spanned) => spanned.value().get(key),
+            _ => None,
+        }
+    }
+}
+
+/// Serializes as plain data, dropping spans: strings, bools, numbers,
+/// sequences, and maps with their keys in match order.
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::String(string) => serializer.serialize_str(string),
+            Self::Bool(bool) => serializer.serialize_bool(*bool),
+            Self::Number(Number::Unsigned(number)) => serializer.serialize_u64(*number),
+            Self::Number(Number::Signed(number)) => serializer.serialize_i64(*number),
+            Self::Number(Number::Float(number)) => serializer.serialize_f64(*number),
+            Self::Seq(values) => serializer.collect_seq(values),
+            Self::Map(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+            Self::Spanned(spanned) => spanned.value().serialize(serializer),
+        }
+    }
+}
+
+/// Deserialize text with a regex tree into a [`Value`], to see what the tree
+/// produces before writing types for it.
+///
+/// ## Example
+/// ```
+/// # use recursive_regex::{to_value, RegexTree};
+/// let regex_tree = RegexTree::root(r"(?P<name>\w+): (?P<nums>.*)")
+///     .with_child("nums", RegexTree::leaf(r"\d+"))
+///     .build();
+///
+/// let value = to_value(&regex_tree, "a: 1 2\nb: 3").unwrap();
+/// let records = value.as_seq().unwrap();
+/// assert_eq!(records.len(), 2);
+/// assert_eq!(records[0].get("name").unwrap().as_str(), Some("a"));
+/// assert_eq!(records[0].get("nums").unwrap().as_seq().unwrap().len(), 2);
+/// ```
+pub fn to_value(regex_tree: &RegexTree, text: &str) -> Result<Value, Error> {
+    to_value_with_options(regex_tree, text, &DEFAULT_OPTIONS)
+}
+
+/// Like [`to_value`], but with [`DeserializeOptions`] that change how the
+/// text is deserialized, such as to [infer types](DeserializeOptions::infer_types).
+pub fn to_value_with_options(
+    regex_tree: &RegexTree,
+    text: &str,
+    options: &DeserializeOptions,
+) -> Result<Value, Error> {
+    from_regex_tree_and_str_with_options(regex_tree, text, options)
+}
+
+impl<'de> Deserialize<'de> for Value {
+    /// Only strings, bools and numbers are wrapped in [`Value::Spanned`],
+    /// since the spans of sequences and maps can be found from the values
+    /// within them
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let spanned = Spanned::<Unspanned>::deserialize(deserializer)?;
+        let (begin, end) = (spanned.begin(), spanned.end());
+        Ok(match spanned.into_inner().0 {
+            value @ (Value::String(_) | Value::Bool(_) | Value::Number(_)) => {
+                Value::Spanned(Spanned::new_raw(Box::new(value), begin, end))
+            }
+            value => value,
+        })
+    }
+}
+
+/// A value whose span has not been attached yet
+struct Unspanned(Value);
+
+impl<'de> Deserialize<'de> for Unspanned {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(ValueVisitor).map(Unspanned)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a string, bool, number, sequence, or map")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+        Ok(Value::Bool(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(Value::Number(Number::Unsigned(v)))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(Value::Number(Number::Signed(v)))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        Ok(Value::Number(Number::Float(v)))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(Value::String(v.to_owned()))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(Value::Seq(values))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut entries = Vec::new();
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(Value::Map(entries))
+    }
+}
+
+/// Drops spans, as serializing does
+#[cfg(feature = "json")]
+impl From<Value> for serde_json::Value {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::String(string) => Self::String(string),
+            Value::Bool(bool) => Self::Bool(bool),
+            Value::Number(Number::Unsigned(number)) => Self::from(number),
+            Value::Number(Number::Signed(number)) => Self::from(number),
+            Value::Number(Number::Float(number)) => Self::from(number),
+            Value::Seq(values) => values.into_iter().map(Self::from).collect(),
+            Value::Map(entries) => Self::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, Self::from(value)))
+                    .collect(),
+            ),
+            Value::Spanned(spanned) => Self::from(*spanned.into_inner()),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::fmt;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+
+/// Something that deserialization passed over without failing, as gathered
+/// with [`DeserializeOptions::collect_warnings`](crate::DeserializeOptions::collect_warnings).
+/// Useful for noticing when a regex tree and the types it fills drift apart.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    /// A named capture group took part in a match, but the struct the match
+    /// was deserialized into has no field for it, so its text was dropped
+    IgnoredGroup { group: String, span: Range<usize> },
+}
+
+impl Warning {
+    /// Byte offsets of the text the warning is about
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            Self::IgnoredGroup { span, .. } => span.clone(),
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::IgnoredGroup { group, span } => write!(
+                f,
+                "capture group `{group}` at {}..{} is not used by any field",
+                span.start, span.end
+            ),
+        }
+    }
+}
+
+/// Shared place [`Warning`]s are gathered into. Clones of the options that hold it gather into
+/// the same place.
+#[derive(Clone, Default)]
+pub(crate) struct WarningCollector(Arc<Mutex<Vec<Warning>>>);
+
+impl WarningCollector {
+    pub fn push(&self, warning: Warning) {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(warning);
+    }
+
+    pub fn snapshot(&self) -> Vec<Warning> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+}
+
+impl fmt::Debug for WarningCollector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("WarningCollector").finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for WarningCollector {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for WarningCollector {}
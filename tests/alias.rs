@@ -0,0 +1,100 @@
+use recursive_regex::regex_tree::DuplicateKeys;
+use recursive_regex::{
+    from_regex_tree_and_str, from_regex_tree_and_str_with_options, to_value, validate,
+    DeserializeOptions, RegexTree, ValidationIssue, Value,
+};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Entry {
+    #[serde(rename = "user-name")]
+    user_name: String,
+    #[serde(rename = "item-ids")]
+    item_ids: Vec<u32>,
+}
+
+fn regex_tree() -> RegexTree {
+    RegexTree::root(r"(?P<user>\w+): (?P<ids>[\d ]*)")
+        .with_alias("user", "user-name")
+        .with_alias("ids", "item-ids")
+        .with_child("ids", RegexTree::leaf(r"\d+"))
+        .build()
+}
+
+#[test]
+fn struct_fields() {
+    let entries: Vec<Entry> = from_regex_tree_and_str(&regex_tree(), "ann: 1 2\nbo: 3").unwrap();
+    assert_eq!(
+        entries,
+        vec![
+            Entry {
+                user_name: "ann".to_owned(),
+                item_ids: vec![1, 2]
+            },
+            Entry {
+                user_name: "bo".to_owned(),
+                item_ids: vec![3]
+            },
+        ]
+    );
+}
+
+#[test]
+fn map_keys() {
+    let value = to_value(&regex_tree(), "ann: 1").unwrap();
+    let entry = &value.as_seq().unwrap()[0];
+    assert_eq!(entry.get("user-name").and_then(Value::as_str), Some("ann"));
+    assert!(entry.get("user").is_none());
+}
+
+#[test]
+fn alias_of_shared_key() {
+    #[derive(Debug, Deserialize, PartialEq, Eq)]
+    struct Reading {
+        #[serde(rename = "temp-c")]
+        temp: Vec<u32>,
+    }
+
+    let regex_tree = RegexTree::root(r"(?P<t[0]>\d+)/(?P<t[1]>\d+)")
+        .with_duplicate_keys(DuplicateKeys::Collect)
+        .with_alias("t", "temp-c")
+        .build();
+    let reading: Reading = from_regex_tree_and_str(&regex_tree, "20/21").unwrap();
+    assert_eq!(reading.temp, vec![20, 21]);
+}
+
+#[test]
+fn strict_uses_alias() {
+    let options = DeserializeOptions::default().strict();
+    let entries: Vec<Entry> =
+        from_regex_tree_and_str_with_options(&regex_tree(), "ann: 1", &options).unwrap();
+    assert_eq!(entries[0].user_name, "ann");
+}
+
+#[test]
+fn validates() {
+    let report = validate::<Vec<Entry>>(&regex_tree());
+    assert!(report.is_ok(), "{report}");
+
+    let regex_tree = RegexTree::leaf(r"(?P<user>\w+): (?P<ids>[\d ]*)");
+    let report = validate::<Vec<Entry>>(&regex_tree);
+    assert!(report.issues().iter().any(|issue| matches!(
+        issue,
+        ValidationIssue::MissingCapture { field, .. } if field == "user-name"
+    )));
+}
+
+#[cfg(feature = "deserialize-regex-tree")]
+#[test]
+fn deserialized_tree() {
+    let regex_tree: RegexTree = serde_json::from_str(
+        r#"{
+            "regex": "(?P<user>\\w+): (?P<ids>[\\d ]*)",
+            "aliases": {"user": "user-name", "ids": "item-ids"},
+            "children": {"ids": {"regex": "\\d+"}}
+        }"#,
+    )
+    .unwrap();
+    let entries: Vec<Entry> = from_regex_tree_and_str(&regex_tree, "ann: 1 2").unwrap();
+    assert_eq!(entries[0].item_ids, vec![1, 2]);
+}
@@ -0,0 +1,84 @@
+#![cfg(feature = "async")]
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_util::StreamExt;
+use recursive_regex::{from_regex_tree_and_async_reader, Error, RegexTree};
+use serde::Deserialize;
+use tokio::io::{AsyncRead, ReadBuf};
+
+/// Reader that returns at most `size` bytes per read, to split text
+/// everywhere, including within characters
+struct Trickle<'a> {
+    bytes: &'a [u8],
+    size: usize,
+}
+
+impl AsyncRead for Trickle<'_> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let len = self.size.min(self.bytes.len()).min(buf.remaining());
+        let (read, rest) = self.bytes.split_at(len);
+        buf.put_slice(read);
+        self.bytes = rest;
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Entry {
+    name: String,
+    value: u32,
+}
+
+async fn collect(regex_tree: &RegexTree, bytes: &[u8], size: usize) -> Vec<Result<Entry, Error>> {
+    from_regex_tree_and_async_reader(regex_tree, Trickle { bytes, size })
+        .collect()
+        .await
+}
+
+fn regex_tree() -> RegexTree {
+    RegexTree::lines(r"(?P<name>\w+)=(?P<value>.*)").build()
+}
+
+#[tokio::test]
+async fn records_in_any_reads() {
+    let text = "äb=1\nc=23\nδ=456";
+    for size in 1..=text.len() {
+        let records = collect(&regex_tree(), text.as_bytes(), size).await;
+        let records: Vec<Entry> = records.into_iter().collect::<Result<_, _>>().unwrap();
+        let names: Vec<&str> = records.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, vec!["äb", "c", "δ"], "reads of {size}");
+        assert_eq!(records[2].value, 456);
+    }
+}
+
+#[tokio::test]
+async fn bad_record_does_not_end_stream() {
+    let records = collect(&regex_tree(), b"a=1\nb=x\nc=3", 4).await;
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[1].as_ref().unwrap_err().span(), Some(6..7));
+    assert_eq!(records[2].as_ref().unwrap().value, 3);
+}
+
+#[tokio::test]
+async fn invalid_utf8_ends_stream() {
+    let records = collect(&regex_tree(), b"a=1\nb=2\n\xff=3\nd=4", 3).await;
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[1].as_ref().unwrap().value, 2);
+    let error = records[2].as_ref().unwrap_err();
+    assert_eq!(error.message(), "input is not valid UTF-8");
+    assert_eq!(error.span(), Some(8..9));
+}
+
+#[tokio::test]
+async fn truncated_character_at_end() {
+    let records = collect(&regex_tree(), b"a=1\n\xce", 16).await;
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].as_ref().unwrap().value, 1);
+    assert_eq!(records[1].as_ref().unwrap_err().span(), Some(4..5));
+}
@@ -0,0 +1,97 @@
+use recursive_regex::{
+    from_regex_tree_and_str_with_options, records_from_regex_tree_and_str_with_options,
+    ChunkedDeserializer, DeserializeOptions, RegexTree, Spanned,
+};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct Setting {
+    key: Spanned<String>,
+    value: Spanned<u32>,
+}
+
+fn settings() -> RegexTree {
+    RegexTree::lines(r"(?P<key>\w+)=(?P<value>\S+)").build()
+}
+
+const DOCUMENT: &str = "[section]\nwidth=80\nheight=x\n";
+const BODY_START: usize = 10;
+
+#[test]
+fn spanned_values_point_into_the_document() {
+    let body = &DOCUMENT[BODY_START..DOCUMENT.len() - 10];
+    let options = DeserializeOptions::new().with_base_offset(BODY_START);
+
+    let settings: Vec<Setting> =
+        from_regex_tree_and_str_with_options(&settings(), body, &options).unwrap();
+    assert_eq!(settings[0].key.substring(DOCUMENT), "width");
+    assert_eq!(settings[0].value.substring(DOCUMENT), "80");
+}
+
+#[test]
+fn errors_point_into_the_document() {
+    let body = &DOCUMENT[BODY_START..];
+    let options = DeserializeOptions::new().with_base_offset(BODY_START);
+
+    let error = from_regex_tree_and_str_with_options::<Vec<Setting>>(&settings(), body, &options)
+        .unwrap_err();
+    assert_eq!(&DOCUMENT[error.span().unwrap()], "x");
+    assert_eq!(error.source_name(), None);
+    assert_eq!(
+        error.to_string(),
+        "[1].value: parsing error: invalid digit found in string"
+    );
+}
+
+#[test]
+fn records_point_into_the_document() {
+    let body = &DOCUMENT[BODY_START..];
+    let options = DeserializeOptions::new()
+        .with_base_offset(BODY_START)
+        .with_source_name("config.ini");
+
+    let (values, errors) =
+        records_from_regex_tree_and_str_with_options::<Setting>(&settings(), body, &options)
+            .split_errors();
+    assert_eq!(values[0].value.substring(DOCUMENT), "80");
+    assert_eq!(&DOCUMENT[errors[0].span().unwrap()], "x");
+    assert_eq!(errors[0].source_name(), Some("config.ini"));
+    assert!(errors[0].to_string().starts_with("config.ini: [1].value: "));
+}
+
+#[test]
+fn chunks_point_into_the_document() {
+    let options = DeserializeOptions::new()
+        .with_base_offset(BODY_START)
+        .with_source_name("config.ini");
+    let regex_tree = settings();
+    let mut deserializer = ChunkedDeserializer::<Setting>::with_options(&regex_tree, &options);
+
+    let mut records = deserializer.feed(&DOCUMENT[BODY_START..20]);
+    records.extend(deserializer.feed(&DOCUMENT[20..]));
+    records.extend(deserializer.finish());
+
+    assert_eq!(
+        records[0].as_ref().unwrap().key.substring(DOCUMENT),
+        "width"
+    );
+    let error = records[1].as_ref().unwrap_err();
+    assert_eq!(&DOCUMENT[error.span().unwrap()], "x");
+    assert_eq!(error.source_name(), Some("config.ini"));
+}
+
+#[test]
+fn source_name_without_offset() {
+    let options = DeserializeOptions::new().with_source_name("numbers.txt");
+    let error = from_regex_tree_and_str_with_options::<Vec<u8>>(
+        &RegexTree::leaf(r"\d+"),
+        "1 300",
+        &options,
+    )
+    .unwrap_err();
+    assert_eq!(error.span(), Some(2..5));
+    assert_eq!(
+        error.to_string(),
+        "numbers.txt: [1]: parsing error: number too large to fit in target type"
+    );
+}
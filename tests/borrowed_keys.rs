@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use recursive_regex::{from_regex_tree_and_str, validate, RegexTree};
+use serde::de::{self, Deserializer, Visitor};
+use serde::Deserialize;
+
+/// Map key that fails if it is given an owned `String`
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct NoAllocKey(String);
+
+impl<'de> Deserialize<'de> for NoAllocKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct KeyVisitor;
+
+        impl<'de> Visitor<'de> for KeyVisitor {
+            type Value = NoAllocKey;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a key")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                Ok(NoAllocKey(v.to_owned()))
+            }
+
+            fn visit_string<E: de::Error>(self, _: String) -> Result<Self::Value, E> {
+                Err(E::custom("key was allocated"))
+            }
+        }
+
+        deserializer.deserialize_str(KeyVisitor)
+    }
+}
+
+#[test]
+fn keys_are_not_allocated() {
+    let regex_tree = RegexTree::leaf(r"(?P<name>\w+)=(?P<value>\d+)");
+    let entries: Vec<HashMap<NoAllocKey, &str>> =
+        from_regex_tree_and_str(&regex_tree, "a=1 b=2").unwrap();
+    assert_eq!(entries[1][&NoAllocKey("name".to_owned())], "b");
+    assert!(validate::<HashMap<NoAllocKey, &str>>(&regex_tree).is_ok());
+}
+
+#[test]
+fn enum_keys() {
+    #[derive(Debug, Deserialize, PartialEq, Eq, Hash)]
+    #[serde(rename_all = "lowercase")]
+    enum Side {
+        Left,
+        Right,
+    }
+
+    let regex_tree = RegexTree::leaf(r"(?P<left>\d+)\|(?P<right>\d+)");
+    let sides: HashMap<Side, u32> = from_regex_tree_and_str(&regex_tree, "3|4").unwrap();
+    assert_eq!(sides, HashMap::from([(Side::Left, 3), (Side::Right, 4)]));
+}
@@ -0,0 +1,103 @@
+use recursive_regex::regex_tree::{BuildError, Case};
+use recursive_regex::{from_regex_tree_and_str, RegexTree};
+use serde::Deserialize;
+
+#[test]
+fn unknown_child() {
+    let result = RegexTree::root(r"(?P<nums>.*)")
+        .with_child("numbers", RegexTree::leaf(r"\d+"))
+        .try_build();
+    let err = result.unwrap_err();
+    assert!(matches!(&err, BuildError::UnknownChild { child } if child == "numbers"));
+    assert_eq!(
+        err.to_string(),
+        "child `numbers` does not match any capture group"
+    );
+}
+
+#[test]
+fn children_by_key_and_index_are_known() {
+    let regex_tree = RegexTree::root(r"(?P<tag[0]>\w+),(?P<tag[1]>\w+) (\d+)")
+        .with_child("tag", RegexTree::leaf(r"[a-z]+"))
+        .with_child_index(0, RegexTree::leaf(r".*"))
+        .with_child_index(3, RegexTree::leaf(r"\d"))
+        .build();
+    assert_eq!(regex_tree.children().count(), 3);
+}
+
+#[test]
+#[should_panic(expected = "child `titel` does not match any capture group")]
+fn build_panics_on_unknown_child() {
+    RegexTree::root(r"(?P<title>\w+)")
+        .with_child("titel", RegexTree::leaf(r".*"))
+        .build();
+}
+
+#[test]
+fn alias_onto_another_group() {
+    let result = RegexTree::root(r"(?P<id>\d+) (?P<name>\w+)")
+        .with_alias("name", "id")
+        .try_build();
+    let err = result.unwrap_err();
+    assert!(matches!(
+        &err,
+        BuildError::DuplicateKey { key, groups } if key == "id" && groups == &["id", "name"]
+    ));
+    assert_eq!(
+        err.to_string(),
+        "capture groups `id`, `name` are all deserialized under key `id`"
+    );
+}
+
+#[test]
+fn renaming_onto_another_group() {
+    let result = RegexTree::root(r"(?P<user_id>\d+) (?P<userId>\d+)")
+        .with_rename_all(Case::Camel)
+        .try_build();
+    assert!(matches!(
+        result,
+        Err(BuildError::DuplicateKey { key, .. }) if key == "userId"
+    ));
+}
+
+#[test]
+fn groups_aliased_together_on_purpose() {
+    #[derive(Debug, Deserialize, PartialEq, Eq)]
+    struct Reading {
+        celsius: i32,
+    }
+
+    let regex_tree = RegexTree::root(r"(?P<c>-?\d+)C|temp=(?P<temp>-?\d+)")
+        .with_alias("c", "celsius")
+        .with_alias("temp", "celsius")
+        .build();
+    let readings: Vec<Reading> = from_regex_tree_and_str(&regex_tree, "5C temp=-2").unwrap();
+    assert_eq!(
+        readings,
+        vec![Reading { celsius: 5 }, Reading { celsius: -2 }]
+    );
+}
+
+#[cfg(feature = "serde-spanned")]
+#[test]
+fn reserved_key() {
+    use serde_spanned::__unstable::START_FIELD;
+
+    let result = RegexTree::root(r"(?P<start>\d+)")
+        .with_alias("start", START_FIELD)
+        .try_build();
+    assert!(matches!(result, Err(BuildError::ReservedKey { key }) if key == START_FIELD));
+}
+
+#[cfg(feature = "deserialize-regex-tree")]
+#[test]
+fn deserialized_tree() {
+    let json = r#"{
+        "regex": "(?P<nums>.*)",
+        "children": { "numbers": { "regex": "\\d+" } }
+    }"#;
+    let err = serde_json::from_str::<RegexTree>(json).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("child `numbers` does not match any capture group"));
+}
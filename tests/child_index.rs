@@ -0,0 +1,54 @@
+use recursive_regex::regex_tree::BuildError;
+use recursive_regex::{from_regex_tree_and_str, validate, RegexTree};
+use serde::Deserialize;
+
+#[test]
+fn unnamed_group_recurses_into_index_child() {
+    let regex_tree = RegexTree::root(r"(\w+): (.*)")
+        .with_child_index(2, RegexTree::leaf(r"\d+"))
+        .build();
+
+    // Group 0, the whole match, comes first
+    let parsed: Vec<(&str, &str, Vec<u32>)> =
+        from_regex_tree_and_str(&regex_tree, "a: 1 2\nb: 3").unwrap();
+    assert_eq!(
+        parsed,
+        vec![("a: 1 2", "a", vec![1, 2]), ("b: 3", "b", vec![3])]
+    );
+}
+
+#[test]
+fn named_child_takes_precedence_over_index_child() {
+    let regex_tree = RegexTree::root(r"(?P<nums>.*)")
+        .with_child("nums", RegexTree::leaf(r"\d+"))
+        .with_child_index(1, RegexTree::leaf(r"\w+"))
+        .build();
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Nums {
+        nums: Vec<u32>,
+    }
+
+    let parsed: Nums = from_regex_tree_and_str(&regex_tree, "1 a 2").unwrap();
+    assert_eq!(parsed, Nums { nums: vec![1, 2] });
+}
+
+#[test]
+fn index_child_is_not_unknown() {
+    let regex_tree = RegexTree::root(r"(\w+): (.*)")
+        .with_child_index(2, RegexTree::leaf(r"\d+"))
+        .build();
+    assert_eq!(regex_tree.child_index(2).unwrap().pattern(), r"\d+");
+
+    let report = validate::<Vec<(&str, &str, Vec<u32>)>>(&regex_tree);
+    assert!(report.is_ok(), "{report}");
+
+    // There is no group 3
+    let result = RegexTree::root(r"(\w+): (.*)")
+        .with_child_index(3, RegexTree::leaf(r"\d+"))
+        .try_build();
+    assert!(matches!(
+        result,
+        Err(BuildError::UnknownChild { child }) if child == "3"
+    ));
+}
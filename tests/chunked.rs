@@ -0,0 +1,114 @@
+use recursive_regex::{from_regex_tree_and_str, ChunkedDeserializer, Error, RegexTree, Spanned};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Entry {
+    name: String,
+    nums: Vec<u32>,
+}
+
+const TEXT: &str = "a: 1 2\nbb: 3\nccc: 45 6 7\nd: 8";
+
+/// Feed `text` in chunks of `size` bytes, collecting every record
+fn feed_in_chunks<T: serde::de::DeserializeOwned>(
+    regex_tree: &RegexTree,
+    text: &str,
+    size: usize,
+) -> Vec<Result<T, Error>> {
+    let mut deserializer = ChunkedDeserializer::new(regex_tree);
+    let mut records = Vec::new();
+    for chunk in text.as_bytes().chunks(size) {
+        records.extend(deserializer.feed(std::str::from_utf8(chunk).unwrap()));
+    }
+    records.extend(deserializer.finish());
+    records
+}
+
+fn entries(regex_tree: &RegexTree, size: usize) -> Vec<Entry> {
+    feed_in_chunks(regex_tree, TEXT, size)
+        .into_iter()
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+#[test]
+fn lines_in_any_chunks() {
+    let regex_tree = RegexTree::lines(r"(?P<name>\w+): (?P<nums>.*)")
+        .with_child("nums", RegexTree::leaf(r"\d+"))
+        .build();
+    let whole: Vec<Entry> = from_regex_tree_and_str(&regex_tree, TEXT).unwrap();
+    for size in 1..=TEXT.len() {
+        assert_eq!(entries(&regex_tree, size), whole, "chunks of {size}");
+    }
+}
+
+#[test]
+fn search_in_any_chunks() {
+    let regex_tree = RegexTree::root(r"(?P<name>\w+): (?P<nums>[\d ]*\d)")
+        .with_child("nums", RegexTree::leaf(r"\d+"))
+        .build();
+    let whole: Vec<Entry> = from_regex_tree_and_str(&regex_tree, TEXT).unwrap();
+    for size in 1..=TEXT.len() {
+        assert_eq!(entries(&regex_tree, size), whole, "chunks of {size}");
+    }
+}
+
+#[test]
+fn records_as_they_complete() {
+    let regex_tree = RegexTree::split(";").build();
+    let mut deserializer = ChunkedDeserializer::<String>::new(&regex_tree);
+
+    assert!(deserializer.feed("ab").is_empty());
+    assert!(deserializer.feed("c").is_empty());
+    let records: Vec<String> = deserializer
+        .feed(";d;e")
+        .into_iter()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(records, vec!["abc", "d"]);
+
+    let records: Vec<String> = deserializer
+        .finish()
+        .into_iter()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(records, vec!["e"]);
+}
+
+#[test]
+fn spans_and_errors_within_stream() {
+    let regex_tree = RegexTree::lines(r"(?P<key>\w+)=(?P<value>.*)").build();
+
+    #[derive(Debug, Deserialize)]
+    struct Setting {
+        key: Spanned<String>,
+        value: u32,
+    }
+
+    let records: Vec<Result<Setting, Error>> =
+        feed_in_chunks(&regex_tree, "a=1\nb=x\nc=3\n???\nd=5", 3);
+    assert_eq!(records.len(), 5);
+
+    let c = records[2].as_ref().unwrap();
+    assert_eq!(c.value, 3);
+    assert_eq!(c.key.substring("a=1\nb=x\nc=3\n???\nd=5"), "c");
+
+    let error = records[1].as_ref().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "[1].value: parsing error: invalid digit found in string"
+    );
+    assert_eq!(error.span(), Some(6..7));
+
+    let error = records[3].as_ref().unwrap_err();
+    assert_eq!(error.span(), Some(12..15));
+
+    assert_eq!(records[4].as_ref().unwrap().value, 5);
+}
+
+#[test]
+fn empty_stream() {
+    let regex_tree = RegexTree::leaf(r"\d+");
+    let deserializer = ChunkedDeserializer::<u32>::new(&regex_tree);
+    assert!(deserializer.finish().is_empty());
+}
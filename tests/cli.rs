@@ -0,0 +1,124 @@
+#![cfg(feature = "cli")]
+
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+const TREE_JSON: &str = r#"{
+    "regex": "(?P<name>\\w+): (?P<nums>.*)",
+    "children": {"nums": {"regex": "\\d+"}}
+}"#;
+
+const TEXT: &str = "a: 1 2\nb: \"3\", 4\n";
+
+/// Write a file for a test into a directory of its own
+fn file(test: &str, name: &str, contents: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(test);
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+fn rrx(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rrx"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> &str {
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    std::str::from_utf8(&output.stdout).unwrap()
+}
+
+#[test]
+fn json() {
+    let tree = file("json", "tree.json", TREE_JSON);
+    let input = file("json", "input.txt", TEXT);
+
+    let output = rrx(
+        &["--tree", tree.to_str().unwrap(), input.to_str().unwrap()],
+        "",
+    );
+    let value: serde_json::Value = serde_json::from_str(stdout(&output)).unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!([
+            {"name": "a", "nums": ["1", "2"]},
+            {"name": "b", "nums": ["3", "4"]},
+        ])
+    );
+}
+
+#[test]
+fn ndjson_from_stdin() {
+    let tree = file("ndjson", "tree.json", TREE_JSON);
+
+    let output = rrx(&["-t", tree.to_str().unwrap(), "-f", "ndjson"], TEXT);
+    assert_eq!(
+        stdout(&output),
+        "{\"name\":\"a\",\"nums\":[\"1\",\"2\"]}\n{\"name\":\"b\",\"nums\":[\"3\",\"4\"]}\n"
+    );
+}
+
+#[test]
+fn csv() {
+    let tree = file(
+        "csv",
+        "tree.toml",
+        r#"
+regex = '(?P<key>\w+)=(?P<value>[^;]*)'
+"#,
+    );
+
+    let output = rrx(
+        &["--tree", tree.to_str().unwrap(), "--format", "csv"],
+        "a=1;b=x,y;c=\"q\"",
+    );
+    assert_eq!(
+        stdout(&output),
+        "key,value\na,1\nb,\"x,y\"\nc,\"\"\"q\"\"\"\n"
+    );
+}
+
+#[test]
+fn csv_nested_and_unnamed() {
+    let tree = file("csv_nested", "tree.json", TREE_JSON);
+    let output = rrx(&["-t", tree.to_str().unwrap(), "-f", "csv"], TEXT);
+    assert_eq!(
+        stdout(&output),
+        "name,nums\na,\"[\"\"1\"\",\"\"2\"\"]\"\nb,\"[\"\"3\"\",\"\"4\"\"]\"\n"
+    );
+
+    let tree = file("csv_nested", "leaf.json", r#"{"regex": "\\d+"}"#);
+    let output = rrx(&["-t", tree.to_str().unwrap(), "-f", "csv"], "1 22");
+    assert_eq!(stdout(&output), "value\n1\n22\n");
+}
+
+#[test]
+fn errors() {
+    let output = rrx(&[], "");
+    assert_eq!(output.status.code(), Some(2));
+
+    let tree = file("errors", "tree.json", r#"{"regex": "("}"#);
+    let output = rrx(&["--tree", tree.to_str().unwrap()], "");
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.starts_with("rrx: invalid regex tree in"), "{stderr}");
+}
@@ -0,0 +1,111 @@
+use recursive_regex::{from_regex_tree_and_str, RegexTree};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Post {
+    title: String,
+    tags: Vec<String>,
+}
+
+fn post_tree() -> RegexTree {
+    RegexTree::root(r"(?m)^# (?P<title>.*)$|#(?P<tags>\w+)")
+        .with_collect_all("tags")
+        .build()
+}
+
+#[test]
+fn collects_groups_across_matches() {
+    let post: Post = from_regex_tree_and_str(&post_tree(), "# Hi\n#a #b\nand #c").unwrap();
+    assert_eq!(post.tags, vec!["a", "b", "c"]);
+}
+
+#[test]
+fn missing_collected_field_is_empty() {
+    let post: Post = from_regex_tree_and_str(&post_tree(), "# Hi\nno tags").unwrap();
+    assert_eq!(post.tags, Vec::<String>::new());
+
+    #[derive(Debug, Deserialize)]
+    struct Tags {
+        tags: Vec<String>,
+    }
+    let tags: Tags = from_regex_tree_and_str(&post_tree(), "nothing").unwrap();
+    assert!(tags.tags.is_empty());
+}
+
+#[test]
+fn sequences_are_unaffected() {
+    let regex_tree = RegexTree::root(r"(?P<tags>\w+)")
+        .with_collect_all("tags")
+        .build();
+    let tags: Vec<(String,)> = from_regex_tree_and_str(&regex_tree, "a b").unwrap();
+    assert_eq!(tags, vec![("a".to_owned(),), ("b".to_owned(),)]);
+}
+
+#[test]
+fn errors_span_the_collected_group() {
+    #[derive(Debug, Deserialize)]
+    struct Totals {
+        #[allow(dead_code)]
+        n: Vec<u8>,
+    }
+
+    let regex_tree = RegexTree::lines(r"n=(?P<n>\w+)")
+        .with_collect_all("n")
+        .build();
+    let error = from_regex_tree_and_str::<Totals>(&regex_tree, "n=1\nskip\nn=x").unwrap_err();
+    assert_eq!(error.span(), Some(11..12));
+    assert_eq!(
+        error.to_string(),
+        "n[1]: parsing error: invalid digit found in string"
+    );
+}
+
+#[test]
+fn collected_groups_recurse_into_children() {
+    #[derive(Debug, Deserialize)]
+    struct Doc {
+        points: Vec<(i32, i32)>,
+    }
+
+    let regex_tree = RegexTree::root(r"\((?P<points>[^)]*)\)")
+        .with_collect_all("points")
+        .with_child("points", RegexTree::leaf(r"-?\d+"))
+        .build();
+    let doc: Doc = from_regex_tree_and_str(&regex_tree, "(1, 2) and (-3, 4)").unwrap();
+    assert_eq!(doc.points, vec![(1, 2), (-3, 4)]);
+}
+
+#[test]
+fn maps_collect_by_key() {
+    let regex_tree = RegexTree::root(r"(?P<word>[a-z]+)|(?P<num>\d+)")
+        .with_collect_all("num")
+        .build();
+    let map: HashMap<String, Vec<String>> =
+        from_regex_tree_and_str(&regex_tree, "1 a 2 b").unwrap();
+    assert_eq!(
+        map,
+        HashMap::from([("num".to_owned(), vec!["1".to_owned(), "2".to_owned()])])
+    );
+
+    #[derive(Debug, Deserialize)]
+    struct Mixed {
+        word: Option<String>,
+        num: Vec<u32>,
+    }
+    // The first match has no word
+    let mixed: Mixed = from_regex_tree_and_str(&regex_tree, "1 a 2 b").unwrap();
+    assert_eq!(mixed.word, None);
+    assert_eq!(mixed.num, vec![1, 2]);
+}
+
+#[cfg(feature = "deserialize-regex-tree")]
+#[test]
+fn deserialized_tree() {
+    let regex_tree: RegexTree = serde_json::from_str(
+        r#"{ "regex": "(?m)^# (?P<title>.*)$|#(?P<tags>\\w+)", "collect_all": ["tags"] }"#,
+    )
+    .unwrap();
+    let post: Post = from_regex_tree_and_str(&regex_tree, "# Hi\n#x").unwrap();
+    assert_eq!(post.tags, vec!["x"]);
+}
@@ -0,0 +1,90 @@
+#![cfg(feature = "regex")]
+
+use recursive_regex::RegexTree;
+
+#[test]
+fn main() {
+    let regex_tree = RegexTree::root(r"(?P<name>\w+): (?P<tags>.*)")
+        .with_child("tags", RegexTree::leaf(r"[a-z]+"))
+        .build();
+
+    let stats = regex_tree.compile_stats();
+    let nodes = stats.nodes();
+    assert_eq!(nodes.len(), 2);
+
+    assert!(nodes[0].path().is_empty());
+    assert_eq!(nodes[0].pattern(), r"(?P<name>\w+): (?P<tags>.*)");
+    assert_eq!(nodes[0].captures_len(), 3);
+
+    assert_eq!(nodes[1].path(), ["tags"]);
+    assert_eq!(nodes[1].captures_len(), 1);
+
+    assert!(nodes.iter().all(|node| node.program_size() > 0));
+    assert_eq!(
+        stats.memory_usage(),
+        nodes.iter().map(|node| node.memory_usage()).sum::<usize>()
+    );
+    assert_eq!(regex_tree.memory_usage(), stats.memory_usage());
+    // Unicode-aware `\w` is much larger than `[a-z]`
+    assert_eq!(stats.largest().unwrap().path(), [] as [&str; 0]);
+}
+
+#[test]
+fn measured_with_the_node_settings() {
+    // Too large for the default size limit
+    let regex_tree = RegexTree::root(r"\w{400}")
+        .with_size_limit(100 << 20)
+        .build();
+    let stats = regex_tree.compile_stats();
+    assert_eq!(stats.nodes().len(), 1);
+    assert!(stats.is_exact());
+    assert!(stats.memory_usage() > 10 << 20);
+
+    let sensitive = RegexTree::leaf(r"[a-z]+").compile_stats();
+    let insensitive = RegexTree::root(r"[a-z]+")
+        .with_case_insensitive(true)
+        .build()
+        .compile_stats();
+    assert!(insensitive.nodes()[0].program_size() > sensitive.nodes()[0].program_size());
+}
+
+#[cfg(feature = "deserialize-regex-tree")]
+#[test]
+fn deserialized_tree() {
+    let regex_tree: RegexTree =
+        serde_json::from_str(r#"{"regex": "\\w{400}", "size_limit": 104857600}"#).unwrap();
+    let stats = regex_tree.compile_stats();
+    assert_eq!(stats.nodes().len(), 1);
+    assert!(stats.memory_usage() > 10 << 20);
+}
+
+#[test]
+fn columns_are_not_measured() {
+    let regex_tree = RegexTree::fixed_width([0..8, 9..15]).build();
+    assert!(regex_tree.compile_stats().nodes().is_empty());
+    assert_eq!(regex_tree.memory_usage(), 0);
+}
+
+#[test]
+fn compiled_regexes_are_not_exact() {
+    use recursive_regex::regex::RegexBuilder;
+
+    let regex = RegexBuilder::new(r"[a-z]+")
+        .case_insensitive(true)
+        .build()
+        .unwrap();
+    let stats = RegexTree::leaf(regex).compile_stats();
+    assert!(!stats.nodes()[0].is_exact());
+    assert!(!stats.is_exact());
+    assert!(stats.memory_usage() > 0);
+}
+
+#[cfg(feature = "fancy")]
+#[test]
+fn fancy_patterns_are_marked() {
+    let regex_tree = RegexTree::fancy(r"(?<=\$)\d+").build();
+    let stats = regex_tree.compile_stats();
+    assert_eq!(stats.nodes().len(), 1);
+    assert!(!stats.is_exact());
+    assert_eq!(stats.memory_usage(), 0);
+}
@@ -0,0 +1,123 @@
+use recursive_regex::regex_tree::{BuildError, DuplicateKeys};
+use recursive_regex::{from_regex_tree_and_str, leaves, RegexTree};
+use serde::Deserialize;
+
+/// Payloads are numbers after `n`, and words otherwise
+fn regex_tree() -> RegexTree {
+    RegexTree::lines(r"(?P<kind>\w) (?P<payload>.*)")
+        .with_child_if(
+            "payload",
+            |groups| groups.name("kind") == Some("n"),
+            RegexTree::leaf(r"\d+"),
+        )
+        .with_child("payload", RegexTree::leaf(r"[a-z]+"))
+        .build()
+}
+
+const TEXT: &str = "n 1 2 x3\nw ab 4 cd";
+
+#[test]
+fn condition_picks_child() {
+    #[derive(Debug, Deserialize, PartialEq, Eq)]
+    struct Line<'a> {
+        kind: &'a str,
+        payload: Vec<&'a str>,
+    }
+
+    let lines: Vec<Line> = from_regex_tree_and_str(&regex_tree(), TEXT).unwrap();
+    assert_eq!(
+        lines,
+        vec![
+            Line {
+                kind: "n",
+                payload: vec!["1", "2", "3"],
+            },
+            Line {
+                kind: "w",
+                payload: vec!["ab", "cd"],
+            },
+        ]
+    );
+}
+
+#[test]
+fn tuples() {
+    let lines: Vec<(&str, Vec<&str>)> = from_regex_tree_and_str(&regex_tree(), TEXT).unwrap();
+    assert_eq!(lines[0].1, vec!["1", "2", "3"]);
+    assert_eq!(lines[1].1, vec!["ab", "cd"]);
+}
+
+#[test]
+fn shared_keys() {
+    let regex_tree = RegexTree::root(r"(?P<kind>\w):(?P<part[0]>\S+) (?P<part[1]>\S+)")
+        .with_duplicate_keys(DuplicateKeys::Collect)
+        .with_child_if(
+            "part",
+            |groups| groups.name("kind") == Some("n"),
+            RegexTree::leaf(r"\d+"),
+        )
+        .build();
+
+    #[derive(Debug, Deserialize)]
+    struct Parts<'a> {
+        #[serde(borrow)]
+        part: Vec<Vec<&'a str>>,
+    }
+
+    let parts: Parts = from_regex_tree_and_str(&regex_tree, "n:a1b2 3").unwrap();
+    assert_eq!(parts.part, vec![vec!["1", "2"], vec!["3"]]);
+}
+
+#[test]
+fn values_by_key() {
+    let regex_tree = RegexTree::root(r"(?P<kind[0]>\w+)=(?P<value>.*)")
+        .with_child_if(
+            "value",
+            |groups| groups.name("kind") == Some("n") && groups.name("missing").is_none(),
+            RegexTree::leaf(r"\d+"),
+        )
+        .build();
+
+    let values: Vec<(&str, Vec<u32>)> = from_regex_tree_and_str(&regex_tree, "n=1 2").unwrap();
+    assert_eq!(values, vec![("n", vec![1, 2])]);
+}
+
+#[test]
+fn inspection() {
+    let found: Vec<_> = leaves(&regex_tree(), TEXT)
+        .map(|(path, text)| (path.to_string(), *text.value()))
+        .collect();
+    assert!(found.contains(&("[0].payload[2]".to_owned(), "3")));
+    assert!(found.contains(&("[1].payload[1]".to_owned(), "cd")));
+
+    let regex_tree = regex_tree();
+    let matches: Vec<_> = regex_tree.matches(TEXT).collect();
+    let payload = matches[0].group("payload").unwrap().matches().unwrap();
+    assert_eq!(payload.len(), 3);
+
+    assert_eq!(regex_tree.depth(), 2);
+    assert_eq!(regex_tree.conditional_children().count(), 1);
+}
+
+#[test]
+fn unknown_group() {
+    let result = RegexTree::root(r"(?P<kind>\w)")
+        .with_child_if("missing", |_| true, RegexTree::leaf(r"\d+"))
+        .try_build();
+    assert!(matches!(
+        result,
+        Err(BuildError::UnknownChild { child }) if child == "missing"
+    ));
+}
+
+#[test]
+fn shareable_between_threads() {
+    let regex_tree = regex_tree();
+    let lines: Vec<(&str, Vec<&str>)> = std::thread::scope(|scope| {
+        scope
+            .spawn(|| from_regex_tree_and_str(&regex_tree, TEXT).unwrap())
+            .join()
+            .unwrap()
+    });
+    assert_eq!(lines.len(), 2);
+}
@@ -0,0 +1,74 @@
+use recursive_regex::{
+    from_regex_tree_and_str, DeserializeOptions, JustStrDeserializer, RegexTree, StrDeserializer,
+};
+use serde::Deserialize;
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Entry {
+    name: String,
+    tags: Vec<String>,
+}
+
+#[test]
+fn regex_tree_new_matches_builder() {
+    let from_new = RegexTree::new(
+        r"(?P<name>\w+): (?P<tags>.*)",
+        vec![("tags".to_owned(), RegexTree::leaf(r"\w+"))],
+    );
+    let from_builder = RegexTree::root(r"(?P<name>\w+): (?P<tags>.*)")
+        .with_child("tags", RegexTree::leaf(r"\w+"))
+        .build();
+
+    let text = "ada: x y\nbob: z";
+    let expected: Vec<Entry> = from_regex_tree_and_str(&from_builder, text).unwrap();
+    let entries: Vec<Entry> = from_regex_tree_and_str(&from_new, text).unwrap();
+    assert_eq!(entries, expected);
+}
+
+#[test]
+fn regex_tree_new_without_children() {
+    let regex_tree = RegexTree::new(r"\d+", Vec::<(&str, RegexTree)>::new());
+    let numbers: Vec<u32> = from_regex_tree_and_str(&regex_tree, "1 22").unwrap();
+    assert_eq!(numbers, vec![1, 22]);
+}
+
+#[test]
+fn str_deserializer_constructors_agree() {
+    let regex_tree = RegexTree::leaf(r"\d+");
+    let options = DeserializeOptions::new();
+    let all = [
+        Vec::<u32>::deserialize(StrDeserializer::new(&regex_tree, "4 5")),
+        Vec::<u32>::deserialize(StrDeserializer::from_regex_tree_and_str(&regex_tree, "4 5")),
+        Vec::<u32>::deserialize(StrDeserializer::from_regex_tree_and_str_with_options(
+            &regex_tree,
+            "4 5",
+            &options,
+        )),
+    ];
+    for numbers in all {
+        assert_eq!(numbers.unwrap(), vec![4, 5]);
+    }
+}
+
+#[test]
+fn just_str_deserializer_parses_text() {
+    assert!(bool::deserialize(JustStrDeserializer::from_str("yes")).unwrap());
+    assert_eq!(
+        f64::deserialize(JustStrDeserializer::from_str("1.5")).unwrap(),
+        1.5
+    );
+
+    let error = u8::deserialize(JustStrDeserializer::new("300", 7)).unwrap_err();
+    assert_eq!(error.span(), Some(7..10));
+}
+
+#[test]
+fn just_str_deserializer_with_options() {
+    let options = DeserializeOptions::new().trim();
+    let deserializer = JustStrDeserializer::with_options("  42 ", 3, &options);
+    assert_eq!(u32::deserialize(deserializer).unwrap(), 42);
+
+    let error =
+        u8::deserialize(JustStrDeserializer::with_options(" 300", 0, &options)).unwrap_err();
+    assert_eq!(error.span(), Some(1..4));
+}
@@ -0,0 +1,56 @@
+use recursive_regex::regex_tree::Count;
+use recursive_regex::{from_regex_tree_and_str, RegexTree};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Record {
+    fields: Vec<u32>,
+}
+
+fn regex_tree(count: Count) -> RegexTree {
+    RegexTree::root(r"(?P<fields>.*)")
+        .with_child("fields", RegexTree::root(r"\d+").with_count(count).build())
+        .build()
+}
+
+#[test]
+fn within_count() {
+    let parsed: Record = from_regex_tree_and_str(&regex_tree(Count::between(1, 2)), "1 2").unwrap();
+    assert_eq!(parsed, Record { fields: vec![1, 2] });
+}
+
+#[test]
+fn too_few_spans_node_text() {
+    let text = "fields: a b";
+    let error =
+        from_regex_tree_and_str::<Record>(&regex_tree(Count::at_least(1)), text).unwrap_err();
+    assert_eq!(error.message(), "expected at least 1 matches but found 0");
+    assert_eq!(error.span(), Some(0..text.len()));
+}
+
+#[test]
+fn too_many_spans_extra_match() {
+    let error =
+        from_regex_tree_and_str::<Record>(&regex_tree(Count::at_most(2)), "1 2 33 4").unwrap_err();
+    assert_eq!(error.message(), "expected at most 2 matches but found more");
+    assert_eq!(error.span(), Some(4..6));
+}
+
+#[test]
+fn leaf_errors_have_spans() {
+    let regex_tree = RegexTree::lines(r"(?P<fields>.*)").build();
+    let error = from_regex_tree_and_str::<Vec<(&str, u8)>>(&regex_tree, "1\n300").unwrap_err();
+    assert_eq!(error.span(), Some(2..5));
+
+    let parsed = from_regex_tree_and_str::<Vec<(&str, u8)>>(&regex_tree, "").unwrap();
+    assert!(parsed.is_empty());
+}
+
+#[test]
+fn display() {
+    assert_eq!(Count::any().to_string(), "any number of");
+    assert_eq!(Count::exactly(3).to_string(), "exactly 3");
+    assert_eq!(Count::between(1, 3).to_string(), "between 1 and 3");
+    assert!(Count::between(1, 3).contains(3));
+    assert!(!Count::at_most(3).contains(4));
+}
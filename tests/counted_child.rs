@@ -0,0 +1,111 @@
+use recursive_regex::{from_regex_tree_and_str, RegexTree, Spanned};
+use serde::Deserialize;
+
+fn report() -> RegexTree {
+    RegexTree::lines(r"(?P<name>\w+): (?P<errors>.*)")
+        .with_counted_child("errors", RegexTree::leaf(r"\bERR\b"))
+        .build()
+}
+
+#[test]
+fn integer_fields_count_matches() {
+    #[derive(Debug, Deserialize, PartialEq, Eq)]
+    struct Line {
+        name: String,
+        errors: u32,
+    }
+
+    let lines: Vec<Line> =
+        from_regex_tree_and_str(&report(), "a: ok ERR ERR\nb: ok\nc: ERRATA ERR").unwrap();
+    let counts: Vec<u32> = lines.iter().map(|line| line.errors).collect();
+    assert_eq!(counts, vec![2, 0, 1]);
+}
+
+#[test]
+fn other_types_see_the_matches() {
+    #[derive(Debug, Deserialize, PartialEq, Eq)]
+    struct Line {
+        errors: Vec<String>,
+    }
+
+    let lines: Vec<Line> = from_regex_tree_and_str(&report(), "a: ERR x ERR").unwrap();
+    assert_eq!(lines[0].errors, vec!["ERR", "ERR"]);
+}
+
+#[test]
+fn optional_and_spanned_counts() {
+    #[derive(Debug, Deserialize)]
+    struct Line {
+        errors: Option<Spanned<i64>>,
+    }
+
+    let lines: Vec<Line> = from_regex_tree_and_str(&report(), "a: ok\nb: ERR").unwrap();
+    let first = lines[0].errors.as_ref().unwrap();
+    assert_eq!(*first.value(), 0);
+    assert_eq!((first.begin(), first.end()), (3, 5));
+    assert_eq!(*lines[1].errors.as_ref().unwrap().value(), 1);
+}
+
+#[test]
+fn count_too_large_for_the_field() {
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Line {
+        errors: u8,
+    }
+
+    let text = format!("a: {}", "ERR ".repeat(300));
+    let err = from_regex_tree_and_str::<Vec<Line>>(&report(), &text).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "[0].errors: invalid value: integer `300`, expected u8"
+    );
+    assert_eq!(err.span(), Some(3..text.len()));
+}
+
+#[test]
+fn unmatched_lines_are_not_counted() {
+    #[derive(Debug, Deserialize)]
+    struct Summary {
+        entries: usize,
+    }
+
+    let regex_tree = RegexTree::root(r"(?s)(?P<entries>.*)")
+        .with_counted_child("entries", RegexTree::lines(r"^\d+ .*").build())
+        .build();
+    let summary: Summary = from_regex_tree_and_str(&regex_tree, "1 a\n# note\n2 b\n").unwrap();
+    assert_eq!(summary.entries, 2);
+}
+
+#[cfg(feature = "deserialize-regex-tree")]
+#[test]
+fn deserialized_tree() {
+    #[derive(Debug, Deserialize)]
+    struct Line {
+        errors: u16,
+    }
+
+    let regex_tree: RegexTree = serde_json::from_str(
+        r#"{
+            "regex": "(?P<name>\\w+): (?P<errors>.*)",
+            "lines": true,
+            "children": {"errors": {"regex": "ERR", "count_matches": true}}
+        }"#,
+    )
+    .unwrap();
+    let lines: Vec<Line> = from_regex_tree_and_str(&regex_tree, "a: ERR ERR").unwrap();
+    assert_eq!(lines[0].errors, 2);
+}
+
+#[test]
+fn validates_against_integer_fields() {
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Line {
+        name: String,
+        errors: usize,
+    }
+
+    let report = recursive_regex::validate::<Vec<Line>>(&report());
+    assert!(report.is_ok(), "{report}");
+}
@@ -0,0 +1,91 @@
+use recursive_regex::regex_tree::Escapes;
+use recursive_regex::{
+    from_regex_tree_and_str, from_regex_tree_and_str_with_options, DeserializeOptions, RegexTree,
+};
+use serde::Deserialize;
+use std::borrow::Cow;
+
+#[derive(Debug, Deserialize)]
+struct Setting<'a> {
+    #[serde(borrow)]
+    key: Cow<'a, str>,
+    #[serde(borrow)]
+    value: Cow<'a, str>,
+}
+
+fn quoted_tree() -> RegexTree {
+    RegexTree::root(r#"(?P<key>\w+)="(?P<value>(?:[^"\\]|\\.)*)""#)
+        .with_child(
+            "value",
+            RegexTree::root("(?s).*")
+                .with_unescape(Escapes::Backslash)
+                .build(),
+        )
+        .build()
+}
+
+#[test]
+fn untouched_text_is_borrowed() {
+    let settings: Vec<Setting> = from_regex_tree_and_str(&quoted_tree(), r#"a="b c""#).unwrap();
+    assert!(matches!(settings[0].key, Cow::Borrowed("a")));
+    assert!(matches!(settings[0].value, Cow::Borrowed("b c")));
+}
+
+#[test]
+fn unescaped_text_is_owned() {
+    let text = r#"a="tab\there" b="\u{e9}t\u{E9} \\ \'q\' \0""#;
+    let settings: Vec<Setting> = from_regex_tree_and_str(&quoted_tree(), text).unwrap();
+    assert!(matches!(&settings[0].value, Cow::Owned(value) if value == "tab\there"));
+    assert_eq!(settings[1].value, "été \\ 'q' \0");
+}
+
+#[test]
+fn trimmed_text_is_borrowed() {
+    let regex_tree = RegexTree::leaf(r"(?P<key>[^=]+)=(?P<value>.*)");
+    let options = DeserializeOptions::new().trim();
+    let settings: Vec<Setting> =
+        from_regex_tree_and_str_with_options(&regex_tree, " a = b ", &options).unwrap();
+    assert!(matches!(settings[0].key, Cow::Borrowed("a")));
+    assert!(matches!(settings[0].value, Cow::Borrowed("b")));
+}
+
+#[test]
+fn mapped_text_is_owned() {
+    let regex_tree = RegexTree::root(r"(?P<key>\w+)=(?P<value>\w+)")
+        .with_value_map("value", [("y", "yes")])
+        .build();
+    let settings: Vec<Setting> = from_regex_tree_and_str(&regex_tree, "a=y b=n").unwrap();
+    assert!(matches!(&settings[0].value, Cow::Owned(value) if value == "yes"));
+    assert!(matches!(settings[1].value, Cow::Borrowed("n")));
+}
+
+#[test]
+fn invalid_escapes_are_errors() {
+    let error =
+        from_regex_tree_and_str::<Vec<Setting>>(&quoted_tree(), r#"a="x" b="\q""#).unwrap_err();
+    assert_eq!(error.message(), "invalid escape `\\q`");
+    assert_eq!(error.span(), Some(9..11));
+
+    for text in [
+        r#"a="\u{}""#,
+        r#"a="\u{+41}""#,
+        r#"a="\u{d800}""#,
+        r#"a="\u41""#,
+    ] {
+        let error = from_regex_tree_and_str::<Vec<Setting>>(&quoted_tree(), text).unwrap_err();
+        assert_eq!(error.message(), "invalid unicode escape");
+    }
+}
+
+#[cfg(feature = "deserialize-regex-tree")]
+#[test]
+fn deserialized_tree() {
+    let regex_tree: RegexTree =
+        serde_json::from_str(r#"{ "regex": "\\S+", "unescape": "backslash" }"#).unwrap();
+    let words: Vec<String> = from_regex_tree_and_str(&regex_tree, r"a\nb c").unwrap();
+    assert_eq!(words, vec!["a\nb", "c"]);
+    assert!(regex_tree
+        .schema()
+        .to_string()
+        .contains("unescape backslash"));
+}
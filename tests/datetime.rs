@@ -0,0 +1,142 @@
+use std::time::Duration;
+
+use recursive_regex::{from_regex_tree_and_str, RegexTree};
+use serde::Deserialize;
+
+#[test]
+fn durations() {
+    let regex_tree = RegexTree::leaf(r"[^,]+");
+
+    let parsed: Vec<Duration> =
+        from_regex_tree_and_str(&regex_tree, "90,1.5s,250ms,1h 30m,2d,0.000000001s,1m30s").unwrap();
+    assert_eq!(
+        parsed,
+        vec![
+            Duration::from_secs(90),
+            Duration::from_millis(1500),
+            Duration::from_millis(250),
+            Duration::from_secs(5400),
+            Duration::from_secs(172_800),
+            Duration::from_nanos(1),
+            Duration::from_secs(90),
+        ]
+    );
+}
+
+#[test]
+fn duration_fields() {
+    #[derive(Debug, Deserialize, PartialEq, Eq)]
+    struct Request {
+        path: String,
+        took: Duration,
+    }
+
+    let regex_tree = RegexTree::leaf(r"(?P<path>\S+) took (?P<took>\S+)");
+    let parsed: Vec<Request> = from_regex_tree_and_str(&regex_tree, "/a took 12ms").unwrap();
+    assert_eq!(
+        parsed,
+        vec![Request {
+            path: "/a".to_owned(),
+            took: Duration::from_millis(12),
+        }]
+    );
+}
+
+#[test]
+fn invalid_duration() {
+    let regex_tree = RegexTree::leaf(r"\S+");
+
+    for text in ["1y", "s", "1.2.3s", "-1s"] {
+        let error = from_regex_tree_and_str::<Vec<Duration>>(&regex_tree, text).unwrap_err();
+        assert_eq!(
+            error.message(),
+            format!("got {text} but expecting a duration")
+        );
+    }
+}
+
+#[cfg(feature = "chrono")]
+mod chrono_formats {
+    use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
+    use recursive_regex::{from_regex_tree_and_str, RegexTree};
+    use serde::Deserialize;
+
+    #[test]
+    fn datetime_with_offset() {
+        let regex_tree = RegexTree::root(r"\[[^\]]+\]")
+            .with_datetime_format("[%d/%b/%Y:%H:%M:%S %z]")
+            .build();
+
+        let parsed: Vec<DateTime<FixedOffset>> =
+            from_regex_tree_and_str(&regex_tree, "[10/Oct/2000:13:55:36 -0700]").unwrap();
+        assert_eq!(
+            parsed,
+            vec![DateTime::parse_from_rfc3339("2000-10-10T13:55:36-07:00").unwrap()]
+        );
+    }
+
+    #[test]
+    fn naive_dates_and_times() {
+        #[derive(Debug, Deserialize)]
+        struct Entry {
+            date: NaiveDate,
+            time: NaiveTime,
+            both: NaiveDateTime,
+        }
+
+        let regex_tree = RegexTree::root(r"(?P<date>\S+) (?P<time>\S+) (?P<both>\S+)")
+            .with_child(
+                "date",
+                RegexTree::root(r".+")
+                    .with_datetime_format("%Y%m%d")
+                    .build(),
+            )
+            .with_child(
+                "time",
+                RegexTree::root(r".+").with_datetime_format("%Hh%M").build(),
+            )
+            .with_child(
+                "both",
+                RegexTree::root(r".+")
+                    .with_datetime_format("%d.%m.%Y-%H:%M:%S%.f")
+                    .build(),
+            )
+            .build();
+
+        let entry: Entry =
+            from_regex_tree_and_str(&regex_tree, "20240131 09h05 31.01.2024-09:05:01.5").unwrap();
+        assert_eq!(entry.date, NaiveDate::from_ymd_opt(2024, 1, 31).unwrap());
+        assert_eq!(entry.time, NaiveTime::from_hms_opt(9, 5, 0).unwrap());
+        assert_eq!(
+            entry.both,
+            entry.date.and_hms_milli_opt(9, 5, 1, 500).unwrap()
+        );
+    }
+
+    #[test]
+    fn text_not_in_format() {
+        let regex_tree = RegexTree::root(r"\S+")
+            .with_datetime_format("%Y-%m-%d")
+            .build();
+
+        let error = from_regex_tree_and_str::<Vec<NaiveDate>>(&regex_tree, "2024-01-01 2024/01/02")
+            .unwrap_err();
+        assert!(
+            error.to_string().starts_with("[1]: parsing error: "),
+            "{error}"
+        );
+        assert_eq!(error.span(), Some(11..21));
+    }
+}
+
+#[test]
+fn validate_durations() {
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Request {
+        took: Duration,
+    }
+
+    let regex_tree = RegexTree::leaf(r"took (?P<took>\S+)");
+    assert!(recursive_regex::validate::<Vec<Request>>(&regex_tree).is_ok());
+}
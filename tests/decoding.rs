@@ -0,0 +1,97 @@
+use recursive_regex::regex_tree::Decoding;
+use recursive_regex::{from_regex_tree_and_str, RegexTree};
+use serde::Deserialize;
+
+fn decoded(decoding: Decoding, text: &str) -> Result<Vec<u8>, recursive_regex::Error> {
+    let regex_tree = RegexTree::root(".*").with_decoding(decoding).build();
+    from_regex_tree_and_str(&regex_tree, text)
+}
+
+#[test]
+fn hex() {
+    assert_eq!(
+        decoded(Decoding::Hex, "00FFa5").unwrap(),
+        vec![0, 255, 0xa5]
+    );
+    assert_eq!(decoded(Decoding::Hex, "").unwrap(), Vec::<u8>::new());
+    assert!(decoded(Decoding::Hex, "abc").is_err());
+    assert!(decoded(Decoding::Hex, "zz").is_err());
+}
+
+#[test]
+fn base64() {
+    assert_eq!(decoded(Decoding::Base64, "aGVsbG8=").unwrap(), b"hello");
+    assert_eq!(decoded(Decoding::Base64, "aGVsbG8").unwrap(), b"hello");
+    assert_eq!(decoded(Decoding::Base64, "aGk=").unwrap(), b"hi");
+    assert_eq!(decoded(Decoding::Base64, "-_8").unwrap(), vec![0xfb, 0xff]);
+    assert_eq!(decoded(Decoding::Base64, "+/8=").unwrap(), vec![0xfb, 0xff]);
+    assert!(decoded(Decoding::Base64, "a").is_err());
+    assert!(decoded(Decoding::Base64, "aGk===").is_err());
+    assert!(decoded(Decoding::Base64, "a*==").is_err());
+}
+
+#[test]
+fn percent() {
+    assert_eq!(decoded(Decoding::Percent, "a%20b%2f").unwrap(), b"a b/");
+    assert!(decoded(Decoding::Percent, "100%").is_err());
+}
+
+#[test]
+fn each_match_of_a_parent() {
+    let regex_tree = RegexTree::root(r"\S+")
+        .with_whole_match_child(RegexTree::root(".*").with_decoding(Decoding::Hex).build())
+        .build();
+
+    let parsed: Vec<Vec<u8>> = from_regex_tree_and_str(&regex_tree, "01 0203").unwrap();
+    assert_eq!(parsed, vec![vec![1], vec![2, 3]]);
+}
+
+#[test]
+fn byte_buf() {
+    struct Bytes(Vec<u8>);
+
+    impl<'de> Deserialize<'de> for Bytes {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct Visitor;
+            impl serde::de::Visitor<'_> for Visitor {
+                type Value = Bytes;
+                fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    f.write_str("bytes")
+                }
+                fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Bytes, E> {
+                    Ok(Bytes(v))
+                }
+            }
+            deserializer.deserialize_byte_buf(Visitor)
+        }
+    }
+
+    let regex_tree = RegexTree::root(r"\S+")
+        .with_decoding(Decoding::Base64)
+        .build();
+    let parsed: Bytes = from_regex_tree_and_str(&regex_tree, "AQI=").unwrap();
+    assert_eq!(parsed.0, vec![1, 2]);
+}
+
+#[test]
+fn error_has_span() {
+    let regex_tree = RegexTree::root(r"(?P<blob>\S+)")
+        .with_child(
+            "blob",
+            RegexTree::root(".*").with_decoding(Decoding::Hex).build(),
+        )
+        .build();
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Record {
+        blob: Vec<u8>,
+    }
+
+    let error = from_regex_tree_and_str::<Vec<Record>>(&regex_tree, "00 0g").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "[1].blob: got 0g but expecting hex bytes"
+    );
+    assert_eq!(error.span(), Some(3..5));
+}
@@ -0,0 +1,157 @@
+use recursive_regex::regex_tree::BuildError;
+use recursive_regex::{from_regex_tree_and_str, RegexTree};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Range {
+    low: u32,
+    high: u32,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Limits {
+    cpu: Range,
+    memory: Range,
+    disks: Vec<u32>,
+}
+
+fn range() -> RegexTree {
+    RegexTree::leaf(r"(?P<low>\d+)-(?P<high>\d+)")
+}
+
+#[test]
+fn own_child_wins() {
+    let regex_tree =
+        RegexTree::root(r"cpu=(?P<cpu>\S+) memory=(?P<memory>\S+) disks=(?P<disks>\S+)")
+            .with_default_child(range())
+            .with_child("disks", RegexTree::leaf(r"\d+"))
+            .build();
+
+    let limits: Limits =
+        from_regex_tree_and_str(&regex_tree, "cpu=1-4 memory=512-2048 disks=1,2,3").unwrap();
+    assert_eq!(
+        limits,
+        Limits {
+            cpu: Range { low: 1, high: 4 },
+            memory: Range {
+                low: 512,
+                high: 2048
+            },
+            disks: vec![1, 2, 3],
+        }
+    );
+}
+
+#[test]
+fn unnamed_groups_untouched() {
+    let regex_tree = RegexTree::root(r"(\S+) (?P<range>\S+)")
+        .with_default_child(range())
+        .build();
+
+    let pairs: Vec<(&str, Range)> = from_regex_tree_and_str(&regex_tree, "a 1-2").unwrap();
+    assert_eq!(pairs, vec![("a", Range { low: 1, high: 2 })]);
+}
+
+#[test]
+fn index_child_wins() {
+    let regex_tree = RegexTree::root(r"(?P<a>\S+) (?P<b>\S+)")
+        .with_default_child(range())
+        .with_child_index(2, RegexTree::leaf(r"\d+"))
+        .build();
+
+    #[derive(Debug, Deserialize, PartialEq, Eq)]
+    struct Both {
+        a: Range,
+        b: Vec<u32>,
+    }
+
+    let both: Both = from_regex_tree_and_str(&regex_tree, "3-5 6,7").unwrap();
+    assert_eq!(
+        both,
+        Both {
+            a: Range { low: 3, high: 5 },
+            b: vec![6, 7],
+        }
+    );
+}
+
+#[test]
+fn introspection() {
+    let regex_tree = RegexTree::root(r"(?P<cpu>\S+)")
+        .with_default_child(
+            RegexTree::root(r"(?P<low>.*)")
+                .with_child("low", RegexTree::leaf(r"\d+"))
+                .build(),
+        )
+        .build();
+
+    assert_eq!(regex_tree.depth(), 3);
+    assert!(regex_tree.default_child().is_some());
+    assert_eq!(regex_tree.children().count(), 0);
+}
+
+#[test]
+fn unknown_child_of_default_child() {
+    let build = || -> Result<RegexTree, BuildError> {
+        let default_child = RegexTree::root(r"(?P<low>.*)")
+            .with_child("missing", RegexTree::leaf(r"\d+"))
+            .try_build()?;
+        RegexTree::root(r"(?P<cpu>\S+)")
+            .with_default_child(default_child)
+            .try_build()
+    };
+    assert!(matches!(
+        build(),
+        Err(BuildError::UnknownChild { child }) if child == "missing"
+    ));
+}
+
+#[cfg(feature = "deserialize-regex-tree")]
+#[test]
+fn unknown_child_of_deserialized_default_child() {
+    let error = serde_json::from_str::<RegexTree>(
+        r#"{
+            "regex": "(?P<cpu>\\S+)",
+            "default_child": {
+                "regex": "(?P<low>.*)",
+                "children": { "missing": { "regex": "\\d+" } }
+            }
+        }"#,
+    )
+    .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("child `missing` does not match any capture group"));
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn compile_stats() {
+    let regex_tree = RegexTree::root(r"(?P<cpu>\S+)")
+        .with_child("cpu", range())
+        .with_default_child(range())
+        .build();
+    let paths: Vec<_> = regex_tree
+        .compile_stats()
+        .nodes()
+        .iter()
+        .map(|node| node.path().to_vec())
+        .collect();
+    assert_eq!(paths, vec![vec![], vec!["cpu"], vec!["*"]]);
+}
+
+#[cfg(feature = "deserialize-regex-tree")]
+#[test]
+fn deserialized_tree() {
+    let regex_tree: RegexTree = serde_json::from_str(
+        r#"{
+            "regex": "cpu=(?P<cpu>\\S+) memory=(?P<memory>\\S+) disks=(?P<disks>\\S+)",
+            "default_child": {"regex": "(?P<low>\\d+)-(?P<high>\\d+)"},
+            "children": {"disks": {"regex": "\\d+"}}
+        }"#,
+    )
+    .unwrap();
+    let limits: Limits =
+        from_regex_tree_and_str(&regex_tree, "cpu=1-4 memory=1-2 disks=5").unwrap();
+    assert_eq!(limits.memory, Range { low: 1, high: 2 });
+}
@@ -0,0 +1,234 @@
+#![cfg(feature = "deserialize-regex-tree")]
+
+use recursive_regex::{from_regex_tree_and_str, RegexTree};
+use serde::Deserialize;
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Timestamp {
+    date: String,
+    time: String,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Span {
+    start: Timestamp,
+    end: Timestamp,
+}
+
+fn timestamp(date: &str, time: &str) -> Timestamp {
+    Timestamp {
+        date: date.to_owned(),
+        time: time.to_owned(),
+    }
+}
+
+#[test]
+fn references_are_replaced() {
+    let regex_tree: RegexTree = serde_json::from_str(
+        r#"{
+            "regex": "(?P<start>\\S+ \\S+) to (?P<end>\\S+ \\S+)",
+            "children": {
+                "start": { "$ref": "timestamp" },
+                "end": { "$ref": "timestamp" }
+            },
+            "definitions": {
+                "timestamp": { "regex": "(?P<date>\\S+) (?P<time>\\S+)" }
+            }
+        }"#,
+    )
+    .unwrap();
+    let span: Span =
+        from_regex_tree_and_str(&regex_tree, "2024-01-02 10:00 to 2024-01-03 11:30").unwrap();
+    assert_eq!(
+        span,
+        Span {
+            start: timestamp("2024-01-02", "10:00"),
+            end: timestamp("2024-01-03", "11:30"),
+        }
+    );
+
+    let built = RegexTree::root(r"(?P<start>\S+ \S+) to (?P<end>\S+ \S+)")
+        .with_child("start", RegexTree::leaf(r"(?P<date>\S+) (?P<time>\S+)"))
+        .with_child("end", RegexTree::leaf(r"(?P<date>\S+) (?P<time>\S+)"))
+        .build();
+    assert_eq!(regex_tree, built);
+}
+
+#[test]
+fn definitions_refer_to_definitions() {
+    let regex_tree: RegexTree = serde_json::from_str(
+        r#"{
+            "regex": "\\[(?P<at>[^\\]]+)\\]",
+            "default_child": { "$ref": "stamp" },
+            "definitions": {
+                "stamp": {
+                    "regex": "(?P<date>\\S+) (?P<time>\\S+)",
+                    "children": { "time": { "$ref": "clock" } }
+                },
+                "clock": { "regex": "(?P<hour>\\d+):(?P<minute>\\d+)" }
+            }
+        }"#,
+    )
+    .unwrap();
+    let clock = regex_tree
+        .default_child()
+        .and_then(|stamp| stamp.child("time"))
+        .unwrap();
+    assert_eq!(clock.pattern(), r"(?P<hour>\d+):(?P<minute>\d+)");
+}
+
+#[test]
+fn nearest_definition_wins() {
+    let regex_tree: RegexTree = serde_json::from_str(
+        r#"{
+            "regex": "(?P<a>.*);(?P<b>.*)",
+            "definitions": { "value": { "regex": "\\d+" } },
+            "children": {
+                "a": { "$ref": "value" },
+                "b": {
+                    "regex": "(?P<inner>.*)",
+                    "definitions": { "value": { "regex": "\\w+" } },
+                    "children": { "inner": { "$ref": "value" } }
+                }
+            }
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(regex_tree.child("a").unwrap().pattern(), r"\d+");
+    let inner = regex_tree.child("b").and_then(|b| b.child("inner"));
+    assert_eq!(inner.unwrap().pattern(), r"\w+");
+}
+
+#[test]
+fn definitions_are_scoped() {
+    let error = serde_json::from_str::<RegexTree>(
+        r#"{
+            "regex": "(?P<a>.*);(?P<b>.*)",
+            "children": {
+                "a": {
+                    "regex": ".*",
+                    "definitions": { "value": { "regex": "\\d+" } }
+                },
+                "b": { "$ref": "value" }
+            }
+        }"#,
+    )
+    .unwrap_err();
+    assert!(error.to_string().contains("no definition named `value`"));
+}
+
+#[test]
+fn cycles_are_errors() {
+    let error = serde_json::from_str::<RegexTree>(
+        r#"{
+            "$ref": "a",
+            "definitions": {
+                "a": { "regex": "(?P<x>.*)", "children": { "x": { "$ref": "b" } } },
+                "b": { "regex": "(?P<y>.*)", "children": { "y": { "$ref": "a" } } }
+            }
+        }"#,
+    )
+    .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("definition `a` refers to itself"));
+}
+
+#[test]
+fn malformed_nodes() {
+    let error = serde_json::from_str::<RegexTree>(r#"{"lines": true}"#).unwrap_err();
+    assert!(error.to_string().contains("missing field `regex`"));
+
+    let error = serde_json::from_str::<RegexTree>(
+        r#"{"regex": ".*", "$ref": "a", "definitions": {"a": {"regex": "a"}}}"#,
+    )
+    .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("reference to `a` cannot also have `regex`"));
+
+    // Definitions are checked where they are used
+    let error = serde_json::from_str::<RegexTree>(
+        r#"{
+            "regex": "(?P<x>.*)",
+            "children": { "x": { "$ref": "bad" } },
+            "definitions": { "bad": { "regex": "(?P<y>.*)", "children": { "z": { "regex": "." } } } }
+        }"#,
+    )
+    .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("child `z` does not match any capture group"));
+}
+
+#[test]
+fn references_have_no_settings() {
+    let error = serde_json::from_str::<RegexTree>(
+        r#"{
+            "regex": "(?P<x>.*)",
+            "children": {
+                "x": {
+                    "$ref": "word",
+                    "lines": true,
+                    "children": { "w": { "regex": "." } },
+                    "case_insensitive": true,
+                    "tag": "w"
+                }
+            },
+            "definitions": { "word": { "regex": "(?P<w>\\w+)" } }
+        }"#,
+    )
+    .unwrap_err();
+    assert!(error.to_string().contains(
+        "reference to `word` cannot also have `lines`, `children`, `case_insensitive`, `tag`"
+    ));
+
+    // Definitions are not settings, and settings at their defaults are not counted
+    let regex_tree: RegexTree = serde_json::from_str(
+        r#"{
+            "$ref": "word",
+            "lines": false,
+            "definitions": { "word": { "regex": "\\w+" } }
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(regex_tree, RegexTree::leaf(r"\w+"));
+}
+
+/// Definitions `d1` to `d{depth}`, each with two children referring to the one before it, so
+/// `d{depth}` has 2^depth leaves once references are replaced
+fn diamonds(depth: usize) -> String {
+    let mut definitions = vec![r#""d0": { "regex": "." }"#.to_owned()];
+    for n in 1..=depth {
+        let prev = n - 1;
+        definitions.push(format!(
+            r#""d{n}": {{
+                "regex": "(?P<a>.)(?P<b>.*)",
+                "children": {{ "a": {{ "$ref": "d{prev}" }}, "b": {{ "$ref": "d{prev}" }} }}
+            }}"#
+        ));
+    }
+    format!(
+        r#"{{ "$ref": "d{depth}", "definitions": {{ {} }} }}"#,
+        definitions.join(", ")
+    )
+}
+
+#[test]
+fn shared_references() {
+    let regex_tree: RegexTree = serde_json::from_str(&diamonds(8)).unwrap();
+    let mut node = &regex_tree;
+    for _ in 0..8 {
+        assert_eq!(node.child("a"), node.child("b"));
+        node = node.child("b").unwrap();
+    }
+    assert_eq!(node.pattern(), ".");
+
+    // Too large to build, and rejected before building much of it
+    let started = std::time::Instant::now();
+    let error = serde_json::from_str::<RegexTree>(&diamonds(40)).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("tree has more than 10000 nodes once references to definitions are replaced"));
+    assert!(started.elapsed().as_secs() < 5);
+}
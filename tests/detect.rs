@@ -0,0 +1,86 @@
+use recursive_regex::{
+    from_regex_trees_and_str, from_regex_trees_and_str_with_options, rank_regex_trees,
+    DeserializeOptions, RegexTree,
+};
+use serde::Deserialize;
+
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+struct Event<'a> {
+    level: &'a str,
+    code: u32,
+}
+
+fn bracketed() -> RegexTree {
+    RegexTree::lines(r"\[(?P<level>\w+)\] (?P<code>\w+)").build()
+}
+
+fn keyed() -> RegexTree {
+    RegexTree::lines(r"level=(?P<level>\w+) code=(?P<code>\d+)").build()
+}
+
+#[test]
+fn coverage_beats_match_count() {
+    let words = RegexTree::leaf(r"\w");
+    let lines = keyed();
+    let text = "level=warn code=7";
+
+    let ranked = rank_regex_trees(&[("words", &words), ("keyed", &lines)], text);
+    assert_eq!(ranked[0].0, "keyed");
+    assert_eq!(ranked[0].1.coverage(), text.len());
+    assert_eq!(ranked[1].1.matches(), 14);
+}
+
+#[test]
+fn ties_keep_their_order() {
+    let (first, second) = (keyed(), keyed());
+    let ranked = rank_regex_trees(&[("first", &first), ("second", &second)], "level=a code=1");
+    let names: Vec<_> = ranked.iter().map(|(name, _)| *name).collect();
+    assert_eq!(names, vec!["first", "second"]);
+}
+
+#[test]
+fn deserializes_with_best_fit() {
+    let (bracketed, keyed) = (bracketed(), keyed());
+    let formats = [("bracketed", &bracketed), ("keyed", &keyed)];
+
+    let (format, events): (_, Vec<Event>) =
+        from_regex_trees_and_str(&formats, "level=warn code=7\nlevel=info code=1").unwrap();
+    assert_eq!(format, "keyed");
+    assert_eq!(
+        events[0],
+        Event {
+            level: "warn",
+            code: 7
+        }
+    );
+}
+
+#[test]
+fn falls_back_when_best_fit_fails() {
+    let (bracketed, keyed) = (bracketed(), keyed());
+    let formats = [("bracketed", &bracketed), ("keyed", &keyed)];
+    // The bracketed format covers more of the text, but its codes are not numbers
+    let text = "[warn] seven_hundred level=info code=1";
+
+    let (format, events): (_, Vec<Event>) = from_regex_trees_and_str(&formats, text).unwrap();
+    assert_eq!(format, "keyed");
+    assert_eq!(events[0].level, "info");
+
+    let ranked = rank_regex_trees(&formats, text);
+    assert_eq!(ranked[0].0, "bracketed");
+    let error = from_regex_trees_and_str::<Vec<Event>>(&formats[..1], text).unwrap_err();
+    assert_eq!(error.span(), Some(7..20));
+}
+
+#[test]
+fn no_tree_matches() {
+    let (bracketed, keyed) = (bracketed(), keyed());
+    let formats = [("bracketed", &bracketed), ("keyed", &keyed)];
+    let options = DeserializeOptions::new().with_source_name("app.log");
+
+    let error =
+        from_regex_trees_and_str_with_options::<Vec<Event>>(&formats, "nothing here", &options)
+            .unwrap_err();
+    assert_eq!(error.to_string(), "app.log: no regex tree matched the text");
+    assert_eq!(error.span(), Some(0..12));
+}
@@ -0,0 +1,84 @@
+use recursive_regex::regex_tree::DuplicateKeys;
+use recursive_regex::{from_regex_tree_and_str, RegexTree};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Reading {
+    value: u32,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Readings {
+    value: Vec<u32>,
+}
+
+fn regex_tree(duplicate_keys: DuplicateKeys) -> RegexTree {
+    RegexTree::root(r"(?P<value[0]>\d+)(?:/(?P<value[1]>\d+))?")
+        .with_duplicate_keys(duplicate_keys)
+        .build()
+}
+
+#[test]
+fn alternation_shares_key() {
+    let regex_tree = RegexTree::leaf(r"temp=(?P<value[c]>\d+)C|temp=(?P<value[f]>\d+)F");
+
+    let readings: Vec<Reading> = from_regex_tree_and_str(&regex_tree, "temp=20C temp=68F").unwrap();
+    assert_eq!(readings, vec![Reading { value: 20 }, Reading { value: 68 }]);
+}
+
+#[test]
+fn error() {
+    let regex_tree = regex_tree(DuplicateKeys::Error);
+
+    let single: Vec<Reading> = from_regex_tree_and_str(&regex_tree, "3").unwrap();
+    assert_eq!(single, vec![Reading { value: 3 }]);
+
+    let err = from_regex_tree_and_str::<Vec<Reading>>(&regex_tree, "3/4").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "[0].value: capture group key `value` matched 2 times"
+    );
+}
+
+#[test]
+fn first_and_last_wins() {
+    let first: Vec<Reading> =
+        from_regex_tree_and_str(&regex_tree(DuplicateKeys::FirstWins), "3/4").unwrap();
+    assert_eq!(first, vec![Reading { value: 3 }]);
+
+    let last: Vec<Reading> =
+        from_regex_tree_and_str(&regex_tree(DuplicateKeys::LastWins), "3/4").unwrap();
+    assert_eq!(last, vec![Reading { value: 4 }]);
+}
+
+#[test]
+fn collect() {
+    let regex_tree = regex_tree(DuplicateKeys::Collect);
+
+    let readings: Vec<Readings> = from_regex_tree_and_str(&regex_tree, "3/4 5").unwrap();
+    assert_eq!(
+        readings,
+        vec![Readings { value: vec![3, 4] }, Readings { value: vec![5] }]
+    );
+}
+
+#[test]
+fn child_applies_to_key() {
+    let regex_tree = RegexTree::root(r"(?P<value[0]>\S+) (?P<value[1]>\S+)")
+        .with_duplicate_keys(DuplicateKeys::Collect)
+        .with_child("value", RegexTree::leaf(r"\d+"))
+        .build();
+
+    #[derive(Debug, Deserialize, PartialEq, Eq)]
+    struct Groups {
+        value: Vec<Vec<u32>>,
+    }
+
+    let groups: Groups = from_regex_tree_and_str(&regex_tree, "1,2 3").unwrap();
+    assert_eq!(
+        groups,
+        Groups {
+            value: vec![vec![1, 2], vec![3]]
+        }
+    );
+}
@@ -0,0 +1,103 @@
+use recursive_regex::regex_tree::EmptyCaptures;
+use recursive_regex::{from_regex_tree_and_str, RegexTree};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Arg {
+    name: String,
+    value: Option<String>,
+}
+
+const PATTERN: &str = r"--(?P<name>\w+)(?:=(?P<value>\w*))?";
+
+fn values(regex_tree: &RegexTree, text: &str) -> Vec<Option<String>> {
+    let args: Vec<Arg> = from_regex_tree_and_str(regex_tree, text).unwrap();
+    args.into_iter().map(|arg| arg.value).collect()
+}
+
+#[test]
+fn empty_is_some_by_default() {
+    let regex_tree = RegexTree::root(PATTERN).build();
+    assert_eq!(
+        values(&regex_tree, "--a=1 --b= --c"),
+        vec![Some("1".to_owned()), Some(String::new()), None]
+    );
+}
+
+#[test]
+fn empty_as_missing() {
+    let regex_tree = RegexTree::root(PATTERN)
+        .with_empty_captures(EmptyCaptures::Missing)
+        .build();
+    assert_eq!(
+        values(&regex_tree, "--a=1 --b= --c"),
+        vec![Some("1".to_owned()), None, None]
+    );
+}
+
+#[test]
+fn missing_required_field() {
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Required {
+        name: String,
+        value: String,
+    }
+
+    let regex_tree = RegexTree::root(PATTERN)
+        .with_empty_captures(EmptyCaptures::Missing)
+        .build();
+    let err = from_regex_tree_and_str::<Vec<Required>>(&regex_tree, "--b=").unwrap_err();
+    assert_eq!(err.to_string(), "[0]: missing field `value`");
+}
+
+#[test]
+fn only_the_node_it_is_set_on() {
+    let regex_tree = RegexTree::lines(r"(?P<name>\w*):(?P<rest>.*)")
+        .with_child(
+            "rest",
+            RegexTree::root(PATTERN)
+                .with_empty_captures(EmptyCaptures::Missing)
+                .build(),
+        )
+        .build();
+
+    #[derive(Debug, Deserialize)]
+    struct Line {
+        name: Option<String>,
+        rest: Vec<Arg>,
+    }
+
+    let lines: Vec<Line> = from_regex_tree_and_str(&regex_tree, ":--a=").unwrap();
+    assert_eq!(lines[0].name, Some(String::new()));
+    assert_eq!(lines[0].rest[0].value, None);
+}
+
+#[test]
+fn positional_groups() {
+    let regex_tree = RegexTree::root(r"(\w*),(\w*)")
+        .with_empty_captures(EmptyCaptures::Missing)
+        .build();
+    let pairs: Vec<(Option<String>, Option<String>)> =
+        from_regex_tree_and_str(&regex_tree, "a, ,b").unwrap();
+    assert_eq!(
+        pairs,
+        vec![(Some("a".to_owned()), None), (None, Some("b".to_owned()))]
+    );
+}
+
+#[cfg(feature = "deserialize-regex-tree")]
+#[test]
+fn deserialized_tree() {
+    let regex_tree: RegexTree = serde_json::from_str(
+        r#"{
+            "regex": "--(?P<name>\\w+)(?:=(?P<value>\\w*))?",
+            "empty_captures": "missing"
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        values(&regex_tree, "--a=1 --b="),
+        vec![Some("1".to_owned()), None]
+    );
+}
@@ -0,0 +1,106 @@
+use recursive_regex::regex_tree::{Count, ValueType};
+use recursive_regex::RegexTree;
+
+fn numbers() -> RegexTree {
+    RegexTree::root(r"(?P<name>\w+): (?P<nums>.*)")
+        .with_child("nums", RegexTree::leaf(r"\d+"))
+        .with_count(Count::at_least(1))
+        .build()
+}
+
+#[test]
+fn clones_are_equal() {
+    let regex_tree = numbers();
+    assert_eq!(regex_tree.clone(), regex_tree);
+    assert_eq!(numbers(), regex_tree);
+}
+
+#[test]
+fn differences() {
+    let different = [
+        RegexTree::root(r"(?P<name>\w+): (?P<nums>.*)")
+            .with_child("nums", RegexTree::leaf(r"\d"))
+            .with_count(Count::at_least(1))
+            .build(),
+        RegexTree::root(r"(?P<name>\w+): (?P<nums>.*)")
+            .with_child("nums", RegexTree::leaf(r"\d+"))
+            .build(),
+        RegexTree::root(r"(?P<name>\w+): (?P<nums>.*)")
+            .with_child("nums", RegexTree::leaf(r"\d+"))
+            .with_count(Count::at_least(1))
+            .with_type("name", ValueType::String)
+            .build(),
+        RegexTree::lines(r"(?P<name>\w+): (?P<nums>.*)")
+            .with_child("nums", RegexTree::leaf(r"\d+"))
+            .with_count(Count::at_least(1))
+            .build(),
+        RegexTree::root(r"(?P<name>\w+): (?P<nums>.*)")
+            .with_child("nums", RegexTree::leaf(r"\d+"))
+            .with_count(Count::at_least(1))
+            .with_case_insensitive(true)
+            .build(),
+    ];
+    for regex_tree in different {
+        assert_ne!(regex_tree, numbers());
+    }
+}
+
+#[test]
+fn conditions_are_only_equal_to_their_clones() {
+    let conditional = || {
+        RegexTree::root(r"(?P<kind>\w+) (?P<value>.*)")
+            .with_child_if(
+                "value",
+                |groups| groups.name("kind") == Some("n"),
+                RegexTree::leaf(r"\d+"),
+            )
+            .build()
+    };
+    let regex_tree = conditional();
+    assert_eq!(regex_tree.clone(), regex_tree);
+    assert_ne!(conditional(), regex_tree);
+}
+
+#[test]
+fn debug_shows_patterns() {
+    let debug = format!("{:?}", numbers());
+    // Backslashes are escaped
+    assert!(debug.contains(r"\\d+"));
+    assert!(debug.contains(r"(?P<name>\\w+): (?P<nums>.*)"));
+}
+
+#[cfg(feature = "deserialize-regex-tree")]
+#[test]
+fn deserialized_tree_equals_built_tree() {
+    let regex_tree: RegexTree = serde_json::from_str(
+        r#"{
+            "regex": "(?P<name>\\w+): (?P<nums>.*)",
+            "children": { "nums": { "regex": "\\d+" } },
+            "count": { "min": 1 }
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(regex_tree, numbers());
+}
+
+#[test]
+fn compiled_regexes_are_compared_by_identity() {
+    use recursive_regex::regex::{Regex, RegexBuilder};
+
+    let insensitive = RegexBuilder::new(r"a(?P<x>b)c")
+        .case_insensitive(true)
+        .build()
+        .unwrap();
+    let regex_tree = RegexTree::leaf(insensitive.clone());
+    assert_ne!(
+        regex_tree,
+        RegexTree::leaf(Regex::new(r"a(?P<x>b)c").unwrap())
+    );
+    assert_ne!(regex_tree, RegexTree::leaf(r"a(?P<x>b)c"));
+    assert_ne!(regex_tree, RegexTree::leaf(insensitive));
+    assert_eq!(regex_tree.clone(), regex_tree);
+
+    let split = RegexTree::split(Regex::new(",").unwrap()).build();
+    assert_ne!(split, RegexTree::split(",").build());
+    assert_eq!(split.clone(), split);
+}
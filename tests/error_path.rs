@@ -0,0 +1,60 @@
+use recursive_regex::{from_regex_tree_and_str, PathSegment, RegexTree};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct Play<'a> {
+    name: &'a str,
+    tags: Vec<u8>,
+}
+
+fn regex_tree() -> RegexTree {
+    RegexTree::lines(r"(?P<name>\w+): (?P<tags>.*)")
+        .with_child("tags", RegexTree::leaf(r"\w+"))
+        .build()
+}
+
+#[test]
+fn path_to_failing_leaf() {
+    let error = from_regex_tree_and_str::<Vec<Play>>(&regex_tree(), "a: 1 2\nb: 3 x").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "[1].tags[1]: parsing error: invalid digit found in string"
+    );
+    assert_eq!(
+        error.path().segments(),
+        [
+            PathSegment::Index(1),
+            PathSegment::Name("tags".to_owned()),
+            PathSegment::Index(1),
+        ]
+    );
+    assert_eq!(error.span(), Some(12..13));
+}
+
+#[test]
+fn path_to_struct_missing_field() {
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Outer<'a> {
+        #[serde(borrow)]
+        plays: Vec<Play<'a>>,
+    }
+
+    let regex_tree = RegexTree::root(r"(?s)(?P<plays>.*)")
+        .with_child("plays", RegexTree::lines(r"(?P<name>\w+)").build())
+        .build();
+    let error = from_regex_tree_and_str::<Outer>(&regex_tree, "a\nb").unwrap_err();
+    assert_eq!(error.to_string(), "plays[0]: missing field `tags`");
+}
+
+#[test]
+fn tuple_elements() {
+    let regex_tree = RegexTree::leaf(r"(\w+)=(\w+)");
+    let error =
+        from_regex_tree_and_str::<Vec<(&str, &str, u32)>>(&regex_tree, "a=1 b=x").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "[1][2]: parsing error: invalid digit found in string"
+    );
+}
@@ -0,0 +1,86 @@
+#![cfg(feature = "export")]
+
+use recursive_regex::{write_csv, write_ndjson, ExportError, RegexTree};
+
+fn ndjson(regex_tree: &RegexTree, text: &str) -> String {
+    let mut out = Vec::new();
+    write_ndjson(regex_tree, text, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+fn csv(regex_tree: &RegexTree, text: &str) -> String {
+    let mut out = Vec::new();
+    write_csv(regex_tree, text, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn ndjson_nested() {
+    let regex_tree = RegexTree::root(r"(?P<name>\w+): (?P<nums>.*)")
+        .with_child("nums", RegexTree::leaf(r"\d+"))
+        .build();
+
+    assert_eq!(
+        ndjson(&regex_tree, "a: 1 2\nb: 3\n"),
+        "{\"name\":\"a\",\"nums\":[\"1\",\"2\"]}\n{\"name\":\"b\",\"nums\":[\"3\"]}\n"
+    );
+    assert_eq!(ndjson(&regex_tree, "nothing here"), "");
+}
+
+#[test]
+fn ndjson_unnamed() {
+    let regex_tree = RegexTree::leaf(r"\d+");
+    assert_eq!(ndjson(&regex_tree, "1 22"), "\"1\"\n\"22\"\n");
+}
+
+#[test]
+fn csv_columns_from_capture_names() {
+    let regex_tree = RegexTree::root(r"(?P<key>\w+)=(?P<value>[^;]*)")
+        .with_fallback(r"(?P<key>\w+)!(?P<flag>\w*)")
+        .build();
+
+    // Every row has every column, even when its pattern lacks the group
+    assert_eq!(
+        csv(&regex_tree, "a=1;b!on;c=\"q\""),
+        "key,value,flag\na,1,\nb,,on\nc,\"\"\"q\"\"\",\n"
+    );
+}
+
+#[test]
+fn csv_header_without_matches() {
+    let regex_tree = RegexTree::leaf(r"(?P<key>\w+)=(?P<value>\w+)");
+    assert_eq!(csv(&regex_tree, ""), "key,value\n");
+}
+
+#[test]
+fn csv_nested_and_unnamed() {
+    let regex_tree = RegexTree::root(r"(?P<name>\w+): (?P<nums>.*)")
+        .with_child("nums", RegexTree::leaf(r"\d+"))
+        .build();
+    assert_eq!(
+        csv(&regex_tree, "a: 1 2\nb: 3 4\n"),
+        "name,nums\na,\"[\"\"1\"\",\"\"2\"\"]\"\nb,\"[\"\"3\"\",\"\"4\"\"]\"\n"
+    );
+
+    let regex_tree = RegexTree::leaf(r"\d+");
+    assert_eq!(csv(&regex_tree, "1 22"), "value\n1\n22\n");
+}
+
+#[test]
+fn write_error() {
+    struct Full;
+
+    impl std::io::Write for Full {
+        fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::ErrorKind::WriteZero.into())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let regex_tree = RegexTree::leaf(r"\d+");
+    let err = write_csv(&regex_tree, "1", Full).unwrap_err();
+    assert!(matches!(err, ExportError::Io(_)), "{err}");
+}
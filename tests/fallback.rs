@@ -0,0 +1,135 @@
+use recursive_regex::{from_regex_tree_and_str, get_uncaptured, RegexTree};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Date {
+    year: u32,
+    month: u32,
+    day: u32,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Event<'a> {
+    name: &'a str,
+    date: Date,
+}
+
+#[test]
+fn main() {
+    let file = "launch on 2021-03-04
+review on 04/05/2021
+retro on 2021-06-07";
+
+    let regex_tree = RegexTree::root(r"(?P<name>\w+) on (?P<date>\S+)")
+        .with_child(
+            "date",
+            RegexTree::root(r"(?P<year>\d{4})-(?P<month>\d\d)-(?P<day>\d\d)")
+                .with_fallback(r"(?P<day>\d\d)/(?P<month>\d\d)/(?P<year>\d{4})")
+                .build(),
+        )
+        .build();
+
+    let events: Vec<Event> = from_regex_tree_and_str(&regex_tree, file).unwrap();
+    let expected = vec![
+        Event {
+            name: "launch",
+            date: Date {
+                year: 2021,
+                month: 3,
+                day: 4,
+            },
+        },
+        Event {
+            name: "review",
+            date: Date {
+                year: 2021,
+                month: 5,
+                day: 4,
+            },
+        },
+        Event {
+            name: "retro",
+            date: Date {
+                year: 2021,
+                month: 6,
+                day: 7,
+            },
+        },
+    ];
+    assert_eq!(expected, events);
+}
+
+#[test]
+fn sequence_takes_earliest_match() {
+    let regex_tree = RegexTree::root(r"\d+")
+        .with_fallback(r"[a-z]+")
+        .with_fallback(r"\w+")
+        .build();
+
+    let tokens: Vec<&str> = from_regex_tree_and_str(&regex_tree, "ab 12 cd3 _x").unwrap();
+    assert_eq!(tokens, vec!["ab", "12", "cd", "3", "_x"]);
+
+    let uncaptured: Vec<&str> = get_uncaptured(&regex_tree, "ab 12 cd3 _x").collect();
+    assert_eq!(uncaptured, vec![" ", " ", " "]);
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct LogLine<'a> {
+    level: &'a str,
+    code: Option<u32>,
+    message: Option<&'a str>,
+}
+
+#[test]
+fn many_record_shapes_per_line() {
+    let regex_tree = RegexTree::lines(r"^(?P<level>ERROR) (?P<code>\d+): (?P<message>.*)$")
+        .with_fallback(r"^(?P<level>WARN): (?P<message>.*)$")
+        .with_fallback(r"^(?P<level>INFO) (?P<code>\d+)$")
+        .with_fallback(r"^(?P<level>DEBUG)$")
+        .build();
+
+    let log = "INFO 7\nERROR 12: disk full\nDEBUG\nWARN: slow";
+    let lines: Vec<LogLine> = from_regex_tree_and_str(&regex_tree, log).unwrap();
+    assert_eq!(
+        lines,
+        vec![
+            LogLine {
+                level: "INFO",
+                code: Some(7),
+                message: None
+            },
+            LogLine {
+                level: "ERROR",
+                code: Some(12),
+                message: Some("disk full")
+            },
+            LogLine {
+                level: "DEBUG",
+                code: None,
+                message: None
+            },
+            LogLine {
+                level: "WARN",
+                code: None,
+                message: Some("slow")
+            },
+        ]
+    );
+    assert_eq!(regex_tree.matching_pattern("WARN: x"), Some(1));
+
+    let error = from_regex_tree_and_str::<Vec<LogLine>>(&regex_tree, "INFO 7\nTRACE").unwrap_err();
+    assert_eq!(error.to_string(), "line 2 does not match");
+}
+
+#[test]
+fn compiled_regexes_keep_their_flags() {
+    use recursive_regex::regex::RegexBuilder;
+
+    let regex = RegexBuilder::new("abc")
+        .case_insensitive(true)
+        .build()
+        .unwrap();
+    let regex_tree = RegexTree::root(regex).with_fallback("xyz").build();
+    let words: Vec<&str> = from_regex_tree_and_str(&regex_tree, "ABC xyz").unwrap();
+    assert_eq!(words, vec!["ABC", "xyz"]);
+}
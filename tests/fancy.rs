@@ -0,0 +1,102 @@
+#![cfg(feature = "fancy")]
+
+use recursive_regex::{from_regex_tree_and_str, validate, RegexTree};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Pair {
+    name: String,
+    value: String,
+}
+
+#[test]
+fn backreference() {
+    // A quoted value may use either quote, but must close with the same one
+    let regex_tree =
+        RegexTree::fancy(r#"(?P<name>\w+)=(?P<quote>["'])(?P<value>.*?)(?P=quote)"#).build();
+
+    let pairs: Vec<Pair> =
+        from_regex_tree_and_str(&regex_tree, r#"a="x" b='y' c="z' d='w'"#).unwrap();
+    assert_eq!(
+        pairs,
+        vec![
+            Pair {
+                name: "a".to_owned(),
+                value: "x".to_owned()
+            },
+            Pair {
+                name: "b".to_owned(),
+                value: "y".to_owned()
+            },
+            Pair {
+                name: "d".to_owned(),
+                value: "w".to_owned()
+            },
+        ]
+    );
+}
+
+#[test]
+fn fancy_child_of_regex_node() {
+    let regex_tree = RegexTree::root(r"(?P<name>\w+): (?P<value>.*)")
+        .with_child("value", RegexTree::fancy(r"\b\d+\b(?!%)").build())
+        .build();
+
+    #[derive(Debug, Deserialize, PartialEq, Eq)]
+    struct Line {
+        name: String,
+        value: Vec<u32>,
+    }
+
+    let lines: Vec<Line> = from_regex_tree_and_str(&regex_tree, "a: 1 50% 2\nb: 3").unwrap();
+    assert_eq!(
+        lines,
+        vec![
+            Line {
+                name: "a".to_owned(),
+                value: vec![1, 2]
+            },
+            Line {
+                name: "b".to_owned(),
+                value: vec![3]
+            },
+        ]
+    );
+}
+
+#[test]
+fn fancy_fallback() {
+    let regex_tree = RegexTree::root(r"#(?P<num>\d+)")
+        .with_fancy_fallback(r"(?<=no\. )(?P<num>\d+)")
+        .build();
+
+    #[derive(Debug, Deserialize, PartialEq, Eq)]
+    struct Issue {
+        num: u32,
+    }
+
+    let issues: Vec<Issue> =
+        from_regex_tree_and_str(&regex_tree, "see #4 and no. 7, not 9").unwrap();
+    assert_eq!(issues, vec![Issue { num: 4 }, Issue { num: 7 }]);
+}
+
+#[test]
+fn flags_and_full_match() {
+    // A word containing an x
+    let regex_tree = RegexTree::fancy(r"(?=\w*x)\w+")
+        .with_case_insensitive(true)
+        .with_full_match(true)
+        .build();
+
+    let parsed: Option<&str> = from_regex_tree_and_str(&regex_tree, "abX").unwrap();
+    assert_eq!(parsed, Some("abX"));
+
+    let parsed: Option<&str> = from_regex_tree_and_str(&regex_tree, "abX!").unwrap();
+    assert_eq!(parsed, None);
+}
+
+#[test]
+fn validates() {
+    let regex_tree = RegexTree::fancy(r"(?P<name>\w+)=(?P<value>\w+)(?=;)").build();
+    assert!(validate::<Vec<Pair>>(&regex_tree).is_ok());
+}
@@ -0,0 +1,81 @@
+use recursive_regex::{from_regex_tree_and_str, records_from_regex_tree_and_str, RegexTree};
+use serde::Deserialize;
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Entry {
+    name: String,
+    tags: Vec<String>,
+}
+
+#[test]
+fn filters_top_level_matches() {
+    let regex_tree = RegexTree::leaf(r"(?P<n>\d+)");
+    let all: Vec<u32> = from_regex_tree_and_str(&regex_tree, "1 20 3 40").unwrap();
+    assert_eq!(all, vec![1, 20, 3, 40]);
+
+    let regex_tree = RegexTree::root(r"(?P<n>\d+)")
+        .with_filter(|groups| groups.name("n").is_some_and(|n| n.len() > 1))
+        .build();
+    let long: Vec<u32> = from_regex_tree_and_str(&regex_tree, "1 20 3 40").unwrap();
+    assert_eq!(long, vec![20, 40]);
+}
+
+#[test]
+fn filtered_lines_are_not_unmatched() {
+    let regex_tree = RegexTree::lines(r"(?P<name>\S+)")
+        .with_filter(|groups| groups.name("name") != Some("#"))
+        .build();
+    let records: Vec<_> = records_from_regex_tree_and_str::<(String,)>(&regex_tree, "a\n#\nb")
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(records, vec![("a".to_owned(),), ("b".to_owned(),)]);
+}
+
+#[test]
+fn filters_child_matches() {
+    let regex_tree = RegexTree::root(r"(?P<name>\w+): (?P<tags>.*)")
+        .with_child(
+            "tags",
+            RegexTree::root(r"(?P<tag>\w+)")
+                .with_filter(|groups| groups.name("tag") != Some("draft"))
+                .build(),
+        )
+        .build();
+    let entries: Vec<Entry> =
+        from_regex_tree_and_str(&regex_tree, "a: x draft y\nb: draft").unwrap();
+    assert_eq!(
+        entries,
+        vec![
+            Entry {
+                name: "a".to_owned(),
+                tags: vec!["x".to_owned(), "y".to_owned()],
+            },
+            Entry {
+                name: "b".to_owned(),
+                tags: vec![],
+            },
+        ]
+    );
+}
+
+#[test]
+fn single_value_from_first_kept_match() {
+    let regex_tree = RegexTree::root(r"(?P<n>\d+)")
+        .with_filter(|groups| groups.name("n") != Some("0"))
+        .build();
+    let first: Option<(u32,)> = from_regex_tree_and_str(&regex_tree, "0 0 7 8").unwrap();
+    assert_eq!(first, Some((7,)));
+
+    let none: Option<(u32,)> = from_regex_tree_and_str(&regex_tree, "0 0").unwrap();
+    assert_eq!(none, None);
+}
+
+#[test]
+fn all_filters_must_hold() {
+    let regex_tree = RegexTree::root(r"(?P<n>\d+)")
+        .with_filter(|groups| groups.name("n").is_some_and(|n| n.starts_with('1')))
+        .with_filter(|groups| groups.name("n").is_some_and(|n| n.ends_with('2')))
+        .build();
+    let numbers: Vec<u32> = from_regex_tree_and_str(&regex_tree, "12 13 22 102").unwrap();
+    assert_eq!(numbers, vec![12, 102]);
+}
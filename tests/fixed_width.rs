@@ -0,0 +1,140 @@
+use recursive_regex::{
+    from_regex_tree_and_str, from_regex_tree_and_str_with_options, DeserializeOptions, RegexTree,
+    Spanned,
+};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Account {
+    id: u32,
+    name: String,
+    balance: Option<i64>,
+}
+
+fn accounts() -> RegexTree {
+    RegexTree::fixed_width_named([("id", 0..4), ("name", 4..14), ("balance", 14..20)]).build()
+}
+
+#[test]
+fn named_columns() {
+    let text = "0001Alice       -120\n0002Bob          300\n";
+    let options = DeserializeOptions::default().trim();
+    let accounts: Vec<Account> =
+        from_regex_tree_and_str_with_options(&accounts(), text, &options).unwrap();
+    assert_eq!(
+        accounts,
+        vec![
+            Account {
+                id: 1,
+                name: "Alice".to_owned(),
+                balance: Some(-120),
+            },
+            Account {
+                id: 2,
+                name: "Bob".to_owned(),
+                balance: Some(300),
+            },
+        ]
+    );
+}
+
+#[test]
+fn short_line_misses_columns() {
+    let options = DeserializeOptions::default().trim();
+    let accounts: Vec<Account> =
+        from_regex_tree_and_str_with_options(&accounts(), "0003Carol", &options).unwrap();
+    assert_eq!(
+        accounts,
+        vec![Account {
+            id: 3,
+            name: "Carol".to_owned(),
+            balance: None,
+        }]
+    );
+}
+
+#[test]
+fn unnamed_columns() {
+    let regex_tree = RegexTree::fixed_width([0..2, 2..4, 4..6]).build();
+    let dates: Vec<(u32, u32, u32)> =
+        from_regex_tree_and_str(&regex_tree, "240115\r\n991231").unwrap();
+    assert_eq!(dates, vec![(24, 1, 15), (99, 12, 31)]);
+}
+
+#[test]
+fn columns_may_overlap_and_skip() {
+    let regex_tree = RegexTree::fixed_width([0..4, 2..4, 6..8]).build();
+    let fields: Vec<(&str, &str, &str)> = from_regex_tree_and_str(&regex_tree, "2024--07").unwrap();
+    assert_eq!(fields, vec![("2024", "24", "07")]);
+}
+
+#[test]
+fn spans_are_offsets_into_the_text() {
+    #[derive(Debug, Deserialize)]
+    struct Code<'a> {
+        #[serde(borrow)]
+        code: Spanned<&'a str>,
+    }
+
+    let regex_tree = RegexTree::fixed_width_named([("code", 2..5)]).build();
+    let codes: Vec<Code> = from_regex_tree_and_str(&regex_tree, "--abc\n--def").unwrap();
+    assert_eq!((codes[1].code.begin(), codes[1].code.end()), (8, 11));
+    assert_eq!(*codes[1].code.value(), "def");
+}
+
+#[test]
+fn column_splitting_a_character_does_not_match() {
+    let regex_tree = RegexTree::fixed_width(std::iter::once(0..1)).build();
+    let err = from_regex_tree_and_str::<Vec<&str>>(&regex_tree, "a\né").unwrap_err();
+    assert_eq!(err.span(), Some(2..4));
+}
+
+#[test]
+fn column_with_child() {
+    let regex_tree = RegexTree::fixed_width_named([("id", 0..3), ("tags", 3..20)])
+        .with_child("tags", RegexTree::leaf(r"\w+"))
+        .build();
+
+    #[derive(Debug, Deserialize, PartialEq, Eq)]
+    struct Item {
+        id: u32,
+        tags: Vec<String>,
+    }
+
+    let items: Vec<Item> = from_regex_tree_and_str(&regex_tree, "007red blue").unwrap();
+    assert_eq!(
+        items,
+        vec![Item {
+            id: 7,
+            tags: vec!["red".to_owned(), "blue".to_owned()],
+        }]
+    );
+}
+
+#[test]
+fn split_literal() {
+    let regex_tree = RegexTree::split_literal("a.b").build();
+    let pieces: Vec<&str> = from_regex_tree_and_str(&regex_tree, "1a.b2axb3a.b").unwrap();
+    assert_eq!(pieces, vec!["1", "2axb3", ""]);
+
+    let pieces: Vec<&str> = from_regex_tree_and_str(&regex_tree, "").unwrap();
+    assert!(pieces.is_empty());
+}
+
+#[test]
+fn split_literal_with_child() {
+    let regex_tree = RegexTree::split_literal(" | ")
+        .with_child_index(0, RegexTree::leaf(r"\d+"))
+        .build();
+    let rows: Vec<Vec<u32>> = from_regex_tree_and_str(&regex_tree, "1 2 | 3 | 4").unwrap();
+    assert_eq!(rows, vec![vec![1, 2], vec![3], vec![4]]);
+}
+
+#[cfg(feature = "deserialize-regex-tree")]
+#[test]
+fn split_literal_from_definition() {
+    let regex_tree: RegexTree =
+        serde_json::from_str(r#"{"regex": "\\d+", "split_literal": "."}"#).unwrap();
+    let parts: Vec<u32> = from_regex_tree_and_str(&regex_tree, "10.0.0.1").unwrap();
+    assert_eq!(parts, vec![10, 0, 0, 1]);
+}
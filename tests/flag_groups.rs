@@ -0,0 +1,124 @@
+use recursive_regex::{
+    from_regex_tree_and_str, from_regex_tree_and_str_with_options, to_value, DeserializeOptions,
+    RegexTree,
+};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Entry {
+    name: String,
+    deleted: bool,
+    pinned: bool,
+}
+
+fn entries() -> RegexTree {
+    RegexTree::lines(r"(?P<name>\w+)(?P<deleted> \(deleted\))?(?: pinned=(?P<pinned>\w+))?")
+        .with_flag("deleted")
+        .build()
+}
+
+#[test]
+fn presence_is_the_value() {
+    let entries: Vec<Entry> =
+        from_regex_tree_and_str(&entries(), "a (deleted) pinned=yes\nb pinned=no").unwrap();
+    assert_eq!(
+        entries,
+        vec![
+            Entry {
+                name: "a".to_owned(),
+                deleted: true,
+                pinned: true,
+            },
+            Entry {
+                name: "b".to_owned(),
+                deleted: false,
+                pinned: false,
+            },
+        ]
+    );
+}
+
+#[test]
+fn other_groups_still_parse_text() {
+    let err = from_regex_tree_and_str::<Vec<Entry>>(&entries(), "a").unwrap_err();
+    assert_eq!(err.to_string(), "[0]: missing field `pinned`");
+
+    let err = from_regex_tree_and_str::<Vec<Entry>>(&entries(), "a pinned=maybe").unwrap_err();
+    assert_eq!(err.span(), Some(9..14));
+}
+
+#[test]
+fn shared_key_is_present_if_any_group_is() {
+    #[derive(Debug, Deserialize, PartialEq, Eq)]
+    struct Line {
+        text: String,
+        marked: bool,
+    }
+
+    let regex_tree = RegexTree::lines(r"(?P<marked_0>\* )?(?P<text>\w+)(?P<marked_1> \*)?")
+        .with_alias("marked_0", "marked")
+        .with_alias("marked_1", "marked")
+        .with_flag("marked")
+        .build();
+    let lines: Vec<Line> = from_regex_tree_and_str(&regex_tree, "* a\nb *\nc\n* d *").unwrap();
+    let marked: Vec<bool> = lines.iter().map(|line| line.marked).collect();
+    assert_eq!(marked, vec![true, true, false, true]);
+}
+
+#[test]
+fn strict_ignores_absent_flags() {
+    #[derive(Debug, Deserialize, PartialEq, Eq)]
+    struct Name {
+        name: String,
+    }
+
+    let options = DeserializeOptions::new().strict();
+    let names: Vec<Name> = from_regex_tree_and_str_with_options(&entries(), "a", &options).unwrap();
+    assert_eq!(names[0].name, "a");
+
+    let err =
+        from_regex_tree_and_str_with_options::<Vec<Name>>(&entries(), "a (deleted)", &options)
+            .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "[0]: capture group `deleted` is not a field"
+    );
+    assert_eq!(err.span(), Some(1..11));
+}
+
+#[test]
+fn flags_are_bools_in_values() {
+    let value = to_value(&entries(), "a (deleted)").unwrap();
+    let record = &value.as_seq().unwrap()[0];
+    assert_eq!(record.get("deleted").unwrap().as_bool(), Some(true));
+    assert_eq!(record.get("pinned"), None);
+}
+
+#[cfg(feature = "deserialize-regex-tree")]
+#[test]
+fn deserialized_tree() {
+    let regex_tree: RegexTree = serde_json::from_str(
+        r#"{
+            "regex": "(?P<name>\\w+)(?P<deleted> \\(deleted\\))?(?: pinned=(?P<pinned>\\w+))?",
+            "lines": true,
+            "flags": ["deleted", "pinned"]
+        }"#,
+    )
+    .unwrap();
+    let entries: Vec<Entry> = from_regex_tree_and_str(&regex_tree, "a pinned=no\nb").unwrap();
+    assert_eq!(
+        entries,
+        vec![
+            Entry {
+                name: "a".to_owned(),
+                deleted: false,
+                pinned: true,
+            },
+            Entry {
+                name: "b".to_owned(),
+                deleted: false,
+                pinned: false,
+            },
+        ]
+    );
+}
@@ -0,0 +1,104 @@
+use recursive_regex::regex::{Regex, RegexBuilder};
+use recursive_regex::regex_tree::BuildError;
+use recursive_regex::{from_regex_tree_and_str, RegexTree};
+
+#[test]
+fn case_insensitive() {
+    let regex_tree = RegexTree::root(r"error|warn")
+        .with_case_insensitive(true)
+        .build();
+
+    let levels: Vec<&str> = from_regex_tree_and_str(&regex_tree, "ERROR Warn info").unwrap();
+    assert_eq!(levels, vec!["ERROR", "Warn"]);
+}
+
+#[test]
+fn inline_flags_override() {
+    let regex_tree = RegexTree::root(r"(?-i:error)|warn")
+        .with_case_insensitive(true)
+        .build();
+
+    let levels: Vec<&str> = from_regex_tree_and_str(&regex_tree, "ERROR error WARN").unwrap();
+    assert_eq!(levels, vec!["error", "WARN"]);
+}
+
+#[test]
+fn multi_line() {
+    let regex_tree = RegexTree::root(r"^\w+$").with_multi_line(true).build();
+
+    let words: Vec<&str> = from_regex_tree_and_str(&regex_tree, "one\ntwo three\nfour").unwrap();
+    assert_eq!(words, vec!["one", "four"]);
+}
+
+#[test]
+fn dot_matches_new_line() {
+    let regex_tree = RegexTree::root(r"<(?P<body>.*?)>")
+        .with_dot_matches_new_line(true)
+        .build();
+
+    let bodies: Vec<(&str, &str)> = from_regex_tree_and_str(&regex_tree, "<a\nb> <c>").unwrap();
+    assert_eq!(bodies, vec![("<a\nb>", "a\nb"), ("<c>", "c")]);
+}
+
+#[test]
+fn flags_apply_to_fallbacks_and_full_match() {
+    let regex_tree = RegexTree::root(r"yes")
+        .with_fallback(r"no")
+        .with_case_insensitive(true)
+        .with_full_match(true)
+        .build();
+
+    let answer: Option<&str> = from_regex_tree_and_str(&regex_tree, "NO").unwrap();
+    assert_eq!(answer, Some("NO"));
+
+    let answer: Option<&str> = from_regex_tree_and_str(&regex_tree, "NO!").unwrap();
+    assert_eq!(answer, None);
+}
+
+#[test]
+fn flags_apply_to_split_regexes() {
+    let regex_tree = RegexTree::split("x").with_case_insensitive(true).build();
+    let pieces: Vec<&str> = from_regex_tree_and_str(&regex_tree, "aXbxc").unwrap();
+    assert_eq!(pieces, vec!["a", "b", "c"]);
+
+    let regex_tree = RegexTree::frames(r"^start").with_multi_line(true).build();
+    let frames: Vec<&str> = from_regex_tree_and_str(&regex_tree, "start a\nstart b").unwrap();
+    assert_eq!(frames, vec!["start a", "start b"]);
+
+    let regex_tree = RegexTree::sections(["head", "tail"])
+        .with_case_insensitive(true)
+        .build();
+    let sections: (&str, &str) = from_regex_tree_and_str(&regex_tree, "HEAD Tail").unwrap();
+    assert_eq!(sections, ("HEAD", "Tail"));
+}
+
+#[cfg(feature = "deserialize-regex-tree")]
+#[test]
+fn split_flags_are_the_same_when_deserialized() {
+    let deserialized: RegexTree =
+        serde_json::from_str(r#"{"regex": "(?s).*", "split": "x", "case_insensitive": true}"#)
+            .unwrap();
+    let built = RegexTree::split("x").with_case_insensitive(true).build();
+    for regex_tree in [&deserialized, &built] {
+        let pieces: Vec<&str> = from_regex_tree_and_str(regex_tree, "aXbxc").unwrap();
+        assert_eq!(pieces, vec!["a", "b", "c"]);
+    }
+    assert_eq!(deserialized, built);
+}
+
+#[test]
+fn compiled_split_regexes_keep_their_flags() {
+    let delimiter = RegexBuilder::new("x")
+        .case_insensitive(true)
+        .build()
+        .unwrap();
+    let regex_tree = RegexTree::split(delimiter).build();
+    let pieces: Vec<&str> = from_regex_tree_and_str(&regex_tree, "aXbxc").unwrap();
+    assert_eq!(pieces, vec!["a", "b", "c"]);
+
+    let delimiter = Regex::new("x").unwrap();
+    let result = RegexTree::split(delimiter)
+        .with_case_insensitive(true)
+        .try_build();
+    assert!(matches!(result, Err(BuildError::CompiledRegex)));
+}
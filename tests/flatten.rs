@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+
+use recursive_regex::{from_regex_tree_and_str, RegexTree};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Address<'a> {
+    street: &'a str,
+    city: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Person<'a> {
+    name: &'a str,
+    age: u32,
+    #[serde(borrow, flatten)]
+    address: Address<'a>,
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Tagged<'a> {
+    name: &'a str,
+    #[serde(borrow, flatten)]
+    rest: HashMap<String, &'a str>,
+}
+
+#[test]
+fn main() {
+    let file = "Lina, 34, 1 Main St, Springfield [a b]
+Sven, 27, 22 Oak Ave, Shelbyville []";
+
+    let regex_tree = RegexTree::root(
+        r"(?P<name>\w+), (?P<age>\d+), (?P<street>[^,]+), (?P<city>[^\[]+) \[(?P<tags>.*)\]",
+    )
+    .with_child("tags", RegexTree::leaf(r"\w+"))
+    .build();
+
+    let people: Vec<Person> = from_regex_tree_and_str(&regex_tree, file).unwrap();
+    let expected = vec![
+        Person {
+            name: "Lina",
+            age: 34,
+            address: Address {
+                street: "1 Main St",
+                city: "Springfield".to_owned(),
+            },
+            tags: vec!["a".to_owned(), "b".to_owned()],
+        },
+        Person {
+            name: "Sven",
+            age: 27,
+            address: Address {
+                street: "22 Oak Ave",
+                city: "Shelbyville".to_owned(),
+            },
+            tags: vec![],
+        },
+    ];
+    assert_eq!(expected, people);
+}
+
+#[test]
+fn flatten_into_map() {
+    let regex_tree = RegexTree::leaf(r"(?P<name>\w+) (?P<color>\w+) (?P<size>\w+)");
+
+    let tagged: Tagged = from_regex_tree_and_str(&regex_tree, "shirt red large").unwrap();
+    assert_eq!(tagged.name, "shirt");
+    assert_eq!(
+        tagged.rest,
+        HashMap::from([("color".to_owned(), "red"), ("size".to_owned(), "large")])
+    );
+}
+
+#[test]
+fn unknown_groups_are_ignored() {
+    #[derive(Debug, Deserialize, PartialEq, Eq)]
+    struct Name<'a> {
+        name: &'a str,
+    }
+
+    let regex_tree = RegexTree::root(r"(?P<name>\w+) (?P<nums>.*)")
+        .with_child("nums", RegexTree::leaf(r"\d+"))
+        .build();
+
+    let names: Vec<Name> = from_regex_tree_and_str(&regex_tree, "a 1 2").unwrap();
+    assert_eq!(names, vec![Name { name: "a" }]);
+}
@@ -0,0 +1,97 @@
+use recursive_regex::{from_regex_tree_and_str, records_from_regex_tree_and_str, RegexTree};
+use serde::Deserialize;
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Entry {
+    time: String,
+    message: String,
+}
+
+fn log_tree() -> RegexTree {
+    RegexTree::frames(r"(?m)^\d\d:\d\d ")
+        .with_child_index(
+            0,
+            RegexTree::leaf(r"(?s)(?P<time>\d\d:\d\d) (?P<message>.*)"),
+        )
+        .build()
+}
+
+#[test]
+fn frames_span_several_lines() {
+    let text = "10:00 started\n10:05 failed:\n  at main\r\n\n10:06 stopped";
+    let entries: Vec<Entry> = from_regex_tree_and_str(&log_tree(), text).unwrap();
+    let messages: Vec<_> = entries.iter().map(|entry| entry.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        vec!["started", "failed:\n  at main\r\n", "stopped"]
+    );
+}
+
+#[test]
+fn blank_preamble_is_skipped() {
+    let entries: Vec<Entry> = from_regex_tree_and_str(&log_tree(), "\n \n10:00 up\n").unwrap();
+    assert_eq!(
+        entries,
+        vec![Entry {
+            time: "10:00".to_owned(),
+            message: "up".to_owned()
+        }]
+    );
+}
+
+#[test]
+fn preamble_is_a_frame() {
+    let regex_tree = RegexTree::frames("(?m)^#").build();
+    let frames: Vec<&str> = from_regex_tree_and_str(&regex_tree, "intro\n#a\n#b\n").unwrap();
+    assert_eq!(frames, vec!["intro", "#a", "#b"]);
+}
+
+#[test]
+fn empty_start_matches_make_no_empty_frames() {
+    let regex_tree = RegexTree::frames("(?m)^").build();
+    let frames: Vec<&str> = from_regex_tree_and_str(&regex_tree, "a\nb\n").unwrap();
+    assert_eq!(frames, vec!["a", "b"]);
+}
+
+#[test]
+fn paragraphs_are_separated_by_blank_lines() {
+    let regex_tree = RegexTree::paragraphs().build();
+    let text = "\n\nname: a\nsize: 1\n \t\n\nname: b\r\nsize: 2\r\n\n";
+    let paragraphs: Vec<&str> = from_regex_tree_and_str(&regex_tree, text).unwrap();
+    assert_eq!(paragraphs, vec!["name: a\nsize: 1", "name: b\r\nsize: 2"]);
+}
+
+#[test]
+fn paragraph_errors_name_the_paragraph() {
+    let regex_tree = RegexTree::paragraphs()
+        .with_child_index(0, RegexTree::leaf(r"(?P<n>\d+)"))
+        .build();
+    let records: Vec<_> =
+        records_from_regex_tree_and_str::<Vec<u8>>(&regex_tree, "1\n2\n\n300").collect();
+    assert_eq!(records[0].as_ref().unwrap(), &vec![1, 2]);
+    let error = records[1].as_ref().unwrap_err();
+    assert_eq!(error.span(), Some(5..8));
+}
+
+#[test]
+fn empty_text_has_no_frames() {
+    let frames: Vec<&str> = from_regex_tree_and_str(&log_tree(), "").unwrap_or_default();
+    assert!(frames.is_empty());
+    let paragraphs: Vec<&str> =
+        from_regex_tree_and_str(&RegexTree::paragraphs().build(), "\n\n").unwrap();
+    assert!(paragraphs.is_empty());
+}
+
+#[cfg(feature = "deserialize-regex-tree")]
+#[test]
+fn deserialized_tree() {
+    let regex_tree: RegexTree =
+        serde_json::from_str(r#"{ "regex": "(?s).*", "paragraphs": true }"#).unwrap();
+    let paragraphs: Vec<&str> = from_regex_tree_and_str(&regex_tree, "a\n\nb").unwrap();
+    assert_eq!(paragraphs, vec!["a", "b"]);
+
+    let regex_tree: RegexTree =
+        serde_json::from_str(r#"{ "regex": "(?s).*", "frames": "(?m)^>" }"#).unwrap();
+    let frames: Vec<&str> = from_regex_tree_and_str(&regex_tree, ">a\nb\n>c").unwrap();
+    assert_eq!(frames, vec![">a\nb", ">c"]);
+}
@@ -0,0 +1,106 @@
+use recursive_regex::{from_regex_tree_and_str, RegexTree};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Version {
+    major: u32,
+    minor: u32,
+}
+
+#[test]
+fn single_match_must_cover_text() {
+    let regex_tree = RegexTree::root(r"a|ab").with_full_match(true).build();
+
+    // Without anchoring, leftmost-first matching would take `a`
+    let parsed: Option<&str> = from_regex_tree_and_str(&regex_tree, "ab").unwrap();
+    assert_eq!(parsed, Some("ab"));
+
+    let parsed: Option<&str> = from_regex_tree_and_str(&regex_tree, "abc").unwrap();
+    assert_eq!(parsed, None);
+}
+
+#[test]
+fn sequence_is_the_whole_match() {
+    let regex_tree = RegexTree::root(r"(?P<nums>[\d ]*)")
+        .with_child("nums", RegexTree::leaf(r"\d+"))
+        .with_full_match(true)
+        .build();
+
+    let parsed: Vec<(&str, Vec<u32>)> = from_regex_tree_and_str(&regex_tree, "1 2").unwrap();
+    assert_eq!(parsed, vec![("1 2", vec![1, 2])]);
+
+    let error = from_regex_tree_and_str::<Vec<(&str, Vec<u32>)>>(&regex_tree, "1 x").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "regular expression does not match the whole text"
+    );
+}
+
+#[test]
+fn children_match_whole_captures() {
+    let regex_tree = RegexTree::root(r"(?P<version>\S+)")
+        .with_child(
+            "version",
+            RegexTree::root(r"(?P<major>\d+)\.(?P<minor>\d+)")
+                .with_full_match(true)
+                .build(),
+        )
+        .build();
+
+    let parsed: Vec<(&str, Version)> = from_regex_tree_and_str(&regex_tree, "1.2 3.4").unwrap();
+    assert_eq!(
+        parsed,
+        vec![
+            ("1.2", Version { major: 1, minor: 2 }),
+            ("3.4", Version { major: 3, minor: 4 }),
+        ]
+    );
+
+    let error =
+        from_regex_tree_and_str::<Vec<(&str, Version)>>(&regex_tree, "1.2 3.4x").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "[1][1]: regular expression does not match"
+    );
+}
+
+#[test]
+fn lines_must_match_whole() {
+    let regex_tree = RegexTree::lines(r"\d+").with_full_match(true).build();
+
+    let parsed: Vec<u32> = from_regex_tree_and_str(&regex_tree, "1\n23").unwrap();
+    assert_eq!(parsed, vec![1, 23]);
+
+    let error = from_regex_tree_and_str::<Vec<u32>>(&regex_tree, "1\n2 3").unwrap_err();
+    assert_eq!(error.to_string(), "line 2 does not match");
+}
+
+#[test]
+fn compiled_regexes_are_not_compiled_again() {
+    use recursive_regex::regex::RegexBuilder;
+    use recursive_regex::regex_tree::BuildError;
+
+    let regex = || {
+        RegexBuilder::new(r"a(?P<x>b)c")
+            .case_insensitive(true)
+            .build()
+            .unwrap()
+    };
+    let parsed: Vec<&str> = from_regex_tree_and_str(&RegexTree::leaf(regex()), "ABC").unwrap();
+    assert_eq!(parsed, vec!["ABC"]);
+
+    // Anchoring would compile the source again without the flag
+    let result = RegexTree::root(regex()).with_full_match(true).try_build();
+    assert!(matches!(result, Err(BuildError::CompiledRegex)));
+    let result = RegexTree::root(r"x")
+        .with_fallback(regex())
+        .with_size_limit(1 << 20)
+        .try_build();
+    assert!(matches!(result, Err(BuildError::CompiledRegex)));
+
+    let regex_tree = RegexTree::root(r"(?i)a(?P<x>b)c")
+        .with_full_match(true)
+        .build();
+    let parsed: Vec<&str> = from_regex_tree_and_str(&regex_tree, "ABC").unwrap();
+    assert_eq!(parsed, vec!["ABC"]);
+}
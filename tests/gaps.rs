@@ -0,0 +1,49 @@
+use recursive_regex::{get_gaps, get_uncaptured, get_uncaptured_spanned, RegexTree};
+
+fn regex_tree() -> RegexTree {
+    RegexTree::leaf(r"(?P<key>\w+)=(?P<value>\w+)")
+}
+
+const TEXT: &str = "  a=1 ; b=2\nc=3 ??\n\n  d=4 é!";
+
+#[test]
+fn spanned_pieces_are_the_uncaptured_text() {
+    let spanned: Vec<_> = get_uncaptured_spanned(&regex_tree(), TEXT).collect();
+    let pieces: Vec<&str> = get_uncaptured(&regex_tree(), TEXT).collect();
+    assert_eq!(spanned.len(), pieces.len());
+    for (spanned, piece) in spanned.iter().zip(pieces) {
+        assert_eq!(*spanned.value(), piece);
+        assert_eq!(spanned.substring(TEXT), piece);
+    }
+}
+
+#[test]
+fn gaps_have_lines_and_columns() {
+    let gaps = get_gaps(&regex_tree(), TEXT);
+    let found: Vec<_> = gaps
+        .iter()
+        .map(|gap| (gap.text(), gap.range(), gap.line(), gap.column()))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            ("  ", 0..2, 1, 1),
+            (" ; ", 5..8, 1, 6),
+            ("\n", 11..12, 1, 12),
+            (" ??\n\n  ", 15..22, 2, 4),
+            (" é!", 25..29, 4, 6),
+        ]
+    );
+    assert_eq!(gaps[4].start(), 25);
+    assert_eq!(gaps[4].end(), 29);
+}
+
+#[test]
+fn no_gaps() {
+    assert!(get_gaps(&regex_tree(), "a=1").is_empty());
+    assert!(get_gaps(&regex_tree(), "").is_empty());
+
+    let gaps = get_gaps(&regex_tree(), "\n\nnothing");
+    assert_eq!(gaps.len(), 1);
+    assert_eq!((gaps[0].line(), gaps[0].column()), (1, 1));
+}
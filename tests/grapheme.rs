@@ -0,0 +1,54 @@
+#![cfg(feature = "unicode-segmentation")]
+
+use recursive_regex::{
+    from_regex_tree_and_str, from_regex_tree_and_str_with_options, DeserializeOptions, Grapheme,
+    RegexTree,
+};
+
+#[test]
+fn graphemes_of_several_chars() {
+    let regex_tree = RegexTree::leaf(r"\S+");
+    let options = DeserializeOptions::new().graphemes();
+
+    let parsed: Vec<Grapheme> =
+        from_regex_tree_and_str_with_options(&regex_tree, "e\u{301} 🇨🇦 x", &options).unwrap();
+    let parsed: Vec<&str> = parsed.iter().map(Grapheme::as_str).collect();
+    assert_eq!(parsed, vec!["e\u{301}", "🇨🇦", "x"]);
+}
+
+#[test]
+fn more_than_one_grapheme_is_an_error() {
+    let regex_tree = RegexTree::leaf(r"\S+");
+    let options = DeserializeOptions::new().graphemes();
+
+    let error =
+        from_regex_tree_and_str_with_options::<Vec<Grapheme>>(&regex_tree, "a ab", &options)
+            .unwrap_err();
+    assert_eq!(error.message(), "got ab but expecting a single grapheme");
+    assert_eq!(error.span(), Some(2..4));
+}
+
+#[test]
+fn chars_still_work() {
+    let regex_tree = RegexTree::leaf(r"\S+");
+    let options = DeserializeOptions::new().graphemes();
+
+    let parsed: Vec<char> =
+        from_regex_tree_and_str_with_options(&regex_tree, "a é", &options).unwrap();
+    assert_eq!(parsed, vec!['a', 'é']);
+
+    assert!(
+        from_regex_tree_and_str_with_options::<Vec<char>>(&regex_tree, "e\u{301}", &options)
+            .is_err()
+    );
+}
+
+#[test]
+fn without_grapheme_mode() {
+    let regex_tree = RegexTree::leaf(r"\S+");
+
+    let parsed: Vec<Grapheme> = from_regex_tree_and_str(&regex_tree, "a").unwrap();
+    assert_eq!(parsed, vec![Grapheme::new("a").unwrap()]);
+
+    assert!(from_regex_tree_and_str::<Vec<Grapheme>>(&regex_tree, "e\u{301}").is_err());
+}
@@ -0,0 +1,112 @@
+use std::collections::{BTreeMap, HashMap};
+
+use recursive_regex::{from_regex_tree_and_str, validate, RegexTree, Spanned};
+use serde::Deserialize;
+
+const DESKTOP: &str = "\
+[Desktop Entry]
+Name=Editor
+Exec=edit %f
+
+[Desktop Action new]
+Name=New Window
+";
+
+fn desktop() -> RegexTree {
+    RegexTree::headed(r"(?m)^\[(?P<section>[^\]]+)\]\r?$")
+        .with_default_child(RegexTree::leaf(r"(?m)^(?P<key>\w+)=(?P<value>.*)$"))
+        .build()
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Entry {
+    key: String,
+    value: String,
+}
+
+#[test]
+fn sections_by_name() {
+    let sections: BTreeMap<String, Vec<Entry>> =
+        from_regex_tree_and_str(&desktop(), DESKTOP).unwrap();
+    let names: Vec<_> = sections.keys().collect();
+    assert_eq!(names, ["Desktop Action new", "Desktop Entry"]);
+    assert_eq!(sections["Desktop Entry"].len(), 2);
+    assert_eq!(
+        sections["Desktop Action new"],
+        vec![Entry {
+            key: "Name".to_owned(),
+            value: "New Window".to_owned()
+        }]
+    );
+}
+
+#[test]
+fn sections_without_children_are_text() {
+    let regex_tree = RegexTree::headed(r"(?m)^== (\w+) ==\r?$").build();
+    let text = "\n== intro ==\nHello.\n\n== outro ==\r\nBye.\r\n";
+    let sections: HashMap<&str, Spanned<&str>> =
+        from_regex_tree_and_str(&regex_tree, text).unwrap();
+    assert_eq!(*sections["intro"].value(), "Hello.\n");
+    assert_eq!(sections["intro"].substring(text), "Hello.\n");
+    assert_eq!(*sections["outro"].value(), "Bye.");
+    assert_eq!(sections["outro"].substring(text), "Bye.");
+}
+
+#[test]
+fn struct_fields_use_their_own_children() {
+    #[derive(Debug, Deserialize)]
+    struct Config {
+        ports: Vec<u16>,
+        #[serde(default)]
+        users: Vec<String>,
+    }
+
+    let regex_tree = RegexTree::headed(r"(?m)^\[(\w+)\]$")
+        .with_child("ports", RegexTree::leaf(r"\d+"))
+        .with_child("users", RegexTree::leaf(r"\w+"))
+        .build();
+    let config: Config = from_regex_tree_and_str(&regex_tree, "[ports]\n80, 443").unwrap();
+    assert_eq!(config.ports, vec![80, 443]);
+    assert!(config.users.is_empty());
+
+    let report = validate::<Config>(&regex_tree);
+    assert!(report.is_ok(), "{report}");
+}
+
+#[test]
+fn errors_name_the_section() {
+    let regex_tree = RegexTree::headed(r"(?m)^\[(\w+)\]$")
+        .with_child("ports", RegexTree::leaf(r"\S+"))
+        .build();
+    let text = "[ports]\n80 http\n";
+    let err = from_regex_tree_and_str::<HashMap<String, Vec<u16>>>(&regex_tree, text).unwrap_err();
+    assert_eq!(err.span(), Some(11..15));
+    assert!(err.to_string().starts_with("ports"), "{err}");
+}
+
+#[test]
+fn text_before_the_first_header() {
+    let text = "orphan=1\n[Desktop Entry]\nName=Editor\n";
+    let err = from_regex_tree_and_str::<HashMap<String, Vec<Entry>>>(&desktop(), text).unwrap_err();
+    assert_eq!(err.span(), Some(0..9));
+    assert_eq!(
+        err.message(),
+        "text before the first header is in no section"
+    );
+}
+
+#[cfg(feature = "deserialize-regex-tree")]
+#[test]
+fn deserialized_tree() {
+    let json = r#"{
+        "regex": "(?m)^\\[(\\w+)\\]$",
+        "headed": true,
+        "children": { "sizes": { "regex": "\\d+" } }
+    }"#;
+    let regex_tree: RegexTree = serde_json::from_str(json).unwrap();
+    assert!(regex_tree.schema().to_string().contains("headed sections"));
+
+    let sections: HashMap<String, Vec<u32>> =
+        from_regex_tree_and_str(&regex_tree, "[sizes]\n1 2\n").unwrap();
+    assert_eq!(sections["sizes"], vec![1, 2]);
+}
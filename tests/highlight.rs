@@ -0,0 +1,48 @@
+#![cfg(feature = "highlight")]
+
+use recursive_regex::{highlight, RegexTree};
+
+/// Replace the escapes with readable markers: `<n>` for the color of depth
+/// `n`, `<gap>` for text no match covers, and `</>` for a reset
+fn markers(highlighted: &str) -> String {
+    highlighted
+        .replace("\x1b[0m", "</>")
+        .replace("\x1b[2m", "<gap>")
+        .replace("\x1b[34m", "<0>")
+        .replace("\x1b[32m", "<1>")
+        .replace("\x1b[33m", "<2>")
+}
+
+#[test]
+fn depths_and_gaps() {
+    let regex_tree = RegexTree::lines(r"(?P<key>\w+)=(?P<values>.*)")
+        .with_child(
+            "values",
+            RegexTree::root(r"\[[^\]]*\]")
+                .with_child_index(0, RegexTree::leaf(r"\d+"))
+                .build(),
+        )
+        .build();
+
+    let text = "a=[1] x\n# note\nb=[]";
+    assert_eq!(
+        markers(&highlight(&regex_tree, text)),
+        "<0>a=</><1>[</><2>1</><1>]</><0> x</><gap>\n# note\n</><0>b=</><1>[]</>"
+    );
+}
+
+#[test]
+fn nothing_matches() {
+    let regex_tree = RegexTree::leaf(r"\d+");
+    assert_eq!(markers(&highlight(&regex_tree, "none")), "<gap>none</>");
+    assert_eq!(highlight(&regex_tree, ""), "");
+}
+
+#[test]
+fn multibyte_text() {
+    let regex_tree = RegexTree::leaf(r"\d+");
+    assert_eq!(
+        markers(&highlight(&regex_tree, "é1✓")),
+        "<gap>é</><0>1</><gap>✓</>"
+    );
+}
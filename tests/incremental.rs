@@ -0,0 +1,126 @@
+use proptest::prelude::*;
+use recursive_regex::{
+    from_regex_tree_and_str, from_regex_tree_and_str_with_options, reparse_records,
+    reparse_records_with_options, DeserializeOptions, Edit, RegexTree, Spanned,
+};
+
+type Record = Spanned<(String, u32)>;
+
+fn lines() -> RegexTree {
+    RegexTree::lines(r"(?P<key>\w+)=(?P<value>\d+)").build()
+}
+
+fn pairs() -> RegexTree {
+    RegexTree::root(r"(?P<key>\w+)=(?P<value>\d+)").build()
+}
+
+/// Apply the edit to `old`, returning the new text and the edit
+fn edit(old: &str, range: std::ops::Range<usize>, inserted: &str) -> (String, Edit) {
+    let mut new = old.to_owned();
+    new.replace_range(range.clone(), inserted);
+    (new, Edit::new(range, inserted.len()))
+}
+
+fn parse(regex_tree: &RegexTree, text: &str) -> Vec<Record> {
+    from_regex_tree_and_str(regex_tree, text).unwrap()
+}
+
+#[test]
+fn only_records_near_the_edit() {
+    let old = "a=1\nb=2\nc=3\nd=4\ne=5";
+    let mut records = parse(&lines(), old);
+
+    let (new, edit) = edit(old, 0..1, "aa");
+    let reparsed = reparse_records(&lines(), &new, &mut records, &edit).unwrap();
+    assert_eq!(reparsed, 0..2);
+    assert_eq!(records, parse(&lines(), &new));
+}
+
+#[test]
+fn inserted_records() {
+    let old = "a=1 b=2 c=3";
+    let mut records = parse(&pairs(), old);
+
+    let (new, edit) = edit(old, 4..4, "x=9 y=8 ");
+    let reparsed = reparse_records(&pairs(), &new, &mut records, &edit).unwrap();
+    assert_eq!(reparsed, 0..5);
+    assert_eq!(records, parse(&pairs(), &new));
+}
+
+#[test]
+fn deleted_records() {
+    let old = "a=1 b=2 c=3 d=4 e=5";
+    let mut records = parse(&pairs(), old);
+
+    let (new, edit) = edit(old, 4..12, "");
+    let reparsed = reparse_records(&pairs(), &new, &mut records, &edit).unwrap();
+    assert_eq!(reparsed, 0..3);
+    assert_eq!(records, parse(&pairs(), &new));
+}
+
+#[test]
+fn changed_neighbours_widen_the_region() {
+    // A semicolon at the end turns every word before it into one record
+    let regex_tree = RegexTree::root(r"(?:\w+ )*\w+;|\w+").build();
+    let old = "a b x c";
+    let mut records: Vec<Spanned<String>> = from_regex_tree_and_str(&regex_tree, old).unwrap();
+    assert_eq!(records.len(), 4);
+
+    let (new, edit) = edit(old, 7..7, ";");
+    let reparsed = reparse_records(&regex_tree, &new, &mut records, &edit).unwrap();
+    assert_eq!(reparsed, 0..1);
+    assert_eq!(records[0].substring(&new), "a b x c;");
+}
+
+#[test]
+fn records_are_kept_on_error() {
+    let old = "a=1\nb=2\nc=3";
+    let mut records = parse(&lines(), old);
+
+    let (new, edit) = edit(old, 6..7, "x");
+    let err = reparse_records(&lines(), &new, &mut records, &edit).unwrap_err();
+    assert_eq!(err.span(), Some(4..7));
+    assert_eq!(records, parse(&lines(), old));
+}
+
+#[test]
+fn with_base_offset() {
+    let options = DeserializeOptions::new().with_base_offset(100);
+    let old = "a=1\nb=2\nc=3";
+    let mut records: Vec<Record> =
+        from_regex_tree_and_str_with_options(&lines(), old, &options).unwrap();
+
+    let (new, edit) = edit(old, 4..5, "bbb");
+    reparse_records_with_options(&lines(), &new, &mut records, &edit, &options).unwrap();
+    let expected: Vec<Record> =
+        from_regex_tree_and_str_with_options(&lines(), &new, &options).unwrap();
+    assert_eq!(records, expected);
+    assert_eq!(records[2].begin(), 110);
+}
+
+#[test]
+fn shift() {
+    let edit = Edit::new(4..6, 5);
+    assert_eq!(edit.shift(3), 3);
+    assert_eq!(edit.shift(4), 4);
+    assert_eq!(edit.shift(5), 9);
+    assert_eq!(edit.shift(6), 9);
+    assert_eq!(edit.shift(10), 13);
+}
+
+proptest! {
+    #[test]
+    fn same_as_a_full_parse(
+        old in "[a-c=0-9 ]{0,30}",
+        start in 0..=30usize,
+        len in 0..5usize,
+        inserted in "[a-c=0-9 ]{0,6}",
+    ) {
+        let start = start.min(old.len());
+        let end = (start + len).min(old.len());
+        let mut records = parse(&pairs(), &old);
+        let (new, edit) = edit(&old, start..end, &inserted);
+        reparse_records(&pairs(), &new, &mut records, &edit).unwrap();
+        prop_assert_eq!(records, parse(&pairs(), &new));
+    }
+}
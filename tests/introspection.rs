@@ -0,0 +1,29 @@
+use recursive_regex::RegexTree;
+
+#[test]
+fn main() {
+    let regex_tree = RegexTree::root(r"(?P<name>\w+) (?P<pairs>.*)")
+        .with_child(
+            "pairs",
+            RegexTree::root(r"(?P<key>\w+)=(?P<value>\w+)")
+                .with_child("value", RegexTree::leaf(r"\d"))
+                .build(),
+        )
+        .build();
+
+    assert_eq!(regex_tree.pattern(), r"(?P<name>\w+) (?P<pairs>.*)");
+    assert_eq!(
+        regex_tree.capture_names().collect::<Vec<_>>(),
+        vec![None, Some("name"), Some("pairs")]
+    );
+    assert_eq!(regex_tree.depth(), 3);
+
+    let children: Vec<_> = regex_tree.children().map(|(name, _)| name).collect();
+    assert_eq!(children, vec!["pairs"]);
+
+    let pairs = regex_tree.child("pairs").unwrap();
+    assert_eq!(pairs.pattern(), r"(?P<key>\w+)=(?P<value>\w+)");
+    assert_eq!(pairs.depth(), 2);
+    assert!(regex_tree.child("name").is_none());
+    assert_eq!(pairs.child("value").unwrap().children().count(), 0);
+}
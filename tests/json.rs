@@ -0,0 +1,90 @@
+use recursive_regex::{
+    from_regex_tree_and_str, from_regex_tree_and_str_with_options, DeserializeOptions, RegexTree,
+};
+use serde_json::{json, Value};
+
+fn inventory() -> RegexTree {
+    RegexTree::lines(r"(?P<item>\w+): (?P<counts>\d+(?: \d+)*)(?: \((?P<note>[^)]*)\))?")
+        .with_child("counts", RegexTree::leaf(r"\d+"))
+        .build()
+}
+
+#[test]
+fn named_groups_are_objects() {
+    let value: Value =
+        from_regex_tree_and_str(&inventory(), "apple: 3 4 (fresh)\npear: 12").unwrap();
+    assert_eq!(
+        value,
+        json!([
+            {"item": "apple", "counts": ["3", "4"], "note": "fresh"},
+            {"item": "pear", "counts": ["12"]},
+        ])
+    );
+}
+
+#[test]
+fn leaves_are_strings() {
+    let value: Value = from_regex_tree_and_str(&RegexTree::leaf(r"\w+"), "a 1").unwrap();
+    assert_eq!(value, json!(["a", "1"]));
+
+    let value: Value = from_regex_tree_and_str(&RegexTree::leaf(r"\w+"), "").unwrap();
+    assert_eq!(value, json!([]));
+}
+
+#[test]
+fn nested_sequences_are_arrays() {
+    let regex_tree = RegexTree::lines(".*")
+        .with_child_index(0, RegexTree::leaf(r"\w+"))
+        .build();
+    let value: Value = from_regex_tree_and_str(&regex_tree, "a b\nc").unwrap();
+    assert_eq!(value, json!([["a", "b"], ["c"]]));
+}
+
+#[test]
+fn types_are_inferred() {
+    let options = DeserializeOptions::default().infer_types();
+    let text = "apple: 3 4 (1.5)\npear: 18446744073709551616 (-2)\nfig: 07 (1e3)\nkiwi: 1 (true)";
+    let value: Value = from_regex_tree_and_str_with_options(&inventory(), text, &options).unwrap();
+    assert_eq!(
+        value,
+        json!([
+            {"item": "apple", "counts": [3, 4], "note": 1.5},
+            {"item": "pear", "counts": ["18446744073709551616"], "note": -2},
+            {"item": "fig", "counts": ["07"], "note": 1000.0},
+            {"item": "kiwi", "counts": [1], "note": true},
+        ])
+    );
+}
+
+#[test]
+fn inference_follows_trim() {
+    let regex_tree = RegexTree::split_literal(",").build();
+    let options = DeserializeOptions::default().infer_types();
+    let value: Value = from_regex_tree_and_str_with_options(&regex_tree, "1, 2", &options).unwrap();
+    assert_eq!(value, json!([1, " 2"]));
+
+    let options = options.trim();
+    let value: Value = from_regex_tree_and_str_with_options(&regex_tree, "1, 2", &options).unwrap();
+    assert_eq!(value, json!([1, 2]));
+}
+
+#[test]
+fn typed_fields_are_unaffected() {
+    let options = DeserializeOptions::default().infer_types();
+    let codes: Vec<String> =
+        from_regex_tree_and_str_with_options(&RegexTree::leaf(r"\d+"), "12 007", &options).unwrap();
+    assert_eq!(codes, vec!["12", "007"]);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn value_converts_to_json() {
+    use recursive_regex::to_value_with_options;
+
+    let options = DeserializeOptions::default().infer_types();
+    let value = to_value_with_options(&inventory(), "apple: 3 (True)", &options).unwrap();
+    assert_eq!(
+        Value::from(value),
+        json!([{"item": "apple", "counts": [3], "note": "True"}])
+    );
+}
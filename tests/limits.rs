@@ -0,0 +1,82 @@
+use std::time::{Duration, Instant};
+
+use recursive_regex::{
+    from_regex_tree_and_str_with_options, records_from_regex_tree_and_str_with_options,
+    DeserializeOptions, RegexTree,
+};
+
+fn regex_tree() -> RegexTree {
+    RegexTree::lines(r"(?P<name>\w+): (?P<nums>.*)")
+        .with_child("nums", RegexTree::leaf(r"\d+"))
+        .build()
+}
+
+type Lines = Vec<(String, Vec<u32>)>;
+
+#[test]
+fn within_limits() {
+    let options = DeserializeOptions::new()
+        .with_deadline(Instant::now() + Duration::from_secs(60))
+        .with_scan_limit(1000);
+    let lines: Lines =
+        from_regex_tree_and_str_with_options(&regex_tree(), "a: 1 2\nb: 3", &options).unwrap();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(options.bytes_scanned(), Some(11 + 3 + 1));
+}
+
+#[test]
+fn deadline_passed() {
+    let options = DeserializeOptions::new().with_deadline(Instant::now());
+    let err =
+        from_regex_tree_and_str_with_options::<Lines>(&regex_tree(), "a: 1", &options).unwrap_err();
+    assert_eq!(
+        err.message(),
+        "deadline passed before the text was deserialized"
+    );
+    assert_eq!(err.span(), Some(0..4));
+}
+
+#[test]
+fn scan_limit_exceeded_by_child() {
+    let options = DeserializeOptions::new().with_scan_limit(12);
+    let err =
+        from_regex_tree_and_str_with_options::<Lines>(&regex_tree(), "a: 1 2\nb: 3", &options)
+            .unwrap_err();
+    assert_eq!(err.to_string(), "[0][1]: scan limit of 12 bytes exceeded");
+    assert_eq!(err.span(), Some(3..6));
+}
+
+#[test]
+fn scan_limit_is_shared_by_clones() {
+    let options = DeserializeOptions::new().with_scan_limit(10);
+    let text = "a: 1";
+    for _ in 0..2 {
+        let _: Lines =
+            from_regex_tree_and_str_with_options(&regex_tree(), text, &options.clone()).unwrap();
+    }
+    assert_eq!(options.bytes_scanned(), Some(10));
+    assert!(from_regex_tree_and_str_with_options::<Lines>(&regex_tree(), text, &options).is_err());
+
+    // A fresh limit starts counting again
+    let options = options.with_scan_limit(10);
+    assert!(from_regex_tree_and_str_with_options::<Lines>(&regex_tree(), text, &options).is_ok());
+}
+
+#[test]
+fn records_stop_at_the_limit() {
+    let options = DeserializeOptions::new().with_scan_limit(11);
+    let regex_tree = regex_tree();
+    let results: Vec<Result<(String, Vec<u32>), _>> =
+        records_from_regex_tree_and_str_with_options(&regex_tree, "a: 1 2\nb: 3", &options)
+            .collect();
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(Result::is_err));
+}
+
+#[test]
+fn no_limits_by_default() {
+    let options = DeserializeOptions::new();
+    assert_eq!(options.deadline(), None);
+    assert_eq!(options.scan_limit(), None);
+    assert_eq!(options.bytes_scanned(), None);
+}
@@ -0,0 +1,45 @@
+use recursive_regex::{from_regex_tree_and_str, get_uncaptured, RegexTree, Spanned};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Entry<'a> {
+    #[serde(borrow)]
+    key: Spanned<&'a str>,
+    values: Vec<u32>,
+}
+
+fn regex_tree() -> RegexTree {
+    RegexTree::lines(r"^(?P<key>\w+): (?P<values>.*)$")
+        .with_child("values", RegexTree::leaf(r"\d+"))
+        .build()
+}
+
+#[test]
+fn one_record_per_line() {
+    let entries: Vec<Entry> = from_regex_tree_and_str(&regex_tree(), "a: 1 2\r\nbc: 3\n").unwrap();
+    assert_eq!(
+        entries,
+        vec![
+            Entry {
+                key: Spanned::new_raw("a", 0, 1),
+                values: vec![1, 2],
+            },
+            Entry {
+                key: Spanned::new_raw("bc", 8, 10),
+                values: vec![3],
+            },
+        ]
+    );
+}
+
+#[test]
+fn unmatched_line_is_an_error() {
+    let error = from_regex_tree_and_str::<Vec<Entry>>(&regex_tree(), "a: 1\n\nb: 2").unwrap_err();
+    assert_eq!(error.to_string(), "line 2 does not match");
+}
+
+#[test]
+fn uncaptured_includes_unmatched_lines() {
+    let uncaptured: Vec<_> = get_uncaptured(&regex_tree(), "a: 1\noops\nb: 2").collect();
+    assert_eq!(uncaptured, vec!["\noops\n"]);
+}
@@ -0,0 +1,66 @@
+use recursive_regex::{
+    from_regex_tree_and_bytes_lossy, from_regex_tree_and_bytes_lossy_with_options,
+    from_regex_tree_and_os_str_lossy, DeserializeOptions, RegexTree, Spanned,
+};
+
+#[test]
+fn valid_input_is_unchanged() {
+    let words: Vec<String> =
+        from_regex_tree_and_bytes_lossy(&RegexTree::leaf(r"\S+"), "né à".as_bytes()).unwrap();
+    assert_eq!(words, vec!["né", "à"]);
+}
+
+#[test]
+fn each_invalid_byte_is_substituted() {
+    // A lone continuation byte, and a three byte character cut short
+    let bytes = b"a\x80b \xe2\x82";
+    let words: Vec<String> =
+        from_regex_tree_and_bytes_lossy(&RegexTree::leaf(r"\S+"), bytes).unwrap();
+    assert_eq!(words, vec!["a\u{1A}b", "\u{1A}\u{1A}"]);
+}
+
+#[test]
+fn spans_index_the_input() {
+    let bytes = b"\xff\xfe x=1 y=2";
+    let regex_tree = RegexTree::leaf(r"(?P<key>\w)=(?P<value>\d)");
+
+    let pairs: Vec<(Spanned<String>, Spanned<u32>)> =
+        from_regex_tree_and_bytes_lossy(&regex_tree, bytes).unwrap();
+    let (key, value) = &pairs[1];
+    assert_eq!(&bytes[key.begin()..key.end()], b"y");
+    assert_eq!(&bytes[value.begin()..value.end()], b"2");
+}
+
+#[test]
+fn error_spans_index_the_input() {
+    let bytes = b"caf\xe9 1 x";
+    let options = DeserializeOptions::new().with_source_name("menu.txt");
+    let err = from_regex_tree_and_bytes_lossy_with_options::<Vec<u32>>(
+        &RegexTree::leaf(r"[\dx]+"),
+        bytes,
+        &options,
+    )
+    .unwrap_err();
+    assert_eq!(&bytes[err.span().unwrap()], b"x");
+    assert_eq!(err.source_name(), Some("menu.txt"));
+}
+
+#[test]
+fn os_str() {
+    let path = std::path::Path::new("backup-3.tar");
+    let numbers: Vec<u32> =
+        from_regex_tree_and_os_str_lossy(&RegexTree::leaf(r"\d+"), path.as_os_str()).unwrap();
+    assert_eq!(numbers, vec![3]);
+}
+
+#[cfg(unix)]
+#[test]
+fn os_str_with_invalid_bytes() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let name = OsStr::from_bytes(b"report-\xff-7.txt");
+    let parts: Vec<String> =
+        from_regex_tree_and_os_str_lossy(&RegexTree::split("-").build(), name).unwrap();
+    assert_eq!(parts, vec!["report", "\u{1A}", "7.txt"]);
+}
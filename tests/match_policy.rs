@@ -0,0 +1,43 @@
+use recursive_regex::regex_tree::MatchPolicy;
+use recursive_regex::{from_regex_tree_and_str, get_uncaptured, RegexTree};
+
+fn tokens() -> RegexTree {
+    RegexTree::root(r"[a-z]+ ?")
+        .with_fallback(r"\d+ ?")
+        .with_match_policy(MatchPolicy::Consecutive)
+        .build()
+}
+
+#[test]
+fn consecutive_tokens() {
+    let parsed: Vec<&str> = from_regex_tree_and_str(&tokens(), "let x 1").unwrap();
+    assert_eq!(parsed, vec!["let ", "x ", "1"]);
+}
+
+#[test]
+fn gaps_are_errors() {
+    let error = from_regex_tree_and_str::<Vec<&str>>(&tokens(), "let = 1").unwrap_err();
+    assert_eq!(error.message(), "text between matches is not matched");
+    assert_eq!(error.span(), Some(4..6));
+
+    let error = from_regex_tree_and_str::<Vec<&str>>(&tokens(), "=let").unwrap_err();
+    assert_eq!(error.span(), Some(0..1));
+
+    let error = from_regex_tree_and_str::<Vec<&str>>(&tokens(), "let;").unwrap_err();
+    assert_eq!(error.span(), Some(3..4));
+}
+
+#[test]
+fn gaps_are_uncaptured() {
+    let uncaptured: Vec<_> = get_uncaptured(&tokens(), "let = 1;").collect();
+    assert_eq!(uncaptured, vec!["= ", ";"]);
+}
+
+#[test]
+fn overlapping() {
+    let regex_tree = RegexTree::root(r"aba")
+        .with_match_policy(MatchPolicy::Overlapping)
+        .build();
+    let parsed: Vec<&str> = from_regex_tree_and_str(&regex_tree, "ababa").unwrap();
+    assert_eq!(parsed, vec!["aba", "aba"]);
+}
@@ -0,0 +1,104 @@
+use recursive_regex::{
+    from_regex_tree_and_str, from_regex_tree_and_str_with_options, DeserializeOptions, RegexTree,
+};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct Entry {
+    name: String,
+    nums: Vec<u32>,
+    note: Option<String>,
+}
+
+fn regex_tree() -> RegexTree {
+    RegexTree::root(r"(?P<name>\w+): (?P<nums>[\d ]*)(?P<note>#.*)?")
+        .with_child("nums", RegexTree::leaf(r"\d+"))
+        .with_child("note", RegexTree::leaf(r"#(?P<text>\w+)"))
+        .build()
+}
+
+const TEXT: &str = "a: 1 2\n-- skipped --\nb: 34 #x";
+
+#[test]
+fn off_by_default() {
+    let options = DeserializeOptions::new();
+    assert!(!options.is_collect_stats());
+    assert!(options.stats().is_none());
+    from_regex_tree_and_str::<Vec<Entry>>(&regex_tree(), TEXT).unwrap();
+}
+
+#[test]
+fn counts_per_node() {
+    let regex_tree = regex_tree();
+    let options = DeserializeOptions::new().collect_stats();
+    from_regex_tree_and_str_with_options::<Vec<Entry>>(&regex_tree, TEXT, &options).unwrap();
+    let stats = options.stats().unwrap();
+
+    let root = stats.node(&regex_tree).unwrap();
+    assert_eq!(root.runs(), 1);
+    assert_eq!(root.matches(), 2);
+    assert_eq!(root.bytes_searched(), TEXT.len());
+    assert_eq!(root.bytes_matched(), "a: 1 2".len() + "b: 34 #x".len());
+    assert_eq!(root.bytes_skipped(), "\n-- skipped --\n".len());
+
+    let nums = stats.node(regex_tree.child("nums").unwrap()).unwrap();
+    assert_eq!((nums.runs(), nums.matches()), (2, 3));
+    assert_eq!(nums.bytes_searched(), "1 2".len() + "34 ".len());
+    assert_eq!(nums.bytes_matched(), 4);
+
+    let paths: Vec<_> = stats
+        .nodes(&regex_tree)
+        .into_iter()
+        .map(|(path, node)| (path, node.matches()))
+        .collect();
+    assert_eq!(
+        paths,
+        vec![
+            (vec![], 2),
+            (vec!["note".to_owned()], 1),
+            (vec!["nums".to_owned()], 3),
+        ]
+    );
+}
+
+#[test]
+fn unmatched_runs() {
+    let regex_tree = RegexTree::leaf(r"(?P<value>\d+)");
+    let options = DeserializeOptions::new().collect_stats();
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Value {
+        value: u32,
+    }
+
+    let value: Option<Value> =
+        from_regex_tree_and_str_with_options(&regex_tree, "none", &options).unwrap();
+    assert!(value.is_none());
+
+    let stats = options.stats().unwrap();
+    let node = stats.node(&regex_tree).unwrap();
+    assert_eq!((node.runs(), node.matches()), (1, 0));
+    assert_eq!(node.bytes_skipped(), 4);
+}
+
+#[test]
+fn adds_up_across_calls_and_clones() {
+    let regex_tree = RegexTree::leaf(r"\d+");
+    let options = DeserializeOptions::new().collect_stats();
+    let clone = options.clone();
+
+    from_regex_tree_and_str_with_options::<Vec<u32>>(&regex_tree, "1 2", &options).unwrap();
+    from_regex_tree_and_str_with_options::<Vec<u32>>(&regex_tree, "3", &clone).unwrap();
+
+    let stats = options.stats().unwrap();
+    assert_eq!(stats, clone.stats().unwrap());
+    let node = stats.node(&regex_tree).unwrap();
+    assert_eq!((node.runs(), node.matches()), (2, 3));
+
+    // Fresh options gather separately
+    let other = DeserializeOptions::new().collect_stats();
+    assert!(other.stats().unwrap().node(&regex_tree).is_none());
+    assert_ne!(options, other);
+}
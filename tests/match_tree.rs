@@ -0,0 +1,49 @@
+use recursive_regex::RegexTree;
+
+#[test]
+fn groups_and_patterns() {
+    let regex_tree = RegexTree::root(r"(?P<key>\w+)=(\d+)")
+        .with_fallback(r"(?P<key>\w+)")
+        .build();
+
+    let matches: Vec<_> = regex_tree.matches("a=1 b").collect();
+    assert_eq!(matches.len(), 2);
+
+    assert_eq!(matches[0].pattern(), r"(?P<key>\w+)=(\d+)");
+    let groups: Vec<_> = matches[0]
+        .groups()
+        .iter()
+        .map(|group| (group.index(), group.name(), group.as_str(), group.range()))
+        .collect();
+    assert_eq!(
+        groups,
+        vec![
+            (0, None, "a=1", 0..3),
+            (1, Some("key"), "a", 0..1),
+            (2, None, "1", 2..3),
+        ]
+    );
+    assert!(matches[0].groups()[2].matches().is_none());
+
+    assert_eq!(matches[1].pattern(), r"(?P<key>\w+)");
+    assert_eq!(matches[1].group("key").unwrap().range(), 4..5);
+}
+
+#[test]
+fn nested_spans_are_absolute() {
+    let regex_tree = RegexTree::split(";")
+        .with_child_index(
+            0,
+            RegexTree::root(r"(?P<nums>.*)")
+                .with_child("nums", RegexTree::leaf(r"\d+"))
+                .build(),
+        )
+        .build();
+
+    let matches: Vec<_> = regex_tree.matches("1 2;34").collect();
+    let pieces = matches[1].groups()[0].matches().unwrap();
+    let nums = pieces[0].group("nums").unwrap().matches().unwrap();
+    assert_eq!(nums.len(), 1);
+    assert_eq!(nums[0].as_str(), "34");
+    assert_eq!(nums[0].range(), 4..6);
+}
@@ -0,0 +1,71 @@
+use recursive_regex::{from_regex_tree_and_str, validate, Matched, RegexTree, Spanned};
+use serde::Deserialize;
+
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+struct Entry {
+    level: String,
+    code: u32,
+}
+
+fn entries() -> RegexTree {
+    RegexTree::lines(r"\[(?P<level>\w+)\]\s+code=(?P<code>\d+).*").build()
+}
+
+#[test]
+fn records_keep_their_lines() {
+    let text = "[warn]  code=7 disk\n[info] code=1";
+    let mut entries: Vec<Matched<Entry>> = from_regex_tree_and_str(&entries(), text).unwrap();
+    assert_eq!(entries[0].text(), "[warn]  code=7 disk");
+    assert_eq!(
+        entries.remove(1).into_parts(),
+        (
+            Entry {
+                level: "info".to_owned(),
+                code: 1
+            },
+            "[info] code=1".to_owned()
+        )
+    );
+}
+
+#[test]
+fn borrowed_text_of_groups() {
+    #[derive(Debug, Deserialize)]
+    struct Raw<'a> {
+        #[serde(borrow)]
+        code: Matched<u32, &'a str>,
+    }
+
+    let entries: Vec<Raw> = from_regex_tree_and_str(&entries(), "[warn] code=007").unwrap();
+    assert_eq!(*entries[0].code.value(), 7);
+    assert_eq!(*entries[0].code.text(), "007");
+}
+
+#[test]
+fn text_of_children() {
+    #[derive(Debug, Deserialize)]
+    struct Line<'a> {
+        #[serde(borrow)]
+        nums: Matched<Vec<u32>, &'a str>,
+    }
+
+    let regex_tree = RegexTree::root(r"nums: (?P<nums>.*)")
+        .with_child("nums", RegexTree::leaf(r"\d+"))
+        .build();
+    let line: Line = from_regex_tree_and_str(&regex_tree, "nums: 1, 2").unwrap();
+    assert_eq!(line.nums.value(), &[1, 2]);
+    assert_eq!(*line.nums.text(), "1, 2");
+}
+
+#[test]
+fn matched_and_spanned() {
+    let entries: Vec<Spanned<Matched<Entry>>> =
+        from_regex_tree_and_str(&entries(), "[warn] code=2\n[info] code=1").unwrap();
+    assert_eq!((entries[1].begin(), entries[1].end()), (14, 27));
+    assert_eq!(entries[1].value().text(), "[info] code=1");
+}
+
+#[test]
+fn validate_looks_through_matched() {
+    assert!(validate::<Vec<Matched<Entry>>>(&entries()).is_ok());
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "miette")]
+
+use miette::{Diagnostic, GraphicalReportHandler, GraphicalTheme, NamedSource, Report};
+use recursive_regex::{
+    from_regex_tree_and_str, from_regex_tree_and_str_with_options, DeserializeOptions, RegexTree,
+};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct Setting {
+    key: String,
+    value: u32,
+}
+
+fn settings() -> RegexTree {
+    RegexTree::lines(r"(?P<key>\w+)=(?P<value>\S+)").build()
+}
+
+fn render(report: &Report) -> String {
+    let mut out = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::unicode_nocolor())
+        .with_width(80)
+        .render_report(&mut out, report.as_ref())
+        .unwrap();
+    out
+}
+
+#[test]
+fn label_covers_the_span() {
+    let error =
+        from_regex_tree_and_str::<Vec<Setting>>(&settings(), "width=80\nheight=x").unwrap_err();
+    let labels: Vec<_> = error.labels().unwrap().collect();
+    assert_eq!(labels.len(), 1);
+    assert_eq!((labels[0].offset(), labels[0].len()), (16, 1));
+    assert_eq!(labels[0].label(), None);
+}
+
+#[test]
+fn errors_without_spans_have_no_labels() {
+    let error = from_regex_tree_and_str::<Setting>(&settings(), "nothing here").unwrap_err();
+    assert_eq!(error.span(), None);
+    assert!(error.labels().is_none());
+}
+
+#[test]
+fn report_shows_the_snippet() {
+    let text = "width=80\nheight=x\n";
+    let options = DeserializeOptions::new().with_source_name("config.ini");
+    let error = from_regex_tree_and_str_with_options::<Vec<Setting>>(&settings(), text, &options)
+        .unwrap_err();
+
+    let report = Report::new(error).with_source_code(NamedSource::new("config.ini", text));
+    let rendered = render(&report);
+    assert!(
+        rendered.contains("config.ini: [1].value: parsing error: invalid digit found in string"),
+        "{rendered}"
+    );
+    assert!(rendered.contains("[config.ini:2:8]"), "{rendered}");
+    assert!(rendered.contains("height=x"), "{rendered}");
+}
+
+#[test]
+fn base_offset_points_into_the_document() {
+    let document = "[section]\nwidth=80\nheight=x\n";
+    let options = DeserializeOptions::new().with_base_offset(10);
+    let error = from_regex_tree_and_str_with_options::<Vec<Setting>>(
+        &settings(),
+        &document[10..],
+        &options,
+    )
+    .unwrap_err();
+
+    let report = Report::new(error).with_source_code(document);
+    let rendered = render(&report);
+    assert!(rendered.contains("3 │ height=x"), "{rendered}");
+}
@@ -0,0 +1,110 @@
+use recursive_regex::{
+    from_regex_tree_and_str, from_regex_tree_and_str_with_options, DeserializeOptions, RegexTree,
+};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct Pair {
+    key: String,
+    value: u32,
+}
+
+fn options() -> DeserializeOptions {
+    DeserializeOptions::new().near_misses()
+}
+
+#[test]
+fn off_by_default() {
+    let regex_tree = RegexTree::leaf(r"(?P<key>\w+)=(?P<value>\d+)");
+    let error = from_regex_tree_and_str::<Pair>(&regex_tree, "a:1").unwrap_err();
+    assert_eq!(error.message(), "regular expression does not match");
+}
+
+#[test]
+fn furthest_fallback() {
+    let regex_tree = RegexTree::root(r"(?P<key>\w+)=(?P<value>\d+)")
+        .with_fallback(r"(?P<key>\w+): (?P<value>\d+)")
+        .build();
+    let error = from_regex_tree_and_str_with_options::<Pair>(&regex_tree, "x abc: ?", &options())
+        .unwrap_err();
+    assert_eq!(
+        error.message(),
+        r#"regular expression does not match; the longest start of a pattern that matches is `(?P<key>\w+): `, which matches "abc: ""#
+    );
+    assert_eq!(error.span(), Some(2..7));
+}
+
+#[test]
+fn flags_and_full_match() {
+    let regex_tree = RegexTree::root(r"key=(?P<value>\d+)")
+        .with_case_insensitive(true)
+        .with_full_match(true)
+        .build();
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Value {
+        value: u32,
+    }
+
+    let error = from_regex_tree_and_str_with_options::<Value>(&regex_tree, "KEY=12!", &options())
+        .unwrap_err();
+    assert!(
+        error.message().contains(r#"which matches "KEY=12""#),
+        "{}",
+        error.message()
+    );
+    assert_eq!(error.span(), Some(0..6));
+}
+
+#[test]
+fn within_child() {
+    let regex_tree = RegexTree::root(r"(?P<name>\w+) \((?P<pair>[^)]*)\)")
+        .with_child("pair", RegexTree::leaf(r"(?P<key>\w+)=(?P<value>\d+)"))
+        .build();
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Entry {
+        name: String,
+        pair: Pair,
+    }
+
+    let error = from_regex_tree_and_str_with_options::<Vec<Entry>>(
+        &regex_tree,
+        "a (b=1) c (d=x)",
+        &options(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        r#"[1].pair: regular expression does not match; the longest start of a pattern that matches is `(?P<key>\w+)=`, which matches "d=""#
+    );
+    assert_eq!(error.span(), Some(11..13));
+}
+
+#[test]
+fn required_root() {
+    let regex_tree = RegexTree::leaf(r"(?P<key>\w+)=(?P<value>\d+)");
+    let options = options().require_match();
+    let error = from_regex_tree_and_str_with_options::<Vec<Pair>>(&regex_tree, "a=b", &options)
+        .unwrap_err();
+    assert_eq!(
+        error.message(),
+        r#"no match of `(?P<key>\w+)=(?P<value>\d+)` in the text; the longest start of a pattern that matches is `(?P<key>\w+)=`, which matches "a=""#
+    );
+    assert_eq!(error.span(), Some(0..2));
+}
+
+#[test]
+fn nothing_matches() {
+    let regex_tree = RegexTree::leaf(r"(?P<key>\w+)=(?P<value>\d+)");
+    let error =
+        from_regex_tree_and_str_with_options::<Pair>(&regex_tree, "!!", &options()).unwrap_err();
+    assert_eq!(
+        error.message(),
+        "regular expression does not match; no start of a pattern matches"
+    );
+    assert_eq!(error.span(), Some(0..2));
+}
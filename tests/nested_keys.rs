@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+
+use recursive_regex::{
+    from_regex_tree_and_str, from_regex_tree_and_str_with_options, to_value, validate,
+    DeserializeOptions, RegexTree, Spanned, ValidationIssue,
+};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Person {
+    name: String,
+    addr: Address,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Address {
+    street: String,
+    city: String,
+    zip: Option<u32>,
+}
+
+fn people() -> RegexTree {
+    RegexTree::lines(
+        r"(?P<name>\w+): (?P<addr_street>[^,]+), (?P<addr_city>\w+)(?: (?P<addr_zip>\d+))?",
+    )
+    .with_nested_keys("_")
+    .build()
+}
+
+#[test]
+fn nested_struct() {
+    let people: Vec<Person> =
+        from_regex_tree_and_str(&people(), "ada: 1 Main St, Leeds 123\nbob: 2 High St, York")
+            .unwrap();
+    assert_eq!(
+        people,
+        vec![
+            Person {
+                name: "ada".to_owned(),
+                addr: Address {
+                    street: "1 Main St".to_owned(),
+                    city: "Leeds".to_owned(),
+                    zip: Some(123),
+                },
+            },
+            Person {
+                name: "bob".to_owned(),
+                addr: Address {
+                    street: "2 High St".to_owned(),
+                    city: "York".to_owned(),
+                    zip: None,
+                },
+            },
+        ]
+    );
+}
+
+#[test]
+fn several_levels() {
+    #[derive(Debug, Deserialize)]
+    struct Shape {
+        from: Point,
+        to: Point,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Eq)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Line {
+        line: Shape,
+    }
+
+    let regex_tree = RegexTree::root(
+        r"\((?P<line.from.x>-?\d+), (?P<line.from.y>-?\d+)\) to \((?P<line.to.x>-?\d+), (?P<line.to.y>-?\d+)\)",
+    )
+    .with_nested_keys(".")
+    .build();
+    let line: Line = from_regex_tree_and_str(&regex_tree, "(1, 2) to (-3, 4)").unwrap();
+    assert_eq!(line.line.from, Point { x: 1, y: 2 });
+    assert_eq!(line.line.to, Point { x: -3, y: 4 });
+}
+
+#[test]
+fn errors_have_the_nested_path() {
+    let err =
+        from_regex_tree_and_str::<Vec<Person>>(&people(), "ada: 1 Main St, Leeds 99999999999")
+            .unwrap_err();
+    assert_eq!(err.path().to_string(), "[0].addr.zip");
+    assert_eq!(err.span(), Some(22..33));
+}
+
+#[test]
+fn spanned_nested_struct() {
+    #[derive(Debug, Deserialize)]
+    struct SpannedPerson {
+        addr: Spanned<Address>,
+    }
+
+    let people: Vec<SpannedPerson> =
+        from_regex_tree_and_str(&people(), "ada: 1 Main St, Leeds").unwrap();
+    assert_eq!(people[0].addr.begin(), 5);
+    assert_eq!(people[0].addr.end(), 21);
+}
+
+#[test]
+fn map_and_value() {
+    let addresses: Vec<HashMap<String, HashMap<String, String>>> = from_regex_tree_and_str(
+        &RegexTree::root(r"(?P<addr_street>[^,]+), (?P<addr_city>\w+)")
+            .with_nested_keys("_")
+            .build(),
+        "1 Main St, Leeds",
+    )
+    .unwrap();
+    assert_eq!(addresses[0]["addr"]["city"], "Leeds");
+
+    let value = to_value(&people(), "ada: 1 Main St, Leeds").unwrap();
+    let person = &value.as_seq().unwrap()[0];
+    assert_eq!(
+        person.get("addr").unwrap().get("street").unwrap().as_str(),
+        Some("1 Main St")
+    );
+}
+
+#[test]
+fn strict_checks_nested_fields() {
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Street {
+        street: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Partial {
+        name: String,
+        addr: Street,
+    }
+
+    let options = DeserializeOptions::new().strict();
+    let err = from_regex_tree_and_str_with_options::<Vec<Partial>>(
+        &people(),
+        "ada: 1 Main St, Leeds",
+        &options,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "[0].addr: capture group `city` is not a field"
+    );
+    assert_eq!(err.span(), Some(16..21));
+}
+
+#[test]
+fn validated() {
+    assert!(validate::<Vec<Person>>(&people()).is_ok());
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Typo {
+        name: String,
+        addr: Misspelled,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Misspelled {
+        street: String,
+        ctiy: String,
+        zip: Option<u32>,
+    }
+
+    let report = validate::<Vec<Typo>>(&people());
+    assert_eq!(
+        report.issues(),
+        [
+            ValidationIssue::MissingCapture {
+                path: vec!["addr".to_owned()],
+                field: "ctiy".to_owned(),
+            },
+            ValidationIssue::UnusedCapture {
+                path: vec!["addr".to_owned()],
+                group: "city".to_owned(),
+            },
+        ]
+    );
+}
+
+#[cfg(feature = "deserialize-regex-tree")]
+#[test]
+fn deserialized_tree() {
+    let regex_tree: RegexTree = serde_json::from_str(
+        r#"{
+            "regex": "(?P<name>\\w+): (?P<addr_street>[^,]+), (?P<addr_city>\\w+)",
+            "lines": true,
+            "key_separator": "_"
+        }"#,
+    )
+    .unwrap();
+    let people: Vec<Person> =
+        from_regex_tree_and_str(&regex_tree, "ada: 1 Main St, Leeds").unwrap();
+    assert_eq!(people[0].addr.city, "Leeds");
+}
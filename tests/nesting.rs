@@ -0,0 +1,49 @@
+use recursive_regex::{from_regex_tree_and_str, validate, RegexTree};
+
+fn paragraphs() -> RegexTree {
+    RegexTree::root(r"(?:[^\n]+\n?)+")
+        .with_child_index(
+            0,
+            RegexTree::root(r"[^\n]+")
+                .with_child_index(0, RegexTree::leaf(r"\w+"))
+                .build(),
+        )
+        .build()
+}
+
+#[test]
+fn three_levels_of_sequences() {
+    let text = "a b\nc\n\nd e f\n";
+
+    let words: Vec<Vec<Vec<String>>> = from_regex_tree_and_str(&paragraphs(), text).unwrap();
+    assert_eq!(
+        words,
+        vec![vec![vec!["a", "b"], vec!["c"]], vec![vec!["d", "e", "f"]]]
+    );
+}
+
+#[test]
+fn innermost_level_parses_values() {
+    let regex_tree = RegexTree::lines(".*")
+        .with_child_index(0, RegexTree::leaf(r"-?\d+"))
+        .build();
+
+    let rows: Vec<Vec<i32>> = from_regex_tree_and_str(&regex_tree, "1 2\n-3").unwrap();
+    assert_eq!(rows, vec![vec![1, 2], vec![-3]]);
+}
+
+#[test]
+fn error_path_goes_through_levels() {
+    let regex_tree = RegexTree::lines(".*")
+        .with_child_index(0, RegexTree::leaf(r"\S+"))
+        .build();
+
+    let error = from_regex_tree_and_str::<Vec<Vec<u8>>>(&regex_tree, "1 2\n3 x").unwrap_err();
+    assert!(error.to_string().starts_with("[1][1]: "), "{error}");
+    assert_eq!(error.span(), Some(6..7));
+}
+
+#[test]
+fn validate_nested_sequences() {
+    assert!(validate::<Vec<Vec<Vec<String>>>>(&paragraphs()).is_ok());
+}
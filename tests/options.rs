@@ -0,0 +1,106 @@
+use recursive_regex::{
+    from_regex_tree_and_str, from_regex_tree_and_str_with_options, DeserializeOptions, RegexTree,
+    Spanned,
+};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Row<'a> {
+    #[serde(borrow)]
+    name: Spanned<&'a str>,
+    age: u32,
+}
+
+fn regex_tree() -> RegexTree {
+    RegexTree::lines(r"(?P<name>[^,]*),(?P<age>[^,]*)(?:,(?P<note>.*))?").build()
+}
+
+#[test]
+fn trim() {
+    let options = DeserializeOptions::new().trim();
+    let rows: Vec<Row> =
+        from_regex_tree_and_str_with_options(&regex_tree(), " ann , 31", &options).unwrap();
+    assert_eq!(
+        rows,
+        vec![Row {
+            name: Spanned::new_raw("ann", 1, 4),
+            age: 31,
+        }]
+    );
+
+    assert!(from_regex_tree_and_str::<Vec<Row>>(&regex_tree(), " ann , 31").is_err());
+}
+
+#[test]
+fn strict() {
+    let text = "ann,31\nbob,42,new";
+
+    let rows: Vec<Row> = from_regex_tree_and_str(&regex_tree(), text).unwrap();
+    assert_eq!(rows.len(), 2);
+
+    let options = DeserializeOptions::new().strict();
+    let error = from_regex_tree_and_str_with_options::<Vec<Row>>(&regex_tree(), text, &options)
+        .unwrap_err();
+    assert_eq!(error.message(), "capture group `note` is not a field");
+    assert_eq!(error.span(), Some(14..17));
+}
+
+#[test]
+fn options_reach_children() {
+    let regex_tree = RegexTree::root(r"(?P<nums>.*)")
+        .with_child("nums", RegexTree::leaf(r"[^;]+"))
+        .build();
+    let options = DeserializeOptions::new().trim().strict();
+
+    #[derive(Debug, Deserialize, PartialEq, Eq)]
+    struct Nums {
+        nums: Vec<u32>,
+    }
+
+    let parsed: Nums =
+        from_regex_tree_and_str_with_options(&regex_tree, "1 ; 2;3 ", &options).unwrap();
+    assert_eq!(
+        parsed,
+        Nums {
+            nums: vec![1, 2, 3]
+        }
+    );
+}
+
+#[test]
+fn require_match() {
+    let regex_tree = RegexTree::root(r"(?P<name>\w+)=(?P<nums>.*)")
+        .with_fallback(r"(?P<name>\w+)!(?P<nums>)")
+        .with_child("nums", RegexTree::leaf(r"\d+"))
+        .build();
+    let options = DeserializeOptions::new().require_match();
+
+    #[derive(Debug, Deserialize, PartialEq, Eq)]
+    struct Entry {
+        name: String,
+        nums: Vec<u32>,
+    }
+
+    let error = from_regex_tree_and_str_with_options::<Vec<Entry>>(&regex_tree, "a: 1", &options)
+        .unwrap_err();
+    assert_eq!(
+        error.message(),
+        r"no match of `(?P<name>\w+)=(?P<nums>.*)` or `(?P<name>\w+)!(?P<nums>)` in the text"
+    );
+    assert_eq!(error.span(), Some(0..4));
+
+    // Only the root must match, so children may still be empty
+    let entries: Vec<Entry> =
+        from_regex_tree_and_str_with_options(&regex_tree, "a=x", &options).unwrap();
+    assert_eq!(
+        entries,
+        vec![Entry {
+            name: "a".to_owned(),
+            nums: vec![]
+        }]
+    );
+
+    // Without the option, nothing matching is an empty sequence
+    let entries: Vec<Entry> = from_regex_tree_and_str(&regex_tree, "a: 1").unwrap();
+    assert!(entries.is_empty());
+}
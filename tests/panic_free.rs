@@ -0,0 +1,268 @@
+//! Arbitrary trees and text must never make deserialization panic. Whatever
+//! the shape of the tree or the type requested, the result is `Ok` or `Err`.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use proptest::prelude::*;
+use recursive_regex::regex_tree::{Builder, Count, Decoding, DuplicateKeys, MatchPolicy};
+use recursive_regex::{
+    from_regex_tree_and_str_with_options, get_uncaptured, leaves, replace_matches, to_value,
+    validate, DeserializeOptions, RegexTree, Spanned,
+};
+use serde::Deserialize;
+
+const PATTERNS: &[&str] = &[
+    r"\d+",
+    r"",
+    r"x*",
+    r"\b",
+    r"(?m)^.*$",
+    r"(\w)(\s)?",
+    r"(?P<a>\w+)",
+    r"(?P<a>\d+)?(?P<b>[a-z]*)",
+    r"(?P<a>.)|(?P<b>\d\d)",
+    r"(?P<a[0]>\d)(?:,(?P<a[1]>\d))?",
+    r"(?P<secs>\d+)(?P<nanos>ms)?",
+];
+
+const DELIMITERS: &[&str] = &[r",", r"\s*", r""];
+
+const KEYS: &[&str] = &["a", "b", "a[0]", "secs", "missing"];
+
+#[derive(Debug, Clone)]
+enum Kind {
+    Root,
+    Lines,
+    Split,
+}
+
+#[derive(Debug, Clone)]
+enum Key {
+    Name(&'static str),
+    Index(usize),
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    kind: Kind,
+    pattern: &'static str,
+    fallback: Option<&'static str>,
+    children: Vec<(Key, Node)>,
+    duplicate_keys: DuplicateKeys,
+    count: Option<(usize, usize)>,
+    match_policy: MatchPolicy,
+    decoding: Option<Decoding>,
+    full_match: bool,
+}
+
+impl Key {
+    fn add_to(&self, builder: Builder, child: RegexTree) -> Builder {
+        match self {
+            Key::Name(name) => builder.with_child(*name, child),
+            Key::Index(index) => builder.with_child_index(*index, child),
+        }
+    }
+}
+
+impl Node {
+    fn builder(&self) -> Builder {
+        let builder: Builder = match self.kind {
+            Kind::Root => RegexTree::root(self.pattern),
+            Kind::Lines => RegexTree::lines(self.pattern),
+            Kind::Split => RegexTree::split(self.pattern),
+        };
+        match self.fallback {
+            Some(fallback) => builder.with_fallback(fallback),
+            None => builder,
+        }
+    }
+
+    fn build(&self) -> RegexTree {
+        let mut builder = self.builder();
+        for (key, child) in &self.children {
+            // Children that match no capture group fail to build, so they are left out
+            let fits = key
+                .add_to(self.builder(), RegexTree::leaf(""))
+                .try_build()
+                .is_ok();
+            if fits {
+                builder = key.add_to(builder, child.build());
+            }
+        }
+        if let Some((min, extra)) = self.count {
+            builder = builder.with_count(Count::between(min, min + extra));
+        }
+        if let Some(decoding) = self.decoding {
+            builder = builder.with_decoding(decoding);
+        }
+        builder
+            .with_duplicate_keys(self.duplicate_keys)
+            .with_match_policy(self.match_policy)
+            .with_full_match(self.full_match)
+            .build()
+    }
+}
+
+fn node() -> impl Strategy<Value = Node> {
+    let leaf = (
+        prop_oneof![Just(Kind::Root), Just(Kind::Lines), Just(Kind::Split)],
+        0..PATTERNS.len(),
+        proptest::option::of(0..PATTERNS.len()),
+        prop_oneof![
+            Just(DuplicateKeys::Error),
+            Just(DuplicateKeys::FirstWins),
+            Just(DuplicateKeys::LastWins),
+            Just(DuplicateKeys::Collect),
+        ],
+        proptest::option::of((0..3usize, 0..3usize)),
+        prop_oneof![
+            Just(MatchPolicy::Search),
+            Just(MatchPolicy::Consecutive),
+            Just(MatchPolicy::Overlapping),
+        ],
+        proptest::option::of(prop_oneof![
+            Just(Decoding::Hex),
+            Just(Decoding::Base64),
+            Just(Decoding::Percent),
+        ]),
+        any::<bool>(),
+    )
+        .prop_map(
+            |(
+                kind,
+                pattern,
+                fallback,
+                duplicate_keys,
+                count,
+                match_policy,
+                decoding,
+                full_match,
+            )| {
+                let pattern = match kind {
+                    Kind::Split => DELIMITERS[pattern % DELIMITERS.len()],
+                    _ => PATTERNS[pattern],
+                };
+                Node {
+                    kind,
+                    pattern,
+                    fallback: fallback.map(|fallback| PATTERNS[fallback]),
+                    children: Vec::new(),
+                    duplicate_keys,
+                    count,
+                    match_policy,
+                    decoding,
+                    full_match,
+                }
+            },
+        );
+    leaf.prop_recursive(3, 12, 3, |inner| {
+        (
+            inner.clone(),
+            prop::collection::vec(
+                (
+                    prop_oneof![
+                        (0..KEYS.len()).prop_map(|key| Key::Name(KEYS[key])),
+                        (0..4usize).prop_map(Key::Index),
+                    ],
+                    inner,
+                ),
+                0..3,
+            ),
+        )
+            .prop_map(|(mut node, children)| {
+                node.children = children;
+                node
+            })
+    })
+}
+
+fn text() -> impl Strategy<Value = String> {
+    "[0-9a-z ,\n%=+/é✓]{0,24}"
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct Record {
+    a: Option<String>,
+    b: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct Required {
+    a: Spanned<Vec<u8>>,
+    b: (String, Option<u8>),
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+enum Choice {
+    A,
+    B(u8),
+}
+
+/// Deserialize the text as each of the types, discarding the results
+macro_rules! deserialize_as {
+    ($regex_tree:expr, $text:expr, $options:expr, $($ty:ty),* $(,)?) => {
+        $(
+            let _ = from_regex_tree_and_str_with_options::<$ty>($regex_tree, $text, $options);
+            let _ = validate::<$ty>($regex_tree);
+        )*
+    };
+}
+
+fn exercise(regex_tree: &RegexTree, text: &str, options: &DeserializeOptions) {
+    deserialize_as!(
+        regex_tree,
+        text,
+        options,
+        (),
+        String,
+        u32,
+        bool,
+        char,
+        Option<i64>,
+        Vec<String>,
+        Vec<f64>,
+        Vec<Vec<u8>>,
+        Vec<Option<Vec<String>>>,
+        Vec<(String,)>,
+        Vec<(String, u8, Option<String>)>,
+        (String, Vec<u8>),
+        Record,
+        Vec<Record>,
+        Option<Required>,
+        Vec<Required>,
+        HashMap<String, Vec<String>>,
+        Vec<HashMap<String, u8>>,
+        Spanned<Vec<Spanned<Record>>>,
+        Duration,
+        Vec<Duration>,
+        Choice,
+        Vec<Choice>,
+        serde::de::IgnoredAny,
+    );
+    let _ = from_regex_tree_and_str_with_options::<Vec<&str>>(regex_tree, text, options);
+    let _ = to_value(regex_tree, text);
+    let _ = leaves(regex_tree, text).count();
+    let _ = get_uncaptured(regex_tree, text).count();
+    let _ = replace_matches(regex_tree, text, |node| node.as_str().to_uppercase());
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(512))]
+
+    #[test]
+    fn never_panics(node in node(), text in text(), trim in any::<bool>(), strict in any::<bool>()) {
+        let regex_tree = node.build();
+        let mut options = DeserializeOptions::new();
+        if trim {
+            options = options.trim();
+        }
+        if strict {
+            options = options.strict();
+        }
+        exercise(&regex_tree, &text, &options);
+    }
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "preserve-order")]
+
+use indexmap::IndexMap;
+use recursive_regex::{from_regex_tree_and_str, RegexTree};
+
+fn record() -> RegexTree {
+    RegexTree::lines(r"(?P<zone>\w+) (?P<host>\w+) (?P<addr>\S+)")
+        .with_child("zone", RegexTree::leaf(r"\w+"))
+        .with_child("host", RegexTree::leaf(r"\w+"))
+        .with_child("addr", RegexTree::leaf(r"[^.]+"))
+        .build()
+}
+
+#[test]
+fn children_in_order_added() {
+    let regex_tree = record();
+    let names: Vec<_> = regex_tree.children().map(|(name, _)| name).collect();
+    assert_eq!(names, vec!["zone", "host", "addr"]);
+}
+
+#[test]
+fn map_keys_in_pattern_order() {
+    let regex_tree = RegexTree::lines(r"(?P<zone>\w+) (?P<host>\w+) (?P<addr>\S+)").build();
+    let maps: Vec<IndexMap<String, String>> =
+        from_regex_tree_and_str(&regex_tree, "eu web 10.0.0.1").unwrap();
+    let keys: Vec<_> = maps[0].keys().map(String::as_str).collect();
+    assert_eq!(keys, vec!["zone", "host", "addr"]);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn json_objects_in_pattern_order() {
+    let regex_tree = RegexTree::lines(r"(?P<zone>\w+) (?P<host>\w+) (?P<addr>\S+)").build();
+    let value = recursive_regex::to_value(&regex_tree, "eu web 10.0.0.1").unwrap();
+    let json = serde_json::Value::from(value);
+    let keys: Vec<_> = json[0].as_object().unwrap().keys().collect();
+    assert_eq!(keys, vec!["zone", "host", "addr"]);
+}
+
+#[cfg(feature = "deserialize-regex-tree")]
+#[test]
+fn deserialized_children_in_file_order() {
+    let regex_tree: RegexTree = serde_json::from_str(
+        r#"{
+            "regex": "(?P<zone>\\w+) (?P<host>\\w+) (?P<addr>\\S+)",
+            "children": {
+                "zone": { "regex": "\\w+" },
+                "host": { "regex": "\\w+" },
+                "addr": { "regex": "[^.]+" }
+            }
+        }"#,
+    )
+    .unwrap();
+    let names: Vec<_> = regex_tree.children().map(|(name, _)| name).collect();
+    assert_eq!(names, vec!["zone", "host", "addr"]);
+}
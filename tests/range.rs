@@ -0,0 +1,87 @@
+use recursive_regex::{
+    from_regex_tree_and_str_range, from_regex_tree_and_str_range_with_options, DeserializeOptions,
+    RegexTree, Spanned,
+};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct Entry {
+    name: Spanned<String>,
+    nums: Vec<Spanned<u32>>,
+}
+
+fn regex_tree() -> RegexTree {
+    RegexTree::lines(r"(?P<name>\w+): (?P<nums>.*)")
+        .with_child("nums", RegexTree::leaf(r"\S+"))
+        .build()
+}
+
+const TEXT: &str = "a: 1 2\nbb: 3 45\nc: x";
+
+#[test]
+fn spans_are_within_the_whole_text() {
+    let entries: Vec<Entry> = from_regex_tree_and_str_range(&regex_tree(), TEXT, 7..15).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].name.substring(TEXT), "bb");
+    let nums: Vec<&str> = entries[0]
+        .nums
+        .iter()
+        .map(|num| num.substring(TEXT))
+        .collect();
+    assert_eq!(nums, vec!["3", "45"]);
+}
+
+#[test]
+fn text_outside_the_range_is_not_matched() {
+    let entries: Vec<Entry> = from_regex_tree_and_str_range(&regex_tree(), TEXT, 0..5).unwrap();
+    assert_eq!(entries.len(), 1);
+    let nums: Vec<u32> = entries[0].nums.iter().map(|num| *num.value()).collect();
+    assert_eq!(nums, vec![1]);
+}
+
+#[test]
+fn error_spans_are_within_the_whole_text() {
+    let err = from_regex_tree_and_str_range::<Vec<Entry>>(&regex_tree(), TEXT, 16..20).unwrap_err();
+    assert_eq!(err.span(), Some(19..20));
+    assert_eq!(
+        err.to_string(),
+        "[0].nums[0]: parsing error: invalid digit found in string"
+    );
+}
+
+#[test]
+fn with_base_offset() {
+    let options = DeserializeOptions::new()
+        .with_base_offset(100)
+        .with_source_name("doc.txt");
+    let entries: Vec<Entry> =
+        from_regex_tree_and_str_range_with_options(&regex_tree(), TEXT, 7..15, &options).unwrap();
+    assert_eq!(entries[0].name.begin(), 107);
+
+    let err = from_regex_tree_and_str_range_with_options::<Vec<Entry>>(
+        &regex_tree(),
+        TEXT,
+        16..20,
+        &options,
+    )
+    .unwrap_err();
+    assert_eq!(err.span(), Some(119..120));
+    assert_eq!(err.source_name(), Some("doc.txt"));
+}
+
+#[test]
+fn invalid_ranges() {
+    let err = from_regex_tree_and_str_range::<Vec<Entry>>(&regex_tree(), TEXT, 10..30).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "range 10..30 is not within the text of 20 bytes"
+    );
+    assert_eq!(err.span(), None);
+
+    let err = from_regex_tree_and_str_range::<Vec<String>>(&RegexTree::leaf(r".+"), "é", 1..2)
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "range 1..2 does not start and end on character boundaries"
+    );
+}
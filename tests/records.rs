@@ -0,0 +1,94 @@
+use recursive_regex::{
+    records_from_regex_tree_and_str, records_from_regex_tree_and_str_with_options,
+    DeserializeOptions, Error, RegexTree, Spanned,
+};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Entry {
+    name: String,
+    value: u32,
+}
+
+fn regex_tree() -> RegexTree {
+    RegexTree::lines(r"(?P<name>\w+)=(?P<value>.*)").build()
+}
+
+const TEXT: &str = "a=1\nb=x\nc=3\n???\nd=5";
+
+#[test]
+fn errors_in_place() {
+    let records: Vec<Result<Entry, Error>> =
+        records_from_regex_tree_and_str(&regex_tree(), TEXT).collect();
+    assert_eq!(records.len(), 5);
+    assert_eq!(
+        records[1].as_ref().unwrap_err().to_string(),
+        "[1].value: parsing error: invalid digit found in string"
+    );
+    assert_eq!(records[1].as_ref().unwrap_err().span(), Some(6..7));
+    assert_eq!(records[3].as_ref().unwrap_err().span(), Some(12..15));
+    assert_eq!(
+        records[4],
+        Ok(Entry {
+            name: "d".to_owned(),
+            value: 5
+        })
+    );
+}
+
+#[test]
+fn skip_bad_records() {
+    let names: Vec<String> = records_from_regex_tree_and_str::<Entry>(&regex_tree(), TEXT)
+        .flatten()
+        .map(|entry| entry.name)
+        .collect();
+    assert_eq!(names, vec!["a", "c", "d"]);
+}
+
+#[test]
+fn split_errors() {
+    let (entries, errors) =
+        records_from_regex_tree_and_str::<Entry>(&regex_tree(), TEXT).split_errors();
+    assert_eq!(entries.len(), 3);
+    let spans: Vec<_> = errors.iter().map(Error::span).collect();
+    assert_eq!(spans, vec![Some(6..7), Some(12..15)]);
+}
+
+#[test]
+fn borrowed_and_spanned() {
+    #[derive(Debug, Deserialize)]
+    struct Borrowed<'a> {
+        #[serde(borrow)]
+        name: Spanned<&'a str>,
+    }
+
+    let regex_tree = RegexTree::leaf(r"(?P<name>\w+)");
+    let records: Vec<Borrowed> = records_from_regex_tree_and_str(&regex_tree, "ab cd")
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(*records[1].name.value(), "cd");
+    assert_eq!((records[1].name.begin(), records[1].name.end()), (3, 5));
+}
+
+#[test]
+fn count_checked_once() {
+    let regex_tree = RegexTree::root(r"\d+")
+        .with_count(recursive_regex::regex_tree::Count::at_least(3))
+        .build();
+    let records: Vec<Result<u32, Error>> =
+        records_from_regex_tree_and_str(&regex_tree, "1 2").collect();
+    assert_eq!(records.len(), 3);
+    assert_eq!(
+        records[2].as_ref().unwrap_err().message(),
+        "expected at least 3 matches but found 2"
+    );
+}
+
+#[test]
+fn require_match() {
+    let options = DeserializeOptions::new().require_match();
+    let records: Vec<Result<Entry, Error>> =
+        records_from_regex_tree_and_str_with_options(&regex_tree(), "", &options).collect();
+    assert_eq!(records.len(), 1);
+    assert!(records[0].is_err());
+}
@@ -0,0 +1,105 @@
+use recursive_regex::regex_tree::Case;
+use recursive_regex::{from_regex_tree_and_str, validate, RegexTree};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+struct Line {
+    user_name: String,
+    item_ids: Vec<u32>,
+}
+
+fn regex_tree() -> RegexTree {
+    RegexTree::root(r"(?P<user_name>\w+): (?P<item_ids>[\d ]*)")
+        .with_rename_all(Case::Kebab)
+        .with_child("item_ids", RegexTree::leaf(r"\d+"))
+        .build()
+}
+
+#[test]
+fn convert() {
+    let cases = [
+        (Case::Snake, "user_name"),
+        (Case::Kebab, "user-name"),
+        (Case::Camel, "userName"),
+        (Case::Pascal, "UserName"),
+        (Case::ScreamingSnake, "USER_NAME"),
+    ];
+    for key in [
+        "user_name",
+        "userName",
+        "UserName",
+        "USER_NAME",
+        "user-name",
+    ] {
+        for (case, expected) in cases {
+            assert_eq!(case.convert(key), expected, "{key} as {case:?}");
+        }
+    }
+    assert_eq!(Case::Snake.convert("ipV4Addr2"), "ip_v4_addr2");
+    assert_eq!(Case::Camel.convert("x"), "x");
+}
+
+#[test]
+fn struct_fields() {
+    let lines: Vec<Line> = from_regex_tree_and_str(&regex_tree(), "ann: 1 2\nbo: 3").unwrap();
+    assert_eq!(
+        lines,
+        vec![
+            Line {
+                user_name: "ann".to_owned(),
+                item_ids: vec![1, 2]
+            },
+            Line {
+                user_name: "bo".to_owned(),
+                item_ids: vec![3]
+            },
+        ]
+    );
+}
+
+#[test]
+fn fallbacks_and_aliases() {
+    #[derive(Debug, Deserialize, PartialEq, Eq)]
+    #[serde(rename_all = "camelCase")]
+    struct Reading {
+        sensor_id: u32,
+        value: u32,
+    }
+
+    // Fallbacks added after the case are converted too, and an alias wins
+    let regex_tree = RegexTree::root(r"(?P<sensor_id>\d+)=(?P<v>\d+)")
+        .with_rename_all(Case::Camel)
+        .with_alias("v", "value")
+        .with_fallback(r"(?P<sensor_id>\d+)!")
+        .build();
+    let readings: Vec<Reading> = from_regex_tree_and_str(&regex_tree, "1=20").unwrap();
+    assert_eq!(
+        readings,
+        vec![Reading {
+            sensor_id: 1,
+            value: 20
+        }]
+    );
+}
+
+#[test]
+fn validates() {
+    let report = validate::<Vec<Line>>(&regex_tree());
+    assert!(report.is_ok(), "{report}");
+}
+
+#[cfg(feature = "deserialize-regex-tree")]
+#[test]
+fn deserialized_tree() {
+    let regex_tree: RegexTree = serde_json::from_str(
+        r#"{
+            "regex": "(?P<user_name>\\w+): (?P<item_ids>[\\d ]*)",
+            "rename_all": "kebab-case",
+            "children": {"item_ids": {"regex": "\\d+"}}
+        }"#,
+    )
+    .unwrap();
+    let lines: Vec<Line> = from_regex_tree_and_str(&regex_tree, "ann: 1 2").unwrap();
+    assert_eq!(lines[0].item_ids, vec![1, 2]);
+}
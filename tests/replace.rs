@@ -0,0 +1,40 @@
+use recursive_regex::{replace_matches, RegexTree};
+
+#[test]
+fn unmatched_text_is_kept() {
+    let regex_tree = RegexTree::leaf(r"\d+");
+
+    let replaced = replace_matches(&regex_tree, "a1 b22 c", |found| {
+        format!("<{}>", found.as_str().len())
+    });
+    assert_eq!(replaced, "a<1> b<2> c");
+}
+
+#[test]
+fn no_matches() {
+    let regex_tree = RegexTree::leaf(r"\d+");
+
+    let replaced = replace_matches(&regex_tree, "abc", |_| unreachable!());
+    assert_eq!(replaced, "abc");
+}
+
+#[test]
+fn rewrite_from_children() {
+    let regex_tree = RegexTree::lines(r"(?P<name>\w+): (?P<scores>.*)")
+        .with_child("scores", RegexTree::leaf(r"\d+"))
+        .build();
+
+    let replaced = replace_matches(&regex_tree, "ann: 1 2\r\nbo: 3\n", |found| {
+        let name = found.group("name").unwrap().as_str();
+        let total: u32 = found
+            .group("scores")
+            .unwrap()
+            .matches()
+            .unwrap()
+            .iter()
+            .map(|score| score.as_str().parse::<u32>().unwrap())
+            .sum();
+        format!("{name}={total}")
+    });
+    assert_eq!(replaced, "ann=3\r\nbo=3\n");
+}
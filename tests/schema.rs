@@ -0,0 +1,76 @@
+use recursive_regex::regex_tree::Count;
+use recursive_regex::RegexTree;
+
+fn config() -> RegexTree {
+    RegexTree::lines(r"(?P<key>\w+)=(?P<value>.*)")
+        .with_fallback(r"(?P<key>\w+)")
+        .with_child_if(
+            "value",
+            |values| values.name("key") == Some("ports"),
+            RegexTree::root(r"\d+")
+                .with_count(Count::at_least(1))
+                .build(),
+        )
+        .with_child(
+            "value",
+            RegexTree::split_literal(",")
+                .with_child_index(0, RegexTree::leaf(r"\w+"))
+                .build(),
+        )
+        .build()
+}
+
+#[test]
+fn text() {
+    assert_eq!(
+        config().schema().to_string(),
+        "\
+pattern `(?P<key>\\w+)=(?P<value>.*)`
+fallback `(?P<key>\\w+)`
+split lines
+groups
+  1 key
+  2 value -> value?0 or value
+child value
+  pattern `(?s).*`
+  split on \",\"
+  groups
+    0 -> 0
+  child 0
+    pattern `\\w+`
+child value?0
+  pattern `\\d+`
+  count at least 1
+"
+    );
+}
+
+#[test]
+fn default_child_and_unnamed_groups() {
+    let regex_tree = RegexTree::root(r"(\w+) (?P<a>\S+) (?P<b>\S+)")
+        .with_child("b", RegexTree::leaf(r"\d"))
+        .with_default_child(RegexTree::leaf(r"\w"))
+        .build();
+    let schema = regex_tree.schema().to_string();
+    assert!(schema.contains("groups\n  1\n  2 a -> *\n  3 b -> b\n"));
+    assert!(schema.ends_with("child b\n  pattern `\\d`\nchild *\n  pattern `\\w`\n"));
+}
+
+#[test]
+fn dot() {
+    let dot = config().schema().to_dot();
+    assert_eq!(
+        dot,
+        r#"digraph regex_tree {
+    node [shape=box];
+    n0 [label="(?P<key>\\w+)=(?P<value>.*)\n(?P<key>\\w+)"];
+    n1 [label="(?s).*"];
+    n2 [label="\\w+"];
+    n1 -> n2 [label="0"];
+    n0 -> n1 [label="value"];
+    n3 [label="\\d+"];
+    n0 -> n3 [label="value?0"];
+}
+"#
+    );
+}
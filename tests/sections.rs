@@ -0,0 +1,105 @@
+use recursive_regex::{from_regex_tree_and_str, validate, RegexTree};
+use serde::Deserialize;
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Record {
+    name: String,
+    size: u32,
+}
+
+fn report_tree() -> RegexTree {
+    RegexTree::sections([r"\A.*", r"(?m)(?:^[a-z]+ \d+\n)+", r"TOTAL \d+"])
+        .with_child_index(1, RegexTree::leaf(r"(?P<name>\w+) (?P<size>\d+)"))
+        .with_child_index(2, RegexTree::leaf(r"\d+"))
+        .build()
+}
+
+#[test]
+fn sections_have_their_own_shapes() {
+    let text = "sizes\n\na 1\nb 2\n\nTOTAL 3\ntrailing";
+    let (title, records, total): (String, Vec<Record>, Vec<u32>) =
+        from_regex_tree_and_str(&report_tree(), text).unwrap();
+    assert_eq!(title, "sizes");
+    assert_eq!(
+        records,
+        vec![
+            Record {
+                name: "a".to_owned(),
+                size: 1
+            },
+            Record {
+                name: "b".to_owned(),
+                size: 2
+            },
+        ]
+    );
+    assert_eq!(total, vec![3]);
+}
+
+#[test]
+fn missing_section_is_an_error() {
+    let text = "sizes\na 1\nno total";
+    let error = from_regex_tree_and_str::<(String, Vec<Record>, Vec<u32>)>(&report_tree(), text)
+        .unwrap_err();
+    assert_eq!(error.message(), "section 3 does not match");
+    assert_eq!(error.span(), Some(10..18));
+}
+
+#[test]
+fn sections_are_searched_in_order() {
+    // The total before the records is not the footer
+    let text = "TOTAL 9\na 1\nTOTAL 1";
+    let (_, _, total): (String, Vec<Record>, Vec<u32>) =
+        from_regex_tree_and_str(&report_tree(), text).unwrap();
+    assert_eq!(total, vec![1]);
+}
+
+#[test]
+fn errors_are_within_the_section() {
+    let error = from_regex_tree_and_str::<(String, Vec<Record>, Vec<u8>)>(
+        &report_tree(),
+        "t\na 1\nTOTAL 300",
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "[2][0]: parsing error: number too large to fit in target type"
+    );
+    assert_eq!(error.span(), Some(12..15));
+}
+
+#[test]
+fn tuple_must_have_an_element_per_section() {
+    let error = from_regex_tree_and_str::<(String, Vec<Record>)>(&report_tree(), "t\na 1\nTOTAL 1")
+        .unwrap_err();
+    assert_eq!(
+        error.message(),
+        "expected 2 matches for a tuple of 2 elements but found 3"
+    );
+}
+
+#[test]
+fn section_children_are_valid() {
+    let report = validate::<(String, Vec<Record>, Vec<u32>)>(&report_tree());
+    assert!(report.is_ok(), "{report:?}");
+    assert!(report_tree()
+        .schema()
+        .to_string()
+        .contains(r"sections `\A.*`, "));
+}
+
+#[cfg(feature = "deserialize-regex-tree")]
+#[test]
+fn deserialized_tree() {
+    let regex_tree: RegexTree = serde_json::from_str(
+        r#"{
+            "regex": "(?s).*",
+            "sections": ["\\A\\w+", "\\d+"],
+            "children": { "1": { "regex": "\\d" } }
+        }"#,
+    )
+    .unwrap();
+    let (word, digits): (String, Vec<u8>) = from_regex_tree_and_str(&regex_tree, "ab 123").unwrap();
+    assert_eq!(word, "ab");
+    assert_eq!(digits, vec![1, 2, 3]);
+}
@@ -0,0 +1,42 @@
+#![cfg(feature = "serde-spanned")]
+
+use recursive_regex::{from_regex_tree_and_str, validate, RegexTree, Spanned};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct Setting {
+    key: serde_spanned::Spanned<String>,
+    value: serde_spanned::Spanned<u32>,
+}
+
+fn regex_tree() -> RegexTree {
+    RegexTree::leaf(r"(?P<key>\w+)\s*=\s*(?P<value>\d+)")
+}
+
+#[test]
+fn deserializes_serde_spanned() {
+    let settings: Vec<serde_spanned::Spanned<Setting>> =
+        from_regex_tree_and_str(&regex_tree(), "a = 1\nbb=22").unwrap();
+
+    assert_eq!(settings[1].span(), 6..11);
+    let setting = settings[1].get_ref();
+    assert_eq!(setting.key.get_ref(), "bb");
+    assert_eq!(setting.key.span(), 6..8);
+    assert_eq!(*setting.value.get_ref(), 22);
+    assert_eq!(setting.value.span(), 9..11);
+}
+
+#[test]
+fn converts_both_ways() {
+    let spanned = Spanned::new_raw("x".to_owned(), 3, 4);
+    let other: serde_spanned::Spanned<String> = spanned.clone().into();
+    assert_eq!(other.span(), 3..4);
+    assert_eq!(other.get_ref(), "x");
+    assert_eq!(Spanned::from(other), spanned);
+}
+
+#[test]
+fn validates() {
+    let report = validate::<Vec<serde_spanned::Spanned<Setting>>>(&regex_tree());
+    assert!(report.is_ok(), "{report}");
+}
@@ -0,0 +1,137 @@
+use std::sync::Arc;
+use std::thread;
+
+use recursive_regex::{DeserializeOptions, Error, RegexTree, SharedDeserializer};
+use serde::Deserialize;
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Request<'a> {
+    method: &'a str,
+    path: &'a str,
+    status: u16,
+}
+
+fn requests() -> Arc<RegexTree> {
+    Arc::new(RegexTree::lines(r"(?P<method>[A-Z]+) (?P<path>\S+) (?P<status>\d+)").build())
+}
+
+fn assert_send_sync<T: Send + Sync + 'static>() {}
+
+#[test]
+fn shared_types_are_send_and_sync() {
+    assert_send_sync::<RegexTree>();
+    assert_send_sync::<DeserializeOptions>();
+    assert_send_sync::<SharedDeserializer>();
+    assert_send_sync::<Error>();
+}
+
+#[test]
+fn tree_cloned_per_thread() {
+    let regex_tree = RegexTree::lines(r"(?P<method>[A-Z]+) (?P<path>\S+) (?P<status>\d+)").build();
+    let workers: Vec<_> = (0..2)
+        .map(|_| {
+            let regex_tree = regex_tree.clone();
+            thread::spawn(move || {
+                let requests: Vec<Request> =
+                    recursive_regex::from_regex_tree_and_str(&regex_tree, "GET / 200").unwrap();
+                requests[0].status
+            })
+        })
+        .collect();
+    for worker in workers {
+        assert_eq!(worker.join().unwrap(), 200);
+    }
+}
+
+#[test]
+fn tree_shared_across_threads() {
+    let regex_tree = requests();
+    let workers: Vec<_> = (0..4)
+        .map(|worker| {
+            let regex_tree = Arc::clone(&regex_tree);
+            thread::spawn(move || {
+                let text = format!("GET /{worker} 200\nPOST /{worker} 404");
+                let requests: Vec<Request> =
+                    recursive_regex::from_regex_tree_and_str(&regex_tree, &text).unwrap();
+                requests.iter().map(|request| request.status).sum::<u16>()
+            })
+        })
+        .collect();
+    for worker in workers {
+        assert_eq!(worker.join().unwrap(), 604);
+    }
+}
+
+#[test]
+fn deserializer_moved_into_threads() {
+    let deserializer = SharedDeserializer::new(requests())
+        .with_options(DeserializeOptions::new().with_source_name("access.log"));
+    let workers: Vec<_> = ["GET / 200", "GET / abc"]
+        .into_iter()
+        .map(|text| {
+            let deserializer = deserializer.clone();
+            thread::spawn(move || {
+                deserializer
+                    .deserialize_str::<Vec<Request>>(text)
+                    .map(|requests| requests.len())
+                    .map_err(|error| error.to_string())
+            })
+        })
+        .collect();
+    let results: Vec<_> = workers
+        .into_iter()
+        .map(|worker| worker.join().unwrap())
+        .collect();
+    assert_eq!(results[0], Ok(1));
+    assert!(results[1].as_ref().unwrap_err().starts_with("access.log: "));
+
+    let clone = deserializer.clone();
+    assert!(Arc::ptr_eq(deserializer.regex_tree(), clone.regex_tree()));
+}
+
+#[test]
+fn records_and_chunks() {
+    let deserializer = SharedDeserializer::from(requests());
+
+    let (values, errors) = deserializer
+        .records::<Request>("GET /a 200\nGET /b x\nPUT /c 201")
+        .split_errors();
+    assert_eq!(values.len(), 2);
+    assert_eq!(errors.len(), 1);
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Status {
+        status: u16,
+    }
+    let mut chunked = deserializer.chunked::<Status>();
+    assert!(chunked.feed("GET /a 2").is_empty());
+    let records = chunked.feed("00\nGET /b 500");
+    assert_eq!(records[0].as_ref().unwrap(), &Status { status: 200 });
+    assert_eq!(
+        chunked.finish()[0].as_ref().unwrap(),
+        &Status { status: 500 }
+    );
+}
+
+#[test]
+fn value_from_shared_tree() {
+    let deserializer = SharedDeserializer::new(RegexTree::leaf(r"(?P<n>\d+)"));
+    let value = deserializer.to_value("7").unwrap();
+    assert_eq!(
+        value.as_seq().unwrap()[0].get("n").unwrap().as_str(),
+        Some("7")
+    );
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn deserializer_moved_into_task() {
+    let deserializer = SharedDeserializer::new(requests());
+    let task = tokio::spawn(async move {
+        deserializer
+            .deserialize_str::<Vec<Request>>("DELETE /x 204")
+            .unwrap()[0]
+            .status
+    });
+    assert_eq!(task.await.unwrap(), 204);
+}
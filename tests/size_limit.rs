@@ -0,0 +1,59 @@
+use recursive_regex::{from_regex_tree_and_str, RegexTree};
+
+#[test]
+fn pattern_over_limit_is_an_error() {
+    let result = RegexTree::root(r"\w{50}\d{50}")
+        .with_size_limit(1000)
+        .try_build();
+    assert!(result.is_err());
+}
+
+#[test]
+fn pattern_within_limit_builds() {
+    let regex_tree = RegexTree::root(r"\d+")
+        .with_size_limit(1 << 20)
+        .with_dfa_size_limit(1 << 20)
+        .build();
+
+    let parsed: Vec<u32> = from_regex_tree_and_str(&regex_tree, "1 22").unwrap();
+    assert_eq!(parsed, vec![1, 22]);
+}
+
+#[test]
+fn fallbacks_are_limited() {
+    let result = RegexTree::root(r"a")
+        .with_fallback(r"\w{50}\d{50}")
+        .with_size_limit(1000)
+        .try_build();
+    assert!(result.is_err());
+}
+
+#[test]
+#[should_panic(expected = "size limit")]
+fn build_panics_over_limit() {
+    RegexTree::root(r"\w{50}\d{50}")
+        .with_size_limit(1000)
+        .build();
+}
+
+#[test]
+fn invalid_patterns_are_errors() {
+    use recursive_regex::regex_tree::BuildError;
+
+    let result = RegexTree::root(r"(?P<open>").try_build();
+    assert!(matches!(result, Err(BuildError::Regex(_))));
+    let result = RegexTree::root(r"a").with_fallback(r"[z-a]").try_build();
+    assert!(matches!(result, Err(BuildError::Regex(_))));
+}
+
+#[test]
+fn limit_can_be_raised() {
+    // Too large for the regex crate's default limit
+    assert!(RegexTree::root(r"\w{400}").try_build().is_err());
+    let regex_tree = RegexTree::root(r"\w{400}")
+        .with_size_limit(100 << 20)
+        .build();
+    let text = "a".repeat(400);
+    let parsed: Vec<&str> = from_regex_tree_and_str(&regex_tree, &text).unwrap();
+    assert_eq!(parsed.len(), 1);
+}
@@ -0,0 +1,154 @@
+use recursive_regex::regex_tree::Count;
+use recursive_regex::{
+    from_regex_tree_and_str_with_options, records_from_regex_tree_and_str_with_options,
+    transcode_with_options, DeserializeOptions, Error, RegexTree, TranscodeError,
+};
+use serde::ser::Error as _;
+use serde::{Deserialize, Serialize, Serializer};
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Entry {
+    name: String,
+    value: u32,
+}
+
+fn regex_tree() -> RegexTree {
+    RegexTree::lines(r"(?P<name>\w+)=(?P<value>.*)").build()
+}
+
+const TEXT: &str = "a=1\nb=x\nc=3\nd=4\ne=5";
+
+fn names(entries: &[Entry]) -> Vec<&str> {
+    entries.iter().map(|entry| entry.name.as_str()).collect()
+}
+
+#[test]
+fn pages() {
+    let options = DeserializeOptions::new().take(2);
+    let entries: Vec<Entry> = from_regex_tree_and_str_with_options(
+        &RegexTree::lines(r"(?P<name>\w+)=(?P<value>\d+)").build(),
+        "a=1\nb=2\nc=3",
+        &options,
+    )
+    .unwrap();
+    assert_eq!(names(&entries), vec!["a", "b"]);
+
+    let options = DeserializeOptions::new().skip(2).take(2);
+    let entries: Vec<Entry> =
+        from_regex_tree_and_str_with_options(&regex_tree(), TEXT, &options).unwrap();
+    assert_eq!(names(&entries), vec!["c", "d"]);
+
+    let options = DeserializeOptions::new().skip(4).take(2);
+    let entries: Vec<Entry> =
+        from_regex_tree_and_str_with_options(&regex_tree(), TEXT, &options).unwrap();
+    assert_eq!(names(&entries), vec!["e"]);
+
+    let options = DeserializeOptions::new().skip(10);
+    let entries: Vec<Entry> =
+        from_regex_tree_and_str_with_options(&regex_tree(), TEXT, &options).unwrap();
+    assert!(entries.is_empty());
+}
+
+#[test]
+fn errors_count_skipped_matches() {
+    let options = DeserializeOptions::new().skip(1).take(1);
+    let error = from_regex_tree_and_str_with_options::<Vec<Entry>>(&regex_tree(), TEXT, &options)
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "[1].value: parsing error: invalid digit found in string"
+    );
+    assert_eq!(error.span(), Some(6..7));
+}
+
+#[test]
+fn after_take_last() {
+    let options = DeserializeOptions::new().take_last(3).skip(1).take(1);
+    let entries: Vec<Entry> =
+        from_regex_tree_and_str_with_options(&regex_tree(), TEXT, &options).unwrap();
+    assert_eq!(names(&entries), vec!["d"]);
+}
+
+#[test]
+fn count() {
+    let regex_tree = RegexTree::root(r"\d+")
+        .with_count(Count::at_most(3))
+        .build();
+    let options = DeserializeOptions::new().skip(3);
+    let error = from_regex_tree_and_str_with_options::<Vec<u32>>(&regex_tree, "1 2 3 4", &options)
+        .unwrap_err();
+    assert_eq!(error.message(), "expected at most 3 matches but found more");
+
+    // The text after the page is not searched
+    let options = DeserializeOptions::new().skip(1).take(1);
+    let page: Vec<u32> =
+        from_regex_tree_and_str_with_options(&regex_tree, "1 2 3 4", &options).unwrap();
+    assert_eq!(page, vec![2]);
+}
+
+#[test]
+fn records() {
+    let options = DeserializeOptions::new().skip(1).take(2);
+    let records: Vec<Result<Entry, Error>> =
+        records_from_regex_tree_and_str_with_options(&regex_tree(), TEXT, &options).collect();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].as_ref().unwrap_err().span(), Some(6..7));
+    assert_eq!(
+        records[1],
+        Ok(Entry {
+            name: "c".to_owned(),
+            value: 3
+        })
+    );
+}
+
+#[test]
+fn matches() {
+    let regex_tree = RegexTree::root(r"(?P<name>\w+): (?P<nums>.*)")
+        .with_child("nums", RegexTree::leaf(r"\d+"))
+        .build();
+    let page: Vec<_> = regex_tree
+        .matches("a: 1\nb: 2 3\nc: 4")
+        .skip(1)
+        .take(1)
+        .collect();
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].range(), 5..11);
+    let nums = page[0].group("nums").unwrap().matches().unwrap();
+    assert_eq!(nums[1].range(), 10..11);
+}
+
+/// Text to transcode into CBOR, whose serializer is only reachable through a value
+struct Transcoded<'a>(&'a RegexTree, &'a str, &'a DeserializeOptions);
+
+impl Serialize for Transcoded<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        transcode_with_options(self.0, self.1, self.2, serializer).map_err(|err| match err {
+            TranscodeError::Deserialize(err) => S::Error::custom(err),
+            TranscodeError::Serialize(err) => err,
+        })
+    }
+}
+
+#[test]
+fn binary_transcoding() {
+    let regex_tree = RegexTree::lines(r"(?P<name>\w+)=(?P<value>\d+)").build();
+    let text = "a=1\nb=2\nc=3\nd=4\ne=5";
+    let pages = [
+        (DeserializeOptions::new().take(2), vec!["a", "b"]),
+        (DeserializeOptions::new().skip(3), vec!["d", "e"]),
+        (DeserializeOptions::new().skip(1).take(2), vec!["b", "c"]),
+        (DeserializeOptions::new().skip(4).take(2), vec!["e"]),
+        (DeserializeOptions::new().skip(9), vec![]),
+        (
+            DeserializeOptions::new().take_last(3).skip(1).take(1),
+            vec!["d"],
+        ),
+    ];
+    for (options, expected) in pages {
+        let mut out = Vec::new();
+        ciborium::ser::into_writer(&Transcoded(&regex_tree, text, &options), &mut out).unwrap();
+        let entries: Vec<Entry> = ciborium::de::from_reader(out.as_slice()).unwrap();
+        assert_eq!(names(&entries), expected);
+    }
+}
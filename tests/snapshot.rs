@@ -0,0 +1,49 @@
+use recursive_regex::{debug_snapshot, RegexTree};
+
+#[test]
+fn unnamed_groups_and_whole_match_children() {
+    let regex_tree = RegexTree::lines(r"(\w+) (?P<rest>.*)")
+        .with_child_index(0, RegexTree::leaf(r"[a-z]+"))
+        .build();
+
+    assert_eq!(
+        debug_snapshot(&regex_tree, "go 12\nup x"),
+        r#"match 0..5 "go 12"
+  #0 0..5 "go 12"
+    match 0..2 "go"
+  #1 0..2 "go"
+  rest 3..5 "12"
+match 6..10 "up x"
+  #0 6..10 "up x"
+    match 6..8 "up"
+    match 9..10 "x"
+  #1 6..8 "up"
+  rest 9..10 "x"
+"#
+    );
+}
+
+#[test]
+fn long_and_escaped_text() {
+    let regex_tree = RegexTree::leaf(r"(?s).+");
+    let text = format!("\"tab\there\"{}", "é".repeat(40));
+    let snapshot = debug_snapshot(&regex_tree, &text);
+    assert_eq!(
+        snapshot,
+        format!(
+            "match 0..{} \"\\\"tab\\there\\\"{}\"...\n",
+            text.len(),
+            "é".repeat(22)
+        )
+    );
+}
+
+#[test]
+fn groups_that_do_not_take_part() {
+    let regex_tree = RegexTree::leaf(r"(?P<num>\d+)|(?P<word>[a-z]+)");
+    assert_eq!(
+        debug_snapshot(&regex_tree, "7 ab"),
+        "match 0..1 \"7\"\n  num 0..1 \"7\"\nmatch 2..4 \"ab\"\n  word 2..4 \"ab\"\n"
+    );
+    assert_eq!(debug_snapshot(&regex_tree, "!"), "");
+}
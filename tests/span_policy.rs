@@ -0,0 +1,68 @@
+use recursive_regex::regex_tree::Escapes;
+use recursive_regex::{
+    from_regex_tree_and_str_with_options, DeserializeOptions, RegexTree, SpanPolicy, Spanned,
+};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct Entry {
+    name: Spanned<String>,
+    size: Spanned<u32>,
+}
+
+fn entry() -> RegexTree {
+    RegexTree::leaf(r"(?P<name>[^|]*)\|(?P<size>[^|]*)")
+}
+
+const TEXT: &str = "  cat |  12 ";
+
+#[test]
+fn transformed_spans_cover_trimmed_text() {
+    let options = DeserializeOptions::new().trim();
+    let entry: Entry = from_regex_tree_and_str_with_options(&entry(), TEXT, &options).unwrap();
+    assert_eq!(entry.name.value(), "cat");
+    assert_eq!(entry.name.substring(TEXT), "cat");
+    assert_eq!(entry.size.substring(TEXT), "12");
+}
+
+#[test]
+fn original_spans_cover_matched_text() {
+    let options = DeserializeOptions::new()
+        .trim()
+        .with_span_policy(SpanPolicy::Original);
+    assert_eq!(options.span_policy(), SpanPolicy::Original);
+    let entry: Entry = from_regex_tree_and_str_with_options(&entry(), TEXT, &options).unwrap();
+    assert_eq!(entry.name.value(), "cat");
+    assert_eq!(*entry.size.value(), 12);
+    assert_eq!(entry.name.substring(TEXT), "  cat ");
+    assert_eq!(entry.size.substring(TEXT), "  12 ");
+}
+
+#[test]
+fn errors_follow_the_policy() {
+    let text = "cat |  x ";
+    let options = DeserializeOptions::new().trim();
+    let error =
+        from_regex_tree_and_str_with_options::<Entry>(&entry(), text, &options).unwrap_err();
+    assert_eq!(&text[error.span().unwrap()], "x");
+
+    let options = options.with_span_policy(SpanPolicy::Original);
+    let error =
+        from_regex_tree_and_str_with_options::<Entry>(&entry(), text, &options).unwrap_err();
+    assert_eq!(&text[error.span().unwrap()], "  x ");
+}
+
+#[test]
+fn unescaped_values_span_their_escapes() {
+    let regex_tree = RegexTree::root(r"(?s).*")
+        .with_unescape(Escapes::Backslash)
+        .build();
+    let text = r"a\tb";
+    for policy in [SpanPolicy::Transformed, SpanPolicy::Original] {
+        let options = DeserializeOptions::new().with_span_policy(policy);
+        let name: Spanned<String> =
+            from_regex_tree_and_str_with_options(&regex_tree, text, &options).unwrap();
+        assert_eq!(name.value(), "a\tb");
+        assert_eq!(name.substring(text), text);
+    }
+}
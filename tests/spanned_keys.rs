@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+
+use recursive_regex::regex_tree::DuplicateKeys;
+use recursive_regex::{from_regex_tree_and_str, validate, RegexTree, Spanned};
+use serde::Deserialize;
+
+#[test]
+fn map_keys() {
+    let regex_tree = RegexTree::leaf(r"w=(?P<width>\d+) h=(?P<height>\d+)");
+    let sizes: HashMap<Spanned<String>, u32> =
+        from_regex_tree_and_str(&regex_tree, "w=3 h=14").unwrap();
+
+    let expected = HashMap::from([
+        (Spanned::new_raw("height".to_owned(), 6, 8), 14),
+        (Spanned::new_raw("width".to_owned(), 2, 3), 3),
+    ]);
+    assert_eq!(sizes, expected);
+}
+
+#[test]
+fn shared_key_spans_every_group() {
+    let regex_tree = RegexTree::root(r"(?P<a[0]>\d+)-(?P<b>\d+)-(?P<a[1]>\d+)")
+        .with_duplicate_keys(DuplicateKeys::Collect)
+        .build();
+    let values: HashMap<Spanned<String>, Vec<u32>> =
+        from_regex_tree_and_str(&regex_tree, "10-20-30").unwrap();
+
+    let expected = HashMap::from([
+        (Spanned::new_raw("a".to_owned(), 0, 8), vec![10, 30]),
+        (Spanned::new_raw("b".to_owned(), 3, 5), vec![20]),
+    ]);
+    assert_eq!(values, expected);
+}
+
+#[test]
+fn nested_maps() {
+    #[derive(Debug, Deserialize)]
+    struct Row {
+        fields: HashMap<Spanned<String>, String>,
+    }
+
+    let regex_tree = RegexTree::root(r"\[(?P<fields>[^\]]*)\]")
+        .with_child("fields", RegexTree::leaf(r"(?P<x>\w+),(?P<y>\w+)"))
+        .build();
+    let rows: Vec<Row> = from_regex_tree_and_str(&regex_tree, "[a,b] [cd,e]").unwrap();
+    let mut spans: Vec<_> = rows[1]
+        .fields
+        .keys()
+        .map(|key| (key.value().as_str(), key.begin(), key.end()))
+        .collect();
+    spans.sort();
+    assert_eq!(spans, vec![("x", 7, 9), ("y", 10, 11)]);
+}
+
+#[test]
+fn validates() {
+    let regex_tree = RegexTree::leaf(r"(?P<a>\d+)");
+    assert!(validate::<HashMap<Spanned<String>, u32>>(&regex_tree).is_ok());
+}
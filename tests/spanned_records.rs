@@ -0,0 +1,47 @@
+use recursive_regex::{from_regex_tree_and_str, RegexTree, Spanned};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Eq, PartialEq)]
+struct Person<'a> {
+    name: &'a str,
+    numbers: Vec<Spanned<u32>>,
+}
+
+#[test]
+fn main() {
+    let file = "Lina: 2\nSelah: 3, 6";
+
+    let regex_tree = RegexTree::root(r"(?P<name>\w+): (?P<numbers>.*)")
+        .with_child("numbers", RegexTree::leaf(r"\d+"))
+        .build();
+
+    let people: Vec<Spanned<Person>> = from_regex_tree_and_str(&regex_tree, file).unwrap();
+    let expected = vec![
+        Spanned::new_raw(
+            Person {
+                name: "Lina",
+                numbers: vec![Spanned::new_raw(2, 6, 7)],
+            },
+            0,
+            7,
+        ),
+        Spanned::new_raw(
+            Person {
+                name: "Selah",
+                numbers: vec![Spanned::new_raw(3, 15, 16), Spanned::new_raw(6, 18, 19)],
+            },
+            8,
+            19,
+        ),
+    ];
+    assert_eq!(expected, people);
+    assert_eq!(people[1].substring(file), "Selah: 3, 6");
+
+    // Outside of a sequence, a record spans all of the text its node was run over
+    let first: Spanned<Person> = from_regex_tree_and_str(&regex_tree, " Lina: 2 ").unwrap();
+    assert_eq!((first.begin(), first.end()), (0, 9));
+
+    // The whole sequence spans the whole input
+    let all: Spanned<Vec<Person>> = from_regex_tree_and_str(&regex_tree, file).unwrap();
+    assert_eq!((all.begin(), all.end()), (0, file.len()));
+}
@@ -0,0 +1,60 @@
+use recursive_regex::{from_regex_tree_and_str, leaves, RegexTree, Spanned};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Pair<'a> {
+    #[serde(borrow)]
+    key: Spanned<&'a str>,
+    value: u32,
+}
+
+fn pairs() -> RegexTree {
+    RegexTree::split(";")
+        .with_child_index(0, RegexTree::leaf(r"(?P<key>\w+)=(?P<value>\d+)"))
+        .build()
+}
+
+#[test]
+fn pieces_recurse_into_whole_match_child() {
+    let parsed: Vec<Pair> = from_regex_tree_and_str(&pairs(), "a=1;bc=22").unwrap();
+    assert_eq!(
+        parsed,
+        vec![
+            Pair {
+                key: Spanned::new_raw("a", 0, 1),
+                value: 1,
+            },
+            Pair {
+                key: Spanned::new_raw("bc", 4, 6),
+                value: 22,
+            },
+        ]
+    );
+}
+
+#[test]
+fn empty_pieces() {
+    let regex_tree = RegexTree::split(",").build();
+
+    let parsed: Vec<&str> = from_regex_tree_and_str(&regex_tree, "").unwrap();
+    assert!(parsed.is_empty());
+
+    let parsed: Vec<&str> = from_regex_tree_and_str(&regex_tree, "a,,b,").unwrap();
+    assert_eq!(parsed, vec!["a", "", "b", ""]);
+}
+
+#[test]
+fn leaves_of_pieces() {
+    let found: Vec<_> = leaves(&pairs(), "a=1;b=2")
+        .map(|(path, value)| (path.to_string(), *value.value()))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            ("[0][0].key".to_owned(), "a"),
+            ("[0][0].value".to_owned(), "1"),
+            ("[1][0].key".to_owned(), "b"),
+            ("[1][0].value".to_owned(), "2"),
+        ]
+    );
+}
@@ -0,0 +1,83 @@
+use recursive_regex::{from_regex_tree_and_str, DeserializeOptions, RegexTree};
+use serde::Deserialize;
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Move {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+enum Event {
+    Login { user: String },
+    Move(Move),
+    Logout,
+}
+
+fn regex_tree() -> RegexTree {
+    RegexTree::lines(r"(?P<kind>\w+)(?: user=(?P<user>\w+)| x=(?P<x>-?\d+) y=(?P<y>-?\d+))?")
+        .with_tag("kind")
+        .build()
+}
+
+#[test]
+fn variant_from_tag() {
+    let events: Vec<Event> =
+        from_regex_tree_and_str(&regex_tree(), "Login user=ada\nMove x=1 y=-2\nLogout").unwrap();
+    assert_eq!(
+        events,
+        vec![
+            Event::Login {
+                user: "ada".to_owned()
+            },
+            Event::Move(Move { x: 1, y: -2 }),
+            Event::Logout,
+        ]
+    );
+}
+
+#[test]
+fn single_record() {
+    let regex_tree = RegexTree::root(r"(?P<kind>\w+) x=(?P<x>-?\d+) y=(?P<y>-?\d+)")
+        .with_tag("kind")
+        .build();
+    let event: Event = from_regex_tree_and_str(&regex_tree, "Move x=3 y=4").unwrap();
+    assert_eq!(event, Event::Move(Move { x: 3, y: 4 }));
+}
+
+#[test]
+fn tag_left_out_of_strict_fields() {
+    let options = DeserializeOptions::new().strict();
+    let events: Vec<Event> = recursive_regex::from_regex_tree_and_str_with_options(
+        &regex_tree(),
+        "Login user=ada\nMove x=1 y=2",
+        &options,
+    )
+    .unwrap();
+    assert_eq!(events.len(), 2);
+}
+
+#[test]
+fn unknown_variant_spans_tag() {
+    let error =
+        from_regex_tree_and_str::<Vec<Event>>(&regex_tree(), "Logout\nJump x=1 y=2").unwrap_err();
+    assert!(error.message().starts_with("unknown variant `Jump`"));
+    assert_eq!(error.span(), Some(7..11));
+}
+
+#[test]
+fn field_errors_have_paths() {
+    let error =
+        from_regex_tree_and_str::<Vec<Event>>(&regex_tree(), "Move x=1 y=99999999999").unwrap_err();
+    assert_eq!(error.span(), Some(11..22));
+}
+
+#[test]
+fn enums_need_tag() {
+    let regex_tree = RegexTree::root(r"(?P<kind>\w+)").build();
+    let error = from_regex_tree_and_str::<Event>(&regex_tree, "Logout").unwrap_err();
+    assert_eq!(
+        error.message(),
+        "enums are not supported without a tag group"
+    );
+}
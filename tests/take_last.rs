@@ -0,0 +1,155 @@
+use recursive_regex::regex_tree::Count;
+use recursive_regex::{
+    from_regex_tree_and_str_with_options, records_from_regex_tree_and_str_with_options,
+    transcode_with_options, DeserializeOptions, Error, RegexTree, TranscodeError,
+};
+use serde::ser::Error as _;
+use serde::{Deserialize, Serialize, Serializer};
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Entry {
+    name: String,
+    value: u32,
+}
+
+fn regex_tree() -> RegexTree {
+    RegexTree::lines(r"(?P<name>\w+)=(?P<value>.*)").build()
+}
+
+const TEXT: &str = "a=1\nb=x\nc=3\nd=4";
+
+#[test]
+fn last_entries() {
+    let options = DeserializeOptions::new().take_last(2);
+    let entries: Vec<Entry> =
+        from_regex_tree_and_str_with_options(&regex_tree(), TEXT, &options).unwrap();
+    let names: Vec<_> = entries.iter().map(|entry| entry.name.as_str()).collect();
+    assert_eq!(names, vec!["c", "d"]);
+}
+
+#[test]
+fn earlier_errors_skipped() {
+    let options = DeserializeOptions::new().take_last(3);
+    let error = from_regex_tree_and_str_with_options::<Vec<Entry>>(&regex_tree(), TEXT, &options)
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "[1].value: parsing error: invalid digit found in string"
+    );
+    assert_eq!(error.span(), Some(6..7));
+}
+
+#[test]
+fn more_than_matches() {
+    let options = DeserializeOptions::new().take_last(10);
+    let entries: Vec<Entry> =
+        from_regex_tree_and_str_with_options(&regex_tree(), "a=1\nb=2", &options).unwrap();
+    assert_eq!(entries.len(), 2);
+}
+
+#[test]
+fn take_none() {
+    let options = DeserializeOptions::new().take_last(0);
+    let entries: Vec<Entry> =
+        from_regex_tree_and_str_with_options(&regex_tree(), TEXT, &options).unwrap();
+    assert!(entries.is_empty());
+}
+
+#[test]
+fn counts_skipped_matches() {
+    let regex_tree = RegexTree::root(r"\d+")
+        .with_count(Count::at_most(3))
+        .build();
+    let options = DeserializeOptions::new().take_last(1);
+    let error = from_regex_tree_and_str_with_options::<Vec<u32>>(&regex_tree, "1 2 3 4", &options)
+        .unwrap_err();
+    assert_eq!(error.message(), "expected at most 3 matches but found more");
+    let options = DeserializeOptions::new().take_last(0);
+    let error = from_regex_tree_and_str_with_options::<Vec<u32>>(&regex_tree, "1 2 3 4", &options)
+        .unwrap_err();
+    assert_eq!(error.message(), "expected at most 3 matches but found 4");
+    let options = DeserializeOptions::new().take_last(1);
+
+    let regex_tree = RegexTree::root(r"\d+")
+        .with_count(Count::at_least(2))
+        .build();
+    let last: Vec<u32> =
+        from_regex_tree_and_str_with_options(&regex_tree, "1 2", &options).unwrap();
+    assert_eq!(last, vec![2]);
+}
+
+#[test]
+fn records() {
+    let options = DeserializeOptions::new().take_last(2);
+    let records: Vec<Result<Entry, Error>> =
+        records_from_regex_tree_and_str_with_options(&regex_tree(), "a=1\nb=x\nc=3", &options)
+            .collect();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].as_ref().unwrap_err().span(), Some(6..7));
+    assert_eq!(
+        records[1],
+        Ok(Entry {
+            name: "c".to_owned(),
+            value: 3
+        })
+    );
+}
+
+#[test]
+fn matches() {
+    let regex_tree = RegexTree::root(r"(?P<name>\w+): (?P<nums>.*)")
+        .with_child("nums", RegexTree::leaf(r"\d+"))
+        .build();
+    let last: Vec<_> = regex_tree
+        .matches("a: 1\nb: 2 3\nc: 4")
+        .take_last(2)
+        .collect();
+    assert_eq!(last.len(), 2);
+    assert_eq!(last[0].range(), 5..11);
+    let nums = last[0].group("nums").unwrap().matches().unwrap();
+    assert_eq!(nums[1].range(), 10..11);
+    assert_eq!(regex_tree.matches("a: 1").take_last(0).len(), 0);
+}
+
+/// Text to transcode into CBOR, whose serializer is only reachable through a value
+struct Transcoded<'a>(&'a RegexTree, &'a str, &'a DeserializeOptions);
+
+impl Serialize for Transcoded<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        transcode_with_options(self.0, self.1, self.2, serializer).map_err(|err| match err {
+            TranscodeError::Deserialize(err) => S::Error::custom(err),
+            TranscodeError::Serialize(err) => err,
+        })
+    }
+}
+
+#[test]
+fn binary_transcoding() {
+    let regex_tree = RegexTree::lines(r"(?P<name>\w+)=(?P<value>\d+)").build();
+    for (n, expected) in [
+        (2, vec!["c", "d"]),
+        (0, vec![]),
+        (9, vec!["a", "b", "c", "d"]),
+    ] {
+        let options = DeserializeOptions::new().take_last(n);
+        let mut out = Vec::new();
+        ciborium::ser::into_writer(
+            &Transcoded(&regex_tree, "a=1\nb=2\nc=3\nd=4", &options),
+            &mut out,
+        )
+        .unwrap();
+        let entries: Vec<Entry> = ciborium::de::from_reader(out.as_slice()).unwrap();
+        let names: Vec<_> = entries.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, expected);
+    }
+}
+
+#[cfg(feature = "deserialize-regex-tree")]
+#[test]
+fn deserialized_tree() {
+    let regex_tree: RegexTree = serde_json::from_str(r#"{"regex": "\\d+"}"#).unwrap();
+    let options = DeserializeOptions::new().take_last(1);
+    let last: Vec<u32> =
+        from_regex_tree_and_str_with_options(&regex_tree, "1 2 3", &options).unwrap();
+    assert_eq!(last, vec![3]);
+}
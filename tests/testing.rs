@@ -0,0 +1,103 @@
+#![cfg(feature = "testing")]
+
+use proptest::prelude::*;
+use recursive_regex::testing::{
+    assert_round_trip, assert_round_trip_with_options, assert_spans_in_bounds,
+    assert_spans_in_bounds_with_options,
+};
+use recursive_regex::{DeserializeOptions, RegexTree};
+use serde::Deserialize;
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Entry {
+    name: String,
+    scores: Vec<u32>,
+}
+
+fn regex_tree() -> RegexTree {
+    RegexTree::lines(r"(?P<name>\w+): (?P<scores>.*)")
+        .with_child("scores", RegexTree::leaf(r"\d+"))
+        .build()
+}
+
+fn write(entries: &[Entry]) -> String {
+    entries
+        .iter()
+        .map(|entry| {
+            let scores: Vec<_> = entry.scores.iter().map(u32::to_string).collect();
+            format!("{}: {}", entry.name, scores.join(" "))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn entries() -> impl Strategy<Value = String> {
+    prop::collection::vec(
+        ("[a-z]{1,8}", prop::collection::vec(0..1000u32, 1..4)),
+        0..5,
+    )
+    .prop_map(|entries| {
+        entries
+            .into_iter()
+            .map(|(name, scores)| {
+                let scores: Vec<_> = scores.iter().map(u32::to_string).collect();
+                format!("{name}: {}", scores.join("  "))
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    })
+}
+
+proptest! {
+    #[test]
+    fn generated_entries(text in entries(), base_offset in 0..100usize) {
+        let regex_tree = regex_tree();
+        assert_round_trip(&regex_tree, &text, |entries: &Vec<Entry>| write(entries));
+        assert_spans_in_bounds(&regex_tree, &text);
+
+        let options = DeserializeOptions::new().with_base_offset(base_offset);
+        assert_round_trip_with_options(&regex_tree, &text, &options, |entries: &Vec<Entry>| write(entries));
+        assert_spans_in_bounds_with_options(&regex_tree, &text, &options);
+    }
+
+    #[test]
+    fn arbitrary_text(text in "[a-z0-9: \n]{0,32}") {
+        assert_spans_in_bounds(&regex_tree(), &text);
+    }
+}
+
+#[test]
+fn returns_the_value() {
+    let entries = assert_round_trip(&regex_tree(), "a: 1  2\nb: 3", |entries: &Vec<Entry>| {
+        write(entries)
+    });
+    assert_eq!(
+        entries,
+        vec![
+            Entry {
+                name: "a".to_owned(),
+                scores: vec![1, 2]
+            },
+            Entry {
+                name: "b".to_owned(),
+                scores: vec![3]
+            },
+        ]
+    );
+}
+
+#[test]
+#[should_panic(expected = "changed after being written")]
+fn lossy_writer() {
+    assert_round_trip(&regex_tree(), "a: 1 2", |entries: &Vec<Entry>| {
+        format!("{}: 1", entries[0].name)
+    });
+}
+
+#[test]
+#[should_panic(expected = "failed to deserialize \"a: 99999999999\"")]
+fn invalid_text() {
+    assert_round_trip(&regex_tree(), "a: 99999999999", |entries: &Vec<Entry>| {
+        write(entries)
+    });
+}
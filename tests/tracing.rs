@@ -0,0 +1,93 @@
+#![cfg(feature = "tracing")]
+
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use recursive_regex::{from_regex_tree_and_str, RegexTree};
+use serde::Deserialize;
+
+/// Output of a subscriber, shared with the test
+#[derive(Clone, Default)]
+struct Output(Arc<Mutex<Vec<u8>>>);
+
+impl io::Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Run `f`, returning what it traced, one event per line
+fn traced(f: impl FnOnce()) -> String {
+    let output = Output::default();
+    let writer = output.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::TRACE)
+        .with_ansi(false)
+        .without_time()
+        .with_target(false)
+        .with_level(false)
+        .with_writer(move || writer.clone())
+        .finish();
+    tracing::subscriber::with_default(subscriber, f);
+    let bytes = output.0.lock().unwrap().clone();
+    String::from_utf8(bytes).unwrap()
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct Line {
+    name: String,
+    nums: Vec<u32>,
+}
+
+#[test]
+fn nodes_matches_and_values() {
+    let regex_tree = RegexTree::root(r"(?P<name>\w+): (?P<nums>.*)")
+        .with_child("nums", RegexTree::leaf(r"\d+"))
+        .build();
+
+    let log = traced(|| {
+        let _: Vec<Line> = from_regex_tree_and_str(&regex_tree, "a: 1\nb: 2 3").unwrap();
+    });
+    let lines: Vec<&str> = log.lines().collect();
+
+    assert!(lines[0].contains("matching node repeatedly"), "{log}");
+    assert!(lines[0].contains("start=0 end=11"), "{log}");
+    // The last number is parsed within the second match, its group, and the child's third match
+    let last = lines.last().unwrap();
+    assert!(
+        last.starts_with("match{index=1 start=5 end=11}:group{index=2 name=\"nums\" start=8 end=11}:match{index=1 start=10 end=11}:"),
+        "{log}"
+    );
+    assert!(
+        last.contains("parsing value kind=\"u32\" text=\"3\" start=10 end=11"),
+        "{log}"
+    );
+}
+
+#[test]
+fn single_match() {
+    let regex_tree = RegexTree::leaf(r"(?P<name>\w+): (?P<nums>.*)");
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Named {
+        name: String,
+    }
+
+    let log = traced(|| {
+        let _: Named = from_regex_tree_and_str(&regex_tree, "x a: 1").unwrap();
+    });
+    assert!(log.contains("node matched once"), "{log}");
+    assert!(log.contains("start=2 end=6"), "{log}");
+    assert!(
+        log.contains(
+            "group{index=1 name=\"name\" start=2 end=3}: parsing value kind=\"str\" text=\"a\""
+        ),
+        "{log}"
+    );
+}
@@ -0,0 +1,184 @@
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::Instant;
+
+use recursive_regex::regex_tree::Count;
+use recursive_regex::{
+    from_regex_tree_and_str, transcode, transcode_with_options, DeserializeOptions, RegexTree,
+    Spanned, TranscodeError,
+};
+use serde::ser::Error as _;
+use serde::{Deserialize, Serialize, Serializer};
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Reading {
+    sensor: String,
+    level: u32,
+    ok: bool,
+}
+
+fn regex_tree() -> RegexTree {
+    RegexTree::lines(r"(?P<sensor>\w+) (?P<level>\d+) (?P<ok>\w+)").build()
+}
+
+const TEXT: &str = "a1 40 true\nb2 7 false";
+
+#[test]
+fn human_readable_keeps_strings() {
+    let mut out = Vec::new();
+    transcode(
+        &regex_tree(),
+        TEXT,
+        &mut serde_json::Serializer::new(&mut out),
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        r#"[{"sensor":"a1","level":"40","ok":"true"},{"sensor":"b2","level":"7","ok":"false"}]"#
+    );
+}
+
+#[test]
+fn human_readable_infers_types_when_asked() {
+    let options = DeserializeOptions::new().infer_types();
+    let mut out = Vec::new();
+    transcode_with_options(
+        &regex_tree(),
+        "a1 40 true",
+        &options,
+        &mut serde_json::Serializer::new(&mut out),
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        r#"[{"sensor":"a1","level":40,"ok":true}]"#
+    );
+}
+
+/// Text to transcode, for serializers that are only reachable through a value
+struct Transcoded<'a>(&'a RegexTree, &'a str, &'a DeserializeOptions);
+
+impl Serialize for Transcoded<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        transcode_with_options(self.0, self.1, self.2, serializer).map_err(|err| match err {
+            TranscodeError::Deserialize(err) => S::Error::custom(err),
+            TranscodeError::Serialize(err) => err,
+        })
+    }
+}
+
+#[test]
+fn binary_format_gets_typed_values() {
+    let mut out = Vec::new();
+    let options = DeserializeOptions::new();
+    ciborium::ser::into_writer(&Transcoded(&regex_tree(), TEXT, &options), &mut out).unwrap();
+
+    let readings: Vec<Reading> = ciborium::de::from_reader(out.as_slice()).unwrap();
+    let expected: Vec<Reading> = from_regex_tree_and_str(&regex_tree(), TEXT).unwrap();
+    assert_eq!(readings, expected);
+}
+
+#[test]
+fn counting_is_within_limits() {
+    // Binary formats search the text twice, once to count the matches
+    let options = DeserializeOptions::new().with_scan_limit(TEXT.len());
+    let mut out = Vec::new();
+    let error = ciborium::ser::into_writer(&Transcoded(&regex_tree(), TEXT, &options), &mut out)
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("scan limit of 21 bytes exceeded"));
+
+    let options = DeserializeOptions::new().with_scan_limit(2 * TEXT.len());
+    let mut out = Vec::new();
+    ciborium::ser::into_writer(&Transcoded(&regex_tree(), TEXT, &options), &mut out).unwrap();
+
+    let options = DeserializeOptions::new().with_deadline(Instant::now());
+    let mut out = Vec::new();
+    let error = ciborium::ser::into_writer(&Transcoded(&regex_tree(), TEXT, &options), &mut out)
+        .unwrap_err();
+    assert!(error.to_string().contains("deadline passed"));
+    // Nothing is written when the counting fails
+    assert!(out.is_empty());
+}
+
+#[test]
+fn deserialize_errors_are_kept() {
+    let regex_tree = RegexTree::lines(r"(?P<sensor>\w+)")
+        .with_count(Count::at_least(3))
+        .build();
+    let mut out = Vec::new();
+    let result = transcode(
+        &regex_tree,
+        TEXT,
+        &mut serde_json::Serializer::new(&mut out),
+    );
+    match result {
+        Err(TranscodeError::Deserialize(err)) => {
+            assert_eq!(err.message(), "expected at least 3 matches but found 2")
+        }
+        _ => panic!("expected a deserialize error"),
+    }
+}
+
+#[test]
+fn deserializers_are_human_readable() {
+    // Addresses deserialize from text only in human readable formats
+    let regex_tree = RegexTree::root(r"(?P<addr>\S+) (?P<rest>.*)")
+        .with_child("rest", RegexTree::leaf(r"\S+"))
+        .build();
+
+    #[derive(Deserialize)]
+    struct Hosts {
+        addr: Spanned<IpAddr>,
+        rest: Vec<IpAddr>,
+    }
+
+    let hosts: Hosts = from_regex_tree_and_str(&regex_tree, "127.0.0.1 10.0.0.1 ::1").unwrap();
+    assert_eq!(*hosts.addr.value(), IpAddr::V4(Ipv4Addr::LOCALHOST));
+    assert_eq!(hosts.rest.len(), 2);
+
+    let root: IpAddr = from_regex_tree_and_str(&RegexTree::leaf(r".+"), "10.1.2.3").unwrap();
+    assert_eq!(root, IpAddr::V4(Ipv4Addr::new(10, 1, 2, 3)));
+}
+
+#[test]
+fn same_shape_as_value() {
+    let regex_tree = RegexTree::root(r"(?P<name>\w+): (?P<nums>.*)")
+        .with_child("nums", RegexTree::leaf(r"\d+"))
+        .build();
+    let text = "a: 1 2\nb: 3\nc: ";
+
+    let mut out = Vec::new();
+    transcode(
+        &regex_tree,
+        text,
+        &mut serde_json::Serializer::new(&mut out),
+    )
+    .unwrap();
+    let value = recursive_regex::to_value(&regex_tree, text).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        serde_json::to_string(&value).unwrap()
+    );
+}
+
+#[test]
+fn stops_at_first_bad_record() {
+    let regex_tree = RegexTree::lines(r"(?P<sensor>\w+) (?P<level>\d+)").build();
+    let mut out = Vec::new();
+    let result = transcode(
+        &regex_tree,
+        "a 1\nb x\nc 3",
+        &mut serde_json::Serializer::new(&mut out),
+    );
+    let Err(TranscodeError::Deserialize(err)) = result else {
+        panic!("expected a deserialize error");
+    };
+    assert_eq!(err.message(), "line 2 does not match");
+    assert_eq!(err.span(), Some(4..7));
+    // The records before it were already written
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        r#"[{"sensor":"a","level":"1"}"#
+    );
+}
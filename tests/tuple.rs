@@ -0,0 +1,111 @@
+use recursive_regex::{from_regex_tree_and_str, validate, RegexTree};
+
+#[test]
+fn pairs_without_whole_match() {
+    let regex_tree = RegexTree::leaf(r"(\w+)=(\d+)");
+
+    let pairs: Vec<(String, u32)> = from_regex_tree_and_str(&regex_tree, "a=1 b=2").unwrap();
+    assert_eq!(pairs, vec![("a".to_owned(), 1), ("b".to_owned(), 2)]);
+}
+
+#[test]
+fn named_groups() {
+    let regex_tree = RegexTree::leaf(r"(?P<key>\w+)=(?P<value>\d+)");
+
+    let pairs: Vec<(&str, u32)> = from_regex_tree_and_str(&regex_tree, "a=1 b=2").unwrap();
+    assert_eq!(pairs, vec![("a", 1), ("b", 2)]);
+}
+
+#[test]
+fn whole_match_first_when_there_is_room() {
+    let regex_tree = RegexTree::leaf(r"(\w+)=(\d+)");
+
+    let triples: Vec<(&str, &str, u32)> = from_regex_tree_and_str(&regex_tree, "a=1").unwrap();
+    assert_eq!(triples, vec![("a=1", "a", 1)]);
+}
+
+#[test]
+fn groups_keep_their_positions() {
+    let regex_tree = RegexTree::leaf(r"(\w+)(?:=(\d+))?(?::(\w+))?");
+
+    let parsed: Vec<(&str, Option<u32>, Option<&str>)> =
+        from_regex_tree_and_str(&regex_tree, "a:x b=2").unwrap();
+    assert_eq!(parsed, vec![("a", None, Some("x")), ("b", Some(2), None)]);
+}
+
+#[test]
+fn children_apply_to_elements() {
+    let regex_tree = RegexTree::root(r"(\w+)=(\S+)")
+        .with_child_index(2, RegexTree::leaf(r"\d+"))
+        .build();
+
+    let pairs: Vec<(&str, Vec<u32>)> = from_regex_tree_and_str(&regex_tree, "a=1,2").unwrap();
+    assert_eq!(pairs, vec![("a", vec![1, 2])]);
+}
+
+#[test]
+fn arity_mismatch_is_an_error() {
+    let regex_tree = RegexTree::leaf(r"(\w+)=(\d+)");
+
+    let error = from_regex_tree_and_str::<Vec<(&str,)>>(&regex_tree, "a=1").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "[0]: expected 2 capture groups for a tuple of 1 elements"
+    );
+    assert_eq!(error.span(), Some(0..3));
+
+    assert!(from_regex_tree_and_str::<Vec<(&str, &str, &str, &str)>>(&regex_tree, "a=1").is_err());
+}
+
+#[test]
+fn validate_pairs() {
+    let regex_tree = RegexTree::root(r"(\w+)=(\S+)")
+        .with_child_index(2, RegexTree::leaf(r"\d+"))
+        .build();
+
+    assert!(validate::<Vec<(&str, Vec<u32>)>>(&regex_tree).is_ok());
+}
+
+#[test]
+fn array_of_matches() {
+    let regex_tree = RegexTree::leaf(r"\d+");
+
+    let octets: [u8; 4] = from_regex_tree_and_str(&regex_tree, "10.0.0.255").unwrap();
+    assert_eq!(octets, [10, 0, 0, 255]);
+}
+
+#[test]
+fn too_few_matches_for_array() {
+    let regex_tree = RegexTree::root(r"(?P<addr>\S+) (?P<port>\d+)")
+        .with_child("addr", RegexTree::leaf(r"\d+"))
+        .build();
+
+    #[derive(Debug, serde::Deserialize)]
+    #[allow(dead_code)]
+    struct Host {
+        addr: [u8; 4],
+        port: u16,
+    }
+
+    let error = from_regex_tree_and_str::<Vec<Host>>(&regex_tree, "10.0.1 80").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "[0].addr: expected 4 matches for a tuple of 4 elements but found 3"
+    );
+    assert_eq!(error.span(), Some(0..6));
+}
+
+#[test]
+fn too_many_matches_for_array() {
+    let regex_tree = RegexTree::leaf(r"\d+");
+
+    let error = from_regex_tree_and_str::<[u8; 4]>(&regex_tree, "10.0.0.1.5.6").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "expected 4 matches for a tuple of 4 elements but found 6"
+    );
+    assert_eq!(error.span(), Some(0..12));
+
+    let error = from_regex_tree_and_str::<(u8, u8)>(&regex_tree, "1 2 3").unwrap_err();
+    assert_eq!(error.span(), Some(0..5));
+}
@@ -0,0 +1,109 @@
+use recursive_regex::{
+    from_regex_tree_and_str, validate, RegexTree, Spanned, Uncaptured, ValidationIssue,
+};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct Assignment<'a> {
+    name: &'a str,
+    value: u32,
+    rest: Uncaptured<String>,
+}
+
+fn assignments() -> RegexTree {
+    RegexTree::lines(r"let (?P<name>\w+) = (?P<value>\d+);.*").build()
+}
+
+#[test]
+fn joined_text_outside_groups() {
+    let parsed: Vec<Assignment> =
+        from_regex_tree_and_str(&assignments(), "let x = 1; // one\nlet y = 2;").unwrap();
+    assert_eq!((parsed[0].name, parsed[0].value), ("x", 1));
+    assert_eq!(parsed[0].rest.value(), "let  = ; // one");
+    assert_eq!(parsed[1].rest.clone().into_inner(), "let  = ;");
+}
+
+#[test]
+fn pieces_with_spans() {
+    #[derive(Debug, Deserialize)]
+    struct Pieces {
+        #[allow(dead_code)]
+        name: String,
+        rest: Uncaptured<Vec<Spanned<String>>>,
+    }
+
+    let parsed: Vec<Pieces> = from_regex_tree_and_str(&assignments(), "let x = 1;").unwrap();
+    let pieces: Vec<_> = parsed[0]
+        .rest
+        .value()
+        .iter()
+        .map(|piece| (piece.value().as_str(), piece.begin(), piece.end()))
+        .collect();
+    assert_eq!(pieces, vec![("let ", 0, 4), (" = ", 5, 8), (";", 9, 10)]);
+}
+
+#[test]
+fn nothing_left_over() {
+    #[derive(Debug, Deserialize)]
+    struct Pair<'a> {
+        key: &'a str,
+        #[serde(borrow)]
+        rest: Uncaptured<&'a str>,
+    }
+
+    let regex_tree = RegexTree::leaf(r"(?P<key>\w+)(?:=(?P<value>\w+))?");
+    let pairs: Vec<Pair> = from_regex_tree_and_str(&regex_tree, "a=1 b").unwrap();
+    assert_eq!((pairs[0].key, *pairs[0].rest.value()), ("a", "="));
+    assert_eq!((pairs[1].key, *pairs[1].rest.value()), ("b", ""));
+}
+
+#[test]
+fn nested_groups_are_captured() {
+    #[derive(Debug, Deserialize)]
+    struct Call<'a> {
+        #[serde(borrow)]
+        args: &'a str,
+        #[serde(borrow)]
+        rest: Uncaptured<Vec<&'a str>>,
+    }
+
+    let regex_tree = RegexTree::leaf(r"f\((?P<args>(\w+)(?:, (\w+))*)\)");
+    let call: Call = from_regex_tree_and_str(&regex_tree, "f(a, b)").unwrap();
+    assert_eq!(call.args, "a, b");
+    assert_eq!(call.rest.value(), &["f(", ")"]);
+}
+
+#[test]
+fn other_fields_without_groups_are_still_missing() {
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Missing<'a> {
+        name: &'a str,
+        comment: Option<&'a str>,
+        value: u32,
+        kind: &'a str,
+    }
+
+    let error = from_regex_tree_and_str::<Vec<Missing>>(&assignments(), "let x = 1;").unwrap_err();
+    assert_eq!(error.to_string(), "[0]: missing field `kind`");
+}
+
+#[test]
+fn validate_accepts_uncaptured_fields() {
+    assert!(validate::<Vec<Assignment>>(&assignments()).is_ok());
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Other {
+        name: String,
+        value: u32,
+        other: String,
+    }
+    assert_eq!(
+        validate::<Vec<Other>>(&assignments()).issues(),
+        [ValidationIssue::MissingCapture {
+            path: vec![],
+            field: "other".to_owned()
+        }]
+    );
+}
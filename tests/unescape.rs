@@ -0,0 +1,113 @@
+use recursive_regex::regex_tree::Escapes;
+use recursive_regex::{from_regex_tree_and_str, RegexTree};
+use serde::Deserialize;
+
+fn quoted(escapes: Escapes) -> RegexTree {
+    RegexTree::root(r#""(?P<text>(?:[^"\\]|\\.|"")*)""#)
+        .with_child(
+            "text",
+            RegexTree::root("(?s).*").with_unescape(escapes).build(),
+        )
+        .build()
+}
+
+#[derive(Debug, Deserialize)]
+struct Quoted {
+    text: String,
+}
+
+fn texts(escapes: Escapes, text: &str) -> Vec<String> {
+    from_regex_tree_and_str::<Vec<Quoted>>(&quoted(escapes), text)
+        .unwrap()
+        .into_iter()
+        .map(|quoted| quoted.text)
+        .collect()
+}
+
+#[test]
+fn doubled_quotes() {
+    let regex_tree = RegexTree::root(r#"(?m)^(?P<name>[^,]*),"(?P<quote>(?:[^"]|"")*)"$"#)
+        .with_child(
+            "quote",
+            RegexTree::root("(?s).*")
+                .with_unescape(Escapes::DoubledQuotes)
+                .build(),
+        )
+        .build();
+    #[derive(Debug, Deserialize)]
+    struct Row {
+        quote: String,
+    }
+
+    let rows: Vec<Row> = from_regex_tree_and_str(
+        &regex_tree,
+        "ann,\"she said \"\"hi\"\"\"\nbob,\"no quotes \\n\"",
+    )
+    .unwrap();
+    assert_eq!(rows[0].quote, "she said \"hi\"");
+    assert_eq!(rows[1].quote, "no quotes \\n");
+}
+
+#[test]
+fn json_escapes() {
+    assert_eq!(
+        texts(
+            Escapes::Json,
+            r#""a\"b\\c\/d\b\f\n\r\t" "\u00e9\ud83d\ude00""#
+        ),
+        vec!["a\"b\\c/d\u{8}\u{c}\n\r\t", "é😀"]
+    );
+}
+
+#[test]
+fn invalid_json_escapes() {
+    for (text, message) in [
+        (r#""\'""#, "invalid escape `\\'`"),
+        (r#""\u{41}""#, "invalid unicode escape"),
+        (r#""\u00e""#, "invalid unicode escape"),
+        (r#""\ud83d""#, "invalid unicode escape"),
+        (r#""\ud83d\u0041""#, "invalid unicode escape"),
+        (r#""\ude00""#, "invalid unicode escape"),
+    ] {
+        let error =
+            from_regex_tree_and_str::<Vec<Quoted>>(&quoted(Escapes::Json), text).unwrap_err();
+        assert_eq!(error.message(), message, "{text}");
+        assert_eq!(error.span(), Some(1..text.len() - 1));
+    }
+}
+
+#[test]
+fn backslash_escapes() {
+    assert_eq!(
+        texts(Escapes::Backslash, r#""\u{1F600}\'\0" "plain""#),
+        vec!["😀'\0", "plain"]
+    );
+}
+
+#[test]
+fn values_are_parsed_after_unescaping() {
+    let regex_tree = RegexTree::root(r"\S+").with_unescape(Escapes::Json).build();
+    let nums: Vec<u32> = from_regex_tree_and_str(&regex_tree, r"\u0031\u0032 3").unwrap();
+    assert_eq!(nums, vec![12, 3]);
+    let chars: Vec<char> = from_regex_tree_and_str(&regex_tree, r"\n x").unwrap();
+    assert_eq!(chars, vec!['\n', 'x']);
+
+    let error = from_regex_tree_and_str::<Vec<u32>>(&regex_tree, r"1 \x").unwrap_err();
+    assert_eq!(error.message(), "invalid escape `\\x`");
+    assert_eq!(error.span(), Some(2..4));
+}
+
+#[test]
+fn schema_shows_escapes() {
+    let schema = quoted(Escapes::DoubledQuotes).schema().to_string();
+    assert!(schema.contains("unescape doubled quotes"), "{schema}");
+}
+
+#[cfg(feature = "deserialize-regex-tree")]
+#[test]
+fn deserialized_tree() {
+    let regex_tree: RegexTree =
+        serde_json::from_str(r#"{ "regex": "\\S+", "unescape": "json" }"#).unwrap();
+    let words: Vec<String> = from_regex_tree_and_str(&regex_tree, r"a\/b").unwrap();
+    assert_eq!(words, vec!["a/b"]);
+}
@@ -0,0 +1,63 @@
+use recursive_regex::{
+    from_regex_tree_and_str, from_regex_tree_and_str_with_options, DeserializeOptions, RegexTree,
+};
+use serde::Deserialize;
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Entry {
+    name: String,
+    signed: (),
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Marker;
+
+fn regex_tree() -> RegexTree {
+    RegexTree::root(r"(?P<name>\w+) (?P<signed>.*)")
+        .with_child("signed", RegexTree::leaf(r"-- signed"))
+        .build()
+}
+
+#[test]
+fn unit_field_ignores_text_by_default() {
+    let entry: Entry = from_regex_tree_and_str(&regex_tree(), "release draft").unwrap();
+    assert_eq!(entry.name, "release");
+}
+
+#[test]
+fn strict_unit_field_requires_match() {
+    let options = DeserializeOptions::new().strict_unit();
+
+    let entry: Entry =
+        from_regex_tree_and_str_with_options(&regex_tree(), "release -- signed", &options).unwrap();
+    assert_eq!(entry.name, "release");
+
+    let error =
+        from_regex_tree_and_str_with_options::<Entry>(&regex_tree(), "release draft", &options)
+            .unwrap_err();
+    assert!(error.message().contains("does not match"));
+}
+
+#[test]
+fn strict_unit_root_and_unit_struct() {
+    let regex_tree = RegexTree::root(r"^BEGIN").build();
+    let options = DeserializeOptions::new().strict_unit();
+
+    from_regex_tree_and_str_with_options::<()>(&regex_tree, "BEGIN data", &options).unwrap();
+    assert!(from_regex_tree_and_str_with_options::<()>(&regex_tree, "data", &options).is_err());
+
+    let marker: Marker =
+        from_regex_tree_and_str_with_options(&regex_tree, "BEGIN data", &options).unwrap();
+    assert_eq!(marker, Marker);
+    assert!(from_regex_tree_and_str_with_options::<Marker>(&regex_tree, "data", &options).is_err());
+}
+
+#[test]
+fn strict_unit_error_has_near_miss_span() {
+    let regex_tree = RegexTree::root(r"BEGIN:\d").build();
+    let options = DeserializeOptions::new().strict_unit().near_misses();
+
+    let error =
+        from_regex_tree_and_str_with_options::<()>(&regex_tree, "BEGIN:x", &options).unwrap_err();
+    assert_eq!(error.span(), Some(0..6));
+}
@@ -0,0 +1,68 @@
+use recursive_regex::{
+    from_regex_tree_and_str, from_regex_tree_and_str_with_options, DeserializeOptions, RegexTree,
+};
+use serde::Deserialize;
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(untagged)]
+enum Line {
+    Pair { key: String, value: String },
+    Word { word: String },
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(untagged)]
+enum Reading {
+    Number { reading: i64 },
+    Flag { reading: bool },
+    Text { reading: String },
+}
+
+#[test]
+fn variant_chosen_by_groups() {
+    let regex_tree = RegexTree::lines(r"(?P<key>\w+)=(?P<value>\w+)|(?P<word>\w+)").build();
+    let lines: Vec<Line> = from_regex_tree_and_str(&regex_tree, "a=1\nb\nc=d").unwrap();
+    assert_eq!(
+        lines,
+        vec![
+            Line::Pair {
+                key: "a".to_owned(),
+                value: "1".to_owned()
+            },
+            Line::Word {
+                word: "b".to_owned()
+            },
+            Line::Pair {
+                key: "c".to_owned(),
+                value: "d".to_owned()
+            },
+        ]
+    );
+}
+
+#[test]
+fn variant_chosen_by_inferred_type() {
+    let regex_tree = RegexTree::lines(r"(?P<reading>\S+)").build();
+    let options = DeserializeOptions::new().infer_types();
+    let readings: Vec<Reading> =
+        from_regex_tree_and_str_with_options(&regex_tree, "-4\ntrue\nhigh", &options).unwrap();
+    assert_eq!(
+        readings,
+        vec![
+            Reading::Number { reading: -4 },
+            Reading::Flag { reading: true },
+            Reading::Text {
+                reading: "high".to_owned()
+            },
+        ]
+    );
+}
+
+#[test]
+fn no_variant_error_spans_record() {
+    let regex_tree = RegexTree::lines(r"(?P<key>\w+)=(?P<value>\w+)|(?P<other>\w+)").build();
+    let error = from_regex_tree_and_str::<Vec<Line>>(&regex_tree, "a=1\nbad").unwrap_err();
+    assert!(error.message().contains("did not match any variant"));
+    assert_eq!(error.span(), Some(4..7));
+    assert_eq!(error.to_string().split(':').next(), Some("[1]"));
+}
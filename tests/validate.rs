@@ -0,0 +1,67 @@
+use recursive_regex::{validate, RegexTree, Spanned, ValidationIssue};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct Play {
+    title: Spanned<String>,
+    year: Option<u32>,
+    tags: Vec<Spanned<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct Tag {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct Catalog {
+    title: String,
+    tags: Vec<Tag>,
+    meta: Tag,
+}
+
+#[test]
+fn matching_tree() {
+    let regex_tree =
+        RegexTree::root(r"Title: (?P<title>.*)\nYear: (?P<year>.*)\nTags: (?P<tags>.*)(?:\n|$)")
+            .with_child("tags", RegexTree::leaf(r"[a-z]+"))
+            .build();
+
+    let report = validate::<Vec<Play>>(&regex_tree);
+    assert!(report.is_ok(), "{report}");
+}
+
+#[test]
+fn mismatched_tree() {
+    let regex_tree = RegexTree::root(r"(?P<title>\w+) (?P<tags>.*) (?P<meta>.*)")
+        .with_child("tags", RegexTree::leaf(r"(?P<nmae>[a-z]+)"))
+        .build();
+
+    let report = validate::<Vec<Catalog>>(&regex_tree);
+    assert_eq!(
+        report.issues(),
+        [
+            ValidationIssue::MissingCapture {
+                path: vec!["tags".to_owned()],
+                field: "name".to_owned(),
+            },
+            ValidationIssue::UnusedCapture {
+                path: vec!["tags".to_owned()],
+                group: "nmae".to_owned(),
+            },
+            ValidationIssue::NotNested {
+                path: vec!["meta".to_owned()],
+            },
+        ]
+    );
+    assert_eq!(
+        report.to_string(),
+        "tags: field `name` has no matching capture group
+tags: capture group `nmae` is not used by any field
+meta: nested data is expected, but the capture group has no child
+"
+    );
+}
@@ -0,0 +1,84 @@
+use recursive_regex::{
+    to_value, to_value_with_options, DeserializeOptions, Number, RegexTree, Spanned, Value,
+};
+
+fn spanned(text: &str, begin: usize, end: usize) -> Value {
+    Value::Spanned(Spanned::new_raw(
+        Box::new(Value::String(text.to_owned())),
+        begin,
+        end,
+    ))
+}
+
+#[test]
+fn nested_value() {
+    let regex_tree = RegexTree::root(r"(?P<name>\w+): (?P<nums>.*)")
+        .with_child("nums", RegexTree::leaf(r"\d+"))
+        .build();
+
+    let value = to_value(&regex_tree, "a: 1 23").unwrap();
+    assert_eq!(
+        value,
+        Value::Seq(vec![Value::Map(vec![
+            ("name".to_owned(), spanned("a", 0, 1)),
+            (
+                "nums".to_owned(),
+                Value::Seq(vec![spanned("1", 3, 4), spanned("23", 5, 7)])
+            ),
+        ])])
+    );
+}
+
+#[test]
+fn matches_without_names_are_strings() {
+    let value = to_value(&RegexTree::leaf(r"\d+"), "1 2").unwrap();
+    assert_eq!(
+        value,
+        Value::Seq(vec![spanned("1", 0, 1), spanned("2", 2, 3)])
+    );
+    assert_eq!(value.as_seq().unwrap()[1].as_str(), Some("2"));
+    assert_eq!(value.get("missing"), None);
+}
+
+#[test]
+fn inferred_types_keep_spans() {
+    let regex_tree = RegexTree::leaf(r"(?P<key>\w+)=(?P<value>\S+)");
+    let options = DeserializeOptions::default().infer_types();
+    let value = to_value_with_options(&regex_tree, "a=-1 b=false c=0x1", &options).unwrap();
+    assert_eq!(
+        value,
+        Value::Seq(vec![
+            Value::Map(vec![
+                ("key".to_owned(), spanned("a", 0, 1)),
+                (
+                    "value".to_owned(),
+                    Value::Spanned(Spanned::new_raw(
+                        Box::new(Value::Number(Number::Signed(-1))),
+                        2,
+                        4
+                    ))
+                ),
+            ]),
+            Value::Map(vec![
+                ("key".to_owned(), spanned("b", 5, 6)),
+                (
+                    "value".to_owned(),
+                    Value::Spanned(Spanned::new_raw(Box::new(Value::Bool(false)), 7, 12))
+                ),
+            ]),
+            Value::Map(vec![
+                ("key".to_owned(), spanned("c", 13, 14)),
+                ("value".to_owned(), spanned("0x1", 15, 18)),
+            ]),
+        ])
+    );
+}
+
+#[test]
+fn types_are_not_inferred_by_default() {
+    let value = to_value(&RegexTree::leaf(r"\S+"), "1 true").unwrap();
+    assert_eq!(
+        value,
+        Value::Seq(vec![spanned("1", 0, 1), spanned("true", 2, 6)])
+    );
+}
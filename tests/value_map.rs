@@ -0,0 +1,100 @@
+use recursive_regex::regex_tree::DuplicateKeys;
+use recursive_regex::{from_regex_tree_and_str, to_value, Matched, RegexTree};
+use serde::Deserialize;
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Line {
+    level: u8,
+    message: String,
+}
+
+const LEVELS: [(&str, &str); 3] = [("DEBUG", "0"), ("INFO", "1"), ("ERROR", "3")];
+
+fn log() -> RegexTree {
+    RegexTree::lines(r"\[(?P<level>\w+)\] (?P<message>.*)")
+        .with_value_map("level", LEVELS)
+        .build()
+}
+
+#[test]
+fn maps_before_parsing() {
+    let lines: Vec<Line> = from_regex_tree_and_str(&log(), "[INFO] up\n[ERROR] down").unwrap();
+    assert_eq!(
+        lines,
+        vec![
+            Line {
+                level: 1,
+                message: "up".to_owned()
+            },
+            Line {
+                level: 3,
+                message: "down".to_owned()
+            },
+        ]
+    );
+}
+
+#[test]
+fn unmapped_text_is_parsed_as_is() {
+    let lines: Vec<Line> = from_regex_tree_and_str(&log(), "[2] warm").unwrap();
+    assert_eq!(lines[0].level, 2);
+
+    let err = from_regex_tree_and_str::<Vec<Line>>(&log(), "[WARN] warm").unwrap_err();
+    assert_eq!(err.span(), Some(1..5));
+}
+
+#[test]
+fn mapped_strings_and_matched_spans() {
+    #[derive(Deserialize)]
+    struct Named {
+        level: Matched<String>,
+    }
+
+    let regex_tree = RegexTree::root(r"(?P<level>\w+)")
+        .with_value_map("level", [("E", "error")])
+        .build();
+    let named: Vec<Named> = from_regex_tree_and_str(&regex_tree, "x E").unwrap();
+    assert_eq!(named[1].level.value(), "error");
+    assert_eq!(named[1].level.text(), "E");
+
+    let value = to_value(&regex_tree, "E").unwrap();
+    assert_eq!(
+        value.as_seq().unwrap()[0].get("level").unwrap().as_str(),
+        Some("error")
+    );
+}
+
+#[test]
+fn maps_collected_and_positional_groups() {
+    let regex_tree = RegexTree::root(r"(?P<n[0]>\w+)-(?P<n[1]>\w+)")
+        .with_duplicate_keys(DuplicateKeys::Collect)
+        .with_value_map("n", [("one", "1"), ("two", "2")])
+        .build();
+    #[derive(Deserialize)]
+    struct Pair {
+        n: Vec<u32>,
+    }
+    let pairs: Vec<Pair> = from_regex_tree_and_str(&regex_tree, "one-two").unwrap();
+    assert_eq!(pairs[0].n, vec![1, 2]);
+
+    let regex_tree = RegexTree::root(r"(?P<a>\w+) (?P<b>\w+)")
+        .with_value_map("b", [("two", "2")])
+        .build();
+    let tuples: Vec<(String, u32)> = from_regex_tree_and_str(&regex_tree, "one two").unwrap();
+    assert_eq!(tuples, vec![("one".to_owned(), 2)]);
+}
+
+#[cfg(feature = "deserialize-regex-tree")]
+#[test]
+fn deserialized_tree() {
+    let regex_tree: RegexTree = serde_json::from_str(
+        r#"{
+            "regex": "\\[(?P<level>\\w+)\\] (?P<message>.*)",
+            "lines": true,
+            "value_maps": { "level": { "INFO": "1" } }
+        }"#,
+    )
+    .unwrap();
+    let lines: Vec<Line> = from_regex_tree_and_str(&regex_tree, "[INFO] up").unwrap();
+    assert_eq!(lines[0].level, 1);
+}
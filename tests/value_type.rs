@@ -0,0 +1,130 @@
+use recursive_regex::regex_tree::ValueType;
+use recursive_regex::{
+    from_regex_tree_and_str, to_value, to_value_with_options, DeserializeOptions, RegexTree,
+};
+use serde::Deserialize;
+
+fn regex_tree() -> RegexTree {
+    RegexTree::lines(r"(?P<name>\w+) (?P<count>\S+) (?P<ratio>\S+) (?P<on>\S+) (?P<id>\S+)")
+        .with_type("count", ValueType::Int)
+        .with_type("ratio", ValueType::Float)
+        .with_type("on", ValueType::Bool)
+        .with_type("id", ValueType::String)
+        .build()
+}
+
+#[test]
+fn values_are_typed() {
+    let value = to_value(&regex_tree(), "a -3 0.5 yes 007").unwrap();
+    assert_eq!(
+        serde_json::to_string(&value).unwrap(),
+        r#"[{"name":"a","count":-3,"ratio":0.5,"on":true,"id":"007"}]"#
+    );
+}
+
+#[test]
+fn types_override_inference() {
+    let options = DeserializeOptions::new().infer_types();
+    let value = to_value_with_options(&regex_tree(), "a 3 1 0 12", &options).unwrap();
+    assert_eq!(
+        serde_json::to_string(&value).unwrap(),
+        r#"[{"name":"a","count":3,"ratio":1.0,"on":false,"id":"12"}]"#
+    );
+}
+
+#[test]
+fn invalid_text_is_an_error() {
+    let error = to_value(&regex_tree(), "a 1 0.5 yes 1\nb 2 half no 2").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "[1].ratio: got \"half\" but expecting a float"
+    );
+    assert_eq!(error.span(), Some(18..22));
+
+    let error = to_value(&regex_tree(), "a 1 1 maybe 1").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "[0].on: got \"maybe\" but expecting a bool"
+    );
+}
+
+#[test]
+fn checked_for_any_rust_type() {
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Row {
+        name: String,
+        count: String,
+    }
+
+    let regex_tree = RegexTree::root(r"(?P<name>\w+)=(?P<count>\S+)")
+        .with_type("count", ValueType::Int)
+        .build();
+    let rows: Vec<Row> = from_regex_tree_and_str(&regex_tree, "a=1 b=-2").unwrap();
+    assert_eq!(rows[1].count, "-2");
+    let error = from_regex_tree_and_str::<Vec<Row>>(&regex_tree, "a=1 b=1.5").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "[1].count: got \"1.5\" but expecting an int"
+    );
+}
+
+#[test]
+fn display() {
+    assert_eq!(ValueType::Int.to_string(), "int");
+    assert_eq!(
+        ValueType::Datetime("%Y-%m-%d".to_owned()).to_string(),
+        "datetime(%Y-%m-%d)"
+    );
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn datetimes() {
+    let regex_tree = RegexTree::root(r"(?P<date>\S+) (?P<event>\w+)")
+        .with_type("date", ValueType::Datetime("%d/%m/%Y".to_owned()))
+        .build();
+    let value = to_value(&regex_tree, "15/3/2024 launch").unwrap();
+    assert_eq!(
+        serde_json::to_string(&value).unwrap(),
+        r#"[{"date":"2024-03-15","event":"launch"}]"#
+    );
+    let error = to_value(&regex_tree, "2024 launch").unwrap_err();
+    assert_eq!(error.span(), Some(0..4));
+}
+
+#[cfg(feature = "deserialize-regex-tree")]
+#[test]
+fn deserialized_tree() {
+    let regex_tree: RegexTree = serde_json::from_str(
+        r#"{
+            "regex": "(?P<host>[\\w.]+):(?P<port>\\S+)",
+            "types": { "port": "int" }
+        }"#,
+    )
+    .unwrap();
+    let value = to_value(&regex_tree, "localhost:8080").unwrap();
+    assert_eq!(
+        serde_json::to_string(&value).unwrap(),
+        r#"[{"host":"localhost","port":8080}]"#
+    );
+
+    let error = serde_json::from_str::<RegexTree>(
+        r#"{"regex": "(?P<port>\\S+)", "types": { "port": "integer" }}"#,
+    )
+    .unwrap_err();
+    assert!(error.to_string().starts_with("unknown type \"integer\""));
+}
+
+#[cfg(all(feature = "deserialize-regex-tree", feature = "chrono"))]
+#[test]
+fn deserialized_datetime() {
+    let regex_tree: RegexTree =
+        serde_json::from_str(r#"{"regex": "(?P<at>.*)", "types": { "at": "datetime(%H:%M)" }}"#)
+            .unwrap();
+    let value = to_value(&regex_tree, "09:30").unwrap();
+    assert_eq!(
+        serde_json::to_string(&value).unwrap(),
+        r#"[{"at":"09:30:00"}]"#
+    );
+}
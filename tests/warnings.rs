@@ -0,0 +1,80 @@
+use recursive_regex::{
+    from_regex_tree_and_str_with_options, DeserializeOptions, RegexTree, Warning,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Deserialize)]
+struct Entry {
+    #[allow(dead_code)]
+    name: String,
+}
+
+#[test]
+fn warns_of_ignored_groups_in_each_record() {
+    let regex_tree = RegexTree::leaf(r"(?P<name>\w+)(?: (?P<size>\d+))?");
+    let options = DeserializeOptions::new().collect_warnings();
+    let entries: Vec<Entry> =
+        from_regex_tree_and_str_with_options(&regex_tree, "a 1\nb\nc 23", &options).unwrap();
+    assert_eq!(entries.len(), 3);
+
+    // Groups that did not take part drop nothing
+    let warnings = options.warnings().unwrap();
+    assert_eq!(
+        warnings,
+        vec![
+            Warning::IgnoredGroup {
+                group: "size".to_owned(),
+                span: 2..3
+            },
+            Warning::IgnoredGroup {
+                group: "size".to_owned(),
+                span: 8..10
+            },
+        ]
+    );
+    assert_eq!(
+        warnings[1].to_string(),
+        "capture group `size` at 8..10 is not used by any field"
+    );
+}
+
+#[test]
+fn warns_of_ignored_groups_in_children() {
+    #[derive(Debug, Deserialize)]
+    struct Outer {
+        #[allow(dead_code)]
+        inner: Entry,
+    }
+
+    let regex_tree = RegexTree::root(r"\[(?P<inner>.*)\]")
+        .with_child("inner", RegexTree::leaf(r"(?P<name>\w+)/(?P<extra>\w+)"))
+        .build();
+    let options = DeserializeOptions::new()
+        .with_base_offset(100)
+        .collect_warnings();
+    let _: Outer = from_regex_tree_and_str_with_options(&regex_tree, "[a/b]", &options).unwrap();
+    assert_eq!(options.warnings().unwrap()[0].span(), 103..104);
+}
+
+#[test]
+fn no_warnings_without_ignored_groups() {
+    let regex_tree = RegexTree::leaf(r"(?P<name>\w+)=(?P<value>\w+)");
+    let options = DeserializeOptions::new().collect_warnings();
+
+    // Maps keep every group
+    let _: Vec<HashMap<String, String>> =
+        from_regex_tree_and_str_with_options(&regex_tree, "a=1", &options).unwrap();
+    assert_eq!(options.warnings(), Some(vec![]));
+
+    assert_eq!(DeserializeOptions::new().warnings(), None);
+}
+
+#[test]
+fn strict_fails_rather_than_warning() {
+    let regex_tree = RegexTree::leaf(r"(?P<name>\w+)=(?P<value>\w+)");
+    let options = DeserializeOptions::new().strict().collect_warnings();
+    let result = from_regex_tree_and_str_with_options::<Vec<Entry>>(&regex_tree, "a=1", &options);
+    assert!(result.is_err());
+    assert_eq!(options.warnings(), Some(vec![]));
+}
@@ -0,0 +1,68 @@
+use recursive_regex::{from_regex_tree_and_str, validate, RegexTree, Spanned};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Entry {
+    key: String,
+    value: u32,
+}
+
+fn blocks() -> RegexTree {
+    RegexTree::root(r"\{[^}]*\}")
+        .with_whole_match_child(RegexTree::leaf(r"(?P<key>\w+)=(?P<value>\d+)"))
+        .build()
+}
+
+#[test]
+fn sequence_of_blocks() {
+    let entries: Vec<Entry> = from_regex_tree_and_str(&blocks(), "{a=1} x=9 {b=2}").unwrap();
+    assert_eq!(
+        entries,
+        vec![
+            Entry {
+                key: "a".to_owned(),
+                value: 1
+            },
+            Entry {
+                key: "b".to_owned(),
+                value: 2
+            },
+        ]
+    );
+}
+
+#[test]
+fn single_block() {
+    let entry: Entry = from_regex_tree_and_str(&blocks(), "x=9 {b=2}").unwrap();
+    assert_eq!(
+        entry,
+        Entry {
+            key: "b".to_owned(),
+            value: 2
+        }
+    );
+
+    let entry: Option<Entry> = from_regex_tree_and_str(&blocks(), "x=9").unwrap();
+    assert_eq!(entry, None);
+
+    let entry: Option<Entry> = from_regex_tree_and_str(&blocks(), "{c=3}").unwrap();
+    assert_eq!(entry.map(|entry| entry.value), Some(3));
+}
+
+#[test]
+fn spans_are_within_whole_text() {
+    #[derive(Debug, Deserialize)]
+    struct SpannedEntry {
+        key: Spanned<String>,
+    }
+
+    let entry: SpannedEntry = from_regex_tree_and_str(&blocks(), "x=9 {b=2}").unwrap();
+    assert_eq!((entry.key.begin(), entry.key.end()), (5, 6));
+}
+
+#[test]
+fn validate_whole_match_child() {
+    assert!(validate::<Vec<Entry>>(&blocks()).is_ok());
+    assert!(validate::<Entry>(&blocks()).is_ok());
+    assert!(validate::<Option<Entry>>(&blocks()).is_ok());
+}
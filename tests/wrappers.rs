@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use recursive_regex::regex_tree::{DuplicateKeys, EmptyCaptures};
+use recursive_regex::{from_regex_tree_and_str, RegexTree};
+use serde::Deserialize;
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Comment {
+    author: String,
+    text: String,
+    reply: Option<Box<Comment>>,
+}
+
+/// Comments written as `author: text {reply}`, nested up to `depth` levels
+fn thread(depth: usize) -> RegexTree {
+    let comment = r"(?P<author>\w+): (?P<text>[^{}]*?) ?\{(?P<reply>.*)\}";
+    let mut regex_tree = RegexTree::root(comment)
+        .with_empty_captures(EmptyCaptures::Missing)
+        .build();
+    for _ in 1..depth {
+        regex_tree = RegexTree::root(comment)
+            .with_empty_captures(EmptyCaptures::Missing)
+            .with_child("reply", regex_tree)
+            .build();
+    }
+    regex_tree
+}
+
+fn comment(author: &str, text: &str, reply: Option<Box<Comment>>) -> Box<Comment> {
+    Box::new(Comment {
+        author: author.to_owned(),
+        text: text.to_owned(),
+        reply,
+    })
+}
+
+#[test]
+fn recursive_structs() {
+    let text = "ada: hi {bob: hello {ada: bye {}}}";
+    let parsed: Box<Comment> = from_regex_tree_and_str(&thread(3), text).unwrap();
+    let bye = comment("ada", "bye", None);
+    assert_eq!(
+        parsed,
+        comment("ada", "hi", Some(comment("bob", "hello", Some(bye))))
+    );
+
+    let parsed: Vec<Comment> = from_regex_tree_and_str(&thread(3), "cy: yo {}").unwrap();
+    assert_eq!(parsed[0].reply, None);
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+enum Expr {
+    Num { value: u32 },
+    Neg { inner: Box<Expr> },
+    Add { left: Box<Expr>, right: Box<Expr> },
+}
+
+/// Expressions in prefix notation, nested up to `depth` levels
+fn expr(depth: usize) -> RegexTree {
+    let mut regex_tree = RegexTree::root(r"^(?P<kind>Num) (?P<value>\d+)$")
+        .with_tag("kind")
+        .build();
+    for _ in 0..depth {
+        regex_tree = RegexTree::root(r"^(?P<kind>Neg) (?P<inner>.+)$")
+            .with_fallback(r"^(?P<kind>Add) \((?P<left>.+)\) \((?P<right>.+)\)$")
+            .with_fallback(r"^(?P<kind>Num) (?P<value>\d+)$")
+            .with_tag("kind")
+            .with_child("inner", regex_tree.clone())
+            .with_child("left", regex_tree.clone())
+            .with_child("right", regex_tree)
+            .build();
+    }
+    regex_tree
+}
+
+#[test]
+fn recursive_enums() {
+    let parsed: Expr = from_regex_tree_and_str(&expr(3), "Add (Neg Num 1) (Num 2)").unwrap();
+    let num = |value| Box::new(Expr::Num { value });
+    assert_eq!(
+        parsed,
+        Expr::Add {
+            left: Box::new(Expr::Neg { inner: num(1) }),
+            right: num(2),
+        }
+    );
+
+    let error = from_regex_tree_and_str::<Expr>(&expr(3), "Neg Neg Num x").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "inner.inner: regular expression does not match"
+    );
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Shared {
+    name: Rc<str>,
+    tags: Arc<Vec<Arc<str>>>,
+    size: Box<u32>,
+    note: Option<Box<str>>,
+}
+
+#[test]
+fn wrappers() {
+    let regex_tree =
+        RegexTree::root(r"(?P<name>\w+) \[(?P<tags>[^\]]*)\] (?P<size>\d+)(?: (?P<note>.*))?")
+            .with_child("tags", RegexTree::leaf(r"\w+"))
+            .build();
+    let shared: Vec<Rc<Shared>> = from_regex_tree_and_str(&regex_tree, "a [x y] 3 ok").unwrap();
+    assert_eq!(
+        *shared[0],
+        Shared {
+            name: "a".into(),
+            tags: Arc::new(vec!["x".into(), "y".into()]),
+            size: Box::new(3),
+            note: Some("ok".into()),
+        }
+    );
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Wrapped(Box<Comment>);
+
+#[test]
+fn newtypes_of_boxes() {
+    let wrapped: Wrapped = from_regex_tree_and_str(&thread(2), "ada: hi {bob: yo {}}").unwrap();
+    assert_eq!(wrapped.0.reply.unwrap().author, "bob");
+}
+
+#[test]
+fn errors_keep_their_path() {
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Sizes {
+        size: Arc<u32>,
+    }
+
+    let regex_tree = RegexTree::root(r"(?P<size>\w+)").build();
+    let error = from_regex_tree_and_str::<Vec<Sizes>>(&regex_tree, "1 x").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "[1].size: parsing error: invalid digit found in string"
+    );
+    assert_eq!(error.span(), Some(2..3));
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Deserialize)]
+struct Key(String);
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Street(String);
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Address {
+    street: Street,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Flag(bool);
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Values(Vec<u32>);
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Note(Option<String>);
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Reading {
+    deleted: Box<Flag>,
+    values: Values,
+    note: Note,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct AddressOf(Address);
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Person {
+    addr: Rc<AddressOf>,
+}
+
+#[test]
+fn newtypes_of_every_kind_of_group() {
+    let regex_tree = RegexTree::root(r"(?P<values[0]>\d+),(?P<values[1]>\d+)(?P<deleted>!)?")
+        .with_duplicate_keys(DuplicateKeys::Collect)
+        .with_flag("deleted")
+        .build();
+    let reading: Reading = from_regex_tree_and_str(&regex_tree, "1,2!").unwrap();
+    assert_eq!(
+        reading,
+        Reading {
+            deleted: Box::new(Flag(true)),
+            values: Values(vec![1, 2]),
+            note: Note(None),
+        }
+    );
+
+    let regex_tree = RegexTree::root(r"(?P<addr_street>\w+)")
+        .with_nested_keys("_")
+        .build();
+    let person: Person = from_regex_tree_and_str(&regex_tree, "Main").unwrap();
+    assert_eq!(person.addr.0.street, Street("Main".to_owned()));
+}
+
+#[test]
+fn newtype_keys() {
+    let regex_tree = RegexTree::leaf(r"w=(?P<width>\d+)");
+    let sizes: HashMap<Key, Box<u32>> = from_regex_tree_and_str(&regex_tree, "w=3").unwrap();
+    assert_eq!(sizes[&Key("width".to_owned())], Box::new(3));
+}